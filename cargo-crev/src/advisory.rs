@@ -0,0 +1,110 @@
+//! Exporting crev advisories in formats used by other tools
+use crate::prelude::*;
+use crev_data::proof::review::{self, AdvisoryRange};
+use serde_json::json;
+
+const ECOSYSTEM: &str = "crates.io";
+
+/// Ids of the advisory: the primary one and its aliases
+///
+/// Advisories without any ids get one derived from the review.
+pub fn advisory_ids(review: &review::Package) -> (String, Vec<String>) {
+    let mut ids = review
+        .advisory
+        .as_ref()
+        .map(|advisory| advisory.ids.clone())
+        .unwrap_or_default();
+    if ids.is_empty() {
+        let digest = crev_common::blake2b256sum(review.to_string().as_bytes());
+        ids.push(format!("CREV-{}", crev_data::Digest::from_vec(digest)));
+    }
+    let id = ids.remove(0);
+    (id, ids)
+}
+
+/// Lowest version affected, given the fixed version and the affected range
+pub fn introduced_version(fixed: &semver::Version, range: AdvisoryRange) -> Option<String> {
+    match range {
+        AdvisoryRange::All => None,
+        AdvisoryRange::Major => Some(format!("{}.0.0", fixed.major)),
+        AdvisoryRange::Minor => Some(format!("{}.{}.0", fixed.major, fixed.minor)),
+    }
+}
+
+fn parse_review(review: &review::Package) -> Result<(&review::Advisory, semver::Version)> {
+    let advisory = review
+        .advisory
+        .as_ref()
+        .ok_or_else(|| format_err!("Not an advisory"))?;
+    Ok((advisory, semver::Version::parse(&review.package.version)?))
+}
+
+/// Advisory in the OSV format (https://ossf.github.io/osv-schema/)
+pub fn to_osv(review: &review::Package) -> Result<serde_json::Value> {
+    let (advisory, fixed) = parse_review(review)?;
+    let (id, aliases) = advisory_ids(review);
+    let introduced = introduced_version(&fixed, advisory.range).unwrap_or_else(|| "0".into());
+
+    Ok(json!({
+        "id": id,
+        "aliases": aliases,
+        "published": review.date.to_rfc3339(),
+        "modified": review.date.to_rfc3339(),
+        "details": review.comment,
+        "affected": [{
+            "package": {
+                "ecosystem": ECOSYSTEM,
+                "name": review.package.name,
+            },
+            "ranges": [{
+                "type": "SEMVER",
+                "events": [
+                    { "introduced": introduced },
+                    { "fixed": fixed.to_string() },
+                ],
+            }],
+        }],
+        "database_specific": {
+            "severity": advisory.severity.to_string(),
+            "crev-author": review.from.id.to_string(),
+            "crev-author-url": review.from.url.url,
+        },
+    }))
+}
+
+/// Advisory in the format of RustSec advisory-db files
+pub fn to_rustsec(review: &review::Package) -> Result<String> {
+    let (advisory, fixed) = parse_review(review)?;
+    let (id, aliases) = advisory_ids(review);
+    let title = review
+        .comment
+        .lines()
+        .next()
+        .filter(|line| !line.trim().is_empty())
+        .unwrap_or("Problem reported in a crev review")
+        .to_owned();
+    let unaffected: Vec<String> = introduced_version(&fixed, advisory.range)
+        .into_iter()
+        .map(|introduced| format!("< {}", introduced))
+        .collect();
+
+    let mut table = toml::value::Table::new();
+    table.insert("id".into(), id.into());
+    table.insert("package".into(), review.package.name.clone().into());
+    table.insert(
+        "date".into(),
+        review.date.format("%Y-%m-%d").to_string().into(),
+    );
+    table.insert("title".into(), title.into());
+    table.insert("description".into(), review.comment.clone().into());
+    table.insert(
+        "patched_versions".into(),
+        vec![format!(">= {}", fixed)].into(),
+    );
+    table.insert("unaffected_versions".into(), unaffected.into());
+    table.insert("aliases".into(), aliases.into());
+
+    let mut root = toml::value::Table::new();
+    root.insert("advisory".into(), table.into());
+    Ok(toml::to_string(&toml::Value::Table(root))?)
+}
@@ -0,0 +1,69 @@
+//! Size of the source code of a package, to estimate the effort of reviewing it
+use crate::prelude::*;
+use std::{fs, path::Path};
+
+#[derive(Default, Clone, Copy)]
+pub struct CodeSize {
+    /// Total size of all files
+    pub bytes: u64,
+    /// Lines of `.rs` files, except empty and comment-only ones
+    pub rust_lines: u64,
+}
+
+fn is_code_line(line: &str) -> bool {
+    let line = line.trim();
+    !line.is_empty() && !line.starts_with("//")
+}
+
+fn is_rust_file(path: &Path) -> bool {
+    path.extension().map(|ext| ext == "rs").unwrap_or(false)
+}
+
+fn count_code_lines(path: &Path) -> u64 {
+    fs::read_to_string(path)
+        .map(|content| content.lines().filter(|line| is_code_line(line)).count() as u64)
+        .unwrap_or(0)
+}
+
+/// Measure sources of a package unpacked in `dir`
+pub fn measure(dir: &Path) -> Result<CodeSize> {
+    let mut size = CodeSize::default();
+    for entry in walkdir::WalkDir::new(dir) {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        size.bytes += entry.metadata()?.len();
+        if is_rust_file(entry.path()) {
+            size.rust_lines += count_code_lines(entry.path());
+        }
+    }
+    Ok(size)
+}
+
+/// Count lines of all `.rs` files under `dir`, the same way as `measure`
+pub fn count_rust_lines(dir: &Path) -> Result<u64> {
+    let mut lines = 0;
+    for entry in walkdir::WalkDir::new(dir) {
+        let entry = entry?;
+        if entry.file_type().is_file() && is_rust_file(entry.path()) {
+            lines += count_code_lines(entry.path());
+        }
+    }
+    Ok(lines)
+}
+
+/// Human-readable amount of bytes, eg. `12.3K`
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["K", "M", "G"];
+    if bytes < 1024 {
+        return format!("{}B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1}{}", value, UNITS[unit])
+}
@@ -0,0 +1,70 @@
+use crev_lib::VerificationStatus;
+
+/// Amount of dependencies in a single verification state
+#[derive(Default, Clone, Copy)]
+pub struct Count {
+    pub crates: usize,
+    pub lines: u64,
+}
+
+impl Count {
+    fn add(&mut self, lines: u64) {
+        self.crates += 1;
+        self.lines += lines;
+    }
+}
+
+/// Review coverage of a set of dependencies
+#[derive(Default, Clone, Copy)]
+pub struct Coverage {
+    pub verified: Count,
+    pub unknown: Count,
+    pub flagged: Count,
+}
+
+fn percent(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+impl Coverage {
+    pub fn add(&mut self, status: &VerificationStatus, lines: u64) {
+        match status {
+            VerificationStatus::Verified => self.verified.add(lines),
+            VerificationStatus::Unknown => self.unknown.add(lines),
+            VerificationStatus::Flagged => self.flagged.add(lines),
+        }
+    }
+
+    fn total(&self) -> Count {
+        Count {
+            crates: self.verified.crates + self.unknown.crates + self.flagged.crates,
+            lines: self.verified.lines + self.unknown.lines + self.flagged.lines,
+        }
+    }
+
+    pub fn print(&self, title: &str) {
+        let total = self.total();
+        println!(
+            "{:10} {:>6} {:>7} {:>9} {:>7}",
+            title, "crates", "", "lines", ""
+        );
+        for (name, count) in &[
+            ("verified", self.verified),
+            ("unknown", self.unknown),
+            ("flagged", self.flagged),
+        ] {
+            println!(
+                "{:10} {:>6} {:>6.1}% {:>9} {:>6.1}%",
+                name,
+                count.crates,
+                percent(count.crates as u64, total.crates as u64),
+                count.lines,
+                percent(count.lines, total.lines)
+            );
+        }
+    }
+}
@@ -4,9 +4,14 @@ use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
+/// Pages (of 100 crates) of the most downloaded crates to get
+const POPULAR_CRATES_PAGES: u64 = 5;
+
 pub struct Client {
     client: crates_io_api::SyncClient,
     cache_dir: PathBuf,
+    owners_cache_dir: PathBuf,
+    popular_crates_path: PathBuf,
 }
 
 fn get_downloads_stats(resp: &crates_io_api::CrateResponse, version: &str) -> (u64, u64) {
@@ -27,9 +32,16 @@ impl Client {
             .join("crates_io")
             .join("get_crate");
         fs::create_dir_all(&cache_dir)?;
+        let owners_cache_dir = local.get_root_cache_dir().join("crates_io").join("owners");
+        fs::create_dir_all(&owners_cache_dir)?;
         Ok(Self {
             client: crates_io_api::SyncClient::new(),
             cache_dir: cache_dir,
+            owners_cache_dir,
+            popular_crates_path: local
+                .get_root_cache_dir()
+                .join("crates_io")
+                .join("popular.json"),
         })
     }
 
@@ -81,22 +93,61 @@ impl Client {
         Ok(resp)
     }
 
-    pub fn get_downloads_count(&self, crate_: &str, version: &str) -> Result<(u64, u64)> {
+    /// Logins of owners (users and teams) of a crate
+    pub fn get_owners(&self, crate_: &str) -> Result<Vec<String>> {
+        let path = self.owners_cache_dir.join(format!("{}.json", crate_));
+        let owners: Vec<crates_io_api::User> = if path.exists() && self.is_fresh(&path)? {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            let owners = self.client.crate_owners(crate_)?;
+            crev_common::store_to_file_with(&path, |file| serde_json::to_writer(file, &owners))??;
+            owners
+        };
+        Ok(owners.into_iter().map(|owner| owner.login).collect())
+    }
+
+    /// Crate data, from the cache unless it's stale
+    pub fn get_crate(&self, crate_: &str) -> Result<crates_io_api::CrateResponse> {
         let cached = self.get_crate_cached(crate_)?;
 
         match cached {
-            Some((resp, true)) => Ok(get_downloads_stats(&resp, version)),
+            Some((resp, true)) => Ok(resp),
             Some((resp, false)) => match self.get_crate_from_crates_io(crate_) {
-                Ok(new_resp) => Ok(get_downloads_stats(&new_resp, version)),
+                Ok(new_resp) => Ok(new_resp),
                 Err(e) => {
-                    eprintln!("Error: {}", e);
-                    Ok(get_downloads_stats(&resp, version))
+                    error!("{}", e);
+                    Ok(resp)
                 }
             },
-            None => Ok(get_downloads_stats(
-                &self.get_crate_from_crates_io(crate_)?,
-                version,
-            )),
+            None => self.get_crate_from_crates_io(crate_),
+        }
+    }
+
+    pub fn get_downloads_count(&self, crate_: &str, version: &str) -> Result<(u64, u64)> {
+        Ok(get_downloads_stats(&self.get_crate(crate_)?, version))
+    }
+
+    /// Names and downloads of the most downloaded crates
+    pub fn get_popular_crates(&self) -> Result<Vec<(String, u64)>> {
+        let path = &self.popular_crates_path;
+        if path.exists() && self.is_fresh(path)? {
+            return Ok(serde_json::from_str(&fs::read_to_string(path)?)?);
+        }
+        let mut popular = vec![];
+        for page in 1..=POPULAR_CRATES_PAGES {
+            let resp = self.client.crates(crates_io_api::ListOptions {
+                sort: crates_io_api::Sort::Downloads,
+                per_page: 100,
+                page,
+                query: None,
+            })?;
+            popular.extend(
+                resp.crates
+                    .into_iter()
+                    .map(|crate_| (crate_.name, crate_.downloads)),
+            );
         }
+        crev_common::store_to_file_with(path, |file| serde_json::to_writer(file, &popular))??;
+        Ok(popular)
     }
 }
@@ -0,0 +1,47 @@
+//! Showing what changed since an earlier reviewed version, for delta reviews
+use crate::prelude::*;
+use crev_data::Id;
+use crev_lib::trustdb::TrustDB;
+use std::{path::Path, process};
+
+/// Newest version of `name` older than `version` that `id` reviewed
+pub fn find_reviewed_base_version(
+    db: &TrustDB,
+    id: &Id,
+    source: &str,
+    name: &str,
+    version: &semver::Version,
+) -> Option<semver::Version> {
+    db.get_package_reviews_by_author(id)
+        .filter(|review| review.package.source == source && review.package.name == name)
+        .filter_map(|review| semver::Version::parse(&review.package.version).ok())
+        .filter(|reviewed| reviewed < version)
+        .max()
+}
+
+/// Show the changes from `old` to `new` with `diff_cmd`, or print them as
+/// a unified diff (with `diff -ruN`)
+///
+/// `diff_cmd` gets both directories as its last two arguments; it's split
+/// into arguments on whitespace, without a shell.
+pub fn show_diff(old: &Path, new: &Path, diff_cmd: Option<&str>) -> Result<()> {
+    let mut args: Vec<String> = match diff_cmd {
+        Some(diff_cmd) => diff_cmd.split_whitespace().map(ToOwned::to_owned).collect(),
+        None => vec!["diff".into(), "-ruN".into()],
+    };
+    if args.is_empty() {
+        bail!("`diff-cmd` is empty");
+    }
+    args.push(old.to_string_lossy().into_owned());
+    args.push(new.to_string_lossy().into_owned());
+
+    let status = process::Command::new(&args[0])
+        .args(&args[1..])
+        .status()
+        .map_err(|e| format_err!("Could not run `{}`: {}", args[0], e))?;
+    // `diff` and many difftools return 1 if there are differences
+    if !status.success() && status.code() != Some(1) {
+        bail!("`{}` returned {}", args[0], status);
+    }
+    Ok(())
+}
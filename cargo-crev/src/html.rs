@@ -0,0 +1,50 @@
+//! Helpers for rendering HTML pages
+use std::fmt::Write;
+
+/// Escape `s` to be used in HTML text and attribute values
+pub fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+th, td { padding: 0.2em 0.8em; text-align: left; border-bottom: 1px solid #ddd; }
+pre { background: #f6f6f6; padding: 0.5em; overflow-x: auto; }
+.verified { color: #080; }
+.flagged { color: #c00; }
+.accepted { color: #068; }
+";
+
+/// Self-contained HTML document with given (already escaped) `body`
+pub fn page(title: &str, body: &str) -> String {
+    let mut s = String::new();
+    writeln!(s, "<!DOCTYPE html>").unwrap();
+    writeln!(s, "<html><head><meta charset=\"utf-8\">").unwrap();
+    writeln!(s, "<title>{}</title>", escape(title)).unwrap();
+    writeln!(s, "<style>{}</style>", STYLE).unwrap();
+    writeln!(s, "</head><body>").unwrap();
+    writeln!(s, "{}", body).unwrap();
+    writeln!(s, "</body></html>").unwrap();
+    s
+}
+
+/// CSS class of a verification result
+pub fn status_class(status: &crev_lib::VerificationStatus) -> &'static str {
+    match status {
+        crev_lib::VerificationStatus::Verified => "verified",
+        crev_lib::VerificationStatus::Unknown => "unknown",
+        crev_lib::VerificationStatus::Flagged => "flagged",
+    }
+}
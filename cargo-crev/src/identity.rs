@@ -0,0 +1,19 @@
+//! Verifying identity claims against what the claimed accounts published
+use crate::prelude::*;
+use crev_data::{proof::identity::Account, Id};
+use std::io::Read;
+
+/// Does the account publish `id` at its `proof_url`?
+pub fn verify_account(account: &Account, id: &Id) -> Result<bool> {
+    let url = match account.raw_proof_url() {
+        Some(url) => url,
+        None => return Ok(false),
+    };
+    let mut response = reqwest::get(&url)?;
+    if !response.status().is_success() {
+        bail!("Fetching {} failed: {}", url, response.status());
+    }
+    let mut content = String::new();
+    response.read_to_string(&mut content)?;
+    Ok(content.contains(&id.to_string()))
+}
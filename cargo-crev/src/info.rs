@@ -0,0 +1,222 @@
+//! Showing everything known about a crate
+use crate::prelude::*;
+use crate::{advisory, crates_io, opts, rustsec, why::describe_id, Repo, PROJECT_SOURCE_CRATES_IO};
+use crev_data::proof::review::{self, Common, Rating};
+use crev_lib::{
+    trustdb::{TrustDB, TrustSet},
+    Local, VerificationStatus,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Versions of the crate resolved in the project in the current directory
+///
+/// Empty outside of a project, or if it doesn't depend on the crate.
+fn resolved_versions(
+    name: &str,
+    features: &opts::CargoFeatures,
+) -> Result<Vec<(std::path::PathBuf, semver::Version)>> {
+    let mut repo = match Repo::auto_open_cwd() {
+        Ok(repo) => repo,
+        Err(_) => return Ok(vec![]),
+    };
+    repo.set_features(features);
+    let mut versions = vec![];
+    repo.for_every_dependency_dir(|pkg_id, path| {
+        if pkg_id.name().as_str() == name {
+            versions.push((path.to_owned(), pkg_id.version().to_owned()));
+        }
+        Ok(())
+    })?;
+    Ok(versions)
+}
+
+fn describe_trust(db: &TrustDB, trust_set: &TrustSet, id: &crev_data::Id) -> String {
+    match trust_set.get(id) {
+        Some(info) => format!(
+            "{}: trusted {}, distance {}",
+            describe_id(db, id),
+            info.effective_trust,
+            info.distance
+        ),
+        None => format!("{}: not in your trust set", describe_id(db, id)),
+    }
+}
+
+fn print_advisory(db: &TrustDB, review: &review::Package) {
+    let (id, aliases) = advisory::advisory_ids(review);
+    let advisory = match &review.advisory {
+        Some(advisory) => advisory,
+        None => return,
+    };
+    let affected = match semver::Version::parse(&review.package.version) {
+        Ok(fixed) => match advisory::introduced_version(&fixed, advisory.range) {
+            Some(introduced) => format!(">= {}, < {}", introduced, fixed),
+            None => format!("< {}", fixed),
+        },
+        Err(_) => format!("< {}", review.package.version),
+    };
+    println!("- {} ({}): versions {}", id, advisory.severity, affected);
+    if !aliases.is_empty() {
+        println!("  also known as {}", aliases.join(", "));
+    }
+    println!("  reported by {}", describe_id(db, &review.from.id));
+}
+
+pub fn show(args: &opts::CrateInfo) -> Result<()> {
+    let local = Local::auto_open()?;
+    let config = local.load_user_config()?;
+    let (db, trust_set) = local.load_db(&args.trust_params.clone().into())?;
+    let name = &args.name;
+
+    let reviews: Vec<_> = db
+        .get_package_reviews_for_package(PROJECT_SOURCE_CRATES_IO, Some(name), None)
+        .collect();
+    let mut by_version: BTreeMap<_, BTreeMap<Rating, Vec<_>>> = BTreeMap::new();
+    for review in &reviews {
+        let version = &review.package.version;
+        by_version
+            .entry((semver::Version::parse(version).ok(), version.clone()))
+            .or_default()
+            .entry(review.review().rating.clone())
+            .or_default()
+            .push(review);
+    }
+
+    println!("{}: {} review(s)", name, reviews.len());
+    // newest versions first
+    for ((_, version), by_rating) in by_version.iter().rev() {
+        println!();
+        println!("{}:", version);
+        for (rating, reviews) in by_rating.iter().rev() {
+            println!("  {:?}:", rating);
+            for review in reviews {
+                println!(
+                    "  - by {}{} on {}, thoroughness: {}, understanding: {}",
+                    describe_id(&db, &review.from.id),
+                    if trust_set.contains(&review.from.id) {
+                        ""
+                    } else {
+                        " (not trusted)"
+                    },
+                    review.date.format("%Y-%m-%d"),
+                    review.review().thoroughness,
+                    review.review().understanding
+                );
+            }
+        }
+    }
+
+    let notes = local.load_notes()?;
+    let notes = notes.get(name, None);
+    if !notes.is_empty() {
+        println!();
+        println!("Your notes:");
+        for note in notes {
+            println!("- {}", crate::format_note(note));
+        }
+    }
+
+    let reviewers: BTreeSet<_> = reviews.iter().map(|review| &review.from.id).collect();
+    println!();
+    println!("Reviewers:");
+    if reviewers.is_empty() {
+        println!("  none");
+    }
+    for id in reviewers {
+        println!("- {}", describe_trust(&db, &trust_set, id));
+    }
+
+    println!();
+    println!("Advisories from your trust set:");
+    let advisories = db.get_advisories(PROJECT_SOURCE_CRATES_IO, Some(name), &trust_set);
+    if advisories.is_empty() {
+        println!("  none");
+    }
+    for review in &advisories {
+        print_advisory(&db, review);
+    }
+
+    let rustsec_db = if args.no_rustsec {
+        None
+    } else {
+        rustsec::AdvisoryDb::load(&local)?
+    };
+    if let Some(rustsec_db) = &rustsec_db {
+        println!();
+        println!("RustSec advisories:");
+        let rustsec_advisories = rustsec_db.get_all_advisories(name);
+        if rustsec_advisories.is_empty() {
+            println!("  none");
+        }
+        for advisory in rustsec_advisories {
+            if advisory.aliases.is_empty() {
+                println!("- {}", advisory.id);
+            } else {
+                println!("- {} ({})", advisory.id, advisory.aliases.join(", "));
+            }
+        }
+    }
+
+    let resolved = resolved_versions(name, &args.features)?;
+    let cratesio = if args.no_downloads {
+        None
+    } else {
+        Some(crates_io::Client::new(&local)?)
+    };
+    if let Some(cratesio) = &cratesio {
+        let version = resolved
+            .first()
+            .map(|(_, version)| version.to_string())
+            .unwrap_or_default();
+        match cratesio.get_downloads_count(name, &version) {
+            Ok((_, total)) => {
+                println!();
+                println!("Downloads: {}", total);
+            }
+            Err(e) => error!("{}", e),
+        }
+    }
+
+    if !resolved.is_empty() {
+        println!();
+        println!("In your project:");
+    }
+    for (pkg_dir, version) in &resolved {
+        let version_str = version.to_string();
+        let digests =
+            crate::get_package_digests(&db, &trust_set, pkg_dir, name, &version_str, &config)?;
+        let result = crate::verify_package(&db, &trust_set, &digests, name, &version_str);
+        println!(
+            "- {}: {}{}",
+            version,
+            result,
+            match result {
+                VerificationStatus::Verified => "",
+                VerificationStatus::Unknown => {
+                    " (not covered by your trust set; see `cargo crev why`)"
+                }
+                VerificationStatus::Flagged => " (flagged by your trust set; see `cargo crev why`)",
+            }
+        );
+        let issues =
+            db.get_advisories_for_version(PROJECT_SOURCE_CRATES_IO, name, &version_str, &trust_set);
+        if !issues.is_empty() {
+            println!(
+                "  affected by {} advisory(ies) from your trust set",
+                issues.len()
+            );
+        }
+        if let Some(rustsec_db) = &rustsec_db {
+            for advisory in rustsec_db.get_advisories(name, version) {
+                println!("  affected by {}", advisory.id);
+            }
+        }
+        if let Some(cratesio) = &cratesio {
+            if let Ok((downloads, _)) = cratesio.get_downloads_count(name, &version_str) {
+                println!("  downloads of this version: {}", downloads);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,69 +1,402 @@
 #[macro_use]
 extern crate structopt;
+#[macro_use]
+extern crate log;
 
 use self::prelude::*;
 use cargo::{
-    core::{package_id::PackageId, SourceId},
+    core::{dependency::Kind as DepKind, package_id::PackageId, Source, SourceId},
     util::important_paths::find_root_manifest_for_wd,
 };
-use crev_lib::ProofStore;
 use crev_lib::{self, local::Local};
 use default::default;
 use semver;
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    fs,
     path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
 };
 use structopt::StructOpt;
 
+mod advisory;
+mod code_size;
+mod coverage;
 mod crates_io;
+mod diff;
+mod html;
+mod identity;
+mod info;
+mod notify;
+mod open;
 mod opts;
 mod prelude;
+mod progress;
+mod provenance;
+mod registry;
+mod report;
+mod rustsec;
+mod serve;
+mod suspicious;
+mod term;
+mod transparency;
+mod tree;
+mod tui;
+mod unsafe_count;
+mod vendor;
+mod vet;
+mod watch;
+mod why;
 
-use crev_data::proof;
+use crev_data::{
+    proof::{self, membership::MembershipStatus::*},
+    DigestType, Id,
+};
 use crev_lib::{TrustOrDistrust, TrustOrDistrust::*};
 
 struct Repo {
     manifest_path: PathBuf,
     config: cargo::util::config::Config,
+    features: Vec<String>,
+    all_features: bool,
+    no_default_features: bool,
+}
+
+/// `--offline`, `--frozen` and `--locked`, set once from the command line
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+static FROZEN: AtomicBool = AtomicBool::new(false);
+static LOCKED: AtomicBool = AtomicBool::new(false);
+
+fn set_cargo_flags(flags: &opts::CargoFlags) {
+    OFFLINE.store(flags.offline, Ordering::Relaxed);
+    FROZEN.store(flags.frozen, Ordering::Relaxed);
+    LOCKED.store(flags.locked, Ordering::Relaxed);
+}
+
+fn cargo_config() -> Result<cargo::util::config::Config> {
+    cargo::core::enable_nightly_features();
+    let mut config = cargo::util::config::Config::default()?;
+    let unstable_flags = if OFFLINE.load(Ordering::Relaxed) {
+        vec!["offline".to_owned()]
+    } else {
+        vec![]
+    };
+    config.configure(
+        0,
+        None,
+        &None,
+        FROZEN.load(Ordering::Relaxed),
+        LOCKED.load(Ordering::Relaxed),
+        &None,
+        &unstable_flags,
+    )?;
+    Ok(config)
+}
+
+/// Make sure the registry index is current, unless network access is disabled
+fn update_source(config: &cargo::util::config::Config, source: &mut dyn Source) -> Result<()> {
+    if config.network_allowed() {
+        source.update()?;
+    }
+    Ok(())
+}
+
+/// Download a crate from crates.io, even if it's not a dependency of anything
+///
+/// Without `version`, the newest release is used: not yanked, and not
+/// a pre-release unless `pre_releases`. A `version` given is used even
+/// if it's one of these.
+fn download_crate(
+    name: &str,
+    version: Option<&str>,
+    pre_releases: bool,
+) -> Result<(PathBuf, semver::Version)> {
+    let config = cargo_config()?;
+    let source_id = SourceId::crates_io(&config)?;
+    let map = cargo::sources::SourceConfigMap::new(&config)?;
+    let mut source = map.load(&source_id)?;
+    update_source(&config, &mut *source)?;
+
+    let dependency = match version {
+        // the registry lists yanked versions only for a precise (locked) source
+        Some(version) => cargo::core::Dependency::parse_no_deprecated(
+            name,
+            Some(&format!("={}", version)),
+            &source_id.with_precise(Some("locked".to_owned())),
+        )?,
+        None => cargo::core::Dependency::parse_no_deprecated(name, None, &source_id)?,
+    };
+    let summary = source
+        .query_vec(&dependency)?
+        .into_iter()
+        .filter(|summary| version.is_some() || pre_releases || !summary.version().is_prerelease())
+        .max_by(|a, b| a.version().cmp(b.version()))
+        .ok_or_else(|| format_err!("Crate {} not found", name))?;
+
+    let pkg = source.download(summary.package_id())?;
+    Ok((pkg.root().to_owned(), summary.version().to_owned()))
 }
 
 impl Repo {
     fn auto_open_cwd() -> Result<Self> {
-        cargo::core::enable_nightly_features();
         let cwd = std::env::current_dir()?;
         let manifest_path = find_root_manifest_for_wd(&cwd)?;
-        let mut config = cargo::util::config::Config::default()?;
-        config.configure(0, None, &None, false, false, &None, &[])?;
+        let config = cargo_config()?;
         Ok(Repo {
             manifest_path,
             config,
+            features: vec![],
+            all_features: true,
+            no_default_features: false,
+        })
+    }
+
+    /// Load the project policy, kept next to the workspace root manifest
+    fn load_policy(&self) -> Result<crev_lib::policy::Policy> {
+        let workspace = cargo::core::Workspace::new(&self.manifest_path, &self.config)?;
+        crev_lib::policy::Policy::load_for_project(workspace.root())
+    }
+
+    /// Resolve dependencies with given features enabled
+    ///
+    /// Unless any features are selected, all features are enabled,
+    /// so that the resolved set is a superset of any actual build.
+    fn set_features(&mut self, features: &opts::CargoFeatures) {
+        if features.all_features || !features.features.is_empty() || features.no_default_features {
+            self.features = features.features.clone();
+            self.all_features = features.all_features;
+            self.no_default_features = features.no_default_features;
+        }
+    }
+
+    fn resolve<'a>(
+        &self,
+        workspace: &cargo::core::Workspace<'a>,
+    ) -> Result<(cargo::core::PackageSet<'a>, cargo::core::Resolve)> {
+        let specs = cargo::ops::Packages::All.to_package_id_specs(workspace)?;
+        cargo::ops::resolve_ws_precisely(
+            workspace,
+            None,
+            &self.features,
+            self.all_features,
+            self.no_default_features,
+            &specs,
+        )
+    }
+
+    /// Distance of every package from the workspace members
+    ///
+    /// Workspace members have depth 0, their direct dependencies depth 1, etc.
+    fn dependency_depths(&self) -> Result<HashMap<PackageId, usize>> {
+        let workspace = cargo::core::Workspace::new(&self.manifest_path, &self.config)?;
+        let (_package_set, resolve) = self.resolve(&workspace)?;
+
+        let mut depths = HashMap::new();
+        let mut pending = VecDeque::new();
+        for member in workspace.members() {
+            depths.insert(member.package_id().clone(), 0);
+            pending.push_back(member.package_id().clone());
+        }
+
+        while let Some(pkg_id) = pending.pop_front() {
+            let depth = depths[&pkg_id] + 1;
+            for (dep_id, _) in resolve.deps(&pkg_id) {
+                if !depths.contains_key(dep_id) {
+                    depths.insert(dep_id.clone(), depth);
+                    pending.push_back(dep_id.clone());
+                }
+            }
+        }
+
+        Ok(depths)
+    }
+
+    /// Workspace members, and dependencies of every package
+    fn dependency_graph(&self) -> Result<tree::Graph> {
+        let workspace = cargo::core::Workspace::new(&self.manifest_path, &self.config)?;
+        let (_package_set, resolve) = self.resolve(&workspace)?;
+
+        let mut deps = HashMap::new();
+        for pkg_id in resolve.iter() {
+            let mut pkg_deps: Vec<_> = resolve
+                .deps(pkg_id)
+                .map(|(dep_id, _)| dep_id.clone())
+                .collect();
+            pkg_deps.sort();
+            deps.insert(pkg_id.clone(), pkg_deps);
+        }
+        Ok(tree::Graph {
+            roots: workspace
+                .members()
+                .map(|member| member.package_id().clone())
+                .collect(),
+            deps,
         })
     }
 
+    /// Packages every workspace member depends on, directly or not
+    fn member_dependencies(&self) -> Result<BTreeMap<String, HashSet<PackageId>>> {
+        let workspace = cargo::core::Workspace::new(&self.manifest_path, &self.config)?;
+        let (_package_set, resolve) = self.resolve(&workspace)?;
+
+        let mut dependencies = BTreeMap::new();
+        for member in workspace.members() {
+            let mut reachable = HashSet::new();
+            let mut pending = vec![member.package_id().clone()];
+            while let Some(pkg_id) = pending.pop() {
+                for (dep_id, _) in resolve.deps(&pkg_id) {
+                    if reachable.insert(dep_id.clone()) {
+                        pending.push(dep_id.clone());
+                    }
+                }
+            }
+            dependencies.insert(member.name().to_string(), reachable);
+        }
+        Ok(dependencies)
+    }
+
+    /// Kinds of dependency edges through which every package is used
+    ///
+    /// Anything reachable through a build dependency is used at build time,
+    /// anything reachable through a dev dependency (and not a build dependency)
+    /// only during development.
+    fn dependency_kinds(&self) -> Result<HashMap<PackageId, HashSet<DepKind>>> {
+        let workspace = cargo::core::Workspace::new(&self.manifest_path, &self.config)?;
+        let (_package_set, resolve) = self.resolve(&workspace)?;
+
+        let mut kinds: HashMap<PackageId, HashSet<DepKind>> = HashMap::new();
+        let mut pending: VecDeque<_> = workspace
+            .members()
+            .map(|member| (member.package_id().clone(), DepKind::Normal))
+            .collect();
+
+        while let Some((pkg_id, kind)) = pending.pop_front() {
+            for (dep_id, deps) in resolve.deps(&pkg_id) {
+                for dep in deps {
+                    let dep_kind = match (kind, dep.kind()) {
+                        (DepKind::Build, _) | (_, DepKind::Build) => DepKind::Build,
+                        (DepKind::Development, _) | (_, DepKind::Development) => {
+                            DepKind::Development
+                        }
+                        (DepKind::Normal, DepKind::Normal) => DepKind::Normal,
+                    };
+                    if kinds.entry(dep_id.clone()).or_default().insert(dep_kind) {
+                        pending.push_back((dep_id.clone(), dep_kind));
+                    }
+                }
+            }
+        }
+
+        Ok(kinds)
+    }
+
+    /// Packages running own code at build time
+    fn build_time_code(&self) -> Result<HashMap<PackageId, BuildTimeCode>> {
+        let workspace = cargo::core::Workspace::new(&self.manifest_path, &self.config)?;
+        let (package_set, _resolve) = self.resolve(&workspace)?;
+
+        let mut code = HashMap::new();
+        for pkg_id in package_set.package_ids() {
+            let pkg = package_set.get(pkg_id)?;
+            let build_time_code = BuildTimeCode {
+                build_script: pkg.targets().iter().any(|t| t.is_custom_build()),
+                proc_macro: pkg
+                    .targets()
+                    .iter()
+                    .any(|t| t.rustc_crate_types().contains(&"proc-macro")),
+            };
+            if build_time_code.build_script || build_time_code.proc_macro {
+                code.insert(pkg_id.clone(), build_time_code);
+            }
+        }
+        Ok(code)
+    }
+
+    /// `Cargo.lock` of the workspace
+    fn lock_file_path(&self) -> Result<PathBuf> {
+        let workspace = cargo::core::Workspace::new(&self.manifest_path, &self.config)?;
+        Ok(workspace.root().join("Cargo.lock"))
+    }
+
+    /// Names of all packages in the dependency graph
+    fn dependency_names(&self) -> Result<HashSet<String>> {
+        let workspace = cargo::core::Workspace::new(&self.manifest_path, &self.config)?;
+        let (package_set, _resolve) = self.resolve(&workspace)?;
+        Ok(package_set
+            .package_ids()
+            .map(|pkg_id| pkg_id.name().to_string())
+            .collect())
+    }
+
+    /// License expression of every package, from its manifest
+    ///
+    /// Packages with only a `license-file` get `non-standard`, ones without
+    /// any license information are left out.
+    fn licenses(&self) -> Result<HashMap<PackageId, String>> {
+        let workspace = cargo::core::Workspace::new(&self.manifest_path, &self.config)?;
+        let (package_set, _resolve) = self.resolve(&workspace)?;
+
+        let mut licenses = HashMap::new();
+        for pkg_id in package_set.package_ids() {
+            let metadata = package_set.get(pkg_id)?.manifest().metadata();
+            let license = match (&metadata.license, &metadata.license_file) {
+                (Some(license), _) => license.clone(),
+                (None, Some(_)) => "non-standard".to_owned(),
+                (None, None) => continue,
+            };
+            licenses.insert(pkg_id.clone(), license);
+        }
+        Ok(licenses)
+    }
+
+    /// Checksums of `.crate` files of packages from registries, as recorded in the index
+    fn registry_checksums(&self) -> Result<HashMap<PackageId, String>> {
+        let workspace = cargo::core::Workspace::new(&self.manifest_path, &self.config)?;
+        let (_package_set, resolve) = self.resolve(&workspace)?;
+
+        Ok(resolve
+            .checksums()
+            .iter()
+            .filter_map(|(pkg_id, checksum)| {
+                checksum
+                    .as_ref()
+                    .map(|checksum| (pkg_id.clone(), checksum.clone()))
+            })
+            .collect())
+    }
+
     fn for_every_dependency_dir(
         &self,
+        f: impl FnMut(&PackageId, &Path) -> Result<()>,
+    ) -> Result<()> {
+        self.for_every_dependency_dir_with_progress(&progress::Progress::hidden(), f)
+    }
+
+    fn for_every_dependency_dir_with_progress(
+        &self,
+        progress: &progress::Progress,
         mut f: impl FnMut(&PackageId, &Path) -> Result<()>,
     ) -> Result<()> {
         let workspace = cargo::core::Workspace::new(&self.manifest_path, &self.config)?;
-        let specs = cargo::ops::Packages::All.to_package_id_specs(&workspace)?;
-        let (package_set, _resolve) = cargo::ops::resolve_ws_precisely(
-            &workspace,
-            None,
-            &[],
-            true,  // all_features
-            false, // no_default_features
-            &specs,
-        )?;
+        let (package_set, _resolve) = self.resolve(&workspace)?;
         let source_id = SourceId::crates_io(&self.config)?;
         let map = cargo::sources::SourceConfigMap::new(&self.config)?;
         let mut source = map.load(&source_id)?;
-        source.update()?;
+        update_source(&self.config, &mut *source)?;
 
+        progress.set_total(package_set.package_ids().count());
         for pkg_id in package_set.package_ids() {
+            progress.next(&format!("{} {}", pkg_id.name(), pkg_id.version()));
             let pkg = package_set.get(pkg_id)?;
 
             if !pkg.root().exists() {
+                if !self.config.network_allowed() {
+                    bail!(
+                        "{} {} is not downloaded yet, and network access is disabled",
+                        pkg_id.name(),
+                        pkg_id.version()
+                    );
+                }
                 source.download(pkg_id)?;
             }
 
@@ -97,18 +430,246 @@ impl Repo {
     }
 }
 
+/// Code of a package that is executed when building it or its dependents
+#[derive(Default, Clone, Copy)]
+struct BuildTimeCode {
+    build_script: bool,
+    proc_macro: bool,
+}
+
+impl BuildTimeCode {
+    /// Marker used in `verify` output: `B` for a build script, `P` for a proc-macro
+    fn marker(self) -> String {
+        let mut marker = String::new();
+        if self.build_script {
+            marker.push('B');
+        }
+        if self.proc_macro {
+            marker.push('P');
+        }
+        if marker.is_empty() {
+            marker.push('-');
+        }
+        marker
+    }
+}
+
+/// Package the crate in the current directory like `cargo publish` would
+///
+/// Returns the name, version, digest and declared repository of the crate
+/// as it will be seen by anyone downloading it from crates.io.
+fn package_current_crate(
+    repo: &Repo,
+    allow_dirty: bool,
+    config: &crev_lib::local::UserConfig,
+) -> Result<(String, String, crev_data::Digest, Option<String>)> {
+    let workspace = cargo::core::Workspace::new(&repo.manifest_path, &repo.config)?;
+    let pkg = workspace.current()?;
+    let name = pkg.name().to_string();
+    let version = pkg.version().to_string();
+
+    let crate_file = cargo::ops::package(
+        &workspace,
+        &cargo::ops::PackageOpts {
+            config: &repo.config,
+            list: false,
+            check_metadata: true,
+            allow_dirty,
+            verify: false,
+            jobs: None,
+            target: None,
+            registry: None,
+        },
+    )?
+    .ok_or_else(|| format_err!("No crate file was created"))?;
+
+    let tmp_dir = tempdir::TempDir::new("crev-publish-check")?;
+    let crate_file = std::fs::File::open(crate_file.path())?;
+    tar::Archive::new(flate2::read::GzDecoder::new(crate_file)).unpack(tmp_dir.path())?;
+
+    let pkg_dir = tmp_dir.path().join(format!("{}-{}", name, version));
+    let digest = crev_lib::get_dir_digest_of_type(
+        &pkg_dir,
+        &digest_ignore_list(&config.digest_ignore),
+        DigestType::Blake2b,
+        &config.digest_options()?,
+    )?;
+    let repository = registry::declared_repository(&pkg_dir)?;
+
+    Ok((name, version, digest, repository))
+}
+
 fn cargo_ignore_list() -> HashSet<PathBuf> {
     let mut ignore_list = HashSet::new();
     ignore_list.insert(PathBuf::from(".cargo-ok"));
+    ignore_list.insert(PathBuf::from(vendor::CHECKSUM_FILE));
     ignore_list.insert(PathBuf::from("Cargo.lock"));
     ignore_list.insert(PathBuf::from("target"));
     ignore_list
 }
 
-fn review_crate(args: &opts::CrateSelectorNameRequired, trust: TrustOrDistrust) -> Result<()> {
-    let repo = Repo::auto_open_cwd()?;
-    let (pkg_dir, crate_version) = repo.find_dependency_dir(&args.name, args.version.as_deref())?;
+/// `cargo_ignore_list` extended with paths from `digest-ignore` config or a review
+fn digest_ignore_list(extra: &[String]) -> HashSet<PathBuf> {
+    let mut ignore_list = cargo_ignore_list();
+    ignore_list.extend(extra.iter().map(PathBuf::from));
+    ignore_list
+}
+
+/// Verification status of a crates.io package version with `digests`
+///
+/// Issues and reviews of other versions applying to it count too (see
+/// `TrustDB::verify_package_version`). Every command showing the status of
+/// a package uses this, so that they agree.
+fn verify_package(
+    db: &crev_lib::trustdb::TrustDB,
+    trust_set: &crev_lib::trustdb::TrustSet,
+    digests: &[(DigestType, crev_data::Digest)],
+    name: &str,
+    version: &str,
+) -> crev_lib::VerificationStatus {
+    db.verify_package_version(digests, PROJECT_SOURCE_CRATES_IO, name, version, trust_set)
+}
+
+/// Digests of a crates.io package to verify it with
+///
+/// Besides the digest calculated with own `digest-ignore` paths, includes
+/// digests calculated with the paths ignored by reviews of the package from
+/// the `trust_set`, each only if it matches the digest of the review that
+/// ignored them, so that trusted reviews match even if their authors
+/// excluded different files. Paths ignored by other reviews are not used.
+fn get_package_digests(
+    db: &crev_lib::trustdb::TrustDB,
+    trust_set: &crev_lib::trustdb::TrustSet,
+    path: &Path,
+    name: &str,
+    version: &str,
+    config: &crev_lib::local::UserConfig,
+) -> Result<Vec<(DigestType, crev_data::Digest)>> {
+    let options = config.digest_options()?;
+    let mut digests = crev_lib::get_dir_digests(
+        path,
+        &digest_ignore_list(&config.digest_ignore),
+        &options,
+        db,
+    )?;
+
+    let mut own_ignore = config.digest_ignore.clone();
+    own_ignore.sort();
+    for review in
+        db.get_package_reviews_for_package(PROJECT_SOURCE_CRATES_IO, Some(name), Some(version))
+    {
+        if !trust_set.contains(&review.from.id) {
+            continue;
+        }
+        let mut ignore = review.package.digest_ignore.clone();
+        ignore.sort();
+        let digest_type = match review.package.digest_type.parse::<DigestType>() {
+            Ok(digest_type) if ignore != own_ignore => digest_type,
+            _ => continue,
+        };
+        let digest = crev_lib::get_dir_digest_of_type(
+            path,
+            &digest_ignore_list(&ignore),
+            digest_type,
+            &options,
+        )?;
+        let matches = digest.as_slice() == review.package.digest.as_slice();
+        if matches && !digests.contains(&(digest_type, digest.clone())) {
+            digests.push((digest_type, digest));
+        }
+    }
+
+    if log_enabled!(log::Level::Debug) {
+        for review in
+            db.get_package_reviews_for_package(PROJECT_SOURCE_CRATES_IO, Some(name), Some(version))
+        {
+            let matches = digests.iter().any(|(digest_type, digest)| {
+                review.package.digest_type == digest_type.as_str()
+                    && review.package.digest == digest.as_slice()
+            });
+            if !matches {
+                let local_digests: Vec<_> = digests
+                    .iter()
+                    .map(|(digest_type, digest)| format!("{} ({})", digest, digest_type.as_str()))
+                    .collect();
+                debug!(
+                    "{} {}: review by {} is of digest {} ({}), but the sources have {}",
+                    name,
+                    version,
+                    review.from.id,
+                    crev_data::Digest::from_vec(review.package.digest.clone()),
+                    review.package.digest_type,
+                    local_digests.join(", ")
+                );
+            }
+        }
+    }
+
+    Ok(digests)
+}
+
+fn review_crate(args: &opts::ReviewCrate, trust: TrustOrDistrust) -> Result<()> {
+    let digest_type: DigestType = args.digest_type.parse()?;
+    let record_unsafe = args.unsafe_;
+    let publish = args.publish;
+    let unsigned = args.unsigned;
+    let diff_base = args.diff_base.as_ref();
+    let no_diff = args.no_diff;
+    let sandbox = args.sandbox;
+    if let Some(path) = &args.path {
+        let version = match &args.crate_.version {
+            Some(version) => version,
+            None => bail!("The version of the package in {} is needed", path.display()),
+        };
+        let local = Local::auto_open()?;
+        let config = local.load_user_config()?;
+        let digest = crev_lib::get_dir_digest_of_type(
+            path,
+            &digest_ignore_list(&config.digest_ignore),
+            digest_type,
+            &config.digest_options()?,
+        )?;
+        if sandbox {
+            open::open_crate(path, &config, None, true)?;
+        }
+        let unsafe_count = if record_unsafe {
+            Some(unsafe_count::count_unsafe(path)?)
+        } else {
+            None
+        };
+        let registry_info = RegistryInfo {
+            checksum: None,
+            repository: registry::declared_repository(path)?,
+        };
+        return create_package_review(
+            &local,
+            &args.source,
+            &args.crate_.name,
+            version,
+            digest_type,
+            digest,
+            unsafe_count,
+            registry_info,
+            trust,
+            publish,
+            unsigned,
+        );
+    }
+    let repo = if args.standalone {
+        None
+    } else {
+        Some(Repo::auto_open_cwd()?)
+    };
+    let args = &args.crate_;
+    let find_pkg_dir = || match &repo {
+        Some(repo) => repo.find_dependency_dir(&args.name, args.version.as_deref()),
+        None => download_crate(&args.name, args.version.as_deref(), false),
+    };
+    let (pkg_dir, crate_version) = find_pkg_dir()?;
     let local = Local::auto_open()?;
+    let config = local.load_user_config()?;
+    let ignore_list = digest_ignore_list(&config.digest_ignore);
+    let options = config.digest_options()?;
 
     // to protect from creating a digest from a crate in unclean state
     // we move the old directory, download a fresh one and double
@@ -118,14 +679,14 @@ fn review_crate(args: &opts::CrateSelectorNameRequired, trust: TrustOrDistrust)
         std::fs::remove_dir_all(&reviewed_pkg_dir)?;
     }
     std::fs::rename(&pkg_dir, &reviewed_pkg_dir)?;
-    let (pkg_dir_second, crate_version_second) =
-        repo.find_dependency_dir(&args.name, args.version.as_deref())?;
+    let (pkg_dir_second, crate_version_second) = find_pkg_dir()?;
     assert_eq!(pkg_dir, pkg_dir_second);
     assert_eq!(crate_version, crate_version_second);
 
-    let digest_clean = crev_lib::get_recursive_digest_for_dir(&pkg_dir, &cargo_ignore_list())?;
+    let digest_clean =
+        crev_lib::get_dir_digest_of_type(&pkg_dir, &ignore_list, digest_type, &options)?;
     let digest_reviewed =
-        crev_lib::get_recursive_digest_for_dir(&reviewed_pkg_dir, &cargo_ignore_list())?;
+        crev_lib::get_dir_digest_of_type(&reviewed_pkg_dir, &ignore_list, digest_type, &options)?;
 
     if digest_clean != digest_reviewed {
         bail!(
@@ -138,32 +699,244 @@ fn review_crate(args: &opts::CrateSelectorNameRequired, trust: TrustOrDistrust)
     }
     std::fs::remove_dir_all(&reviewed_pkg_dir)?;
 
-    let passphrase = crev_common::read_passphrase()?;
-    let id = local.read_current_unlocked_id(&passphrase)?;
+    if !no_diff {
+        let base_version = match diff_base {
+            Some(base) => Some(semver::Version::parse(base)?),
+            None => {
+                let (db, _trust_set) = local.load_db(&default())?;
+                diff::find_reviewed_base_version(
+                    &db,
+                    &local.get_current_userid()?,
+                    PROJECT_SOURCE_CRATES_IO,
+                    &args.name,
+                    &crate_version,
+                )
+            }
+        };
+        if let Some(base_version) = base_version {
+            eprintln!("Changes since {} {}:", args.name, base_version);
+            let (base_dir, _) = download_crate(&args.name, Some(&base_version.to_string()), false)?;
+            diff::show_diff(&base_dir, &pkg_dir, config.diff_cmd.as_deref())?;
+            if !crev_common::yes_or_no_was_y("Continue to the review? (y/n) ")? {
+                bail!("User canceled");
+            }
+        }
+    }
+
+    if sandbox {
+        open::open_crate(&pkg_dir, &config, None, true)?;
+    }
+
+    let unsafe_count = if record_unsafe {
+        Some(unsafe_count::count_unsafe(&pkg_dir)?)
+    } else {
+        None
+    };
+
+    let registry_info = RegistryInfo::of_package_dir(&pkg_dir)?;
+    registry_info.warn_about_repository_change(&local, &args.name, &crate_version.to_string())?;
+
+    create_package_review(
+        &local,
+        PROJECT_SOURCE_CRATES_IO,
+        &args.name,
+        &crate_version.to_string(),
+        digest_type,
+        digest_clean,
+        unsafe_count,
+        registry_info,
+        trust,
+        publish,
+        unsigned,
+    )
+}
+
+/// Verification status of every (non-local) dependency in `repo`
+fn dependency_statuses(
+    local: &Local,
+    repo: &Repo,
+    db: &crev_lib::trustdb::TrustDB,
+    trust_set: &crev_lib::trustdb::TrustSet,
+) -> Result<watch::Statuses> {
+    let config = local.load_user_config()?;
+    let local_reviews = local.load_local_reviews()?;
+    let current_dir = std::env::current_dir()?;
+    let mut statuses = watch::Statuses::new();
+    repo.for_every_dependency_dir(|pkg_id, path| {
+        if path.starts_with(&current_dir) {
+            return Ok(());
+        }
+        let name = pkg_id.name().to_string();
+        let version = pkg_id.version().to_string();
+        let digests = get_package_digests(db, trust_set, path, &name, &version, &config)?;
+        let status = match verify_package(db, trust_set, &digests, &name, &version) {
+            crev_lib::VerificationStatus::Unknown if local_reviews.find(&digests).is_some() => {
+                crev_lib::VerificationStatus::Verified
+            }
+            status => status,
+        };
+        statuses.insert((name, version), status);
+        Ok(())
+    })?;
+    Ok(statuses)
+}
+
+/// `verify deps --unsafe` column: amount of `unsafe` code in `path`
+///
+/// Followed by the change since the latest trusted review (of another version)
+/// that recorded it.
+fn unsafe_column(
+    db: &crev_lib::trustdb::TrustDB,
+    trust_set: &crev_lib::trustdb::TrustSet,
+    path: &Path,
+    name: &str,
+    version: &str,
+) -> Result<String> {
+    let count = unsafe_count::count_unsafe(path)?.total();
+    let reviewed = db
+        .get_package_reviews_for_package(PROJECT_SOURCE_CRATES_IO, Some(name), None)
+        .filter(|review| review.package.version != version && trust_set.contains(&review.from.id))
+        .filter_map(|review| review.unsafe_count.map(|count| (review.date, count)))
+        .max_by_key(|(date, _)| *date);
+    let change = match reviewed {
+        Some((_, reviewed)) => format!("{:+}", count as i64 - reviewed.total() as i64),
+        None => String::new(),
+    };
+    Ok(format!("{:>5} {:>5}", count, change))
+}
+
+/// What the registry and the manifest said about a package at review time
+struct RegistryInfo {
+    checksum: Option<String>,
+    repository: Option<String>,
+}
+
+impl RegistryInfo {
+    fn of_package_dir(pkg_dir: &Path) -> Result<Self> {
+        Ok(RegistryInfo {
+            checksum: registry::crate_file_checksum(pkg_dir)?,
+            repository: registry::declared_repository(pkg_dir)?,
+        })
+    }
+
+    /// Warn if the repository differs from the one recorded in the newest
+    /// review of another version of the package
+    fn warn_about_repository_change(&self, local: &Local, name: &str, version: &str) -> Result<()> {
+        let repository = match &self.repository {
+            Some(repository) => repository,
+            None => return Ok(()),
+        };
+        let (db, _trust_set) = local.load_db(&default())?;
+        let reviewed = db
+            .get_package_reviews_for_package(PROJECT_SOURCE_CRATES_IO, Some(name), None)
+            .filter(|review| review.package.version != version)
+            .filter_map(|review| {
+                review
+                    .package
+                    .repository
+                    .clone()
+                    .map(|repository| (review.date, review.package.version, repository))
+            })
+            .max_by_key(|(date, _, _)| *date);
+        if let Some((_, reviewed_version, reviewed_repository)) = reviewed {
+            if reviewed_repository.trim_end_matches('/') != repository.trim_end_matches('/') {
+                warn!(
+                    "{} {} declares repository {}, but it was {} when {} was reviewed",
+                    name, version, repository, reviewed_repository, reviewed_version
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Interactively create, sign and store a review of a crates.io package
+///
+/// With `unsigned`, the review is printed without a signature instead.
+#[allow(clippy::too_many_arguments)]
+fn create_package_review(
+    local: &Local,
+    source: &str,
+    name: &str,
+    version: &str,
+    digest_type: DigestType,
+    digest: crev_data::Digest,
+    unsafe_count: Option<proof::review::UnsafeCount>,
+    registry_info: RegistryInfo,
+    trust: TrustOrDistrust,
+    publish: bool,
+    unsigned: bool,
+) -> Result<()> {
+    let signer = if unsigned {
+        None
+    } else {
+        let passphrase = crev_common::read_passphrase()?;
+        let id = local.read_current_unlocked_id(&passphrase)?;
+        Some((id, passphrase))
+    };
+    let from = match &signer {
+        Some((id, _)) => id.id.to_owned(),
+        None => local.read_current_locked_id()?.to_pubid(),
+    };
 
     let review = proof::review::PackageBuilder::default()
-        .from(id.id.to_owned())
+        .from(from)
         .package(proof::PackageInfo {
             id: None,
-            source: PROJECT_SOURCE_CRATES_IO.to_owned(),
-            name: args.name.clone(),
-            version: crate_version.to_string(),
-            digest: digest_clean.into_vec(),
-            digest_type: proof::default_digest_type(),
+            source: source.to_owned(),
+            name: name.to_owned(),
+            version: version.to_owned(),
+            digest: digest.into_vec(),
+            digest_type: digest_type.to_string(),
             revision: "".into(),
             revision_type: proof::default_revision_type(),
+            digest_ignore: local.load_user_config()?.digest_ignore,
+            checksum: registry_info.checksum,
+            repository: registry_info.repository,
         })
         .review(trust.to_review())
+        .unsafe_count(unsafe_count)
         .build()
         .map_err(|e| format_err!("{}", e))?;
 
     let review = crev_lib::util::edit_proof_content_iteractively(&review.into())?;
 
+    let (id, passphrase) = match signer {
+        Some(signer) => signer,
+        None => return print_unsigned_proof(&review),
+    };
     let proof = review.sign_by(&id)?;
 
-    local.insert(&proof)?;
+    local.insert_own(&proof, &id)?;
+
+    let action = match trust {
+        TrustOrDistrust::Trust => "Review",
+        TrustOrDistrust::Distrust => "Flag",
+    };
+    publish_if_wanted(
+        local,
+        publish,
+        &passphrase,
+        &format!("{} {} {}", action, name, version),
+    )
+}
+
+/// Print a proof to be signed on the machine holding the Id
+fn print_unsigned_proof(content: &proof::Content) -> Result<()> {
+    print!("{}", content.to_unsigned_string());
+    eprintln!("Sign it with `cargo crev proof sign` where your Id is, and add the signature with `cargo crev proof attach-signature`");
     Ok(())
 }
+
+/// Commit and push new proofs, if asked to with `--publish` or `auto-publish` in the config
+fn publish_if_wanted(local: &Local, publish: bool, passphrase: &str, message: &str) -> Result<()> {
+    if publish || local.load_user_config()?.auto_publish {
+        let secrets = crev_lib::secret::FixedSecretProvider(passphrase.to_owned());
+        local.publish_proofs(message, &secrets)?;
+    }
+    Ok(())
+}
+
 const PROJECT_SOURCE_CRATES_IO: &str = "https://crates.io";
 
 fn find_reviews(
@@ -179,15 +952,85 @@ fn find_reviews(
     ))
 }
 
-fn list_reviews(crate_: &opts::CrateSelector) -> Result<()> {
+/// Find Ids matching a string that is either an Id, or a proof repository URL
+fn find_ids_by_id_or_url(db: &crev_lib::trustdb::TrustDB, id_or_url: &str) -> Result<Vec<Id>> {
+    if let Ok(id) = Id::crevid_from_str(id_or_url) {
+        if db.all_known_ids().contains(&id) {
+            return Ok(vec![id]);
+        }
+    }
+
+    let ids = db.lookup_ids_by_url(id_or_url);
+    if ids.is_empty() {
+        bail!("No known Id matches {}", id_or_url);
+    }
+    Ok(ids)
+}
+
+fn list_reviews(args: &opts::QueryReview) -> Result<()> {
+    let crate_ = &args.crate_;
+    if args.author.is_some() || args.grep.is_some() {
+        let local = crev_lib::Local::auto_open()?;
+        let (db, _trust_set) = local.load_db(&default())?;
+        let authors = match args.author {
+            Some(ref author) => Some(find_ids_by_id_or_url(&db, author)?),
+            None => None,
+        };
+        let reviews: Vec<_> = match (&args.grep, &authors) {
+            (Some(grep), _) => db.search_package_reviews(grep).collect(),
+            (None, Some(authors)) => authors
+                .iter()
+                .flat_map(|id| db.get_package_reviews_by_author(id))
+                .collect(),
+            (None, None) => unreachable!(),
+        };
+        for review in reviews {
+            if review.package.source == PROJECT_SOURCE_CRATES_IO
+                && crate_.name.iter().all(|name| *name == review.package.name)
+                && crate_
+                    .version
+                    .iter()
+                    .all(|version| *version == review.package.version)
+                && authors
+                    .iter()
+                    .all(|authors| authors.contains(&review.from.id))
+            {
+                println!("{}", review);
+            }
+        }
+        return Ok(());
+    }
+
     // TODO: take trust params?
     for review in find_reviews(crate_, &default())? {
         println!("{}", review);
     }
 
+    // notes go to stderr, to keep the output a valid list of proofs
+    if let Some(name) = &crate_.name {
+        let local = crev_lib::Local::auto_open()?;
+        for note in local.load_notes()?.get(name, crate_.version.as_deref()) {
+            eprintln!("Note: {}", format_note(note));
+        }
+    }
+
     Ok(())
 }
 
+/// Note like `foo 1.0.0 (2019-05-01): check the ffi module next time`
+fn format_note(note: &crev_lib::note::Note) -> String {
+    format!(
+        "{} {}({}): {}",
+        note.name,
+        note.version
+            .as_ref()
+            .map(|version| format!("{} ", version))
+            .unwrap_or_default(),
+        note.date.format("%Y-%m-%d"),
+        note.text
+    )
+}
+
 fn tilda_home_path(home: &Option<PathBuf>, path: &Path) -> String {
     if let Some(home) = home {
         match path.strip_prefix(home) {
@@ -199,14 +1042,59 @@ fn tilda_home_path(home: &Option<PathBuf>, path: &Path) -> String {
     }
 }
 
+/// Log messages of crev crates on stderr, as much as `verbosity` asks for
+///
+/// `RUST_LOG` (as in `env_logger`) overrides it.
+fn init_logger(verbosity: &opts::Verbosity) {
+    use log::LevelFilter;
+    use std::io::Write;
+    let level = match verbosity.verbose {
+        _ if verbosity.quiet => LevelFilter::Error,
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Info,
+        2 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+    let mut builder = env_logger::Builder::new();
+    builder
+        .format(|buf, record| {
+            let level = match record.level() {
+                log::Level::Warn => "warning".to_owned(),
+                level => level.to_string().to_lowercase(),
+            };
+            writeln!(buf, "{}: {}", level, record.args())
+        })
+        .filter(None, LevelFilter::Warn);
+    for module in &["cargo_crev", "crev_lib", "crev_data", "crev_common"] {
+        builder.filter(Some(module), level);
+    }
+    if let Ok(filters) = std::env::var("RUST_LOG") {
+        builder.parse(&filters);
+    }
+    builder.init();
+}
+
 fn main() -> Result<()> {
     let opts = opts::Opts::from_args();
+    set_cargo_flags(&opts.cargo_flags);
+    init_logger(&opts.verbosity);
+    let verbosity = opts.verbosity;
     let opts::MainCommand::Crev(command) = opts.command;
     match command {
         opts::Command::New(cmd) => match cmd {
             opts::New::Id(args) => {
-                let res =
-                    crev_lib::generate_id(args.url, args.github_username, args.use_https_push);
+                let ssh_key = match (&args.ssh_key, args.ssh_agent) {
+                    (public_key, true) => Some(crev_lib::SshKey::Agent(public_key.as_deref())),
+                    (Some(key), false) => Some(crev_lib::SshKey::File(key)),
+                    (None, false) => None,
+                };
+                let res = crev_lib::generate_id(
+                    args.url,
+                    args.github_username,
+                    args.use_https_push,
+                    ssh_key,
+                    &crev_lib::secret::TerminalSecretProvider,
+                );
                 if res.is_err() {
                     eprintln!("Visit https://github.com/dpc/crev/wiki/Proof-Repository for help.");
                 }
@@ -225,25 +1113,218 @@ fn main() -> Result<()> {
         opts::Command::Verify(cmd) => match cmd {
             opts::Verify::Deps(args) => {
                 let local = crev_lib::Local::auto_open()?;
-                let (db, trust_set) = local.load_db(&args.trust_params.clone().into())?;
+                let config = local.load_user_config()?;
+                if args.fetch || config.fetch_before_verify {
+                    local.fetch_trusted(args.trust_params.clone().into(), false)?;
+                    if !args.no_rustsec {
+                        rustsec::AdvisoryDb::fetch(&local)?;
+                    }
+                }
+                let mut repo = Repo::auto_open_cwd()?;
+                repo.set_features(&args.features);
+                let (db, trust_set) = local.load_db_for_crates(
+                    &args.trust_params.clone().into(),
+                    &repo.dependency_names()?,
+                )?;
+                let rustsec_db = if args.no_rustsec {
+                    None
+                } else {
+                    rustsec::AdvisoryDb::load(&local)?
+                };
 
-                let repo = Repo::auto_open_cwd()?;
                 let ignore_list = cargo_ignore_list();
                 let current_dir = std::env::current_dir()?;
                 let cratesio = crates_io::Client::new(&local)?;
+                // only the text output shows crates.io data
+                let popular_crates = if args.no_suspicious_check || args.output != "text" {
+                    None
+                } else {
+                    Some(cratesio.get_popular_crates().unwrap_or_else(|e| {
+                        warn!("Couldn't get popular crates from crates.io: {}", e);
+                        vec![]
+                    }))
+                };
                 let home_dir = dirs::home_dir();
+                let max_depth = if args.direct_only {
+                    Some(1)
+                } else {
+                    args.max_depth
+                };
+                let depths = match max_depth {
+                    Some(_) => Some(repo.dependency_depths()?),
+                    None => None,
+                };
+                let checksums = if args.no_registry_check {
+                    None
+                } else {
+                    Some(repo.registry_checksums()?)
+                };
+                let kinds = if args.no_dev || args.no_build || args.only_build {
+                    Some(repo.dependency_kinds()?)
+                } else {
+                    None
+                };
+                let build_time_code = repo.build_time_code()?;
+                let member_dependencies = if args.package.is_some() || args.per_member {
+                    Some(repo.member_dependencies()?)
+                } else {
+                    None
+                };
+                let only = match (&args.package, &member_dependencies) {
+                    (Some(member), Some(member_dependencies)) => {
+                        Some(member_dependencies.get(member).ok_or_else(|| {
+                            format_err!("`{}` is not a member of the workspace", member)
+                        })?)
+                    }
+                    _ => None,
+                };
+                let local_reviews = if args.no_local_reviews {
+                    default()
+                } else {
+                    local.load_local_reviews()?
+                };
+                let mut statuses = HashMap::new();
+                let mut crates_by_owner: BTreeMap<String, Vec<String>> = BTreeMap::new();
+                let licenses = repo.licenses()?;
+                let policy = repo.load_policy()?;
+                let today = crev_common::now().date().naive_local();
+                let mut report = vec![];
+                let mut digest_pins = local.load_digest_pins()?;
+                let mut registry_checked = registry::CheckCache::load(&local)?;
+                // digests calculated with other settings are pinned separately
+                let pin_settings = crev_lib::digest_pin::PinSettings::new(
+                    &config.digest_ignore,
+                    &config.digest_options()?,
+                );
+                let mut changed_digests = vec![];
+                let progress = progress::Progress::new(verbosity.quiet);
+                let mut stdout = termcolor::StandardStream::stdout(term::color_choice(&args.color));
 
-                repo.for_every_dependency_dir(|pkg_id, path| {
-                    if path.starts_with(&current_dir) {
-                        // ignore local dependencies
+                repo.for_every_dependency_dir_with_progress(&progress, |pkg_id, path| {
+                    // ignore local dependencies (but not ones vendored in the project)
+                    if path.starts_with(&current_dir) && pkg_id.source_id().is_path() {
                         return Ok(());
                     }
 
+                    if let Some(only) = only {
+                        if !only.contains(pkg_id) {
+                            return Ok(());
+                        }
+                    }
+
+                    if let (Some(depths), Some(max_depth)) = (&depths, max_depth) {
+                        if depths.get(pkg_id).map(|&depth| depth > max_depth) == Some(true) {
+                            return Ok(());
+                        }
+                    }
+
+                    if let Some(kinds) = &kinds {
+                        if let Some(kinds) = kinds.get(pkg_id) {
+                            let is_dev = kinds.contains(&DepKind::Development);
+                            let is_build = kinds.contains(&DepKind::Build);
+                            let is_normal = kinds.contains(&DepKind::Normal);
+                            if (args.no_dev && is_dev && !is_build && !is_normal)
+                                || (args.no_build && is_build && !is_dev && !is_normal)
+                                || (args.only_build && !is_build)
+                            {
+                                return Ok(());
+                            }
+                        }
+                    }
+
                     let pkg_name = pkg_id.name().as_str();
                     let pkg_version = pkg_id.version().to_string();
+                    let checksum = checksums.as_ref().and_then(|c| c.get(pkg_id));
+
+                    let vendored_path;
+                    let path = if let Some(vendor_dir) = &args.vendor {
+                        vendored_path =
+                            match vendor::find_package_dir(vendor_dir, pkg_name, &pkg_version)? {
+                                Some(vendored_path) => vendored_path,
+                                None => {
+                                    progress.clear();
+                                    warn!(
+                                        "{} {}: not vendored in {}",
+                                        pkg_name,
+                                        pkg_version,
+                                        vendor_dir.display()
+                                    );
+                                    return Ok(());
+                                }
+                            };
+                        for problem in
+                            vendor::check_package_dir(&vendored_path, checksum.map(String::as_str))?
+                        {
+                            progress.clear();
+                            warn!("{} {}: {}", pkg_name, pkg_version, problem);
+                        }
+                        vendored_path.as_path()
+                    } else {
+                        if let Some(checksum) = checksum {
+                            if let Some(problem) =
+                                registry_checked.check_package_dir(path, checksum, &ignore_list)?
+                            {
+                                progress.clear();
+                                warn!("{} {}: {}", pkg_name, pkg_version, problem);
+                            }
+                        }
+                        path
+                    };
 
-                    let digest = crev_lib::get_dir_digest(&path, &ignore_list)?;
-                    let result = db.verify_digest(&digest, &trust_set);
+                    let digests = get_package_digests(
+                        &db,
+                        &trust_set,
+                        path,
+                        pkg_name,
+                        &pkg_version,
+                        &config,
+                    )?;
+                    let digest_str = digests[0].1.to_string();
+                    // git dependencies keep their version across commits
+                    let pin_source = pkg_id.source_id().to_url().to_string();
+                    if let Some(pin) = digest_pins.check(
+                        &pin_source,
+                        pkg_name,
+                        &pkg_version,
+                        &digest_str,
+                        &pin_settings,
+                    ) {
+                        progress.clear();
+                        if args.repin {
+                            warn!(
+                                "{} {}: pinned the new digest {} (was {})",
+                                pkg_name, pkg_version, digest_str, pin.digest
+                            );
+                            digest_pins.repin(
+                                &pin_source,
+                                pkg_name,
+                                &pkg_version,
+                                &digest_str,
+                                &pin_settings,
+                            );
+                        } else {
+                            error!(
+                                "{} {}: DIGEST CHANGED to {} from {}, seen on {}; the sources were \
+                                 modified, in the local registry cache or by re-publishing",
+                                pkg_name,
+                                pkg_version,
+                                digest_str,
+                                pin.digest,
+                                pin.date.format("%Y-%m-%d")
+                            );
+                            changed_digests.push(format!("{} {}", pkg_name, pkg_version));
+                        }
+                    }
+                    let result = verify_package(&db, &trust_set, &digests, pkg_name, &pkg_version);
+                    // negative reviews from the trust set still take precedence
+                    let locally_reviewed = result == crev_lib::VerificationStatus::Unknown
+                        && local_reviews.find(&digests).is_some();
+                    let result = if locally_reviewed {
+                        crev_lib::VerificationStatus::Verified
+                    } else {
+                        result
+                    };
+                    let digest = &digests[0].1;
                     let pkg_review_count =
                         db.get_package_review_count(PROJECT_SOURCE_CRATES_IO, Some(pkg_name), None);
                     let pkg_version_review_count = db.get_package_review_count(
@@ -252,54 +1333,662 @@ fn main() -> Result<()> {
                         Some(&pkg_version),
                     );
 
+                    let advisories = db.get_advisories_for_version(
+                        PROJECT_SOURCE_CRATES_IO,
+                        pkg_name,
+                        &pkg_version,
+                        &trust_set,
+                    );
+                    let rustsec_advisories = rustsec_db
+                        .as_ref()
+                        .map(|rustsec_db| rustsec_db.get_advisories(pkg_name, pkg_id.version()))
+                        .unwrap_or_default();
+                    let mut advisory_ids: Vec<String> = advisories
+                        .iter()
+                        .flat_map(|review| review.advisory.iter())
+                        .flat_map(|advisory| advisory.ids.iter().cloned())
+                        .collect();
+                    let crev_advisory_count = advisory_ids.len();
+                    for advisory in &rustsec_advisories {
+                        // the same problem might be reported in both
+                        if !advisory_ids.contains(&advisory.id)
+                            && !advisory.aliases.iter().any(|a| advisory_ids.contains(a))
+                        {
+                            advisory_ids.push(advisory.id.clone());
+                        }
+                    }
+                    // crev advisories don't need to have any ids
+                    let advisory_count = advisories.len().max(crev_advisory_count)
+                        + advisory_ids.len()
+                        - crev_advisory_count;
+                    let build_time_marker = build_time_code
+                        .get(pkg_id)
+                        .cloned()
+                        .unwrap_or_default()
+                        .marker();
+                    statuses.insert(pkg_id.clone(), result);
+                    let license = licenses.get(pkg_id).map(String::as_str).unwrap_or("-");
+                    let license_denied = policy.is_license_denied(license);
+                    let needs_attention = result != crev_lib::VerificationStatus::Verified
+                        || advisory_count > 0
+                        || license_denied;
+                    let accepted = match policy.find_exception(pkg_name, &pkg_version) {
+                        Some(exception) if needs_attention => {
+                            if exception.is_expired(today) {
+                                progress.clear();
+                                warn!(
+                                    "{} {}: policy exception expired on {}",
+                                    pkg_name,
+                                    pkg_version,
+                                    exception.expires.as_ref().map_or("", String::as_str)
+                                );
+                                None
+                            } else {
+                                Some(exception.reason.clone())
+                            }
+                        }
+                        _ => None,
+                    };
+
+                    let users = db.get_usage_count(
+                        PROJECT_SOURCE_CRATES_IO,
+                        pkg_name,
+                        &pkg_version,
+                        &trust_set,
+                    );
+
+                    if args.output != "text" {
+                        let mut reviewers: Vec<_> = db
+                            .get_package_reviews_for_package(
+                                PROJECT_SOURCE_CRATES_IO,
+                                Some(pkg_name),
+                                Some(&pkg_version),
+                            )
+                            .filter(|review| {
+                                digests.iter().any(|(digest_type, digest)| {
+                                    review.package.digest_type == digest_type.as_str()
+                                        && review.package.digest == digest.as_slice()
+                                })
+                            })
+                            .map(|review| report::Reviewer {
+                                id: review.from.id.to_string(),
+                                url: db.lookup_url(&review.from.id).map(|url| url.url.clone()),
+                                trusted: trust_set.contains(&review.from.id),
+                            })
+                            .collect();
+                        if locally_reviewed {
+                            reviewers.push(report::Reviewer {
+                                id: "local review".into(),
+                                url: None,
+                                trusted: true,
+                            });
+                        }
+                        let entry = report::Entry {
+                            name: pkg_name.to_owned(),
+                            version: pkg_version,
+                            result,
+                            reviewers,
+                            advisories: advisory_ids,
+                            build_time_code: build_time_marker,
+                            license: license.to_owned(),
+                            license_denied,
+                            accepted,
+                            users,
+                        };
+                        if args.output == "json-lines" {
+                            progress.clear();
+                            println!("{}", report::to_json_line(&entry));
+                        } else {
+                            report.push(entry);
+                        }
+                        return Ok(());
+                    }
+
                     let (version_downloads, total_downloads) = cratesio
                         .get_downloads_count(&pkg_name, &pkg_version)
                         .map(|(a, b)| (a.to_string(), b.to_string()))
                         .unwrap_or_else(|e| {
-                            eprintln!("Error: {}", e);
+                            progress.clear();
+                            error!("{}", e);
                             ("err".into(), "err".into())
                         });
+                    if let Some(popular_crates) = &popular_crates {
+                        // errors were just reported when getting downloads
+                        if let Ok(resp) = cratesio.get_crate(pkg_name) {
+                            for warning in suspicious::warnings(&resp, popular_crates) {
+                                progress.clear();
+                                warn!("{} {}: {}", pkg_name, pkg_version, warning);
+                            }
+                        }
+                    }
 
-                    if args.verbose {
-                        println!(
-                            "{:8} {:2} {:2} {:>7} {:>8} {} {:40}",
-                            result,
+                    if args.group_by_owner {
+                        match cratesio.get_owners(pkg_name) {
+                            Ok(owners) => {
+                                for owner in owners {
+                                    crates_by_owner
+                                        .entry(owner)
+                                        .or_default()
+                                        .push(format!("{} {}", pkg_name, pkg_version));
+                                }
+                            }
+                            Err(e) => {
+                                progress.clear();
+                                error!("{}", e);
+                            }
+                        }
+                    }
+
+                    let unsafe_column = if args.unsafe_ {
+                        format!(
+                            "{} ",
+                            unsafe_column(&db, &trust_set, path, pkg_name, &pkg_version)?
+                        )
+                    } else {
+                        String::new()
+                    };
+                    let size_column = if args.size {
+                        let size = code_size::measure(path)?;
+                        format!(
+                            "{:>7} {:>7} ",
+                            code_size::format_bytes(size.bytes),
+                            size.rust_lines
+                        )
+                    } else {
+                        String::new()
+                    };
+                    let license_column = if args.license {
+                        format!("{:24} ", license)
+                    } else {
+                        String::new()
+                    };
+                    let users_column = if args.users {
+                        format!("{:>5} ", users)
+                    } else {
+                        String::new()
+                    };
+                    let review_age_column = if args.review_age {
+                        let age = db
+                            .get_verifying_review_date(
+                                &digests,
+                                PROJECT_SOURCE_CRATES_IO,
+                                pkg_name,
+                                &pkg_version,
+                                &trust_set,
+                            )
+                            .map(|date| {
+                                let now = args.trust_params.as_of.unwrap_or_else(chrono::Utc::now);
+                                (now - date).num_days().to_string()
+                            })
+                            .unwrap_or_else(|| "-".into());
+                        format!("{:>5} ", age)
+                    } else {
+                        String::new()
+                    };
+
+                    let line = if verbosity.verbose > 0 {
+                        format!(
+                            "{:2} {:2} {:2} {:2} {}{}{}{}{}{:>7} {:>8} {} {:40} {}",
                             pkg_version_review_count,
                             pkg_review_count,
+                            advisory_count,
+                            build_time_marker,
+                            unsafe_column,
+                            size_column,
+                            license_column,
+                            users_column,
+                            review_age_column,
                             version_downloads,
                             total_downloads,
                             digest,
-                            tilda_home_path(&home_dir, &path)
-                        );
+                            tilda_home_path(&home_dir, &path),
+                            advisory_ids.join(" ")
+                        )
                     } else {
-                        println!(
-                            "{:8} {:2} {:2} {:>7} {:>8} {:40}",
-                            result,
+                        format!(
+                            "{:2} {:2} {:2} {:2} {}{}{}{}{}{:>7} {:>8} {:40}",
                             pkg_version_review_count,
                             pkg_review_count,
+                            advisory_count,
+                            build_time_marker,
+                            unsafe_column,
+                            size_column,
+                            license_column,
+                            users_column,
+                            review_age_column,
                             version_downloads,
                             total_downloads,
                             tilda_home_path(&home_dir, &path)
-                        );
+                        )
+                    };
+                    let line = if args.show_reviewers {
+                        let reviewers: Vec<String> = db
+                            .get_verifying_reviewers(
+                                &digests,
+                                PROJECT_SOURCE_CRATES_IO,
+                                pkg_name,
+                                &pkg_version,
+                                &trust_set,
+                            )
+                            .iter()
+                            .map(|id| match db.lookup_url(id) {
+                                Some(url) => url.url.clone(),
+                                None => id.to_string(),
+                            })
+                            .collect();
+                        format!("{} {}", line, reviewers.join(" "))
+                    } else {
+                        line
+                    };
+                    let line = if result == crev_lib::VerificationStatus::Flagged {
+                        let mut alternatives: Vec<String> = db
+                            .get_alternatives(PROJECT_SOURCE_CRATES_IO, pkg_name, &trust_set)
+                            .into_iter()
+                            .map(|alternative| alternative.alternative)
+                            .collect();
+                        alternatives.sort();
+                        alternatives.dedup();
+                        if alternatives.is_empty() {
+                            line
+                        } else {
+                            format!("{} alternatives: {}", line, alternatives.join(" "))
+                        }
+                    } else {
+                        line
+                    };
+                    let line = if license_denied {
+                        format!("{} denied license: {}", line, license)
+                    } else {
+                        line
+                    };
+                    let line = if locally_reviewed {
+                        format!("{} (local review)", line)
+                    } else {
+                        line
+                    };
+                    progress.clear();
+                    if let Some(reason) = accepted {
+                        let line = format!("{} accepted: {}", line, reason);
+                        term::print_accepted_line(&mut stdout, &result, &line)?;
+                    } else {
+                        // flagged crates, known vulnerabilities and denied licenses need attention
+                        let highlight = result == crev_lib::VerificationStatus::Flagged
+                            || advisory_count > 0
+                            || license_denied;
+                        term::print_status_line(&mut stdout, &result, highlight, &line)?;
                     }
 
                     Ok(())
                 })?;
-            }
-        },
-        opts::Command::Query(cmd) => match cmd {
-            opts::Query::Id(cmd) => match cmd {
-                opts::QueryId::Current => crev_lib::show_current_id()?,
-                opts::QueryId::Own => crev_lib::list_own_ids()?,
-                opts::QueryId::Trusted(args) => {
-                    let local = crev_lib::Local::auto_open()?;
-                    let (_db, trust_set) = local.load_db(&args.trust_params.into())?;
-                    for id in &trust_set {
-                        println!("{}", id);
-                    }
+                progress.clear();
+                registry_checked.store()?;
+                if digest_pins.is_changed() {
+                    local.store_digest_pins(&digest_pins)?;
                 }
-                opts::QueryId::All => {
-                    let local = crev_lib::Local::auto_open()?;
+
+                if let Some(member_dependencies) =
+                    member_dependencies.as_ref().filter(|_| args.per_member)
+                {
+                    // keep reports clean
+                    let text = args.output == "text";
+                    if text {
+                        println!();
+                    }
+                    for (member, dependencies) in member_dependencies {
+                        let count = |status: crev_lib::VerificationStatus| {
+                            dependencies
+                                .iter()
+                                .filter(|pkg_id| statuses.get(pkg_id) == Some(&status))
+                                .count()
+                        };
+                        let line = format!(
+                            "{}: {} verified, {} flagged, {} unknown",
+                            member,
+                            count(crev_lib::VerificationStatus::Verified),
+                            count(crev_lib::VerificationStatus::Flagged),
+                            count(crev_lib::VerificationStatus::Unknown)
+                        );
+                        if text {
+                            println!("{}", line);
+                        } else {
+                            eprintln!("{}", line);
+                        }
+                    }
+                }
+
+                // every version of a crate needs its own review
+                let mut versions_by_name: BTreeMap<&str, Vec<_>> = BTreeMap::new();
+                for pkg_id in statuses.keys() {
+                    versions_by_name
+                        .entry(pkg_id.name().as_str())
+                        .or_default()
+                        .push(pkg_id.version());
+                }
+                let duplicates: Vec<_> = versions_by_name
+                    .into_iter()
+                    .filter(|(_, versions)| versions.len() > 1)
+                    .collect();
+                if !duplicates.is_empty() {
+                    let mut lines = vec![format!(
+                        "Multiple versions of {} crates (each one needs its own review):",
+                        duplicates.len()
+                    )];
+                    for (name, mut versions) in duplicates {
+                        versions.sort();
+                        let versions: Vec<_> = versions.iter().map(ToString::to_string).collect();
+                        lines.push(format!("  {}: {}", name, versions.join(", ")));
+                    }
+                    // keep reports clean
+                    if args.output == "text" {
+                        println!();
+                        for line in lines {
+                            println!("{}", line);
+                        }
+                    } else {
+                        for line in lines {
+                            eprintln!("{}", line);
+                        }
+                    }
+                }
+
+                if args.group_by_owner && args.output == "text" {
+                    let mut owners: Vec<_> = crates_by_owner.into_iter().collect();
+                    owners.sort_by_key(|(_, crates)| std::cmp::Reverse(crates.len()));
+                    println!();
+                    for (owner, crates) in owners {
+                        println!("{} ({}): {}", owner, crates.len(), crates.join(", "));
+                    }
+                }
+
+                match args.output.as_str() {
+                    "html" => print!("{}", report::to_html(&report)),
+                    "markdown" => print!("{}", report::to_markdown(&report)),
+                    "sarif" => {
+                        let lock_file_path = repo.lock_file_path()?;
+                        let lock_file_uri = lock_file_path
+                            .strip_prefix(&current_dir)
+                            .unwrap_or(&lock_file_path)
+                            .to_string_lossy()
+                            .replace('\\', "/");
+                        let lock_file = fs::read_to_string(&lock_file_path)?;
+                        println!("{}", report::to_sarif(&report, &lock_file_uri, &lock_file));
+                    }
+                    _ => {}
+                }
+
+                if !changed_digests.is_empty() {
+                    bail!(
+                        "Digests of {} changed since they were first seen (`--repin` to accept \
+                         the new ones, once you know why)",
+                        changed_digests.join(", ")
+                    );
+                }
+            }
+            opts::Verify::Crate(args) => {
+                let local = crev_lib::Local::auto_open()?;
+                let (db, trust_set) = local.load_db(&args.trust_params.into())?;
+
+                let (path, version) =
+                    download_crate(&args.crate_.name, args.crate_.version.as_deref(), args.pre)?;
+                let version = version.to_string();
+                let digests = get_package_digests(
+                    &db,
+                    &trust_set,
+                    &path,
+                    &args.crate_.name,
+                    &version,
+                    &local.load_user_config()?,
+                )?;
+                let result = verify_package(&db, &trust_set, &digests, &args.crate_.name, &version);
+
+                let mut stdout = termcolor::StandardStream::stdout(term::color_choice(&args.color));
+                term::print_status_line(
+                    &mut stdout,
+                    &result,
+                    result == crev_lib::VerificationStatus::Flagged,
+                    &format!("{} {} {}", args.crate_.name, version, digests[0].1),
+                )?;
+                for review in db.get_package_reviews_for_package(
+                    PROJECT_SOURCE_CRATES_IO,
+                    Some(&args.crate_.name),
+                    Some(&version),
+                ) {
+                    if digests.iter().any(|(digest_type, digest)| {
+                        review.package.digest_type == digest_type.as_str()
+                            && review.package.digest == digest.as_slice()
+                    }) {
+                        println!("{}", review);
+                    }
+                }
+            }
+            opts::Verify::Coverage(args) => {
+                let local = crev_lib::Local::auto_open()?;
+                let (db, trust_set) = local.load_db(&args.trust_params.into())?;
+
+                let repo = Repo::auto_open_cwd()?;
+                let depths = repo.dependency_depths()?;
+                let config = local.load_user_config()?;
+                let current_dir = std::env::current_dir()?;
+
+                let mut all = coverage::Coverage::default();
+                let mut direct = coverage::Coverage::default();
+                let mut transitive = coverage::Coverage::default();
+
+                repo.for_every_dependency_dir(|pkg_id, path| {
+                    if path.starts_with(&current_dir) {
+                        // ignore local dependencies
+                        return Ok(());
+                    }
+
+                    let digests = get_package_digests(
+                        &db,
+                        &trust_set,
+                        path,
+                        pkg_id.name().as_str(),
+                        &pkg_id.version().to_string(),
+                        &config,
+                    )?;
+                    let result = verify_package(
+                        &db,
+                        &trust_set,
+                        &digests,
+                        pkg_id.name().as_str(),
+                        &pkg_id.version().to_string(),
+                    );
+                    let lines = code_size::count_rust_lines(path)?;
+
+                    all.add(&result, lines);
+                    if depths.get(pkg_id) == Some(&1) {
+                        direct.add(&result, lines);
+                    } else {
+                        transitive.add(&result, lines);
+                    }
+
+                    Ok(())
+                })?;
+
+                all.print("all");
+                if args.breakdown {
+                    println!();
+                    direct.print("direct");
+                    println!();
+                    transitive.print("transitive");
+                }
+            }
+            opts::Verify::Compare(args) => {
+                let local = crev_lib::Local::auto_open()?;
+                let mut repo = Repo::auto_open_cwd()?;
+                repo.set_features(&args.features);
+                let config = local.load_user_config()?;
+                let params: crev_lib::trustdb::TrustDistanceParams =
+                    args.trust_params.clone().into();
+                let base_params = config.apply_trust_profile(&args.base, params.clone())?;
+                let other_params = config.apply_trust_profile(&args.other, params)?;
+
+                let (db, base_trust_set) =
+                    local.load_db_for_crates(&base_params, &repo.dependency_names()?)?;
+                let other_trust_set = local.calculate_trust_set(&db, &other_params)?;
+                let base = dependency_statuses(&local, &repo, &db, &base_trust_set)?;
+                let other = dependency_statuses(&local, &repo, &db, &other_trust_set)?;
+
+                let changes = watch::changes(&base, &other);
+                for change in &changes {
+                    println!("{}", change);
+                }
+                eprintln!(
+                    "{} of {} dependencies verified differently with `{}` and `{}`",
+                    changes.len(),
+                    base.len(),
+                    args.base,
+                    args.other
+                );
+            }
+            opts::Verify::Tree(args) => {
+                let local = crev_lib::Local::auto_open()?;
+                let mut repo = Repo::auto_open_cwd()?;
+                repo.set_features(&args.features);
+                let (db, trust_set) = local.load_db_for_crates(
+                    &args.trust_params.clone().into(),
+                    &repo.dependency_names()?,
+                )?;
+                let statuses = dependency_statuses(&local, &repo, &db, &trust_set)?;
+                let graph = repo.dependency_graph()?;
+
+                let annotations = graph
+                    .deps
+                    .keys()
+                    .map(|pkg_id| {
+                        let name = pkg_id.name().to_string();
+                        let version = pkg_id.version().to_string();
+                        let reviews = (
+                            db.get_package_review_count(
+                                PROJECT_SOURCE_CRATES_IO,
+                                Some(&name),
+                                Some(&version),
+                            ),
+                            db.get_package_review_count(
+                                PROJECT_SOURCE_CRATES_IO,
+                                Some(&name),
+                                None,
+                            ),
+                        );
+                        let status = statuses.get(&(name, version)).cloned();
+                        (pkg_id.clone(), tree::Annotation { status, reviews })
+                    })
+                    .collect();
+                let mut stdout = termcolor::StandardStream::stdout(term::color_choice(&args.color));
+                tree::print(&mut stdout, &graph, &annotations, args.max_depth)?;
+            }
+        },
+        opts::Command::Tui(args) => {
+            tui::run(&args)?;
+        }
+        opts::Command::Serve(args) => {
+            serve::run(&args)?;
+        }
+        opts::Command::PublishCheck(args) => {
+            let local = crev_lib::Local::auto_open()?;
+            let (db, trust_set) = local.load_db(&args.trust_params.into())?;
+
+            let repo = Repo::auto_open_cwd()?;
+            let config = local.load_user_config()?;
+            let (name, version, digest, repository) =
+                package_current_crate(&repo, args.allow_dirty, &config)?;
+            println!("{} {} {}", name, version, digest);
+
+            let current_dir = std::env::current_dir()?;
+            let home_dir = dirs::home_dir();
+            let mut coverage = coverage::Coverage::default();
+
+            repo.for_every_dependency_dir(|pkg_id, path| {
+                if path.starts_with(&current_dir) {
+                    // ignore local dependencies
+                    return Ok(());
+                }
+
+                let digests = get_package_digests(
+                    &db,
+                    &trust_set,
+                    path,
+                    pkg_id.name().as_str(),
+                    &pkg_id.version().to_string(),
+                    &config,
+                )?;
+                let result = verify_package(
+                    &db,
+                    &trust_set,
+                    &digests,
+                    pkg_id.name().as_str(),
+                    &pkg_id.version().to_string(),
+                );
+                match result {
+                    crev_lib::VerificationStatus::Verified => {}
+                    _ => println!("{:8} {}", result, tilda_home_path(&home_dir, path)),
+                }
+                coverage.add(&result, 0);
+
+                Ok(())
+            })?;
+
+            println!(
+                "dependencies: {} verified, {} unknown, {} flagged",
+                coverage.verified.crates, coverage.unknown.crates, coverage.flagged.crates
+            );
+
+            if args.review {
+                create_package_review(
+                    &local,
+                    PROJECT_SOURCE_CRATES_IO,
+                    &name,
+                    &version,
+                    DigestType::Blake2b,
+                    digest,
+                    None,
+                    RegistryInfo {
+                        checksum: None,
+                        repository,
+                    },
+                    TrustOrDistrust::Trust,
+                    false,
+                    false,
+                )?;
+            }
+        }
+        opts::Command::Query(cmd) => match cmd {
+            opts::Query::Id(cmd) => match cmd {
+                opts::QueryId::Current => crev_lib::show_current_id()?,
+                opts::QueryId::Own => crev_lib::list_own_ids()?,
+                opts::QueryId::Trusted(args) => {
+                    let local = crev_lib::Local::auto_open()?;
+                    let (_db, trust_set) = local.load_db(&args.trust_params.into())?;
+                    let mut trusted: Vec<_> = trust_set.iter().collect();
+                    trusted.sort_by(|a, b| (a.1.distance, a.0).cmp(&(b.1.distance, b.0)));
+                    for (id, info) in trusted {
+                        println!("{} {:6} {:>2}", id, info.effective_trust, info.distance);
+                    }
+                }
+                opts::QueryId::Trusters(args) => {
+                    let local = crev_lib::Local::auto_open()?;
+                    let (db, _trust_set) = local.load_db(&default())?;
+                    let ids = match args.id {
+                        Some(ref id) => find_ids_by_id_or_url(&db, id)?,
+                        None => vec![local.read_current_id()?],
+                    };
+                    for id in &ids {
+                        for edge in db.get_trusters_of(id) {
+                            println!(
+                                "{} {} {} {}",
+                                edge.from,
+                                db.lookup_url(&edge.from)
+                                    .map_or("-", |url| url.url.as_str()),
+                                edge.level,
+                                edge.date.to_rfc3339()
+                            );
+                        }
+                    }
+                }
+                opts::QueryId::All => {
+                    let local = crev_lib::Local::auto_open()?;
                     let (db, _trust_set) = local.load_db(&default())?;
 
                     for id in &db.all_known_ids() {
@@ -307,7 +1996,258 @@ fn main() -> Result<()> {
                     }
                 }
             },
-            opts::Query::Review(args) => list_reviews(&args.crate_)?,
+            opts::Query::Review(args) => list_reviews(&args)?,
+            opts::Query::Trust(args) => {
+                let local = crev_lib::Local::auto_open()?;
+                let (db, _trust_set) = local.load_db(&default())?;
+                let of = match args.of {
+                    Some(ref of) => Some(find_ids_by_id_or_url(&db, of)?),
+                    None => None,
+                };
+                let by = match args.by {
+                    Some(ref by) => Some(find_ids_by_id_or_url(&db, by)?),
+                    None => None,
+                };
+
+                for edge in db.get_trust_edges() {
+                    if of.as_ref().map(|of| of.contains(&edge.to)).unwrap_or(true)
+                        && by
+                            .as_ref()
+                            .map(|by| by.contains(&edge.from))
+                            .unwrap_or(true)
+                    {
+                        println!(
+                            "{} -> {} {} {}",
+                            edge.from,
+                            edge.to,
+                            edge.level,
+                            edge.date.to_rfc3339()
+                        );
+                    }
+                }
+            }
+            opts::Query::Alternatives(args) => {
+                let local = crev_lib::Local::auto_open()?;
+                let (db, trust_set) = local.load_db(&args.trust_params.into())?;
+                for alternative in db.get_alternatives(&args.source, &args.name, &trust_set) {
+                    println!("{} {}", alternative.alternative, alternative.from.id);
+                    for line in alternative.comment.lines() {
+                        println!("    {}", line);
+                    }
+                }
+            }
+            opts::Query::Reviewers(args) => {
+                let local = crev_lib::Local::auto_open()?;
+                let (db, trust_set) = local.load_db(&args.trust_params.into())?;
+                eprintln!("reviews pkgs thoroughness last-activity trusted-by id url");
+                for stats in db.get_reviewer_stats(&trust_set) {
+                    println!(
+                        "{:7} {:4} {:11} {:13} {:10} {} {}",
+                        stats.review_count,
+                        stats.package_count,
+                        stats
+                            .average_thoroughness
+                            .map(|t| format!("{:.1}", t))
+                            .unwrap_or_else(|| "-".into()),
+                        stats
+                            .last_activity
+                            .map(|date| date.format("%Y-%m-%d").to_string())
+                            .unwrap_or_else(|| "-".into()),
+                        stats.trusted_by,
+                        stats.id,
+                        db.lookup_url(&stats.id)
+                            .map(|url| url.url.as_str())
+                            .unwrap_or("-")
+                    );
+                }
+            }
+            opts::Query::Members(args) => {
+                let local = crev_lib::Local::auto_open()?;
+                let (db, _trust_set) = local.load_db(&default())?;
+                let orgs = match args.org {
+                    Some(ref org) => find_ids_by_id_or_url(&db, org)?,
+                    None => vec![local.get_current_userid()?],
+                };
+                for org in &orgs {
+                    for member in db.get_members_of(org) {
+                        println!("{}", member);
+                    }
+                }
+            }
+            opts::Query::Stats => {
+                let local = crev_lib::Local::auto_open()?;
+                let (db, _trust_set) = local.load_db(&default())?;
+                let stats = db.get_stats();
+
+                println!("ids: {}", stats.id_count);
+                println!("trust-edges: {}", stats.trust_edge_count);
+                println!("package-reviews:");
+                for (source, count) in &stats.package_reviews_by_source {
+                    println!("  {}: {}", source, count);
+                }
+                println!("package-reviews-by-author:");
+                for (id, count) in &stats.package_reviews_by_author {
+                    println!("  {}: {}", id, count);
+                }
+                if let Some(date) = stats.oldest_proof_date {
+                    println!("oldest-proof: {}", date.to_rfc3339());
+                }
+                if let Some(date) = stats.newest_proof_date {
+                    println!("newest-proof: {}", date.to_rfc3339());
+                }
+            }
+        },
+        opts::Command::Open(args) => {
+            let repo = Repo::auto_open_cwd()?;
+            let (pkg_dir, _version) =
+                repo.find_dependency_dir(&args.crate_.name, args.crate_.version.as_deref())?;
+            let config = Local::auto_open()?.load_user_config()?;
+            open::open_crate(&pkg_dir, &config, args.cmd.as_deref(), args.sandbox)?;
+        }
+        opts::Command::Why(args) => {
+            why::explain(&args)?;
+        }
+        opts::Command::Provenance(args) => {
+            let local = Local::auto_open()?;
+            let (pkg_dir, version) =
+                download_crate(&args.crate_.name, args.crate_.version.as_deref(), false)?;
+            let repository = match args.repository {
+                Some(repository) => repository,
+                None => registry::declared_repository(&pkg_dir)?.ok_or_else(|| {
+                    format_err!(
+                        "{} {} doesn't declare its repository; use `--repository`",
+                        args.crate_.name,
+                        version
+                    )
+                })?,
+            };
+            let report = provenance::check(
+                &pkg_dir,
+                &repository,
+                &local.get_root_cache_dir().join("provenance"),
+                &args.crate_.name,
+                &version.to_string(),
+            )?;
+            for note in &report.notes {
+                warn!("{}", note);
+            }
+            println!(
+                "Comparing {} {} with {} at {} ({}{})",
+                args.crate_.name,
+                version,
+                repository,
+                report.commit,
+                report.found_by,
+                if report.path_in_vcs.is_empty() {
+                    String::new()
+                } else {
+                    format!(", in {}", report.path_in_vcs)
+                }
+            );
+            for path in &report.added {
+                println!("added:   {}", path.display());
+            }
+            for path in &report.changed {
+                println!("changed: {}", path.display());
+            }
+            if !report.is_clean() {
+                bail!(
+                    "{} file(s) of the published crate are not in the repository",
+                    report.added.len() + report.changed.len()
+                );
+            }
+            println!("Published sources match the repository");
+        }
+        opts::Command::Crate(cmd) => match cmd {
+            opts::Crate::Info(args) => {
+                info::show(&args)?;
+            }
+        },
+        opts::Command::Note(cmd) => match cmd {
+            opts::Note::Add(args) => {
+                let local = Local::auto_open()?;
+                let mut notes = local.load_notes()?;
+                notes.add(crev_lib::note::Note::new(
+                    &args.crate_.name,
+                    args.crate_.version.as_deref(),
+                    &args.message,
+                ));
+                local.store_notes(&notes)?;
+            }
+            opts::Note::Remove(args) => {
+                let local = Local::auto_open()?;
+                let mut notes = local.load_notes()?;
+                let removed = notes.remove(&args.crate_.name, args.crate_.version.as_deref());
+                if removed == 0 {
+                    bail!("No notes to remove");
+                }
+                local.store_notes(&notes)?;
+                eprintln!("Removed {} note(s)", removed);
+            }
+            opts::Note::List(args) => {
+                let local = Local::auto_open()?;
+                for note in local.load_notes()?.notes {
+                    if args.name.iter().all(|name| *name == note.name) {
+                        println!("{}", format_note(&note));
+                    }
+                }
+            }
+        },
+        opts::Command::LocalReview(cmd) => match cmd {
+            opts::LocalReview::Add(args) => {
+                let local = Local::auto_open()?;
+                let config = local.load_user_config()?;
+                let (db, trust_set) = local.load_db(&default())?;
+                let (pkg_dir, version) = if args.standalone {
+                    download_crate(&args.crate_.name, args.crate_.version.as_deref(), false)?
+                } else {
+                    Repo::auto_open_cwd()?
+                        .find_dependency_dir(&args.crate_.name, args.crate_.version.as_deref())?
+                };
+                let version = version.to_string();
+                let (digest_type, digest) = get_package_digests(
+                    &db,
+                    &trust_set,
+                    &pkg_dir,
+                    &args.crate_.name,
+                    &version,
+                    &config,
+                )?
+                .remove(0);
+                let mut reviews = local.load_local_reviews()?;
+                reviews.add(crev_lib::local_review::LocalReview::new(
+                    &args.crate_.name,
+                    &version,
+                    digest_type,
+                    &digest,
+                    &args.comment,
+                ));
+                local.store_local_reviews(&reviews)?;
+                eprintln!(
+                    "Marked {} {} ({}) as reviewed locally",
+                    args.crate_.name, version, digest
+                );
+            }
+            opts::LocalReview::Remove(args) => {
+                let local = Local::auto_open()?;
+                let mut reviews = local.load_local_reviews()?;
+                if !reviews.remove(&args.name, &args.version) {
+                    bail!("No local review of {} {}", args.name, args.version);
+                }
+                local.store_local_reviews(&reviews)?;
+            }
+            opts::LocalReview::List => {
+                let local = Local::auto_open()?;
+                for review in local.load_local_reviews()?.reviews {
+                    println!(
+                        "{} {} {} {}",
+                        review.date.format("%Y-%m-%d"),
+                        review.name,
+                        review.version,
+                        review.comment
+                    );
+                }
+            }
         },
         opts::Command::Review(args) => {
             review_crate(&args, TrustOrDistrust::Trust)?;
@@ -315,16 +2255,486 @@ fn main() -> Result<()> {
         opts::Command::Flag(args) => {
             review_crate(&args, TrustOrDistrust::Distrust)?;
         }
-        opts::Command::Trust(args) => {
+        opts::Command::Trust(opts::TrustIds {
+            import: Some(source),
+            level,
+            trust: args,
+        }) => {
+            if !args.pub_ids.is_empty() {
+                bail!("Ids can't be given together with `--import`");
+            }
+            if args.unsigned {
+                bail!("`--unsigned` can't be used together with `--import`");
+            }
+            let content = if source.starts_with("https://") {
+                let mut response = reqwest::get(&source)?;
+                if !response.status().is_success() {
+                    bail!("Fetching {} failed: {}", source, response.status());
+                }
+                response.text()?
+            } else {
+                fs::read_to_string(&source)?
+            };
+            let mut entries = vec![];
+            for entry in crev_lib::trust_list::parse(&content)? {
+                if crev_common::yes_or_no_was_y(&format!(
+                    "Trust {} ({})? (y/n) ",
+                    entry.id, entry.url
+                ))? {
+                    entries.push(entry);
+                }
+            }
+            if entries.is_empty() {
+                return Ok(());
+            }
+            let local = Local::auto_open()?;
+            let passphrase = crev_common::read_passphrase()?;
+            let count = local.build_trust_proof_from_list(&entries, &passphrase, level.parse()?)?;
+            let message = format!("Trust {} Ids from {}", count, source);
+            publish_if_wanted(&local, args.publish, &passphrase, &message)?;
+        }
+        opts::Command::Trust(opts::TrustIds { trust: args, .. }) if args.unsigned => {
+            let local = Local::auto_open()?;
+            print_unsigned_proof(&local.build_unsigned_trust_proof(args.pub_ids, Trust)?)?;
+        }
+        opts::Command::Distrust(args) if args.unsigned => {
+            let local = Local::auto_open()?;
+            print_unsigned_proof(&local.build_unsigned_trust_proof(args.pub_ids, Distrust)?)?;
+        }
+        opts::Command::Trust(opts::TrustIds { trust: args, .. }) => {
             let local = Local::auto_open()?;
             let passphrase = crev_common::read_passphrase()?;
+            let message = format!("Trust {}", args.pub_ids.join(", "));
             local.build_trust_proof(args.pub_ids, &passphrase, Trust)?;
+            publish_if_wanted(&local, args.publish, &passphrase, &message)?;
         }
         opts::Command::Distrust(args) => {
             let local = Local::auto_open()?;
             let passphrase = crev_common::read_passphrase()?;
+            let message = format!("Distrust {}", args.pub_ids.join(", "));
             local.build_trust_proof(args.pub_ids, &passphrase, Distrust)?;
+            publish_if_wanted(&local, args.publish, &passphrase, &message)?;
         }
+        opts::Command::Simulate(opts::Simulate::Trust(args)) => {
+            let local = Local::auto_open()?;
+            let mut repo = Repo::auto_open_cwd()?;
+            repo.set_features(&args.features);
+            let params = args.trust_params.clone().into();
+            let (mut db, trust_set) =
+                local.load_db_for_crates(&params, &repo.dependency_names()?)?;
+            let ids = find_ids_by_id_or_url(&db, &args.id)?;
+            let statuses = dependency_statuses(&local, &repo, &db, &trust_set)?;
+
+            let own_id = local.read_current_id()?;
+            for id in &ids {
+                db.add_simulated_trust(&own_id, id, args.level.parse()?);
+            }
+            let new_trust_set = local.calculate_trust_set(&db, &params)?;
+            let new_statuses = dependency_statuses(&local, &repo, &db, &new_trust_set)?;
+
+            let url_of = |id: &Id| db.lookup_url(id).map_or("-", |url| url.url.as_str());
+            let added: BTreeSet<_> = new_trust_set
+                .ids()
+                .filter(|id| !trust_set.contains(id))
+                .collect();
+            let removed: BTreeSet<_> = trust_set
+                .ids()
+                .filter(|id| !new_trust_set.contains(id))
+                .collect();
+            for id in &added {
+                println!("+ {} {}", id, url_of(id));
+            }
+            for id in &removed {
+                println!("- {} {}", id, url_of(id));
+            }
+            println!(
+                "Trust set: {} -> {} Ids",
+                trust_set.len(),
+                new_trust_set.len()
+            );
+
+            let changes = watch::changes(&statuses, &new_statuses);
+            for change in &changes {
+                println!("{}", change);
+            }
+            println!(
+                "Dependencies changing their verification status: {}",
+                changes.len()
+            );
+        }
+        opts::Command::Issue(args) => {
+            let local = Local::auto_open()?;
+            let passphrase = crev_common::read_passphrase()?;
+            let id = local.read_current_unlocked_id(&passphrase)?;
+            let issue = proof::IssueBuilder::default()
+                .from(id.id.to_owned())
+                .source(args.source)
+                .name(args.name)
+                .versions(args.versions)
+                .build()
+                .map_err(|e| format_err!("{}", e))?;
+            let issue = crev_lib::util::edit_proof_content_iteractively(&issue.into())?;
+            let message = match issue {
+                proof::Content::Issue(ref issue) => {
+                    format!("Report issue in {} {}", issue.name, issue.versions)
+                }
+                _ => unreachable!(),
+            };
+            local.insert_own(&issue.sign_by(&id)?, &id)?;
+            publish_if_wanted(&local, args.publish, &passphrase, &message)?;
+        }
+        opts::Command::Alternative(args) => {
+            let local = Local::auto_open()?;
+            let passphrase = crev_common::read_passphrase()?;
+            let id = local.read_current_unlocked_id(&passphrase)?;
+            let alternative = proof::AlternativeBuilder::default()
+                .from(id.id.to_owned())
+                .source(args.source)
+                .name(args.name)
+                .alternative(args.alternative)
+                .build()
+                .map_err(|e| format_err!("{}", e))?;
+            let alternative = crev_lib::util::edit_proof_content_iteractively(&alternative.into())?;
+            let message = match alternative {
+                proof::Content::Alternative(ref alternative) => format!(
+                    "Recommend {} instead of {}",
+                    alternative.alternative, alternative.name
+                ),
+                _ => unreachable!(),
+            };
+            local.insert_own(&alternative.sign_by(&id)?, &id)?;
+            publish_if_wanted(&local, args.publish, &passphrase, &message)?;
+        }
+        opts::Command::Use(args) => {
+            let local = Local::auto_open()?;
+            let mut repo = Repo::auto_open_cwd()?;
+            repo.set_features(&args.features);
+            let passphrase = crev_common::read_passphrase()?;
+            let id = local.read_current_unlocked_id(&passphrase)?;
+            // dependencies used outside of development, from crates.io
+            let mut pkg_ids: Vec<_> = repo
+                .dependency_kinds()?
+                .into_iter()
+                .filter(|(pkg_id, kinds)| {
+                    pkg_id.source_id().is_registry()
+                        && (kinds.contains(&DepKind::Normal) || kinds.contains(&DepKind::Build))
+                })
+                .map(|(pkg_id, _)| pkg_id)
+                .collect();
+            for name in &args.names {
+                if !pkg_ids.iter().any(|pkg_id| pkg_id.name().as_str() == name) {
+                    bail!("`{}` is not a dependency used outside of development", name);
+                }
+            }
+            if !args.names.is_empty() {
+                pkg_ids
+                    .retain(|pkg_id| args.names.iter().any(|name| pkg_id.name().as_str() == name));
+            }
+            pkg_ids.sort();
+
+            let names = pkg_ids
+                .iter()
+                .map(|pkg_id| pkg_id.name().to_string())
+                .collect();
+            let (db, _trust_set) = local.load_db_for_crates(&default(), &names)?;
+            let mut count = 0;
+            for pkg_id in pkg_ids {
+                let name = pkg_id.name().to_string();
+                let version = pkg_id.version().to_string();
+                if db.is_used_by(PROJECT_SOURCE_CRATES_IO, &name, &version, &id.id.id) {
+                    continue;
+                }
+                let usage = proof::UsageBuilder::default()
+                    .from(id.id.to_owned())
+                    .source(PROJECT_SOURCE_CRATES_IO.to_owned())
+                    .name(name)
+                    .package_version(version)
+                    .comment(args.comment.clone())
+                    .build()
+                    .map_err(|e| format_err!("{}", e))?;
+                local.insert_own(&usage.sign_by(&id)?, &id)?;
+                count += 1;
+            }
+            if count == 0 {
+                eprintln!("Nothing new to record");
+                return Ok(());
+            }
+            eprintln!("Recorded usage of {} crate versions", count);
+            let message = format!("Use {} crate versions", count);
+            publish_if_wanted(&local, args.publish, &passphrase, &message)?;
+        }
+        opts::Command::Id(cmd) => match cmd {
+            opts::Id::Claim(args) => {
+                let local = Local::auto_open()?;
+                let passphrase = crev_common::read_passphrase()?;
+                let id = local.read_current_unlocked_id(&passphrase)?;
+                let account = proof::identity::Account {
+                    service: args.service,
+                    name: args.name,
+                    proof_url: args.proof_url,
+                };
+                if account.raw_proof_url().is_none() {
+                    bail!(
+                        "{} is not a gist or a repository file of {}",
+                        account.proof_url,
+                        account.name
+                    );
+                }
+                if !identity::verify_account(&account, &id.id.id)? {
+                    bail!("{} doesn't contain {}", account.proof_url, id.id.id);
+                }
+                let message = format!("Claim {} account {}", account.service, account.name);
+                let claim = proof::IdentityClaimBuilder::default()
+                    .from(id.id.to_owned())
+                    .accounts(vec![account])
+                    .build()
+                    .map_err(|e| format_err!("{}", e))?;
+                local.insert_own(&claim.sign_by(&id)?, &id)?;
+                publish_if_wanted(&local, args.publish, &passphrase, &message)?;
+            }
+            opts::Id::Lookup(args) => {
+                let local = Local::auto_open()?;
+                let (db, _trust_set) = local.load_db(&default())?;
+                for claim in db.get_identity_claims(&args.service, &args.name) {
+                    let account = claim
+                        .accounts
+                        .iter()
+                        .find(|account| {
+                            account.service == args.service
+                                && account.name.eq_ignore_ascii_case(&args.name)
+                        })
+                        .expect("indexed by this account");
+                    let verified = match identity::verify_account(account, &claim.from.id) {
+                        Ok(true) => "verified",
+                        Ok(false) => "not verified",
+                        Err(e) => {
+                            error!("{}", e);
+                            "not verified"
+                        }
+                    };
+                    let url = db
+                        .lookup_url(&claim.from.id)
+                        .map(|url| url.url.as_str())
+                        .unwrap_or("-");
+                    println!("{} {} {}", claim.from.id, url, verified);
+                }
+            }
+        },
+        opts::Command::Repo(cmd) => match cmd {
+            opts::Repo::Status => {
+                let local = Local::auto_open()?;
+                let status = local.repo_status()?;
+                match (&status.branch, &status.upstream) {
+                    (Some(branch), Some(upstream)) => {
+                        println!("On branch {}, pushed to {}", branch, upstream)
+                    }
+                    (Some(branch), None) => println!("On branch {}, never pushed", branch),
+                    (None, _) => println!("Not on any branch"),
+                }
+                if status.uncommitted_proofs > 0 {
+                    println!(
+                        "{} proof(s) not committed yet (use `cargo crev commit`)",
+                        status.uncommitted_proofs
+                    );
+                }
+                if status.ahead > 0 {
+                    println!(
+                        "{} proof(s) in {} commit(s) not pushed yet (use `cargo crev push`)",
+                        status.unpushed_proofs, status.ahead
+                    );
+                }
+                if status.behind > 0 {
+                    println!(
+                        "{} commit(s) not pulled yet (use `cargo crev pull`)",
+                        status.behind
+                    );
+                }
+                if !status.other_changes.is_empty() {
+                    println!("Other uncommitted changes:");
+                    for path in &status.other_changes {
+                        println!("  {}", path.display());
+                    }
+                }
+                if status.is_published() {
+                    println!("Everything is published");
+                }
+            }
+            opts::Repo::Compact => {
+                let local = Local::auto_open()?;
+                let removed = local.compact_proofs()?;
+                eprintln!("Removed {} duplicated or superseded proofs", removed);
+            }
+            opts::Repo::Verify => {
+                let local = Local::auto_open()?;
+                if !local.verify_remote_git_commits()? {
+                    bail!("Some proof repositories failed verification");
+                }
+            }
+            opts::Repo::Lint(args) => {
+                let local = Local::auto_open()?;
+                let issues = local.lint_proofs(args.remotes)?;
+                for (path, issue) in &issues {
+                    println!("{}:{}", path.display(), issue);
+                }
+                if !issues.is_empty() {
+                    bail!("Found {} issues", issues.len());
+                }
+            }
+            opts::Repo::Migrate => {
+                let local = Local::auto_open()?;
+                let passphrase = crev_common::read_passphrase()?;
+                let migrated = local.migrate_proofs(&passphrase)?;
+                eprintln!("Migrated {} proofs", migrated);
+            }
+            opts::Repo::Anchor => {
+                let local = Local::auto_open()?;
+                let log = local.load_user_config()?.transparency_log()?;
+                let anchored = transparency::anchor_own_proofs(&local, &log)?;
+                eprintln!("Anchored {} proofs", anchored);
+            }
+            opts::Repo::CheckAnchors(args) => {
+                let local = Local::auto_open()?;
+                let log = local.load_user_config()?.transparency_log()?;
+                let mut issues = local.check_anchors(&log, args.remotes)?;
+                if args.online {
+                    issues.extend(transparency::check_anchors_online(
+                        &local,
+                        &log,
+                        args.remotes,
+                    )?);
+                }
+                for (path, issue) in &issues {
+                    println!("{}: {}", path.display(), issue);
+                }
+                if !issues.is_empty() {
+                    bail!("Found {} issues", issues.len());
+                }
+            }
+        },
+        opts::Command::Proof(cmd) => match cmd {
+            opts::Proof::Import(args) => {
+                let local = Local::auto_open()?;
+                let proofs = if args.path.as_os_str() == "-" {
+                    let stdin = std::io::stdin();
+                    proof::Proof::parse(stdin.lock())?
+                } else {
+                    proof::Proof::parse_from(&args.path)?
+                };
+                let count = proofs.len();
+                let imported = local.import_proofs(proofs)?;
+                eprintln!("Imported {} of {} proofs", imported, count);
+            }
+            opts::Proof::Export(args) => {
+                let local = Local::auto_open()?;
+                match local.find_proof(&args.signature)? {
+                    Some(proof) => print!("{}", proof),
+                    None => bail!("Proof with signature {} not found", args.signature),
+                }
+            }
+            opts::Proof::Decrypt => {
+                let local = Local::auto_open()?;
+                let passphrase = crev_common::read_passphrase()?;
+                let decrypted = local.decrypt_proofs(&passphrase)?;
+                eprintln!("Decrypted {} new proofs", decrypted);
+            }
+            opts::Proof::Sign(args) => {
+                let proofs = if args.path.as_os_str() == "-" {
+                    let stdin = std::io::stdin();
+                    proof::Proof::parse(stdin.lock())?
+                } else {
+                    proof::Proof::parse_from(&args.path)?
+                };
+                let local = Local::auto_open()?;
+                let passphrase = crev_common::read_passphrase()?;
+                let id = local.read_current_unlocked_id(&passphrase)?;
+                let mut signatures = vec![];
+                for proof in &proofs {
+                    if !proof.is_unsigned() {
+                        bail!("The proofs should be unsigned, but one has a signature");
+                    }
+                    eprint!("{}", proof.body);
+                    if !crev_common::yes_or_no_was_y("Sign this proof? (y/n) ")? {
+                        bail!("User canceled");
+                    }
+                    signatures.push(proof.detached_signature_by(&id)?);
+                }
+                for signature in signatures {
+                    println!("{}", signature);
+                }
+            }
+            opts::Proof::AttachSignature(args) => {
+                let local = Local::auto_open()?;
+                let proofs = proof::Proof::parse_from(&args.path)?;
+                if proofs.len() != args.signatures.len() {
+                    bail!(
+                        "Got {} signatures for {} proofs",
+                        args.signatures.len(),
+                        proofs.len()
+                    );
+                }
+                let mut signed = vec![];
+                for (proof, signature) in proofs.into_iter().zip(&args.signatures) {
+                    signed.push(proof.with_signature(signature)?);
+                }
+                let count = signed.len();
+                let imported = local.import_proofs(signed)?;
+                eprintln!("Stored {} of {} signed proofs", imported, count);
+            }
+        },
+        opts::Command::Bundle(cmd) => match cmd {
+            opts::Bundle::Export(args) => {
+                let local = Local::auto_open()?;
+                let passphrase = crev_common::read_passphrase()?;
+                let id = local.read_current_unlocked_id(&passphrase)?;
+                let (db, _trust_set) = local.load_db(&default())?;
+                let proofs: Vec<_> = db
+                    .proofs()
+                    .iter()
+                    .filter(|proof| args.all || proof.content.author_id() == id.id.id)
+                    .filter(|proof| {
+                        args.crates.is_empty()
+                            || crev_lib::repo_index::crate_name(&proof.content)
+                                .is_some_and(|name| args.crates.iter().any(|c| c == name))
+                    })
+                    .filter(|proof| {
+                        args.signatures.is_empty() || args.signatures.contains(&proof.signature)
+                    })
+                    .cloned()
+                    .collect();
+                if proofs.is_empty() {
+                    bail!("No proofs to bundle");
+                }
+                let bundle = crev_lib::bundle::create(&id, &proofs)?;
+                match args.output {
+                    Some(path) => crev_common::store_str_to_file(&path, &bundle)?,
+                    None => print!("{}", bundle),
+                }
+                eprintln!("Bundled {} proofs", proofs.len());
+            }
+            opts::Bundle::Import(args) => {
+                let local = Local::auto_open()?;
+                let text = if args.path.as_os_str() == "-" {
+                    use std::io::Read;
+                    let mut text = String::new();
+                    std::io::stdin().read_to_string(&mut text)?;
+                    text
+                } else {
+                    crev_common::read_file_to_string(&args.path)?
+                };
+                let bundle = crev_lib::bundle::Bundle::parse(&text)?;
+                let (db, _trust_set) = local.load_db(&default())?;
+                eprintln!(
+                    "Bundle by {} ({}) of {}",
+                    bundle.header.from.id,
+                    db.lookup_url(&bundle.header.from.id)
+                        .map_or(bundle.header.from.url.url.as_str(), |url| url.url.as_str()),
+                    bundle.header.date.to_rfc3339()
+                );
+                let count = bundle.proofs.len();
+                let imported = local.import_proofs(bundle.proofs)?;
+                eprintln!("Imported {} of {} proofs", imported, count);
+            }
+        },
         opts::Command::Git(git) => {
             let local = Local::auto_open()?;
             let status = local.run_git(git.args)?;
@@ -332,28 +2742,266 @@ fn main() -> Result<()> {
         }
         opts::Command::Diff => {
             let local = Local::auto_open()?;
-            let status = local.run_git(vec!["diff".into(), "HEAD".into()])?;
-            std::process::exit(status.code().unwrap_or(-159));
+            print!("{}", local.diff()?);
         }
-        opts::Command::Commit => {
+        opts::Command::Commit(args) => {
             let local = Local::auto_open()?;
-            let status = local.run_git(vec!["commit".into(), "-a".into()])?;
-            std::process::exit(status.code().unwrap_or(-159));
+            if args.sign || local.load_user_config()?.sign_commits {
+                let passphrase = crev_common::read_passphrase()?;
+                local.commit_signed(
+                    &passphrase,
+                    args.message.as_deref().unwrap_or("Update proofs"),
+                )?;
+            } else {
+                local.commit(args.message.as_deref().unwrap_or("Update proofs"))?;
+            }
         }
         opts::Command::Push => {
             let local = Local::auto_open()?;
-            let status = local.run_git(vec!["push".into()])?;
-            std::process::exit(status.code().unwrap_or(-159));
+            local.push()?;
         }
         opts::Command::Pull => {
             let local = Local::auto_open()?;
-            let status = local.run_git(vec!["pull".into()])?;
-            std::process::exit(status.code().unwrap_or(-159));
+            local.pull()?;
+        }
+        opts::Command::Completions(args) => {
+            opts::Opts::clap().gen_completions_to("cargo-crev", args.shell, &mut std::io::stdout());
+        }
+        opts::Command::Advisory(cmd) => match cmd {
+            opts::Advisory::Export(args) => {
+                let local = Local::auto_open()?;
+                let (db, trust_set) = local.load_db(&args.trust_params.into())?;
+                let reviews =
+                    db.get_advisories(PROJECT_SOURCE_CRATES_IO, args.name.as_deref(), &trust_set);
+                match args.format.as_str() {
+                    "osv" => {
+                        let mut advisories = vec![];
+                        for review in &reviews {
+                            advisories.push(advisory::to_osv(review)?);
+                        }
+                        println!("{}", serde_json::to_string_pretty(&advisories)?);
+                    }
+                    "rustsec" => {
+                        for review in &reviews {
+                            println!("{}", advisory::to_rustsec(review)?);
+                        }
+                    }
+                    format => bail!("Unknown format: {}", format),
+                }
+            }
+        },
+        opts::Command::Export(cmd) => match cmd {
+            opts::Export::Vet(args) => {
+                let local = Local::auto_open()?;
+                let id = local.read_current_locked_id()?;
+                let who = args.who.unwrap_or_else(|| id.url.url.clone());
+                let mut criteria_map = vet::default_criteria_map();
+                vet::apply_criteria_mappings(&mut criteria_map, &args.criteria)?;
+                let (db, _trust_set) = local.load_db(&default())?;
+                let reviews: Vec<_> = db
+                    .get_package_reviews_by_author(&id.to_pubid().id)
+                    .filter(|review| review.package.source == PROJECT_SOURCE_CRATES_IO)
+                    .collect();
+                print!("{}", vet::to_audits_toml(&reviews, &who, &criteria_map)?);
+            }
+        },
+        opts::Command::Import(cmd) => match cmd {
+            opts::Import::Vet(args) => {
+                let mut criteria_map = vet::default_criteria_map();
+                vet::apply_criteria_mappings(&mut criteria_map, &args.criteria)?;
+                let audits = vet::parse_audits_toml(&fs::read_to_string(&args.path)?)?;
+                let local = Local::auto_open()?;
+                let config = local.load_user_config()?;
+                let passphrase = crev_common::read_passphrase()?;
+                let id = local.read_current_unlocked_id(&passphrase)?;
+
+                let mut imported = 0;
+                for audit in &audits {
+                    let version = match &audit.version {
+                        Some(version) => version,
+                        None => {
+                            warn!(
+                                "Skipping {} {}: only full audits of a version can be imported",
+                                audit.name,
+                                audit
+                                    .delta
+                                    .as_ref()
+                                    .or(audit.violation.as_ref())
+                                    .map_or("", String::as_str)
+                            );
+                            continue;
+                        }
+                    };
+                    let rating = match vet::rating_for_criteria(&criteria_map, &audit.criteria) {
+                        Some(rating) => rating,
+                        None => {
+                            warn!(
+                                "Skipping {} {}: no rating for criteria {}",
+                                audit.name,
+                                version,
+                                audit.criteria.join(", ")
+                            );
+                            continue;
+                        }
+                    };
+
+                    let (path, _) = download_crate(&audit.name, Some(version), false)?;
+                    let registry_info = RegistryInfo::of_package_dir(&path)?;
+                    let digest_type = DigestType::default();
+                    let digest = crev_lib::get_dir_digest_of_type(
+                        &path,
+                        &digest_ignore_list(&config.digest_ignore),
+                        digest_type,
+                        &config.digest_options()?,
+                    )?;
+                    let review = proof::review::PackageBuilder::default()
+                        .from(id.id.to_owned())
+                        .package(proof::PackageInfo {
+                            id: None,
+                            source: PROJECT_SOURCE_CRATES_IO.to_owned(),
+                            name: audit.name.clone(),
+                            version: version.clone(),
+                            digest: digest.into_vec(),
+                            digest_type: digest_type.to_string(),
+                            revision: "".into(),
+                            revision_type: proof::default_revision_type(),
+                            digest_ignore: config.digest_ignore.clone(),
+                            checksum: registry_info.checksum,
+                            repository: registry_info.repository,
+                        })
+                        .review(proof::review::Review {
+                            rating,
+                            criteria: audit.criteria.clone(),
+                            ..default()
+                        })
+                        .comment(audit.review_comment())
+                        .build()
+                        .map_err(|e| format_err!("{}", e))?;
+
+                    let review = crev_lib::util::edit_proof_content_iteractively(&review.into())?;
+                    if !crev_common::yes_or_no_was_y(&format!(
+                        "Sign the review of {} {}? (y/n) ",
+                        audit.name, version
+                    ))? {
+                        continue;
+                    }
+                    let proof = review.sign_by(&id)?;
+                    local.insert_own(&proof, &id)?;
+                    imported += 1;
+                }
+                eprintln!("Imported {} reviews", imported);
+                if imported > 0 {
+                    let message = format!("Import {} reviews from cargo-vet audits", imported);
+                    publish_if_wanted(&local, args.publish, &passphrase, &message)?;
+                }
+            }
+        },
+        opts::Command::Org(cmd) => {
+            let local = Local::auto_open()?;
+            let (args, status, action) = match cmd {
+                opts::Org::Add(args) => (args, Member, "Add members"),
+                opts::Org::Remove(args) => (args, Revoked, "Remove members"),
+            };
+            if args.unsigned {
+                print_unsigned_proof(
+                    &local.build_unsigned_membership_proof(args.pub_ids, status)?,
+                )?;
+            } else {
+                let passphrase = crev_common::read_passphrase()?;
+                let message = format!("{} {}", action, args.pub_ids.join(", "));
+                local.build_membership_proof(args.pub_ids, &passphrase, status)?;
+                publish_if_wanted(&local, args.publish, &passphrase, &message)?;
+            }
+        }
+        opts::Command::Watch(args) => {
+            let local = Local::auto_open()?;
+            let mut repo = Repo::auto_open_cwd()?;
+            repo.set_features(&args.features);
+            let mut watched = local.proof_paths()?;
+            watched.push(repo.lock_file_path()?);
+
+            let mut stamp = None;
+            let mut statuses = watch::Statuses::new();
+            loop {
+                let new_stamp = watch::Stamp::of(&watched)?;
+                if stamp.as_ref() != Some(&new_stamp) {
+                    let (db, trust_set) = local.load_db_for_crates(
+                        &args.trust_params.clone().into(),
+                        &repo.dependency_names()?,
+                    )?;
+                    let new_statuses = dependency_statuses(&local, &repo, &db, &trust_set)?;
+                    if stamp.is_none() {
+                        let count = |status: crev_lib::VerificationStatus| {
+                            new_statuses.values().filter(|s| **s == status).count()
+                        };
+                        println!(
+                            "Watching {} dependencies: {} verified, {} flagged, {} unknown",
+                            new_statuses.len(),
+                            count(crev_lib::VerificationStatus::Verified),
+                            count(crev_lib::VerificationStatus::Flagged),
+                            count(crev_lib::VerificationStatus::Unknown)
+                        );
+                    }
+                    for change in watch::changes(&statuses, &new_statuses) {
+                        println!("{}", change);
+                        local.run_hook(
+                            crev_lib::hooks::STATUS_CHANGED,
+                            &[
+                                ("CREV_PACKAGE_NAME", change.name.clone()),
+                                ("CREV_PACKAGE_VERSION", change.version.clone()),
+                                ("CREV_OLD_STATUS", change.old.clone().unwrap_or_default()),
+                                ("CREV_STATUS", change.new.clone().unwrap_or_default()),
+                            ],
+                        )?;
+                    }
+                    statuses = new_statuses;
+                    stamp = Some(new_stamp);
+                }
+                watch::sleep(args.interval);
+            }
+        }
+        opts::Command::Notify(args) => {
+            let local = Local::auto_open()?;
+            if args.fetch {
+                local.fetch_trusted(args.trust_params.clone().into(), false)?;
+            }
+            let (db, trust_set) = local.load_db(&args.trust_params.into())?;
+            let own_id = local.get_current_userid()?;
+            // outside of a project, proofs about any crate are relevant
+            let dependencies = match Repo::auto_open_cwd() {
+                Ok(repo) => Some(repo.dependency_names()?),
+                Err(_) => None,
+            };
+            match local.take_new_foreign_proofs()? {
+                Some(proofs) => {
+                    for proof in &proofs {
+                        if let Some(line) = notify::describe_if_relevant(
+                            proof,
+                            &db,
+                            &trust_set,
+                            &own_id,
+                            dependencies.as_ref(),
+                        ) {
+                            println!("{}", line);
+                        }
+                    }
+                }
+                None => eprintln!("Remembered current proofs; next runs will show only new ones"),
+            }
         }
         opts::Command::Fetch(cmd) => match cmd {
-            opts::Fetch::Trusted(params) => {
+            opts::Fetch::Trusted(args) => {
                 let local = Local::auto_open()?;
-                local.fetch_trusted(params.into())?;
+                local.fetch_trusted(args.trust_params.into(), args.prune)?;
+                rustsec::AdvisoryDb::fetch(&local)?;
+                let discovered = local.load_discovered_urls()?.len();
+                if discovered > 0 {
+                    eprintln!(
+                        "Discovered {} proof repositories of Ids trusted by your trust set; \
+                         `cargo crev fetch discovered` fetches them",
+                        discovered
+                    );
+                }
             }
             opts::Fetch::Url(params) => {
                 let local = Local::auto_open()?;
@@ -362,6 +3010,11 @@ fn main() -> Result<()> {
             opts::Fetch::All => {
                 let local = Local::auto_open()?;
                 local.fetch_all()?;
+                rustsec::AdvisoryDb::fetch(&local)?;
+            }
+            opts::Fetch::Discovered => {
+                let local = Local::auto_open()?;
+                local.fetch_discovered()?;
             }
         },
     }
@@ -0,0 +1,119 @@
+//! `cargo crev notify`: new proofs worth knowing about
+use crev_data::{
+    proof::{self, review::Common, Content},
+    Id,
+};
+use crev_lib::trustdb::{TrustDB, TrustSet};
+use std::collections::HashSet;
+
+fn describe_id(db: &TrustDB, id: &Id) -> String {
+    match db.lookup_url(id) {
+        Some(url) => url.url.clone(),
+        None => id.to_string(),
+    }
+}
+
+/// Describe `proof` in one line, if it's relevant
+///
+/// Relevant are reviews, issues and alternatives by trusted Ids about
+/// `dependencies` (by name; all packages if `None`), and trust proofs about
+/// `own_id` or by trusted Ids.
+pub fn describe_if_relevant(
+    proof: &proof::Proof,
+    db: &TrustDB,
+    trust_set: &TrustSet,
+    own_id: &Id,
+    dependencies: Option<&HashSet<String>>,
+) -> Option<String> {
+    let is_dependency = |source: &str, name: &str| {
+        source == crate::PROJECT_SOURCE_CRATES_IO
+            && dependencies.map(|deps| deps.contains(name)).unwrap_or(true)
+    };
+    let author = proof.content.author_id();
+    match &proof.content {
+        Content::Package(review) => {
+            if !trust_set.contains(&author)
+                || !is_dependency(&review.package.source, &review.package.name)
+            {
+                return None;
+            }
+            let kind = if review.advisory.is_some() {
+                "advisory"
+            } else {
+                "review"
+            };
+            Some(format!(
+                "{:11} {} {} {} by {}",
+                kind,
+                review.package.name,
+                review.package.version,
+                format!("{:?}", review.review().rating).to_lowercase(),
+                describe_id(db, &author)
+            ))
+        }
+        Content::Issue(issue) => {
+            if !trust_set.contains(&author) || !is_dependency(&issue.source, &issue.name) {
+                return None;
+            }
+            Some(format!(
+                "{:11} {} {} {} by {}",
+                "issue",
+                issue.name,
+                issue.versions,
+                issue.severity,
+                describe_id(db, &author)
+            ))
+        }
+        Content::Alternative(alternative) => {
+            if !trust_set.contains(&author)
+                || !is_dependency(&alternative.source, &alternative.name)
+            {
+                return None;
+            }
+            Some(format!(
+                "{:11} {} -> {} by {}",
+                "alternative",
+                alternative.name,
+                alternative.alternative,
+                describe_id(db, &author)
+            ))
+        }
+        Content::Trust(trust) => {
+            let about_me = trust.ids.iter().any(|pub_id| &pub_id.id == own_id);
+            if !about_me && !trust_set.contains(&author) {
+                return None;
+            }
+            let ids: Vec<String> = trust
+                .ids
+                .iter()
+                .map(|pub_id| {
+                    if &pub_id.id == own_id {
+                        "you".to_owned()
+                    } else {
+                        describe_id(db, &pub_id.id)
+                    }
+                })
+                .collect();
+            Some(format!(
+                "{:11} {} -> {} by {}",
+                "trust",
+                trust.trust,
+                ids.join(", "),
+                describe_id(db, &author)
+            ))
+        }
+        Content::Encrypted(encrypted) => {
+            if &author == own_id || !encrypted.is_encrypted_to(own_id) {
+                return None;
+            }
+            Some(format!(
+                "{:11} proof for you by {} (read with `cargo crev proof decrypt`)",
+                "encrypted",
+                describe_id(db, &author)
+            ))
+        }
+        Content::Membership(_) | Content::Code(_) | Content::IdentityClaim(_) => None,
+        // created in bulk, and too weak a signal to notify about
+        Content::Usage(_) => None,
+    }
+}
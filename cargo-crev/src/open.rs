@@ -0,0 +1,100 @@
+//! Opening crate sources for a review, optionally in a sandbox
+//!
+//! Tools used to inspect code (editors and their plugins, in particular)
+//! might execute parts of it. In a sandbox, they work on a disposable copy
+//! of the sources, so they can't modify the original ones (that are
+//! digested when reviewing) or anything else.
+use crate::prelude::*;
+use std::{fs, path::Path, process};
+
+/// Used with `--sandbox`, unless `sandbox-cmd` is configured
+///
+/// Bubblewrap with a read-only view of the filesystem and no network;
+/// only the copy of the sources is writable.
+const DEFAULT_SANDBOX_CMD: &str = "bwrap --ro-bind / / --dev /dev --proc /proc --tmpfs /tmp \
+                                   --bind {dir} {dir} --unshare-net --die-with-parent";
+
+fn copy_dir(src: &Path, dst: &Path) -> Result<()> {
+    for entry in walkdir::WalkDir::new(src) {
+        let entry = entry?;
+        let dst_path = dst.join(entry.path().strip_prefix(src)?);
+        let file_type = entry.file_type();
+        if file_type.is_dir() {
+            fs::create_dir_all(&dst_path)?;
+        } else if file_type.is_file() {
+            fs::copy(entry.path(), &dst_path)?;
+        } else if file_type.is_symlink() {
+            copy_symlink(entry.path(), &dst_path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(src: &Path, dst: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(src)?, dst)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(src: &Path, _dst: &Path) -> Result<()> {
+    bail!("Can't copy symlink {} into the sandbox", src.display())
+}
+
+/// Run `open_cmd` (or the editor) on `dir`, inside `sandbox_cmd` if given
+///
+/// Commands are split into arguments on whitespace; no shell is involved.
+fn open_dir(dir: &Path, open_cmd: Option<&str>, sandbox_cmd: Option<&str>) -> Result<()> {
+    let dir_str = dir.to_string_lossy();
+    let mut args: Vec<String> = vec![];
+    if let Some(sandbox_cmd) = sandbox_cmd {
+        args.extend(
+            sandbox_cmd
+                .split_whitespace()
+                .map(|arg| arg.replace("{dir}", &dir_str)),
+        );
+    }
+    match open_cmd {
+        Some(open_cmd) => args.extend(open_cmd.split_whitespace().map(ToOwned::to_owned)),
+        None => args.push(
+            crev_lib::util::get_editor_to_use()
+                .to_string_lossy()
+                .into_owned(),
+        ),
+    }
+    args.push(dir_str.into_owned());
+
+    let status = process::Command::new(&args[0])
+        .args(&args[1..])
+        .current_dir(dir)
+        .status()
+        .map_err(|e| format_err!("Could not run `{}`: {}", args[0], e))?;
+    if !status.success() {
+        bail!("`{}` returned {}", args[0], status);
+    }
+    Ok(())
+}
+
+/// Open sources of a crate in `pkg_dir`
+///
+/// `cmd` overrides `open-cmd` from the `config`.
+pub fn open_crate(
+    pkg_dir: &Path,
+    config: &crev_lib::local::UserConfig,
+    cmd: Option<&str>,
+    sandbox: bool,
+) -> Result<()> {
+    let open_cmd = cmd.or(config.open_cmd.as_deref());
+    if !sandbox {
+        return open_dir(pkg_dir, open_cmd, None);
+    }
+
+    let tmp_dir = tempdir::TempDir::new("crev-sandbox")?;
+    let dir = tmp_dir.path().join(pkg_dir.file_name().unwrap_or_default());
+    copy_dir(pkg_dir, &dir)?;
+    open_dir(
+        &dir,
+        open_cmd,
+        Some(config.sandbox_cmd.as_deref().unwrap_or(DEFAULT_SANDBOX_CMD)),
+    )
+}
@@ -1,4 +1,4 @@
-use std::ffi::OsString;
+use std::{ffi::OsString, path::PathBuf};
 
 #[derive(Debug, StructOpt, Clone)]
 pub struct CrateSelector {
@@ -25,6 +25,7 @@ impl From<CrateSelectorNameRequired> for CrateSelector {
 pub struct NewId {
     #[structopt(long = "url")]
     /// URL of a git repository to be associated with the new Id
+    /// (`<repo-url>#<dir>` to keep proofs in a directory of the repository)
     pub url: Option<String>,
     #[structopt(long = "github-username")]
     /// Github username (instead of --url)
@@ -32,6 +33,14 @@ pub struct NewId {
     #[structopt(long = "https-push")]
     /// Setup `https` instead of recommended `ssh`-based push url
     pub use_https_push: bool,
+    #[structopt(long = "ssh-key", parse(from_os_str))]
+    /// Derive the Id from an ed25519 SSH key (eg. `~/.ssh/id_ed25519`), so it
+    /// can be created again from it; with --ssh-agent, the public key file
+    /// (eg. `~/.ssh/id_ed25519.pub`) picks the key
+    pub ssh_key: Option<PathBuf>,
+    #[structopt(long = "ssh-agent")]
+    /// Derive the Id from an ed25519 SSH key held by ssh-agent
+    pub ssh_agent: bool,
 }
 
 #[derive(Debug, StructOpt, Clone)]
@@ -47,10 +56,25 @@ pub struct SwitchId {
     pub id: String,
 }
 
+/// Cargo features to resolve dependencies with
+#[derive(Debug, StructOpt, Clone)]
+pub struct CargoFeatures {
+    #[structopt(long = "features")]
+    /// Space-separated list of features to activate
+    pub features: Vec<String>,
+    #[structopt(long = "all-features")]
+    /// Activate all available features (default, unless other features are selected)
+    pub all_features: bool,
+    #[structopt(long = "no-default-features")]
+    /// Do not activate the `default` feature
+    pub no_default_features: bool,
+}
+
 /// Parameters describing trust graph traversal
 #[derive(Debug, StructOpt, Clone)]
 pub struct TrustParams {
-    #[structopt(long = "depth", default_value = "10")]
+    #[structopt(long = "trust-depth", raw(alias = r#""depth""#), default_value = "10")]
+    /// Maximum distance of trusted Ids (also `--depth`, its former name)
     pub depth: u64,
     #[structopt(long = "high-cost", default_value = "0")]
     pub high_cost: u64,
@@ -58,6 +82,47 @@ pub struct TrustParams {
     pub medium_cost: u64,
     #[structopt(long = "low-cost", default_value = "5")]
     pub low_cost: u64,
+    #[structopt(long = "weakest-link")]
+    /// Trust Ids no more than the weakest link of the trust path leading to them
+    pub weakest_link: bool,
+    #[structopt(
+        long = "min-trust",
+        default_value = "low",
+        raw(possible_values = r#"&["high", "medium", "low"]"#)
+    )]
+    /// Only use Ids trusted at least this much
+    pub min_trust: String,
+    #[structopt(
+        long = "ignore-id",
+        parse(try_from_str = "crev_data::Id::crevid_from_str"),
+        raw(number_of_values = "1")
+    )]
+    /// Leave out reviews and trust of an Id (can be repeated;
+    /// see also `ignored-ids` in the config)
+    pub ignore_ids: Vec<crev_data::Id>,
+    #[structopt(long = "max-review-age")]
+    /// Treat reviews older than this many days as expired
+    pub max_review_age: Option<i64>,
+    #[structopt(long = "as-of", parse(try_from_str = "parse_as_of"))]
+    /// Only use proofs created before this time (`YYYY-MM-DD` for the start
+    /// of the day, UTC, or RFC 3339), eg. to check what was verified back then;
+    /// goes by the dates proofs' authors gave them, which can't be checked
+    pub as_of: Option<chrono::DateTime<chrono::Utc>>,
+    #[structopt(long = "criteria", raw(number_of_values = "1"))]
+    /// Only count positive reviews asserting a criteria, eg. `safe-to-deploy` (can be repeated)
+    pub criteria: Vec<String>,
+}
+
+fn parse_as_of(s: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(chrono::DateTime::from_utc(
+            date.and_hms(0, 0, 0),
+            chrono::Utc,
+        ));
+    }
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map(|date| date.with_timezone(&chrono::Utc))
+        .map_err(|e| format!("Invalid time `{}`: {}", s, e))
 }
 
 impl From<TrustParams> for crev_lib::trustdb::TrustDistanceParams {
@@ -67,14 +132,210 @@ impl From<TrustParams> for crev_lib::trustdb::TrustDistanceParams {
             high_trust_distance: params.high_cost,
             medium_trust_distance: params.medium_cost,
             low_trust_distance: params.low_cost,
+            cap_by_weakest_link: params.weakest_link,
+            min_trust: match params.min_trust.as_str() {
+                "high" => crev_data::proof::trust::TrustLevel::High,
+                "medium" => crev_data::proof::trust::TrustLevel::Medium,
+                _ => crev_data::proof::trust::TrustLevel::Low,
+            },
+            ignored_ids: params.ignore_ids.into_iter().collect(),
+            max_review_age: params.max_review_age.map(chrono::Duration::days),
+            as_of: params.as_of,
+            criteria: params.criteria,
         }
     }
 }
 
 #[derive(Debug, StructOpt, Clone)]
 pub struct VerifyDeps {
-    #[structopt(long = "verbose", short = "v")]
-    pub verbose: bool,
+    #[structopt(long = "fetch")]
+    /// Fetch proofs from trusted Ids before verifying
+    /// (can be made the default with `fetch-before-verify` in the config)
+    pub fetch: bool,
+    #[structopt(long = "dep-depth")]
+    /// Only verify dependencies up to a given depth (1 being direct dependencies)
+    pub max_depth: Option<usize>,
+    #[structopt(long = "package", short = "p")]
+    /// Only verify dependencies of a given workspace member
+    pub package: Option<String>,
+    #[structopt(long = "per-member")]
+    /// Show how many dependencies of every workspace member are verified, flagged and unknown
+    pub per_member: bool,
+    #[structopt(long = "direct-only")]
+    /// Only verify direct dependencies (same as `--dep-depth 1`)
+    pub direct_only: bool,
+    #[structopt(long = "no-dev")]
+    /// Skip dependencies used only during development (dev-dependencies)
+    pub no_dev: bool,
+    #[structopt(long = "no-build")]
+    /// Skip dependencies used only at build time (build-dependencies)
+    pub no_build: bool,
+    #[structopt(long = "only-build", conflicts_with = "no_build")]
+    /// Only verify dependencies used at build time (build-dependencies)
+    pub only_build: bool,
+    #[structopt(long = "no-rustsec")]
+    /// Don't check dependencies against the RustSec advisory database
+    pub no_rustsec: bool,
+    #[structopt(long = "vendor", parse(from_os_str))]
+    /// Verify sources vendored with `cargo vendor` in a given directory,
+    /// checking them against `.cargo-checksum.json` and the registry checksums
+    pub vendor: Option<PathBuf>,
+    #[structopt(long = "no-registry-check")]
+    /// Don't cross-check unpacked sources against the registry checksums
+    pub no_registry_check: bool,
+    #[structopt(long = "no-local-reviews")]
+    /// Ignore own local reviews (see `cargo crev local-review`)
+    pub no_local_reviews: bool,
+    #[structopt(long = "repin")]
+    /// Accept digests that changed since they were first seen (only once you know why)
+    pub repin: bool,
+    #[structopt(long = "no-suspicious-check")]
+    /// Don't warn about crates that are new, barely downloaded, or named like
+    /// a much more popular crate (possible typosquatting)
+    pub no_suspicious_check: bool,
+    #[structopt(
+        long = "output",
+        default_value = "text",
+        raw(possible_values = r#"&["text", "html", "markdown", "sarif", "json-lines"]"#)
+    )]
+    /// Output format: a text table, a self-contained report to share,
+    /// SARIF for code scanning (eg. on GitHub), or a JSON object per
+    /// dependency, printed as soon as it's verified
+    pub output: String,
+    #[structopt(
+        long = "color",
+        default_value = "auto",
+        raw(possible_values = r#"&["auto", "always", "never"]"#)
+    )]
+    /// Color the output (`auto`: only in a terminal, unless NO_COLOR is set)
+    pub color: String,
+    #[structopt(long = "unsafe")]
+    /// Show the amount of `unsafe` code, and its change since the last review
+    pub unsafe_: bool,
+    #[structopt(long = "size")]
+    /// Show the size of sources and lines of Rust code, to estimate the review effort
+    pub size: bool,
+    #[structopt(long = "review-age")]
+    /// Show the age (in days) of the newest review backing the `verified` status
+    pub review_age: bool,
+    #[structopt(long = "license")]
+    /// Show the license of every dependency
+    pub license: bool,
+    #[structopt(long = "users")]
+    /// Show how many trusted Ids use the version in production (`cargo crev use`)
+    pub users: bool,
+    #[structopt(long = "group-by-owner")]
+    /// Show which crates.io owners the dependencies come from, the most prolific first
+    pub group_by_owner: bool,
+    #[structopt(long = "show-reviewers")]
+    /// Show trusted reviewers (their proof repository URLs) that the status is based on
+    pub show_reviewers: bool,
+    #[structopt(flatten)]
+    pub features: CargoFeatures,
+    #[structopt(flatten)]
+    pub trust_params: TrustParams,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct ReviewCrate {
+    #[structopt(flatten)]
+    pub crate_: CrateSelectorNameRequired,
+    #[structopt(
+        long = "digest-type",
+        default_value = "blake2b",
+        raw(possible_values = r#"&["blake2b", "sha256"]"#)
+    )]
+    /// Digest algorithm to identify the reviewed code with
+    pub digest_type: String,
+    #[structopt(long = "unsafe")]
+    /// Record the amount of `unsafe` code in the review
+    pub unsafe_: bool,
+    #[structopt(long = "standalone")]
+    /// Download the crate from crates.io, instead of looking for it
+    /// in dependencies of the current project
+    pub standalone: bool,
+    #[structopt(long = "path", parse(from_os_str), conflicts_with = "standalone")]
+    /// Review sources in a local directory (eg. vendored code, a fork or
+    /// an unpacked pre-release); the version has to be given
+    pub path: Option<PathBuf>,
+    #[structopt(long = "source", default_value = "https://crates.io")]
+    /// Where the package reviewed with `--path` comes from
+    pub source: String,
+    #[structopt(long = "publish")]
+    /// Commit and push the review right away
+    /// (can be made the default with `auto-publish` in the config)
+    pub publish: bool,
+    #[structopt(long = "unsigned", conflicts_with = "publish")]
+    /// Print the review unsigned instead of storing it, to sign it on the
+    /// machine holding your Id (`cargo crev proof sign`)
+    pub unsigned: bool,
+    #[structopt(long = "diff-base")]
+    /// Show changes since this version before reviewing (by default: since
+    /// the newest older version you reviewed)
+    pub diff_base: Option<String>,
+    #[structopt(long = "no-diff", conflicts_with = "diff_base")]
+    /// Don't show changes since an earlier reviewed version
+    pub no_diff: bool,
+    #[structopt(long = "sandbox")]
+    /// Inspect a copy of the sources in a sandbox (see `sandbox-cmd` in the
+    /// config) before writing the review
+    pub sandbox: bool,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct VerifyCrate {
+    #[structopt(flatten)]
+    pub crate_: CrateSelectorNameRequired,
+    #[structopt(long = "pre")]
+    /// Without a version, use the newest one even if it's a pre-release
+    pub pre: bool,
+    #[structopt(
+        long = "color",
+        default_value = "auto",
+        raw(possible_values = r#"&["auto", "always", "never"]"#)
+    )]
+    /// Color the output (`auto`: only in a terminal, unless NO_COLOR is set)
+    pub color: String,
+    #[structopt(flatten)]
+    pub trust_params: TrustParams,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct VerifyCoverage {
+    #[structopt(long = "breakdown")]
+    /// Additionally show coverage of direct and transitive dependencies separately
+    pub breakdown: bool,
+    #[structopt(flatten)]
+    pub trust_params: TrustParams,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct VerifyTree {
+    #[structopt(long = "dep-depth")]
+    /// Only show dependencies up to a given depth (1 being direct dependencies)
+    pub max_depth: Option<usize>,
+    #[structopt(
+        long = "color",
+        default_value = "auto",
+        raw(possible_values = r#"&["auto", "always", "never"]"#)
+    )]
+    /// Color the output (`auto`: only in a terminal, unless NO_COLOR is set)
+    pub color: String,
+    #[structopt(flatten)]
+    pub features: CargoFeatures,
+    #[structopt(flatten)]
+    pub trust_params: TrustParams,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct VerifyCompare {
+    /// Trust profile (`trust-profiles` of the config) to compare with;
+    /// `default` uses the trust parameters given on the command line
+    pub base: String,
+    /// Trust profile to compare to the first one
+    pub other: String,
+    #[structopt(flatten)]
+    pub features: CargoFeatures,
     #[structopt(flatten)]
     pub trust_params: TrustParams,
 }
@@ -84,12 +345,373 @@ pub enum Verify {
     /// Verify dependencies
     #[structopt(name = "deps")]
     Deps(VerifyDeps),
+
+    /// Download and verify a single crate (does not have to be a dependency)
+    #[structopt(name = "crate")]
+    Crate(VerifyCrate),
+
+    /// Summarize how much of the dependency tree is reviewed
+    #[structopt(name = "coverage")]
+    Coverage(VerifyCoverage),
+
+    /// Show the dependency tree with verification status of every crate
+    #[structopt(name = "tree")]
+    Tree(VerifyTree),
+
+    /// Show dependencies verified differently under two trust profiles
+    #[structopt(name = "compare")]
+    Compare(VerifyCompare),
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct PublishCheck {
+    #[structopt(long = "review")]
+    /// Create a package review of the crate after checking it
+    pub review: bool,
+    #[structopt(long = "allow-dirty")]
+    /// Allow packaging with uncommitted changes
+    pub allow_dirty: bool,
+    #[structopt(flatten)]
+    pub trust_params: TrustParams,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct Tui {
+    #[structopt(flatten)]
+    pub features: CargoFeatures,
+    #[structopt(flatten)]
+    pub trust_params: TrustParams,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct Serve {
+    #[structopt(long = "port", default_value = "8080")]
+    /// Port to listen on (on the local interface only)
+    pub port: u16,
+    #[structopt(flatten)]
+    pub features: CargoFeatures,
+    #[structopt(flatten)]
+    pub trust_params: TrustParams,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct Completions {
+    #[structopt(raw(possible_values = "&structopt::clap::Shell::variants()"))]
+    /// Shell to generate the completion script for
+    pub shell: structopt::clap::Shell,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct Open {
+    #[structopt(flatten)]
+    pub crate_: CrateSelectorNameRequired,
+    #[structopt(long = "cmd")]
+    /// Command to open the sources with (instead of `open-cmd` from the config)
+    pub cmd: Option<String>,
+    #[structopt(long = "sandbox")]
+    /// Open a copy of the sources in a sandbox (see `sandbox-cmd` in the config)
+    pub sandbox: bool,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct Why {
+    #[structopt(flatten)]
+    pub crate_: CrateSelectorNameRequired,
+    #[structopt(long = "no-rustsec")]
+    /// Don't check the crate against the RustSec advisory database
+    pub no_rustsec: bool,
+    #[structopt(flatten)]
+    pub features: CargoFeatures,
+    #[structopt(flatten)]
+    pub trust_params: TrustParams,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct CrateInfo {
+    /// Name of the crate
+    pub name: String,
+    #[structopt(long = "no-rustsec")]
+    /// Don't look up the crate in the RustSec advisory database
+    pub no_rustsec: bool,
+    #[structopt(long = "no-downloads")]
+    /// Don't look up download counts on crates.io
+    pub no_downloads: bool,
+    #[structopt(flatten)]
+    pub features: CargoFeatures,
+    #[structopt(flatten)]
+    pub trust_params: TrustParams,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub enum Crate {
+    /// Show everything known about a crate: reviews, advisories, reviewers...
+    #[structopt(name = "info")]
+    Info(CrateInfo),
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct Provenance {
+    #[structopt(flatten)]
+    pub crate_: CrateSelectorNameRequired,
+    #[structopt(long = "repository")]
+    /// Repository to compare with (instead of the one in the crate's `Cargo.toml`)
+    pub repository: Option<String>,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct LocalReviewAdd {
+    #[structopt(flatten)]
+    pub crate_: CrateSelectorNameRequired,
+    #[structopt(long = "standalone")]
+    /// Download the crate from crates.io, instead of looking for it
+    /// in dependencies of the current project
+    pub standalone: bool,
+    #[structopt(long = "comment", default_value = "")]
+    /// Note to keep with the review
+    pub comment: String,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct LocalReviewRemove {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub enum LocalReview {
+    /// Mark a crate version as reviewed, without creating a proof
+    #[structopt(name = "add")]
+    Add(LocalReviewAdd),
+
+    /// Remove the local review of a crate version
+    #[structopt(name = "remove")]
+    Remove(LocalReviewRemove),
+
+    /// List local reviews
+    #[structopt(name = "list")]
+    List,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct NoteAdd {
+    #[structopt(flatten)]
+    pub crate_: CrateSelectorNameRequired,
+    #[structopt(long = "message", short = "m")]
+    /// Text of the note
+    pub message: String,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct NoteRemove {
+    #[structopt(flatten)]
+    pub crate_: CrateSelectorNameRequired,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct NoteList {
+    /// Only notes about a given crate
+    pub name: Option<String>,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub enum Note {
+    /// Attach a note to a crate version (or to the crate, without a version)
+    #[structopt(name = "add")]
+    Add(NoteAdd),
+
+    /// Remove notes attached to a crate version (or to the crate, without a version)
+    #[structopt(name = "remove")]
+    Remove(NoteRemove),
+
+    /// List notes
+    #[structopt(name = "list")]
+    List(NoteList),
 }
 
 #[derive(Debug, StructOpt, Clone)]
 pub struct Trust {
     /// Public IDs to create Trust Proof for
     pub pub_ids: Vec<String>,
+    #[structopt(long = "publish")]
+    /// Commit and push the proof right away
+    /// (can be made the default with `auto-publish` in the config)
+    pub publish: bool,
+    #[structopt(long = "unsigned", conflicts_with = "publish")]
+    /// Print the proof unsigned instead of storing it, to sign it on the
+    /// machine holding your Id (`cargo crev proof sign`)
+    pub unsigned: bool,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct TrustIds {
+    #[structopt(flatten)]
+    pub trust: Trust,
+    #[structopt(long = "import")]
+    /// Trust Ids from a list (file or https URL) of `<id> <proof-repository-url>` lines,
+    /// asking about every one
+    pub import: Option<String>,
+    #[structopt(
+        long = "level",
+        default_value = "medium",
+        raw(possible_values = r#"&["low", "medium", "high"]"#)
+    )]
+    /// Trust level of Ids imported with `--import`
+    pub level: String,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct Issue {
+    /// Name of the crate
+    pub name: String,
+    /// Affected versions, as a semver requirement (eg. ">=1.0.0, <1.2.3")
+    pub versions: String,
+    #[structopt(long = "source", default_value = "https://crates.io")]
+    /// Where the package comes from
+    pub source: String,
+    #[structopt(long = "publish")]
+    /// Commit and push the proof right away
+    /// (can be made the default with `auto-publish` in the config)
+    pub publish: bool,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct Alternative {
+    /// Name of the crate to replace
+    pub name: String,
+    /// Name of the recommended crate
+    pub alternative: String,
+    #[structopt(long = "source", default_value = "https://crates.io")]
+    /// Where the packages come from
+    pub source: String,
+    #[structopt(long = "publish")]
+    /// Commit and push the proof right away
+    /// (can be made the default with `auto-publish` in the config)
+    pub publish: bool,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct Use {
+    /// Names of the crates (all dependencies used outside of development if none)
+    pub names: Vec<String>,
+    #[structopt(long = "comment", default_value = "")]
+    /// Comment added to every proof, eg. what the crates are used in
+    pub comment: String,
+    #[structopt(long = "publish")]
+    /// Commit and push the proofs right away
+    /// (can be made the default with `auto-publish` in the config)
+    pub publish: bool,
+    #[structopt(flatten)]
+    pub features: CargoFeatures,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct IdClaim {
+    #[structopt(raw(possible_values = r#"&["github", "crates-io"]"#))]
+    /// Service of the account
+    pub service: String,
+    /// Login on the service
+    pub name: String,
+    /// URL of a public gist or repository file of the account, containing the current Id
+    pub proof_url: String,
+    #[structopt(long = "publish")]
+    /// Commit and push the proof right away
+    /// (can be made the default with `auto-publish` in the config)
+    pub publish: bool,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct IdLookup {
+    /// Login on the service
+    pub name: String,
+    #[structopt(
+        long = "service",
+        default_value = "github",
+        raw(possible_values = r#"&["github", "crates-io"]"#)
+    )]
+    /// Service of the account
+    pub service: String,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub enum Id {
+    /// Claim an account on another service as belonging to the current Id
+    #[structopt(name = "claim")]
+    Claim(IdClaim),
+
+    /// Find Ids (and their proof repositories) claiming an account on another service
+    #[structopt(name = "lookup")]
+    Lookup(IdLookup),
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub enum Org {
+    /// Vouch for Ids as members of the organization of the current Id
+    #[structopt(name = "add")]
+    Add(Trust),
+
+    /// Revoke membership of Ids in the organization of the current Id
+    #[structopt(name = "remove")]
+    Remove(Trust),
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct AdvisoryExport {
+    #[structopt(
+        long = "format",
+        default_value = "osv",
+        raw(possible_values = r#"&["osv", "rustsec"]"#)
+    )]
+    /// Output format: OSV (JSON) or RustSec advisory-db (TOML)
+    pub format: String,
+    /// Only export advisories about a given crate
+    pub name: Option<String>,
+    #[structopt(flatten)]
+    pub trust_params: TrustParams,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub enum Advisory {
+    /// Export advisories of trusted Ids for use by other tools
+    #[structopt(name = "export")]
+    Export(AdvisoryExport),
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct ExportVet {
+    #[structopt(long = "who")]
+    /// Auditor to name in the audits (the URL of the current Id by default)
+    pub who: Option<String>,
+    #[structopt(long = "criteria", raw(number_of_values = "1"))]
+    /// Criteria to use for reviews of a rating, as `<rating>=<criteria>` (eg. `neutral=safe-to-run`;
+    /// an empty criteria leaves them out); negative and dangerous reviews become violations
+    pub criteria: Vec<String>,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub enum Export {
+    /// Print own package reviews as cargo-vet `audits.toml`
+    #[structopt(name = "vet")]
+    Vet(ExportVet),
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct ImportVet {
+    #[structopt(parse(from_os_str))]
+    /// cargo-vet `audits.toml` to import
+    pub path: PathBuf,
+    #[structopt(long = "criteria", raw(number_of_values = "1"))]
+    /// Rating to give audits of a criteria, as `<rating>=<criteria>` (like in `export vet`)
+    pub criteria: Vec<String>,
+    #[structopt(long = "publish")]
+    /// Commit and push the reviews right away
+    pub publish: bool,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub enum Import {
+    /// Review crates audited in cargo-vet `audits.toml`, starting from drafts made of the audits
+    #[structopt(name = "vet")]
+    Vet(ImportVet),
 }
 
 #[derive(Debug, StructOpt, Clone)]
@@ -98,11 +720,67 @@ pub struct FetchUrl {
     pub url: String,
 }
 
+#[derive(Debug, StructOpt, Clone)]
+pub struct FetchTrusted {
+    #[structopt(flatten)]
+    pub trust_params: TrustParams,
+    #[structopt(long = "prune")]
+    /// Remove cached proof repositories other than the ones of trusted Ids
+    /// (or of Ids trusted by them, see `fetch discovered`), including the
+    /// ones fetched with `fetch url`
+    pub prune: bool,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct Watch {
+    #[structopt(long = "interval", default_value = "2")]
+    /// Seconds between checks for changes
+    pub interval: u64,
+    #[structopt(flatten)]
+    pub features: CargoFeatures,
+    #[structopt(flatten)]
+    pub trust_params: TrustParams,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct SimulateTrust {
+    /// Id (or proof repository URL) to trust
+    pub id: String,
+    #[structopt(
+        long = "level",
+        default_value = "medium",
+        raw(possible_values = r#"&["distrust", "none", "low", "medium", "high"]"#)
+    )]
+    /// Trust level to simulate
+    pub level: String,
+    #[structopt(flatten)]
+    pub features: CargoFeatures,
+    #[structopt(flatten)]
+    pub trust_params: TrustParams,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub enum Simulate {
+    /// Show how the trust set and verification of dependencies would change
+    /// if you trusted an Id
+    #[structopt(name = "trust")]
+    Trust(SimulateTrust),
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct Notify {
+    #[structopt(long = "fetch")]
+    /// Fetch proofs from trusted Ids first
+    pub fetch: bool,
+    #[structopt(flatten)]
+    pub trust_params: TrustParams,
+}
+
 #[derive(Debug, StructOpt, Clone)]
 pub enum Fetch {
     #[structopt(name = "trusted")]
-    /// Fetch updates from trusted Ids
-    Trusted(TrustParams),
+    /// Fetch updates from trusted Ids and the RustSec advisory database
+    Trusted(FetchTrusted),
 
     #[structopt(name = "url")]
     /// Fetch from a single public proof repository
@@ -110,7 +788,13 @@ pub enum Fetch {
 
     #[structopt(name = "all")]
     /// Fetch all previously retrieved public proof repositories
+    /// and the RustSec advisory database
     All,
+
+    #[structopt(name = "discovered")]
+    /// Fetch proof repositories of Ids trusted by your trust set, but outside of it,
+    /// found by the last `fetch trusted`
+    Discovered,
 }
 
 #[derive(Debug, StructOpt, Clone)]
@@ -130,6 +814,10 @@ pub enum QueryId {
     /// List trusted ids
     #[structopt(name = "trusted")]
     Trusted(QueryIdTrusted),
+
+    /// List Ids that published trust in an Id, with their levels and dates
+    #[structopt(name = "trusters")]
+    Trusters(QueryIdTrusters),
 }
 
 #[derive(Debug, StructOpt, Clone)]
@@ -138,10 +826,23 @@ pub struct QueryIdTrusted {
     pub trust_params: TrustParams,
 }
 
+#[derive(Debug, StructOpt, Clone)]
+pub struct QueryIdTrusters {
+    /// Id or proof repository URL (the current Id if not given)
+    pub id: Option<String>,
+}
+
 #[derive(Debug, StructOpt, Clone)]
 pub struct QueryReview {
     #[structopt(flatten)]
     pub crate_: CrateSelector,
+    #[structopt(long = "author")]
+    /// Only reviews by a given Id (or Ids using a given proof repository URL)
+    pub author: Option<String>,
+    #[structopt(long = "grep")]
+    /// Only reviews with comments containing words starting with
+    /// the given ones, ignoring case (eg. "unsafe transm")
+    pub grep: Option<String>,
 }
 
 #[derive(Debug, StructOpt, Clone)]
@@ -153,6 +854,58 @@ pub enum Query {
     /// Query reviews
     #[structopt(name = "review")]
     Review(QueryReview),
+
+    /// Query trust relationships between Ids
+    #[structopt(name = "trust")]
+    Trust(QueryTrust),
+
+    /// Query alternatives to a crate recommended by trusted Ids
+    #[structopt(name = "alternatives")]
+    Alternatives(QueryAlternatives),
+
+    /// Show review activity of known Ids, and how many trusted Ids trust them
+    #[structopt(name = "reviewers")]
+    Reviewers(QueryReviewers),
+
+    /// Query current members of an organization
+    #[structopt(name = "members")]
+    Members(QueryMembers),
+    /// Show statistics about the proofs in the trust database
+    #[structopt(name = "stats")]
+    Stats,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct QueryAlternatives {
+    /// Name of the crate
+    pub name: String,
+    #[structopt(long = "source", default_value = "https://crates.io")]
+    /// Where the package comes from
+    pub source: String,
+    #[structopt(flatten)]
+    pub trust_params: TrustParams,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct QueryReviewers {
+    #[structopt(flatten)]
+    pub trust_params: TrustParams,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct QueryMembers {
+    /// Organization Id (or proof repository URL); the current Id by default
+    pub org: Option<String>,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct QueryTrust {
+    #[structopt(long = "of")]
+    /// Only trust in a given Id (or Ids using a given proof repository URL)
+    pub of: Option<String>,
+    #[structopt(long = "by")]
+    /// Only trust by a given Id (or Ids using a given proof repository URL)
+    pub by: Option<String>,
 }
 
 #[derive(Debug, StructOpt, Clone)]
@@ -169,6 +922,154 @@ pub enum Edit {
     Readme,
 }
 
+#[derive(Debug, StructOpt, Clone)]
+pub enum Repo {
+    /// Show proofs and changes that are not committed or pushed yet
+    #[structopt(name = "status")]
+    Status,
+
+    /// Remove duplicated and superseded proofs from the local proof repository
+    #[structopt(name = "compact")]
+    Compact,
+
+    /// Verify CrevID signatures of commits in fetched proof repositories
+    #[structopt(name = "verify")]
+    Verify,
+
+    /// Strictly check proofs in the local proof repository
+    #[structopt(name = "lint")]
+    Lint(RepoLint),
+
+    /// Upgrade own proofs in older format versions to the current one
+    #[structopt(name = "migrate")]
+    Migrate,
+
+    /// Log own proofs in the transparency log from the config, so they can't be backdated unnoticed
+    #[structopt(name = "anchor")]
+    Anchor,
+
+    /// Check proofs against their transparency log anchors
+    #[structopt(name = "check-anchors")]
+    CheckAnchors(RepoCheckAnchors),
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct RepoLint {
+    #[structopt(long = "remotes")]
+    /// Check fetched proof repositories too
+    pub remotes: bool,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct RepoCheckAnchors {
+    #[structopt(long = "remotes")]
+    /// Check fetched proof repositories too
+    pub remotes: bool,
+
+    #[structopt(long = "online")]
+    /// Also look up the entries in the log, to make sure they are still there
+    pub online: bool,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub enum Bundle {
+    /// Write proofs to a single signed file, eg. to carry it to an offline machine
+    #[structopt(name = "export")]
+    Export(BundleExport),
+
+    /// Import proofs from a bundle file
+    #[structopt(name = "import")]
+    Import(BundleImport),
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct BundleExport {
+    #[structopt(long = "all")]
+    /// All known proofs (fetched and imported ones too), not just own ones
+    pub all: bool,
+    #[structopt(long = "crate", raw(number_of_values = "1"))]
+    /// Only proofs about the given crates (can be repeated)
+    pub crates: Vec<String>,
+    #[structopt(long = "signature", raw(number_of_values = "1"))]
+    /// Only proofs with the given signatures (can be repeated)
+    pub signatures: Vec<String>,
+    #[structopt(long = "output", short = "o", parse(from_os_str))]
+    /// File to write the bundle to (stdout by default)
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct BundleImport {
+    #[structopt(parse(from_os_str))]
+    /// Bundle file (`-` for stdin)
+    pub path: PathBuf,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct ProofImport {
+    #[structopt(parse(from_os_str))]
+    /// File to read proofs from (`-` for stdin)
+    pub path: PathBuf,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct ProofExport {
+    #[structopt(long = "signature")]
+    /// Signature of the proof to print
+    pub signature: String,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct ProofSign {
+    #[structopt(parse(from_os_str))]
+    /// File with unsigned proofs (`-` for stdin)
+    pub path: PathBuf,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct ProofAttachSignature {
+    #[structopt(parse(from_os_str))]
+    /// File with the unsigned proofs that were signed
+    pub path: PathBuf,
+    /// Signatures printed by `proof sign`, in the same order as the proofs
+    pub signatures: Vec<String>,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub enum Proof {
+    /// Import proofs from a file, eg. received by email
+    #[structopt(name = "import")]
+    Import(ProofImport),
+
+    /// Print a proof, eg. to send it by email
+    #[structopt(name = "export")]
+    Export(ProofExport),
+
+    /// Decrypt proofs encrypted to your Id (see `encrypt-to` of `proof-routes` in the config)
+    #[structopt(name = "decrypt")]
+    Decrypt,
+
+    /// Sign unsigned proofs (eg. from `review --unsigned`) made by your Id on
+    /// another machine, printing their signatures
+    #[structopt(name = "sign")]
+    Sign(ProofSign),
+
+    /// Add signatures made with `proof sign` to unsigned proofs, and store them
+    #[structopt(name = "attach-signature")]
+    AttachSignature(ProofAttachSignature),
+}
+
+#[derive(Debug, StructOpt, Clone)]
+pub struct Commit {
+    #[structopt(long = "sign")]
+    /// Sign the commit with the current CrevID
+    /// (can be made the default with `sign-commits` in the config)
+    pub sign: bool,
+    #[structopt(long = "message", short = "m")]
+    /// Commit message (`Update proofs` by default)
+    pub message: Option<String>,
+}
+
 #[derive(Debug, StructOpt, Clone)]
 pub struct Git {
     /// Arguments to git command
@@ -194,13 +1095,59 @@ pub enum Command {
     #[structopt(name = "verify")]
     Verify(Verify),
 
+    /// Open the sources of a crate, to review them
+    #[structopt(name = "open")]
+    Open(Open),
+
+    /// Explain the verification status of a dependency
+    #[structopt(name = "why")]
+    Why(Why),
+
+    /// Look into a crate
+    #[structopt(name = "crate")]
+    Crate(Crate),
+
+    /// Compare the published sources of a crate with its repository
+    #[structopt(name = "provenance")]
+    Provenance(Provenance),
+
+    /// Keep private notes about crates (never published)
+    #[structopt(name = "note")]
+    Note(Note),
+
+    /// Mark crates as reviewed on this machine only (never signed or published)
+    #[structopt(name = "local-review")]
+    LocalReview(LocalReview),
+
     /// Review a crate
     #[structopt(name = "review")]
-    Review(CrateSelectorNameRequired),
+    Review(ReviewCrate),
 
     /// Flag a crate as buggy/low-quality/dangerous
     #[structopt(name = "flag")]
-    Flag(CrateSelectorNameRequired),
+    Flag(ReviewCrate),
+
+    /// Browse, verify and review dependencies interactively
+    #[structopt(name = "tui")]
+    Tui(Tui),
+
+    /// Browse the trust set, reviews and verification results in a web browser
+    #[structopt(name = "serve")]
+    Serve(Serve),
+
+    /// Check the crate in the current directory before publishing it
+    #[structopt(name = "publish-check")]
+    PublishCheck(PublishCheck),
+
+    /// Work with advisories
+    #[structopt(name = "advisory")]
+    Advisory(Advisory),
+    /// Export own reviews for use by other tools
+    #[structopt(name = "export")]
+    Export(Export),
+    /// Turn reviews made with other tools into own reviews
+    #[structopt(name = "import")]
+    Import(Import),
 
     /// Query Ids, packages, reviews...
     #[structopt(name = "query")]
@@ -208,36 +1155,84 @@ pub enum Command {
 
     /// Trust an Id
     #[structopt(name = "trust")]
-    Trust(Trust),
+    Trust(TrustIds),
 
     /// Distrust an Id
     #[structopt(name = "distrust")]
     Distrust(Trust),
 
+    /// See what a proof would change before creating it
+    #[structopt(name = "simulate")]
+    Simulate(Simulate),
+
+    /// Report an issue affecting a range of versions of a crate
+    #[structopt(name = "issue")]
+    Issue(Issue),
+
+    /// Recommend replacing a crate with another one
+    #[structopt(name = "alternative")]
+    Alternative(Alternative),
+
+    /// State that you use dependency versions from `Cargo.lock` in production, without reviewing them
+    #[structopt(name = "use")]
+    Use(Use),
+
+    /// Link the current Id to accounts on other services, or find Ids by such accounts
+    #[structopt(name = "id")]
+    Id(Id),
+
+    /// Manage members of an organization (using the current Id as the organization Id)
+    #[structopt(name = "org")]
+    Org(Org),
+
     /// Fetch proofs from external sources
     #[structopt(name = "fetch")]
     Fetch(Fetch),
 
+    /// Show proofs added since the last run that concern your dependencies or trust
+    #[structopt(name = "notify")]
+    Notify(Notify),
+
+    /// Re-verify dependencies when `Cargo.lock` or proofs change, showing status changes
+    #[structopt(name = "watch")]
+    Watch(Watch),
+
+    /// Manage the local proof repository
+    #[structopt(name = "repo")]
+    Repo(Repo),
+
+    /// Move individual proofs in and out, without proof repositories
+    #[structopt(name = "proof")]
+    Proof(Proof),
+
+    /// Move many proofs at once in a signed file, where git can't reach
+    #[structopt(name = "bundle")]
+    Bundle(Bundle),
+
     /// Run raw git commands in the local proof repository
     #[structopt(name = "git")]
     #[structopt(raw(setting = "structopt::clap::AppSettings::TrailingVarArg"))]
     Git(Git),
 
-    /// See changes in the local proof repository (alias to `git diff`)
+    /// See uncommitted changes in the local proof repository (like `git diff HEAD`)
     #[structopt(name = "diff")]
     Diff,
 
-    /// Commit changes to the local proof repository (alias to `git commit -a`)
+    /// Commit changes to the local proof repository (like `git commit -a`)
     #[structopt(name = "commit")]
-    Commit,
+    Commit(Commit),
 
-    /// Push local changes to the public proof repository (alias to `git push HEAD`)
+    /// Push local changes to the public proof repository (like `git push`)
     #[structopt(name = "push")]
     Push,
 
-    /// Pull changes from the public proof repository (alias to `git pull`)
+    /// Pull changes from the public proof repository (like `git pull --ff-only`)
     #[structopt(name = "pull")]
     Pull,
+
+    /// Print a shell completion script for `cargo-crev` to stdout
+    #[structopt(name = "completions")]
+    Completions(Completions),
 }
 
 /// Cargo will pass the name of the `cargo-<tool>`
@@ -248,11 +1243,43 @@ pub enum MainCommand {
     Crev(Command),
 }
 
+/// Cargo's flags limiting updates of `Cargo.lock` and network access
+#[derive(Debug, StructOpt, Clone, Default)]
+pub struct CargoFlags {
+    #[structopt(long = "offline", raw(global = "true"))]
+    /// Don't access the network (don't update the registry index or download crates)
+    pub offline: bool,
+    #[structopt(long = "frozen", raw(global = "true"))]
+    /// Require Cargo.lock and the cache to be up to date
+    pub frozen: bool,
+    #[structopt(long = "locked", raw(global = "true"))]
+    /// Require Cargo.lock to be up to date
+    pub locked: bool,
+}
+
+/// How much to tell on stderr
+#[derive(Debug, StructOpt, Clone, Default)]
+pub struct Verbosity {
+    #[structopt(
+        long = "verbose",
+        short = "v",
+        parse(from_occurrences),
+        raw(global = "true")
+    )]
+    /// Show more details, and log what's going on (`-vv` for debug logs, `-vvv` for all)
+    pub verbose: u64,
+    #[structopt(long = "quiet", short = "q", raw(global = "true"))]
+    /// Don't show progress, and log only errors
+    pub quiet: bool,
+}
+
 #[derive(Debug, StructOpt, Clone)]
 #[structopt(name = "crev", about = "Distributed code review system")]
 pub struct Opts {
     #[structopt(subcommand)]
     pub command: MainCommand,
-    //    #[structopt(flatten)]
-    //    verbosity: Verbosity,
+    #[structopt(flatten)]
+    pub cargo_flags: CargoFlags,
+    #[structopt(flatten)]
+    pub verbosity: Verbosity,
 }
@@ -0,0 +1,87 @@
+//! Progress of long operations, shown on stderr
+use std::{
+    cell::Cell,
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+/// Single-line progress indicator: `[ 12/300] name  ETA 1m20s`
+///
+/// Drawn only when stderr is a terminal. Uses interior mutability,
+/// so that the code doing the work can `clear` it before printing.
+pub struct Progress {
+    enabled: bool,
+    total: Cell<usize>,
+    done: Cell<usize>,
+    start: Instant,
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs < 60 {
+        format!("{}s", secs)
+    } else {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    }
+}
+
+impl Progress {
+    pub fn new(quiet: bool) -> Self {
+        Self {
+            enabled: !quiet && atty::is(atty::Stream::Stderr),
+            total: Cell::new(0),
+            done: Cell::new(0),
+            start: Instant::now(),
+        }
+    }
+
+    pub fn hidden() -> Self {
+        Self::new(true)
+    }
+
+    pub fn set_total(&self, total: usize) {
+        self.total.set(total);
+    }
+
+    /// Start working on the next item, called `name`
+    pub fn next(&self, name: &str) {
+        let done = self.done.get();
+        self.done.set(done + 1);
+        if !self.enabled {
+            return;
+        }
+
+        let total = self.total.get();
+        let eta = if done == 0 {
+            String::new()
+        } else {
+            let elapsed = self.start.elapsed();
+            let left = total.saturating_sub(done) as u32;
+            format!("  ETA {}", format_duration(elapsed / done as u32 * left))
+        };
+        let width = total.to_string().len();
+        eprint!(
+            "\r\x1b[K[{:>width$}/{}] {}{}",
+            done + 1,
+            total,
+            name,
+            eta,
+            width = width
+        );
+        let _ = io::stderr().flush();
+    }
+
+    /// Remove the progress line, eg. before printing something
+    pub fn clear(&self) {
+        if self.enabled {
+            eprint!("\r\x1b[K");
+            let _ = io::stderr().flush();
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
@@ -0,0 +1,183 @@
+//! Checking published crate sources against the repository they claim to come from
+//!
+//! `cargo package` records the commit it packaged in `.cargo_vcs_info.json`;
+//! crates published without it are looked up by the usual release tags.
+//! Files that are in the published crate, but not in the repository at
+//! that commit, were never reviewed by anyone following the repository.
+use crate::prelude::*;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Files `cargo package` generates or rewrites
+const GENERATED_FILES: &[&str] = &[
+    ".cargo-ok",
+    ".cargo_vcs_info.json",
+    "Cargo.toml",
+    "Cargo.lock",
+];
+
+/// The commit a crate was packaged from, as recorded by `cargo package`
+struct VcsInfo {
+    sha1: String,
+    /// Directory of the crate in the repository
+    path_in_vcs: String,
+}
+
+fn read_vcs_info(pkg_dir: &Path) -> Result<Option<VcsInfo>> {
+    let path = pkg_dir.join(".cargo_vcs_info.json");
+    if !path.exists() {
+        return Ok(None);
+    }
+    let info: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path)?)?;
+    Ok(info["git"]["sha1"].as_str().map(|sha1| VcsInfo {
+        sha1: sha1.to_owned(),
+        path_in_vcs: info["path_in_vcs"].as_str().unwrap_or("").to_owned(),
+    }))
+}
+
+/// Fetch all branches and tags of the repository at `url` to a bare clone in `dir`
+fn fetch_repository(url: &str, dir: &Path) -> Result<git2::Repository> {
+    let repo = if dir.exists() {
+        git2::Repository::open_bare(dir)?
+    } else {
+        git2::Repository::init_bare(dir)?
+    };
+    eprintln!("Fetching {} to {}", url, dir.display());
+    repo.remote_anonymous(url)?.fetch(
+        &["+refs/heads/*:refs/heads/*", "+refs/tags/*:refs/tags/*"],
+        None,
+        None,
+    )?;
+    Ok(repo)
+}
+
+/// Tags a release of `name` `version` is commonly tagged with
+fn release_tags(name: &str, version: &str) -> Vec<String> {
+    vec![
+        format!("v{}", version),
+        version.to_owned(),
+        format!("{}-v{}", name, version),
+        format!("{}-{}", name, version),
+        format!("{}/v{}", name, version),
+    ]
+}
+
+/// Result of comparing published sources with the repository
+pub struct Report {
+    /// Commit the sources were compared with
+    pub commit: String,
+    /// How the commit was found
+    pub found_by: String,
+    /// Directory of the crate in the repository
+    pub path_in_vcs: String,
+    /// Problems with finding the commit
+    pub notes: Vec<String>,
+    /// Files only in the published crate
+    pub added: Vec<PathBuf>,
+    /// Files different in the published crate
+    pub changed: Vec<PathBuf>,
+}
+
+impl Report {
+    pub fn is_clean(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compare the published sources of `name` `version` in `pkg_dir` with the
+/// repository at `url`, keeping a clone of it in `cache_dir`
+pub fn check(
+    pkg_dir: &Path,
+    url: &str,
+    cache_dir: &Path,
+    name: &str,
+    version: &str,
+) -> Result<Report> {
+    let digest = crev_data::Digest::from_vec(crev_common::blake2b256sum(url.as_bytes()));
+    let repo = fetch_repository(url, &cache_dir.join(digest.to_string()))?;
+
+    let mut notes = vec![];
+    let mut found = None;
+    let vcs_info = read_vcs_info(pkg_dir)?;
+    if let Some(vcs_info) = &vcs_info {
+        match git2::Oid::from_str(&vcs_info.sha1).and_then(|oid| repo.find_commit(oid)) {
+            Ok(commit) => {
+                found = Some((
+                    commit,
+                    "recorded in .cargo_vcs_info.json".to_owned(),
+                    vcs_info.path_in_vcs.clone(),
+                ))
+            }
+            Err(_) => notes.push(format!(
+                "commit {} recorded in .cargo_vcs_info.json is not in the repository",
+                vcs_info.sha1
+            )),
+        }
+    } else {
+        notes.push("the crate doesn't record the commit it was published from".into());
+    }
+    if found.is_none() {
+        let path_in_vcs = vcs_info
+            .map(|vcs_info| vcs_info.path_in_vcs)
+            .unwrap_or_default();
+        for tag in release_tags(name, version) {
+            if let Ok(commit) = repo
+                .revparse_single(&format!("refs/tags/{}", tag))
+                .and_then(|object| object.peel_to_commit())
+            {
+                found = Some((commit, format!("tag {}", tag), path_in_vcs));
+                break;
+            }
+        }
+    }
+    let (commit, found_by, path_in_vcs) = found.ok_or_else(|| {
+        format_err!(
+            "Can't find the commit {} {} was published from in {}",
+            name,
+            version,
+            url
+        )
+    })?;
+    let tree = commit.tree()?;
+
+    let mut added = vec![];
+    let mut changed = vec![];
+    for entry in walkdir::WalkDir::new(pkg_dir).sort_by(|a, b| a.file_name().cmp(b.file_name())) {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel_path = entry.path().strip_prefix(pkg_dir)?.to_owned();
+        if GENERATED_FILES
+            .iter()
+            .any(|file| rel_path == Path::new(file))
+        {
+            continue;
+        }
+        // `cargo package` keeps the original manifest as `Cargo.toml.orig`
+        let repo_rel_path = if rel_path == Path::new("Cargo.toml.orig") {
+            PathBuf::from("Cargo.toml")
+        } else {
+            rel_path.clone()
+        };
+        let repo_path = Path::new(&path_in_vcs).join(&repo_rel_path);
+        match tree.get_path(&repo_path) {
+            Ok(tree_entry) => match repo.find_blob(tree_entry.id()) {
+                Ok(ref blob) if blob.content() == fs::read(entry.path())?.as_slice() => {}
+                _ => changed.push(rel_path),
+            },
+            Err(_) => added.push(rel_path),
+        }
+    }
+
+    Ok(Report {
+        commit: commit.id().to_string(),
+        found_by,
+        path_in_vcs,
+        notes,
+        added,
+        changed,
+    })
+}
@@ -0,0 +1,177 @@
+//! Cross-checking unpacked crate sources with the registry
+//!
+//! Cargo keeps every downloaded `.crate` file in `~/.cargo/registry/cache`
+//! and unpacks it into `~/.cargo/registry/src`. The index records
+//! the SHA-256 checksum of every published `.crate` file.
+use crate::prelude::*;
+use sha2::Digest;
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Path of the `.crate` file that `pkg_dir` was unpacked from
+///
+/// `~/.cargo/registry/src/<index>/<name>-<version>` was unpacked from
+/// `~/.cargo/registry/cache/<index>/<name>-<version>.crate`.
+fn crate_file_path(pkg_dir: &Path) -> Option<PathBuf> {
+    let dir_name = pkg_dir.file_name()?;
+    let index_dir = pkg_dir.parent()?;
+    let registry_dir = index_dir.parent()?.parent()?;
+    Some(
+        registry_dir
+            .join("cache")
+            .join(index_dir.file_name()?)
+            .join(format!("{}.crate", dir_name.to_string_lossy())),
+    )
+}
+
+fn sha256sum_file(path: &Path) -> Result<String> {
+    let mut hasher = sha2::Sha256::default();
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.result()))
+}
+
+/// Checksum of the `.crate` file that `pkg_dir` was unpacked from, as
+/// recorded in the registry index
+///
+/// `None` for sources that didn't come from a registry.
+pub fn crate_file_checksum(pkg_dir: &Path) -> Result<Option<String>> {
+    match crate_file_path(pkg_dir) {
+        Some(crate_file) if crate_file.exists() => Ok(Some(sha256sum_file(&crate_file)?)),
+        _ => Ok(None),
+    }
+}
+
+/// Repository URL declared in the manifest (`Cargo.toml`) in `pkg_dir`
+pub fn declared_repository(pkg_dir: &Path) -> Result<Option<String>> {
+    let manifest: toml::Value = toml::from_str(&fs::read_to_string(pkg_dir.join("Cargo.toml"))?)?;
+    Ok(manifest
+        .get("package")
+        .and_then(|package| package.get("repository"))
+        .and_then(toml::Value::as_str)
+        .map(ToOwned::to_owned))
+}
+
+/// Check sources unpacked in `pkg_dir` against the registry `checksum`
+///
+/// Returns a description of the problem, if any was found.
+pub fn check_package_dir(
+    pkg_dir: &Path,
+    checksum: &str,
+    ignore_list: &HashSet<PathBuf>,
+) -> Result<Option<String>> {
+    let crate_file = match crate_file_path(pkg_dir) {
+        Some(path) => path,
+        None => return Ok(Some("no registry .crate file".into())),
+    };
+    if !crate_file.exists() {
+        return Ok(Some(format!("missing {}", crate_file.display())));
+    }
+    let crate_file_checksum = sha256sum_file(&crate_file)?;
+    if crate_file_checksum != checksum {
+        debug!(
+            "{}: checksum {}, registry index has {}",
+            crate_file.display(),
+            crate_file_checksum,
+            checksum
+        );
+        return Ok(Some(format!(
+            "checksum of {} does not match the registry index",
+            crate_file.display()
+        )));
+    }
+
+    let tmp_dir = tempdir::TempDir::new("crev-registry-check")?;
+    tar::Archive::new(flate2::read::GzDecoder::new(fs::File::open(&crate_file)?))
+        .unpack(tmp_dir.path())?;
+    let published_dir = tmp_dir.path().join(pkg_dir.file_name().unwrap_or_default());
+    if crev_lib::get_dir_digest(&published_dir, ignore_list)?
+        != crev_lib::get_dir_digest(pkg_dir, ignore_list)?
+    {
+        return Ok(Some(
+            "unpacked sources differ from the published crate".into(),
+        ));
+    }
+
+    Ok(None)
+}
+
+/// Number of entries in `dir` and the latest modification time among them
+///
+/// Any change to the sources (including removing a file, which modifies
+/// its directory) changes it, unless modification times are forged.
+fn modification_stamp(dir: &Path) -> Result<String> {
+    let mut entries = 0;
+    let mut latest = std::time::UNIX_EPOCH;
+    for entry in walkdir::WalkDir::new(dir) {
+        let modified = entry?.metadata()?.modified()?;
+        entries += 1;
+        latest = latest.max(modified);
+    }
+    let latest = latest.duration_since(std::time::UNIX_EPOCH)?;
+    Ok(format!(
+        "{} {}.{:09}",
+        entries,
+        latest.as_secs(),
+        latest.subsec_nanos()
+    ))
+}
+
+/// Package directories that passed `check_package_dir`
+///
+/// Unpacking and digesting every crate on every `verify` is slow, so
+/// directories are checked again only when their registry checksum or
+/// their `modification_stamp` changes. Kept in the cache directory.
+pub struct CheckCache {
+    path: PathBuf,
+    /// Checksum and modification stamp by package directory
+    checked: BTreeMap<String, String>,
+    changed: bool,
+}
+
+impl CheckCache {
+    pub fn load(local: &crev_lib::Local) -> Result<Self> {
+        let path = local.get_root_cache_dir().join("registry-checked.json");
+        let checked = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            BTreeMap::new()
+        };
+        Ok(Self {
+            path,
+            checked,
+            changed: false,
+        })
+    }
+
+    pub fn store(&self) -> Result<()> {
+        if self.changed {
+            crev_common::store_to_file_with(&self.path, |file| {
+                serde_json::to_writer(file, &self.checked)
+            })??;
+        }
+        Ok(())
+    }
+
+    /// Like `check_package_dir`, skipping directories checked before
+    pub fn check_package_dir(
+        &mut self,
+        pkg_dir: &Path,
+        checksum: &str,
+        ignore_list: &HashSet<PathBuf>,
+    ) -> Result<Option<String>> {
+        let key = pkg_dir.display().to_string();
+        let stamp = format!("{} {}", checksum, modification_stamp(pkg_dir)?);
+        if self.checked.get(&key) == Some(&stamp) {
+            return Ok(None);
+        }
+        let problem = check_package_dir(pkg_dir, checksum, ignore_list)?;
+        if problem.is_none() {
+            self.checked.insert(key, stamp);
+            self.changed = true;
+        }
+        Ok(problem)
+    }
+}
@@ -0,0 +1,335 @@
+//! Verification reports, for sharing with people who don't run crev
+use crate::html::{self, escape};
+use crev_lib::VerificationStatus;
+use serde_json::json;
+use std::fmt::Write;
+
+/// Author of a review matching the verified code
+pub struct Reviewer {
+    pub id: String,
+    /// Proof repository of the author
+    pub url: Option<String>,
+    pub trusted: bool,
+}
+
+/// Verification result of a single dependency
+pub struct Entry {
+    pub name: String,
+    pub version: String,
+    pub result: VerificationStatus,
+    pub reviewers: Vec<Reviewer>,
+    pub advisories: Vec<String>,
+    /// `B` for a build script, `P` for a proc-macro
+    pub build_time_code: String,
+    /// License expression, `-` if unknown
+    pub license: String,
+    /// Is the license denied by the project policy
+    pub license_denied: bool,
+    /// Reason from the project policy, if accepted despite needing attention
+    pub accepted: Option<String>,
+    /// Trusted Ids using the version in production
+    pub users: usize,
+}
+
+impl Entry {
+    fn status(&self) -> String {
+        match &self.accepted {
+            Some(reason) => format!("{} (accepted: {})", self.result, reason),
+            None => self.result.to_string(),
+        }
+    }
+
+    fn license(&self) -> String {
+        if self.license_denied {
+            format!("{} (denied)", self.license)
+        } else {
+            self.license.clone()
+        }
+    }
+}
+
+const TITLE: &str = "Dependency verification report";
+
+fn summary(entries: &[Entry]) -> String {
+    let count = |status: VerificationStatus| entries.iter().filter(|e| e.result == status).count();
+    let accepted = entries.iter().filter(|e| e.accepted.is_some()).count();
+    let mut s = format!(
+        "{} dependencies: {} verified, {} flagged, {} unknown",
+        entries.len(),
+        count(VerificationStatus::Verified),
+        count(VerificationStatus::Flagged),
+        count(VerificationStatus::Unknown)
+    );
+    if accepted > 0 {
+        s.push_str(&format!(" ({} accepted by the project policy)", accepted));
+    }
+    s
+}
+
+pub fn to_html(entries: &[Entry]) -> String {
+    let mut body = String::new();
+    writeln!(body, "<h1>{}</h1>", TITLE).unwrap();
+    writeln!(
+        body,
+        "<p>Generated by cargo-crev on {}.</p>",
+        crev_common::now().format("%Y-%m-%d %H:%M")
+    )
+    .unwrap();
+    writeln!(body, "<p>{}</p>", summary(entries)).unwrap();
+    writeln!(
+        body,
+        "<table><tr><th>Status</th><th>Crate</th><th>Version</th><th>Reviewers</th><th>Advisories</th><th>Build-time code</th><th>License</th></tr>"
+    )
+    .unwrap();
+    for entry in entries {
+        let reviewers: Vec<String> = entry
+            .reviewers
+            .iter()
+            .map(|reviewer| {
+                let mut s = match &reviewer.url {
+                    Some(url) => {
+                        format!("<a href=\"{}\">{}</a>", escape(url), escape(&reviewer.id))
+                    }
+                    None => escape(&reviewer.id),
+                };
+                if !reviewer.trusted {
+                    s.push_str(" (not trusted)");
+                }
+                s
+            })
+            .collect();
+        writeln!(
+            body,
+            "<tr><td class=\"{}\">{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            if entry.accepted.is_some() {
+                "accepted"
+            } else {
+                html::status_class(&entry.result)
+            },
+            escape(&entry.status()),
+            escape(&entry.name),
+            escape(&entry.version),
+            reviewers.join("<br>"),
+            escape(&entry.advisories.join(" ")),
+            escape(&entry.build_time_code),
+            escape(&entry.license())
+        )
+        .unwrap();
+    }
+    writeln!(body, "</table>").unwrap();
+    html::page(TITLE, &body)
+}
+
+/// Escape `s` to be used in a Markdown table cell
+fn escape_markdown(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+}
+
+pub fn to_markdown(entries: &[Entry]) -> String {
+    let mut s = String::new();
+    writeln!(s, "# {}", TITLE).unwrap();
+    writeln!(s).unwrap();
+    writeln!(
+        s,
+        "Generated by cargo-crev on {}.",
+        crev_common::now().format("%Y-%m-%d %H:%M")
+    )
+    .unwrap();
+    writeln!(s).unwrap();
+    writeln!(s, "{}", summary(entries)).unwrap();
+    writeln!(s).unwrap();
+    writeln!(
+        s,
+        "| Status | Crate | Version | Reviewers | Advisories | Build-time code | License |"
+    )
+    .unwrap();
+    writeln!(
+        s,
+        "|--------|-------|---------|-----------|------------|-----------------|---------|"
+    )
+    .unwrap();
+    for entry in entries {
+        let reviewers: Vec<String> = entry
+            .reviewers
+            .iter()
+            .map(|reviewer| {
+                let mut s = match &reviewer.url {
+                    Some(url) => format!("[{}]({})", escape_markdown(&reviewer.id), url),
+                    None => escape_markdown(&reviewer.id),
+                };
+                if !reviewer.trusted {
+                    s.push_str(" (not trusted)");
+                }
+                s
+            })
+            .collect();
+        writeln!(
+            s,
+            "| {} | {} | {} | {} | {} | {} | {} |",
+            escape_markdown(&entry.status()),
+            escape_markdown(&entry.name),
+            escape_markdown(&entry.version),
+            reviewers.join(", "),
+            escape_markdown(&entry.advisories.join(" ")),
+            entry.build_time_code,
+            escape_markdown(&entry.license())
+        )
+        .unwrap();
+    }
+    s
+}
+
+/// A single line of JSON describing `entry`, for streaming results as they come
+pub fn to_json_line(entry: &Entry) -> String {
+    json!({
+        "name": entry.name,
+        "version": entry.version,
+        "result": entry.result.to_string(),
+        "accepted": entry.accepted,
+        "reviewers": entry.reviewers.iter().map(|reviewer| json!({
+            "id": reviewer.id,
+            "url": reviewer.url,
+            "trusted": reviewer.trusted,
+        })).collect::<Vec<_>>(),
+        "advisories": entry.advisories,
+        "build-time-code": entry.build_time_code,
+        "license": entry.license,
+        "license-denied": entry.license_denied,
+        "users": entry.users,
+    })
+    .to_string()
+}
+
+/// Rules of SARIF results: id, short description and level
+const SARIF_RULES: &[(&str, &str, &str)] = &[
+    (
+        "crev/flagged",
+        "Dependency flagged by a trusted reviewer",
+        "error",
+    ),
+    (
+        "crev/advisory",
+        "Dependency affected by a known advisory",
+        "error",
+    ),
+    (
+        "crev/denied-license",
+        "Dependency license denied by the project policy",
+        "error",
+    ),
+    (
+        "crev/unknown",
+        "Dependency not reviewed by any trusted reviewer",
+        "warning",
+    ),
+];
+
+/// Line (starting at 1) of the `[[package]]` entry in `Cargo.lock`
+fn lock_file_line(lock_file: &str, name: &str, version: &str) -> Option<usize> {
+    let name_line = format!("name = \"{}\"", name);
+    let version_line = format!("version = \"{}\"", version);
+    let lines: Vec<_> = lock_file.lines().collect();
+    lines
+        .windows(2)
+        .position(|pair| pair[0] == name_line && pair[1] == version_line)
+        .map(|i| i + 1)
+}
+
+/// Report in SARIF 2.1.0, eg. for GitHub code scanning
+///
+/// Every policy violation of a dependency is a result, pointing at its entry
+/// in `Cargo.lock` (`lock_file_uri`, relative to the root of the project).
+/// Ones accepted by the project policy are reported as suppressed.
+pub fn to_sarif(entries: &[Entry], lock_file_uri: &str, lock_file: &str) -> String {
+    let mut results = vec![];
+    for entry in entries {
+        let mut violations = vec![];
+        match entry.result {
+            VerificationStatus::Flagged => violations.push((
+                "crev/flagged",
+                "is flagged by a trusted reviewer".to_owned(),
+            )),
+            VerificationStatus::Unknown => violations.push((
+                "crev/unknown",
+                "is not reviewed by any trusted reviewer".to_owned(),
+            )),
+            VerificationStatus::Verified => {}
+        }
+        if !entry.advisories.is_empty() {
+            violations.push((
+                "crev/advisory",
+                format!("is affected by {}", entry.advisories.join(", ")),
+            ));
+        }
+        if entry.license_denied {
+            violations.push((
+                "crev/denied-license",
+                format!(
+                    "has license {}, denied by the project policy",
+                    entry.license
+                ),
+            ));
+        }
+
+        let region = match lock_file_line(lock_file, &entry.name, &entry.version) {
+            Some(line) => json!({ "startLine": line }),
+            None => json!({ "startLine": 1 }),
+        };
+        for (rule_id, message) in violations {
+            let mut result = json!({
+                "ruleId": rule_id,
+                "ruleIndex": SARIF_RULES.iter().position(|rule| rule.0 == rule_id),
+                "level": SARIF_RULES.iter().find(|rule| rule.0 == rule_id).map(|rule| rule.2),
+                "message": {
+                    "text": format!("{} {} {}", entry.name, entry.version, message),
+                },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": lock_file_uri },
+                        "region": region,
+                    },
+                }],
+                "partialFingerprints": {
+                    "crevDependency/v1": format!("{}:{}:{}", rule_id, entry.name, entry.version),
+                },
+            });
+            if let Some(reason) = &entry.accepted {
+                result["suppressions"] = json!([{
+                    "kind": "external",
+                    "justification": reason,
+                }]);
+            }
+            results.push(result);
+        }
+    }
+
+    let rules: Vec<_> = SARIF_RULES
+        .iter()
+        .map(|(id, description, level)| {
+            json!({
+                "id": id,
+                "shortDescription": { "text": description },
+                "defaultConfiguration": { "level": level },
+            })
+        })
+        .collect();
+    let sarif = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "cargo-crev",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/dpc/crev",
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    });
+    serde_json::to_string_pretty(&sarif).expect("SARIF report is valid JSON")
+}
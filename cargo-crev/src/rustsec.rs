@@ -0,0 +1,190 @@
+//! RustSec advisory database
+//!
+//! https://github.com/RustSec/advisory-db keeps advisories about
+//! crates.io packages, one file per advisory under `crates/<name>/`.
+use crate::prelude::*;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+const ADVISORY_DB_URL: &str = "https://github.com/RustSec/advisory-db.git";
+
+/// An advisory from the RustSec database
+pub struct Advisory {
+    pub id: String,
+    /// Other ids of the same problem (eg. CVE numbers)
+    pub aliases: Vec<String>,
+    patched: Vec<semver::VersionReq>,
+    unaffected: Vec<semver::VersionReq>,
+}
+
+fn get_str<'a>(table: Option<&'a toml::Value>, key: &str) -> Option<&'a str> {
+    table
+        .and_then(|table| table.get(key))
+        .and_then(toml::Value::as_str)
+}
+
+fn get_str_list(table: Option<&toml::Value>, key: &str) -> Vec<String> {
+    table
+        .and_then(|table| table.get(key))
+        .and_then(toml::Value::as_array)
+        .map(|list| {
+            list.iter()
+                .filter_map(toml::Value::as_str)
+                .map(ToOwned::to_owned)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn get_version_reqs(table: Option<&toml::Value>, key: &str) -> Result<Vec<semver::VersionReq>> {
+    let mut reqs = vec![];
+    for req in get_str_list(table, key) {
+        reqs.push(semver::VersionReq::parse(&req)?);
+    }
+    Ok(reqs)
+}
+
+impl Advisory {
+    /// Parse an advisory file, returning the name of the affected package too
+    ///
+    /// Handles both the older `.toml` files and the newer `.md` ones
+    /// (with TOML front matter). Withdrawn advisories are `None`.
+    fn parse(content: &str) -> Result<Option<(String, Advisory)>> {
+        let front_matter = match content.find("```toml") {
+            Some(start) => {
+                let rest = &content[start + "```toml".len()..];
+                let end = rest
+                    .find("```")
+                    .ok_or_else(|| format_err!("Unterminated front matter"))?;
+                &rest[..end]
+            }
+            None => content,
+        };
+        let value: toml::Value = toml::from_str(front_matter)?;
+        let advisory = value.get("advisory");
+        if advisory.and_then(|a| a.get("withdrawn")).is_some() {
+            return Ok(None);
+        }
+        let versions = value.get("versions");
+
+        let package = get_str(advisory, "package")
+            .ok_or_else(|| format_err!("Missing `package`"))?
+            .to_owned();
+        let id = get_str(advisory, "id")
+            .ok_or_else(|| format_err!("Missing `id`"))?
+            .to_owned();
+
+        // older files keep version ranges in the `advisory` table
+        let (patched, unaffected) = if versions.is_some() {
+            (
+                get_version_reqs(versions, "patched")?,
+                get_version_reqs(versions, "unaffected")?,
+            )
+        } else {
+            (
+                get_version_reqs(advisory, "patched_versions")?,
+                get_version_reqs(advisory, "unaffected_versions")?,
+            )
+        };
+
+        Ok(Some((
+            package,
+            Advisory {
+                id,
+                aliases: get_str_list(advisory, "aliases"),
+                patched,
+                unaffected,
+            },
+        )))
+    }
+
+    pub fn affects(&self, version: &semver::Version) -> bool {
+        !self
+            .patched
+            .iter()
+            .chain(self.unaffected.iter())
+            .any(|req| req.matches(version))
+    }
+}
+
+/// Local copy of the RustSec advisory database
+pub struct AdvisoryDb {
+    advisories_by_package: HashMap<String, Vec<Advisory>>,
+}
+
+impl AdvisoryDb {
+    fn path(local: &crev_lib::Local) -> PathBuf {
+        local.get_root_cache_dir().join("rustsec-advisory-db")
+    }
+
+    /// Clone or update the local copy of the database
+    pub fn fetch(local: &crev_lib::Local) -> Result<()> {
+        crev_lib::local::fetch_or_clone_git_repo(ADVISORY_DB_URL, &Self::path(local))
+    }
+
+    /// Load the local copy, if it was fetched already
+    ///
+    /// The database is downloaded only by `fetch trusted`, `fetch all` and
+    /// `verify deps --fetch`, never implicitly.
+    pub fn load(local: &crev_lib::Local) -> Result<Option<Self>> {
+        let path = Self::path(local);
+        if !path.exists() {
+            eprintln!(
+                "RustSec advisory database not fetched yet; \
+                 run `cargo crev fetch trusted` to download it"
+            );
+            return Ok(None);
+        }
+        Ok(Some(Self::load_from(&path.join("crates"))?))
+    }
+
+    fn load_from(path: &Path) -> Result<Self> {
+        let mut advisories_by_package: HashMap<String, Vec<Advisory>> = HashMap::new();
+        for entry in walkdir::WalkDir::new(path) {
+            let entry = entry?;
+            let is_advisory = entry
+                .path()
+                .extension()
+                .map(|ext| ext == "toml" || ext == "md")
+                .unwrap_or(false);
+            if !entry.file_type().is_file() || !is_advisory {
+                continue;
+            }
+            match Advisory::parse(&fs::read_to_string(entry.path())?) {
+                Ok(Some((package, advisory))) => advisories_by_package
+                    .entry(package)
+                    .or_default()
+                    .push(advisory),
+                Ok(None) => {}
+                Err(e) => error!("{}: {}", entry.path().display(), e),
+            }
+        }
+        Ok(Self {
+            advisories_by_package,
+        })
+    }
+
+    /// All advisories about a crate
+    pub fn get_all_advisories(&self, name: &str) -> &[Advisory] {
+        self.advisories_by_package
+            .get(name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Advisories affecting given version of a crate
+    pub fn get_advisories(&self, name: &str, version: &semver::Version) -> Vec<&Advisory> {
+        self.advisories_by_package
+            .get(name)
+            .map(|advisories| {
+                advisories
+                    .iter()
+                    .filter(|advisory| advisory.affects(version))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,285 @@
+//! Local web dashboard for the trust database
+//!
+//! A minimal HTTP server, listening only on the loopback interface,
+//! rendering proofs loaded at start-up as plain HTML pages. Requests for
+//! other hosts are refused, so pages can't be read through DNS rebinding.
+use crate::html::{self, escape};
+use crate::prelude::*;
+use crate::{opts, Repo, PROJECT_SOURCE_CRATES_IO};
+use crev_lib::{Local, VerificationStatus};
+use std::{
+    collections::BTreeMap,
+    env,
+    fmt::Write as FmtWrite,
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    time::Duration,
+};
+
+/// How long to wait for a request, as requests are handled one at a time
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Verification result of a dependency of the current project
+struct Dependency {
+    name: String,
+    version: String,
+    result: VerificationStatus,
+}
+
+struct Dashboard {
+    db: crev_lib::trustdb::TrustDB,
+    trust_set: crev_lib::trustdb::TrustSet,
+    /// `None` if not started in a Cargo project
+    deps: Option<Vec<Dependency>>,
+    /// Port the server listens on, to check `Host` of requests with
+    port: u16,
+}
+
+/// Is `url` safe to link to: `http` or `https` only, as URLs come from
+/// foreign proofs and eg. `javascript:` ones would run in the dashboard
+fn is_linkable(url: &str) -> bool {
+    let url = url.to_ascii_lowercase();
+    url.starts_with("https://") || url.starts_with("http://")
+}
+
+/// Is `host` (the `Host` header) one of the server itself
+fn is_own_host(host: &str, port: u16) -> bool {
+    ["127.0.0.1", "localhost"]
+        .iter()
+        .any(|name| host.eq_ignore_ascii_case(&format!("{}:{}", name, port)))
+}
+
+const NAV: &str = "<p><a href=\"/\">Trust set</a> | <a href=\"/crates\">Reviewed crates</a> | <a href=\"/project\">Project</a></p>";
+
+impl Dashboard {
+    fn new(args: &opts::Serve, port: u16) -> Result<Self> {
+        let local = Local::auto_open()?;
+        let config = local.load_user_config()?;
+        let (db, trust_set) = local.load_db(&args.trust_params.clone().into())?;
+
+        let deps = match Repo::auto_open_cwd() {
+            Ok(mut repo) => {
+                repo.set_features(&args.features);
+                let current_dir = env::current_dir()?;
+                let mut deps = vec![];
+                repo.for_every_dependency_dir(|pkg_id, path| {
+                    if path.starts_with(&current_dir) {
+                        // ignore local dependencies
+                        return Ok(());
+                    }
+                    let name = pkg_id.name().to_string();
+                    let version = pkg_id.version().to_string();
+                    let digests = crate::get_package_digests(
+                        &db, &trust_set, path, &name, &version, &config,
+                    )?;
+                    deps.push(Dependency {
+                        result: crate::verify_package(&db, &trust_set, &digests, &name, &version),
+                        name,
+                        version,
+                    });
+                    Ok(())
+                })?;
+                deps.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+                Some(deps)
+            }
+            Err(_) => None,
+        };
+
+        Ok(Self {
+            db,
+            trust_set,
+            deps,
+            port,
+        })
+    }
+
+    /// Status code and HTML content of a page at `path`
+    fn render(&self, path: &str) -> (&'static str, String) {
+        let mut parts = path.trim_matches('/').splitn(2, '/');
+        match (parts.next(), parts.next()) {
+            (Some(""), None) => ("200 OK", self.render_trust_set()),
+            (Some("crates"), None) => ("200 OK", self.render_crates()),
+            (Some("crate"), Some(name)) => ("200 OK", self.render_crate(name)),
+            (Some("project"), None) => ("200 OK", self.render_project()),
+            _ => (
+                "404 Not Found",
+                html::page("Not found", &format!("{}<h1>Not found</h1>", NAV)),
+            ),
+        }
+    }
+
+    fn render_trust_set(&self) -> String {
+        let mut ids: Vec<_> = self.trust_set.iter().collect();
+        ids.sort_by(|a, b| (a.1.distance, a.0).cmp(&(b.1.distance, b.0)));
+        let mut body = String::new();
+        writeln!(body, "{}<h1>Trust set</h1>", NAV).unwrap();
+        writeln!(
+            body,
+            "<table><tr><th>Id</th><th>Trust</th><th>Distance</th><th>URL</th><th>Reviews</th></tr>"
+        )
+        .unwrap();
+        for (id, info) in ids {
+            let url = self
+                .db
+                .lookup_url(id)
+                .map(|url| url.url.as_str())
+                .unwrap_or("");
+            let url = if is_linkable(url) {
+                format!("<a href=\"{}\">{}</a>", escape(url), escape(url))
+            } else {
+                escape(url)
+            };
+            writeln!(
+                body,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&id.to_string()),
+                info.effective_trust,
+                info.distance,
+                url,
+                self.db.get_package_reviews_by_author(id).count()
+            )
+            .unwrap();
+        }
+        writeln!(body, "</table>").unwrap();
+        html::page("Trust set", &body)
+    }
+
+    fn render_crates(&self) -> String {
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for review in self
+            .db
+            .get_package_reviews_for_package(PROJECT_SOURCE_CRATES_IO, None, None)
+        {
+            *counts.entry(review.package.name).or_default() += 1;
+        }
+        let mut body = String::new();
+        writeln!(body, "{}<h1>Reviewed crates</h1>", NAV).unwrap();
+        writeln!(body, "<table><tr><th>Crate</th><th>Reviews</th></tr>").unwrap();
+        for (name, count) in counts {
+            writeln!(
+                body,
+                "<tr><td><a href=\"/crate/{}\">{}</a></td><td>{}</td></tr>",
+                escape(&name),
+                escape(&name),
+                count
+            )
+            .unwrap();
+        }
+        writeln!(body, "</table>").unwrap();
+        html::page("Reviewed crates", &body)
+    }
+
+    fn render_crate(&self, name: &str) -> String {
+        let mut body = String::new();
+        writeln!(body, "{}<h1>{}</h1>", NAV, escape(name)).unwrap();
+        for review in
+            self.db
+                .get_package_reviews_for_package(PROJECT_SOURCE_CRATES_IO, Some(name), None)
+        {
+            let trusted = if self.trust_set.contains(&review.from.id) {
+                "trusted"
+            } else {
+                "not trusted"
+            };
+            writeln!(
+                body,
+                "<h2>{} ({})</h2><pre>{}</pre>",
+                escape(&review.package.version),
+                trusted,
+                escape(&review.to_string())
+            )
+            .unwrap();
+        }
+        html::page(name, &body)
+    }
+
+    fn render_project(&self) -> String {
+        let mut body = String::new();
+        writeln!(body, "{}<h1>Project</h1>", NAV).unwrap();
+        match &self.deps {
+            None => writeln!(body, "<p>Not started in a Cargo project.</p>").unwrap(),
+            Some(deps) => {
+                writeln!(
+                    body,
+                    "<table><tr><th>Status</th><th>Reviews</th><th>Crate</th><th>Version</th></tr>"
+                )
+                .unwrap();
+                for dep in deps {
+                    writeln!(
+                        body,
+                        "<tr><td class=\"{}\">{}</td><td>{}</td><td><a href=\"/crate/{}\">{}</a></td><td>{}</td></tr>",
+                        html::status_class(&dep.result),
+                        dep.result,
+                        self.db.get_package_review_count(
+                            PROJECT_SOURCE_CRATES_IO,
+                            Some(&dep.name),
+                            Some(&dep.version)
+                        ),
+                        escape(&dep.name),
+                        escape(&dep.name),
+                        escape(&dep.version)
+                    )
+                    .unwrap();
+                }
+                writeln!(body, "</table>").unwrap();
+            }
+        }
+        html::page("Project", &body)
+    }
+
+    fn handle(&self, stream: TcpStream) -> Result<()> {
+        stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+        stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        let mut host = None;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+            let mut header = line.splitn(2, ':');
+            if let (Some(name), Some(value)) = (header.next(), header.next()) {
+                if name.trim().eq_ignore_ascii_case("host") {
+                    host = Some(value.trim().to_owned());
+                }
+            }
+        }
+
+        let mut parts = request_line.split_whitespace();
+        let (status, content) = match (parts.next(), parts.next()) {
+            _ if !host.is_some_and(|host| is_own_host(&host, self.port)) => {
+                ("403 Forbidden", String::new())
+            }
+            (Some("GET"), Some(path)) => self.render(path.split('?').next().unwrap_or(path)),
+            _ => ("405 Method Not Allowed", String::new()),
+        };
+
+        let mut stream = reader.into_inner();
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            content.len(),
+            content
+        )?;
+        Ok(())
+    }
+}
+
+pub fn run(args: &opts::Serve) -> Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", args.port))?;
+    let dashboard = Dashboard::new(args, listener.local_addr()?.port())?;
+    eprintln!("Listening on http://{}/", listener.local_addr()?);
+
+    for stream in listener.incoming() {
+        if let Err(e) = stream
+            .map_err(Into::into)
+            .and_then(|stream| dashboard.handle(stream))
+        {
+            error!("{}", e);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,82 @@
+//! Heuristics spotting dependencies that deserve a closer look
+//!
+//! Typosquatting and other supply-chain attacks usually involve crates that
+//! are new, barely used, and named like a popular crate. None of this proves
+//! anything, but it's cheap to check with data from crates.io.
+
+/// Crates first published less than this many days ago are new
+const NEW_CRATE_DAYS: i64 = 30;
+
+/// Crates with fewer downloads (of all versions) are barely used
+const FEW_DOWNLOADS: u64 = 1000;
+
+/// A crate with this many times more downloads is much more popular
+const MUCH_MORE_POPULAR: u64 = 100;
+
+/// Name as crates.io compares names: `-` and `_` are the same, and case doesn't matter
+fn normalize(name: &str) -> Vec<char> {
+    name.chars()
+        .map(|c| {
+            if c == '_' {
+                '-'
+            } else {
+                c.to_ascii_lowercase()
+            }
+        })
+        .collect()
+}
+
+/// Levenshtein distance
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut cur = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + if ca == cb { 0 } else { 1 };
+            cur.push(substitution.min(prev[j + 1] + 1).min(cur[j] + 1));
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+/// Much more popular crate in `popular` (names and downloads) with a name
+/// easy to mistake for `name`
+fn similar_popular_crate<'a>(
+    name: &str,
+    downloads: u64,
+    popular: &'a [(String, u64)],
+) -> Option<&'a str> {
+    let name = normalize(name);
+    // the longer the name, the more typos go unnoticed
+    let max_distance = if name.len() < 8 { 1 } else { 2 };
+    popular
+        .iter()
+        .filter(|(_, popular_downloads)| *popular_downloads >= downloads * MUCH_MORE_POPULAR)
+        .find(|(popular_name, _)| {
+            let distance = edit_distance(&name, &normalize(popular_name));
+            0 < distance && distance <= max_distance
+        })
+        .map(|(popular_name, _)| popular_name.as_str())
+}
+
+/// Reasons why the crate looks suspicious, compared to `popular` crates
+/// (names and downloads)
+pub fn warnings(resp: &crates_io_api::CrateResponse, popular: &[(String, u64)]) -> Vec<String> {
+    let crate_ = &resp.crate_data;
+    let mut warnings = vec![];
+    let age = chrono::Utc::now() - crate_.created_at;
+    if age < chrono::Duration::days(NEW_CRATE_DAYS) {
+        warnings.push(format!("first published {} days ago", age.num_days()));
+    }
+    if crate_.downloads < FEW_DOWNLOADS {
+        warnings.push(format!("only {} downloads", crate_.downloads));
+    }
+    if let Some(similar) = similar_popular_crate(&crate_.name, crate_.downloads, popular) {
+        warnings.push(format!(
+            "name similar to a much more popular crate: {}",
+            similar
+        ));
+    }
+    warnings
+}
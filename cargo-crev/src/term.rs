@@ -0,0 +1,71 @@
+//! Colored terminal output
+use crev_lib::VerificationStatus;
+use std::{env, io::Write};
+use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+
+/// Translate the `--color` option into a `ColorChoice` for stdout
+///
+/// `auto` colors only when printing to a terminal, and the `NO_COLOR`
+/// environment variable is not set (https://no-color.org/).
+pub fn color_choice(color: &str) -> ColorChoice {
+    match color {
+        "always" => ColorChoice::Always,
+        "never" => ColorChoice::Never,
+        _ => {
+            if env::var_os("NO_COLOR").is_some() || !atty::is(atty::Stream::Stdout) {
+                ColorChoice::Never
+            } else {
+                ColorChoice::Auto
+            }
+        }
+    }
+}
+
+pub fn status_color(status: &VerificationStatus) -> Color {
+    match status {
+        VerificationStatus::Verified => Color::Green,
+        VerificationStatus::Flagged => Color::Red,
+        VerificationStatus::Unknown => Color::Yellow,
+    }
+}
+
+/// Print a line of `verify` output: colored `status` followed by `rest`
+///
+/// `highlight` makes the whole line stand out, for things that need attention.
+pub fn print_status_line(
+    stdout: &mut StandardStream,
+    status: &VerificationStatus,
+    highlight: bool,
+    rest: &str,
+) -> std::io::Result<()> {
+    stdout.set_color(
+        ColorSpec::new()
+            .set_fg(Some(status_color(status)))
+            .set_bold(highlight),
+    )?;
+    write!(stdout, "{:8}", status)?;
+    if highlight {
+        stdout.set_color(ColorSpec::new().set_fg(Some(Color::Red)).set_bold(true))?;
+    } else {
+        stdout.reset()?;
+    }
+    write!(stdout, " {}", rest)?;
+    stdout.reset()?;
+    writeln!(stdout)?;
+    Ok(())
+}
+
+/// Print a line of `verify` output for a dependency accepted by the project policy
+///
+/// It's shown distinctly, but doesn't stand out like things needing attention.
+pub fn print_accepted_line(
+    stdout: &mut StandardStream,
+    status: &VerificationStatus,
+    rest: &str,
+) -> std::io::Result<()> {
+    stdout.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))?;
+    write!(stdout, "{:8}", status)?;
+    stdout.reset()?;
+    writeln!(stdout, " {}", rest)?;
+    Ok(())
+}
@@ -0,0 +1,135 @@
+//! Talking to transparency logs with a Rekor-compatible API
+use crate::prelude::*;
+use crev_data::proof;
+use crev_lib::{
+    local::Local,
+    transparency::{self, Anchor, Issue, Log},
+};
+use serde_json::Value;
+use std::{io::Read, path::PathBuf};
+
+fn entries_url(log: &str) -> String {
+    format!("{}/api/v1/log/entries", log.trim_end_matches('/'))
+}
+
+fn read_response(mut response: reqwest::Response, url: &str) -> Result<Value> {
+    let mut content = String::new();
+    response.read_to_string(&mut content)?;
+    if !response.status().is_success() {
+        bail!("{} failed: {}: {}", url, response.status(), content.trim());
+    }
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Anchor of `signature` from a response with a single log entry
+fn anchor_from_response(log: &str, signature: &str, response: &Value) -> Result<Anchor> {
+    let (entry_id, entry) = response
+        .as_object()
+        .and_then(|entries| entries.iter().next())
+        .ok_or_else(|| format_err!("No entry in the response of {}", log))?;
+    let invalid = || format_err!("Invalid entry {} in {}", entry_id, log);
+    let body = entry["body"].as_str().ok_or_else(invalid)?;
+    let body = String::from_utf8(base64::decode(body)?)?;
+    let inclusion = &entry["verification"]["inclusionProof"];
+    Ok(Anchor {
+        signature: signature.to_owned(),
+        log: log.to_owned(),
+        entry_id: entry_id.to_owned(),
+        log_index: inclusion["logIndex"].as_u64().ok_or_else(invalid)?,
+        integrated_time: entry["integratedTime"].as_i64().ok_or_else(invalid)?,
+        body,
+        checkpoint: inclusion["checkpoint"]
+            .as_str()
+            .ok_or_else(invalid)?
+            .to_owned(),
+        hashes: inclusion["hashes"]
+            .as_array()
+            .ok_or_else(invalid)?
+            .iter()
+            .map(|hash| hash.as_str().map(ToOwned::to_owned).ok_or_else(invalid))
+            .collect::<Result<_>>()?,
+    })
+}
+
+/// Log `proof` in the transparency log `log`
+pub fn submit(log: &Log, proof: &proof::Proof) -> Result<Anchor> {
+    let url = entries_url(&log.url);
+    let body: Value = serde_json::from_str(&transparency::entry_body(proof)?)?;
+    let response = reqwest::Client::new().post(&url).json(&body).send()?;
+    let anchor =
+        anchor_from_response(&log.url, proof.signature(), &read_response(response, &url)?)?;
+    anchor.verify(log, proof)?;
+    Ok(anchor)
+}
+
+/// The entry of `anchor` as `log` has it now, with a fresh inclusion proof
+pub fn fetch(log: &Log, anchor: &Anchor) -> Result<Anchor> {
+    let url = format!("{}/{}", entries_url(&log.url), anchor.entry_id);
+    let response = reqwest::get(&url)?;
+    anchor_from_response(&log.url, &anchor.signature, &read_response(response, &url)?)
+}
+
+/// Anchor all own proofs that aren't anchored yet in `log`
+///
+/// Returns the number of anchored proofs.
+pub fn anchor_own_proofs(local: &Local, log: &Log) -> Result<usize> {
+    let mut anchored = 0;
+    let mut by_url: Vec<(crev_data::Url, Vec<Anchor>)> = vec![];
+    for (url, proof) in local.unanchored_proofs()? {
+        let anchor = match submit(log, &proof) {
+            Ok(anchor) => anchor,
+            Err(e) => {
+                warn!("Couldn't anchor proof {}: {}", proof.signature(), e);
+                continue;
+            }
+        };
+        anchored += 1;
+        match by_url.last_mut() {
+            Some((last_url, anchors)) if *last_url == url => anchors.push(anchor),
+            _ => by_url.push((url, vec![anchor])),
+        }
+    }
+    for (url, anchors) in by_url {
+        local.add_anchors(&url, anchors)?;
+    }
+    Ok(anchored)
+}
+
+/// Compare anchors with what `log` has now
+///
+/// Catches anchors of entries that were never logged, or that the log
+/// doesn't include in its tree anymore.
+pub fn check_anchors_online(
+    local: &Local,
+    log: &Log,
+    include_remotes: bool,
+) -> Result<Vec<(PathBuf, Issue)>> {
+    let mut issues = vec![];
+    for (dir, anchors) in local.load_anchors(include_remotes)? {
+        let mut anchors: Vec<_> = anchors.into_values().collect();
+        anchors.sort_by_key(|anchor| (anchor.log.clone(), anchor.log_index));
+        for anchor in anchors {
+            let message = match fetch(log, &anchor) {
+                Err(e) => format!("couldn't look up the entry in {}: {}", log.url, e),
+                Ok(ref logged)
+                    if logged.body != anchor.body
+                        || logged.integrated_time != anchor.integrated_time =>
+                {
+                    format!("{} has a different entry {}", log.url, anchor.entry_id)
+                }
+                Ok(logged) => match logged.verify_inclusion(log) {
+                    Ok(()) => continue,
+                    Err(e) => format!("{} for entry {} in {}", e, anchor.entry_id, log.url),
+                },
+            };
+            issues.push((
+                dir.clone(),
+                Issue {
+                    signature: anchor.signature,
+                    message,
+                },
+            ));
+        }
+    }
+    Ok(issues)
+}
@@ -0,0 +1,131 @@
+//! `verify tree`: the dependency graph, with verification status of every crate
+use cargo::core::package_id::PackageId;
+use crev_lib::VerificationStatus;
+use std::{
+    collections::{HashMap, HashSet},
+    io::{self, Write},
+};
+use termcolor::{ColorSpec, StandardStream, WriteColor};
+
+/// Workspace members, and dependencies of every package
+pub struct Graph {
+    pub roots: Vec<PackageId>,
+    pub deps: HashMap<PackageId, Vec<PackageId>>,
+}
+
+impl Graph {
+    fn deps(&self, pkg_id: &PackageId) -> &[PackageId] {
+        self.deps.get(pkg_id).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// What's shown about a package
+pub struct Annotation {
+    /// `None` for local packages, which are not verified
+    pub status: Option<VerificationStatus>,
+    /// Reviews of this version, and of all versions
+    pub reviews: (usize, usize),
+}
+
+struct Tree<'a> {
+    graph: &'a Graph,
+    annotations: &'a HashMap<PackageId, Annotation>,
+    max_depth: Option<usize>,
+    /// Packages already shown with their dependencies
+    shown: HashSet<PackageId>,
+}
+
+impl<'a> Tree<'a> {
+    fn is_verified(&self, pkg_id: &PackageId) -> bool {
+        match self.annotations.get(pkg_id).and_then(|a| a.status) {
+            Some(status) => status == VerificationStatus::Verified,
+            None => true,
+        }
+    }
+
+    /// Packages below `pkg_id` that are not verified
+    fn unverified_below(&self, pkg_id: &PackageId) -> usize {
+        let mut reachable = HashSet::new();
+        let mut pending = vec![pkg_id];
+        while let Some(pkg_id) = pending.pop() {
+            for dep_id in self.graph.deps(pkg_id) {
+                if reachable.insert(dep_id) {
+                    pending.push(dep_id);
+                }
+            }
+        }
+        reachable
+            .into_iter()
+            .filter(|dep_id| !self.is_verified(dep_id))
+            .count()
+    }
+
+    fn print_package(
+        &mut self,
+        stdout: &mut StandardStream,
+        pkg_id: &PackageId,
+        prefix: &str,
+        depth: usize,
+    ) -> io::Result<()> {
+        write!(stdout, "{} {}", pkg_id.name(), pkg_id.version())?;
+        if let Some(annotation) = self.annotations.get(pkg_id) {
+            if let Some(status) = &annotation.status {
+                write!(stdout, " ")?;
+                stdout
+                    .set_color(ColorSpec::new().set_fg(Some(crate::term::status_color(status))))?;
+                write!(stdout, "{}", status)?;
+                stdout.reset()?;
+                let (version_reviews, total_reviews) = annotation.reviews;
+                write!(stdout, " (reviews: {}/{})", version_reviews, total_reviews)?;
+            }
+        }
+        let unverified = self.unverified_below(pkg_id);
+        if unverified > 0 {
+            write!(stdout, ", {} unverified below", unverified)?;
+        }
+        if self.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+            return writeln!(stdout);
+        }
+        if !self.shown.insert(pkg_id.clone()) {
+            // like `cargo tree`, show dependencies only once
+            if !self.graph.deps(pkg_id).is_empty() {
+                write!(stdout, " (*)")?;
+            }
+            return writeln!(stdout);
+        }
+        writeln!(stdout)?;
+
+        let deps = self.graph.deps(pkg_id);
+        for (i, dep_id) in deps.iter().enumerate() {
+            let last = i + 1 == deps.len();
+            write!(stdout, "{}{}", prefix, if last { "└── " } else { "├── " })?;
+            let dep_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            self.print_package(stdout, dep_id, &dep_prefix, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Print the dependency tree of every workspace member
+///
+/// Dependencies deeper than `max_depth` (1 being direct dependencies) are left out.
+pub fn print(
+    stdout: &mut StandardStream,
+    graph: &Graph,
+    annotations: &HashMap<PackageId, Annotation>,
+    max_depth: Option<usize>,
+) -> io::Result<()> {
+    let mut tree = Tree {
+        graph,
+        annotations,
+        max_depth,
+        shown: HashSet::new(),
+    };
+    for (i, root) in graph.roots.iter().enumerate() {
+        if i > 0 {
+            writeln!(stdout)?;
+        }
+        tree.print_package(stdout, root, "", 0)?;
+    }
+    Ok(())
+}
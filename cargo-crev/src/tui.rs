@@ -0,0 +1,479 @@
+//! Terminal UI for reviewing and verifying dependencies
+//!
+//! Every screen is a full-screen list navigated with the arrow keys,
+//! with the keys it accepts shown at the bottom. Pagers, editors and
+//! reviews run with the terminal restored to normal.
+use crate::prelude::*;
+use crate::{opts, why::describe_id, Repo, PROJECT_SOURCE_CRATES_IO};
+use crev_lib::{Local, TrustOrDistrust, VerificationStatus};
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyModifiers},
+    execute, queue,
+    style::{self, Attribute},
+    terminal,
+};
+use std::{
+    env,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+/// A dependency, as listed in the main screen
+struct Dependency {
+    name: String,
+    version: String,
+    path: PathBuf,
+    result: VerificationStatus,
+}
+
+/// Keeps the terminal in raw mode, on the alternate screen, while alive
+struct Terminal;
+
+impl Terminal {
+    fn enter() -> Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(Terminal)
+    }
+
+    fn leave(&self) -> Result<()> {
+        execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen)?;
+        terminal::disable_raw_mode()?;
+        Ok(())
+    }
+
+    /// Run `f`, that uses the terminal normally (eg. a pager), with the UI suspended
+    fn suspend<T>(&self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        self.leave()?;
+        let res = f();
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        res
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = self.leave();
+    }
+}
+
+fn truncate(line: &str, width: usize) -> String {
+    line.chars().take(width).collect()
+}
+
+fn is_back(code: &KeyCode) -> bool {
+    matches!(
+        code,
+        KeyCode::Esc | KeyCode::Backspace | KeyCode::Left | KeyCode::Char('b')
+    )
+}
+
+/// A full-screen list of lines, with a title and the keys it accepts
+struct Screen {
+    title: String,
+    lines: Vec<String>,
+    help: &'static str,
+    /// Whether one of the lines is selected, or they are only scrolled
+    selectable: bool,
+    /// Selected line, or the first one shown if not `selectable`
+    selected: usize,
+    offset: usize,
+    /// Shown above the help, until the next key is pressed
+    message: Option<String>,
+}
+
+impl Screen {
+    fn new(help: &'static str, selectable: bool) -> Self {
+        Self {
+            title: String::new(),
+            lines: vec![],
+            help,
+            selectable,
+            selected: 0,
+            offset: 0,
+            message: None,
+        }
+    }
+
+    /// Rows available for the lines, between the title and the message
+    fn page_height() -> Result<usize> {
+        let (_cols, rows) = terminal::size()?;
+        Ok((rows as usize).saturating_sub(3).max(1))
+    }
+
+    fn draw(&mut self) -> Result<()> {
+        let (cols, _rows) = terminal::size()?;
+        let cols = cols as usize;
+        let height = Self::page_height()?;
+        if !self.selectable || self.selected < self.offset {
+            self.offset = self.selected;
+        } else if self.selected >= self.offset + height {
+            self.offset = self.selected + 1 - height;
+        }
+
+        let mut out = io::stdout();
+        queue!(
+            out,
+            terminal::Clear(terminal::ClearType::All),
+            cursor::MoveTo(0, 0),
+            style::SetAttribute(Attribute::Bold),
+            style::Print(truncate(&self.title, cols)),
+            style::SetAttribute(Attribute::Reset)
+        )?;
+        for (i, line) in self.lines.iter().enumerate().skip(self.offset).take(height) {
+            queue!(out, cursor::MoveTo(0, (i - self.offset + 1) as u16))?;
+            if self.selectable && i == self.selected {
+                queue!(
+                    out,
+                    style::SetAttribute(Attribute::Reverse),
+                    style::Print(truncate(line, cols)),
+                    style::SetAttribute(Attribute::Reset)
+                )?;
+            } else {
+                queue!(out, style::Print(truncate(line, cols)))?;
+            }
+        }
+        if let Some(message) = &self.message {
+            queue!(
+                out,
+                cursor::MoveTo(0, height as u16 + 1),
+                style::Print(truncate(message, cols))
+            )?;
+        }
+        queue!(
+            out,
+            cursor::MoveTo(0, height as u16 + 2),
+            style::SetAttribute(Attribute::Reverse),
+            style::Print(truncate(self.help, cols)),
+            style::SetAttribute(Attribute::Reset)
+        )?;
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Show the screen until a key other than a movement one is pressed
+    ///
+    /// Ctrl-C is returned as `q`.
+    fn run(&mut self) -> Result<KeyCode> {
+        loop {
+            self.draw()?;
+            let key = match event::read()? {
+                Event::Key(key) => key,
+                // redraw, eg. after resizing
+                _ => continue,
+            };
+            let height = Self::page_height()?;
+            let last = if self.selectable {
+                self.lines.len().saturating_sub(1)
+            } else {
+                self.lines.len().saturating_sub(height)
+            };
+            self.selected = match key.code {
+                KeyCode::Up => self.selected.saturating_sub(1),
+                KeyCode::Down => (self.selected + 1).min(last),
+                KeyCode::PageUp => self.selected.saturating_sub(height),
+                KeyCode::PageDown => (self.selected + height).min(last),
+                KeyCode::Home => 0,
+                KeyCode::End => last,
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(KeyCode::Char('q'));
+                }
+                code => {
+                    self.message = None;
+                    return Ok(code);
+                }
+            };
+        }
+    }
+}
+
+/// Pager command line, split like a shell would (`PAGER="less -R"` is common)
+fn get_pager_to_use() -> Result<Vec<String>> {
+    let pager = env::var("PAGER").unwrap_or_else(|_| "less".into());
+    let args = shell_words::split(&pager)?;
+    if args.is_empty() {
+        bail!("PAGER is empty");
+    }
+    Ok(args)
+}
+
+fn show_file(path: &Path) -> Result<()> {
+    let pager = get_pager_to_use()?;
+    let status = process::Command::new(&pager[0])
+        .args(&pager[1..])
+        .arg(path)
+        .status()
+        .map_err(|e| format_err!("Could not run `{}`: {}", pager[0], e))?;
+
+    if !status.success() {
+        bail!("Pager returned {}", status);
+    }
+    Ok(())
+}
+
+/// Relative paths of all files of a package, sorted
+fn list_files(path: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for entry in walkdir::WalkDir::new(path).sort_by(|a, b| a.file_name().cmp(b.file_name())) {
+        let entry = entry?;
+        if entry.file_type().is_file() {
+            files.push(entry.path().strip_prefix(path)?.to_owned());
+        }
+    }
+    Ok(files)
+}
+
+/// State of the interface: everything needed to verify dependencies
+struct Tui {
+    local: Local,
+    config: crev_lib::local::UserConfig,
+    trust_params: crev_lib::trustdb::TrustDistanceParams,
+    db: crev_lib::trustdb::TrustDB,
+    trust_set: crev_lib::trustdb::TrustSet,
+    deps: Vec<Dependency>,
+    terminal: Terminal,
+}
+
+impl Tui {
+    fn new(args: &opts::Tui) -> Result<Self> {
+        let local = Local::auto_open()?;
+        let config = local.load_user_config()?;
+        let trust_params: crev_lib::trustdb::TrustDistanceParams = args.trust_params.clone().into();
+        let (db, trust_set) = local.load_db(&trust_params)?;
+
+        let mut repo = Repo::auto_open_cwd()?;
+        repo.set_features(&args.features);
+        let current_dir = env::current_dir()?;
+        let mut deps = vec![];
+        repo.for_every_dependency_dir(|pkg_id, path| {
+            if path.starts_with(&current_dir) {
+                // ignore local dependencies
+                return Ok(());
+            }
+            deps.push(Dependency {
+                name: pkg_id.name().to_string(),
+                version: pkg_id.version().to_string(),
+                path: path.to_owned(),
+                result: VerificationStatus::Unknown,
+            });
+            Ok(())
+        })?;
+        deps.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+
+        let mut tui = Self {
+            local,
+            config,
+            trust_params,
+            db,
+            trust_set,
+            deps,
+            terminal: Terminal::enter()?,
+        };
+        tui.verify()?;
+        Ok(tui)
+    }
+
+    /// Reload proofs and verify all dependencies again
+    fn reload(&mut self) -> Result<()> {
+        let (db, trust_set) = self.local.load_db(&self.trust_params)?;
+        self.db = db;
+        self.trust_set = trust_set;
+        self.verify()
+    }
+
+    fn verify(&mut self) -> Result<()> {
+        for dep in &mut self.deps {
+            let digests = crate::get_package_digests(
+                &self.db,
+                &self.trust_set,
+                &dep.path,
+                &dep.name,
+                &dep.version,
+                &self.config,
+            )?;
+            dep.result =
+                crate::verify_package(&self.db, &self.trust_set, &digests, &dep.name, &dep.version);
+        }
+        Ok(())
+    }
+
+    fn review_count(&self, dep: &Dependency) -> usize {
+        self.db.get_package_review_count(
+            PROJECT_SOURCE_CRATES_IO,
+            Some(&dep.name),
+            Some(&dep.version),
+        )
+    }
+
+    fn run(&mut self) -> Result<()> {
+        let mut screen = Screen::new(
+            "Up/Down: move, Enter: open, r: reload proofs, q: quit",
+            true,
+        );
+        screen.title = format!("{:8} {:>7} crate", "status", "reviews");
+        loop {
+            screen.lines = self
+                .deps
+                .iter()
+                .map(|dep| {
+                    format!(
+                        "{:8} {:>7} {} {}",
+                        dep.result,
+                        self.review_count(dep),
+                        dep.name,
+                        dep.version
+                    )
+                })
+                .collect();
+            let go_on = match screen.run()? {
+                KeyCode::Char('q') => false,
+                KeyCode::Char('r') => {
+                    self.reload()?;
+                    screen.message = Some("Proofs reloaded".into());
+                    true
+                }
+                KeyCode::Enter | KeyCode::Right if !self.deps.is_empty() => {
+                    self.run_dependency(screen.selected)?
+                }
+                _ => true,
+            };
+            if !go_on {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Screen of a single dependency; `false` when the user wants to quit
+    fn run_dependency(&mut self, i: usize) -> Result<bool> {
+        let mut screen = Screen::new(
+            "f: browse files, v: view reviews, t: review, d: flag, Esc: back, q: quit",
+            false,
+        );
+        loop {
+            let dep = &self.deps[i];
+            screen.title = format!("{} {}", dep.name, dep.version);
+            screen.lines = vec![
+                format!("status:  {}", dep.result),
+                format!("reviews: {}", self.review_count(dep)),
+                format!("path:    {}", dep.path.display()),
+            ];
+            let go_on = match screen.run()? {
+                KeyCode::Char('q') => false,
+                code if is_back(&code) => return Ok(true),
+                KeyCode::Char('f') => self.run_files(i)?,
+                KeyCode::Char('v') => self.run_reviews(i)?,
+                KeyCode::Char('t') => {
+                    screen.message = Some(self.review(i, TrustOrDistrust::Trust)?);
+                    true
+                }
+                KeyCode::Char('d') => {
+                    screen.message = Some(self.review(i, TrustOrDistrust::Distrust)?);
+                    true
+                }
+                _ => true,
+            };
+            if !go_on {
+                return Ok(false);
+            }
+        }
+    }
+
+    /// Screen with reviews of a dependency; `false` when the user wants to quit
+    ///
+    /// Every review is marked with whether its author is in the trust set.
+    fn run_reviews(&self, i: usize) -> Result<bool> {
+        let dep = &self.deps[i];
+        let mut screen = Screen::new("Up/Down: scroll, Esc: back, q: quit", false);
+        screen.title = format!("Reviews of {} {}", dep.name, dep.version);
+        for review in self.db.get_package_reviews_for_package(
+            PROJECT_SOURCE_CRATES_IO,
+            Some(&dep.name),
+            Some(&dep.version),
+        ) {
+            let trusted = if self.trust_set.contains(&review.from.id) {
+                "trusted"
+            } else {
+                "not trusted"
+            };
+            screen.lines.push(format!(
+                "[{}] {}",
+                trusted,
+                describe_id(&self.db, &review.from.id)
+            ));
+            screen
+                .lines
+                .extend(review.to_string().lines().map(|line| format!("  {}", line)));
+            screen.lines.push(String::new());
+        }
+        if screen.lines.is_empty() {
+            screen.lines.push("No reviews".into());
+        }
+        loop {
+            match screen.run()? {
+                KeyCode::Char('q') => return Ok(false),
+                code if is_back(&code) => return Ok(true),
+                _ => {}
+            }
+        }
+    }
+
+    /// Review the dependency just like `cargo crev review` does, then verify again
+    ///
+    /// Returns a message about the outcome.
+    fn review(&mut self, i: usize, trust: TrustOrDistrust) -> Result<String> {
+        let dep = &self.deps[i];
+        let args = opts::ReviewCrate {
+            crate_: opts::CrateSelectorNameRequired {
+                name: dep.name.clone(),
+                version: Some(dep.version.clone()),
+            },
+            digest_type: crev_data::DigestType::default().to_string(),
+            unsafe_: false,
+            standalone: false,
+            path: None,
+            source: crate::PROJECT_SOURCE_CRATES_IO.to_owned(),
+            publish: false,
+            unsigned: false,
+            diff_base: None,
+            no_diff: false,
+            sandbox: false,
+        };
+        if let Err(e) = self.terminal.suspend(|| crate::review_crate(&args, trust)) {
+            return Ok(format!("Error: {}", e));
+        }
+        self.reload()?;
+        Ok("Review created".into())
+    }
+
+    /// Screen listing files of a dependency; `false` when the user wants to quit
+    fn run_files(&self, i: usize) -> Result<bool> {
+        let dep = &self.deps[i];
+        let files = list_files(&dep.path)?;
+        let mut screen = Screen::new("Up/Down: move, Enter: view, Esc: back, q: quit", true);
+        screen.title = format!("Files of {} {}", dep.name, dep.version);
+        screen.lines = files
+            .iter()
+            .map(|file| file.display().to_string())
+            .collect();
+        loop {
+            match screen.run()? {
+                KeyCode::Char('q') => return Ok(false),
+                code if is_back(&code) => return Ok(true),
+                KeyCode::Enter | KeyCode::Right if !files.is_empty() => {
+                    let path = dep.path.join(&files[screen.selected]);
+                    if let Err(e) = self.terminal.suspend(|| show_file(&path)) {
+                        screen.message = Some(format!("Error: {}", e));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+pub fn run(args: &opts::Tui) -> Result<()> {
+    Tui::new(args)?.run()
+}
@@ -0,0 +1,68 @@
+//! Counting `unsafe` Rust code, similarly to cargo-geiger
+//!
+//! Sources are only tokenized (not parsed), so everything in `.rs` files
+//! is counted, including code that is not compiled (eg. examples, tests,
+//! disabled features).
+use crate::prelude::*;
+use crev_data::proof::review::UnsafeCount;
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use std::{fs, path::Path, str::FromStr};
+
+fn count_in_tokens(tokens: TokenStream, count: &mut UnsafeCount) {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            TokenTree::Group(group) => count_in_tokens(group.stream(), count),
+            TokenTree::Ident(ident) if ident == "unsafe" => {
+                // `unsafe extern "C" fn` is a function too
+                let mut rest = tokens[i + 1..].iter().skip_while(|token| match token {
+                    TokenTree::Ident(ident) => ident == "extern",
+                    TokenTree::Literal(_) => true,
+                    _ => false,
+                });
+                match rest.next() {
+                    Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+                        if let Some(TokenTree::Ident(ident)) = tokens.get(i + 1) {
+                            // `unsafe extern { ... }` declares functions, doesn't call them
+                            if ident == "extern" {
+                                continue;
+                            }
+                        }
+                        count.blocks += 1
+                    }
+                    Some(TokenTree::Ident(ident)) if ident == "fn" => count.functions += 1,
+                    Some(TokenTree::Ident(ident)) if ident == "impl" => count.impls += 1,
+                    Some(TokenTree::Ident(ident)) if ident == "trait" => count.traits += 1,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Count `unsafe` code in `.rs` files of a package in `dir`
+///
+/// Files that can't be tokenized are skipped.
+pub fn count_unsafe(dir: &Path) -> Result<UnsafeCount> {
+    let mut count = UnsafeCount::default();
+    for entry in walkdir::WalkDir::new(dir) {
+        let entry = entry?;
+        let is_rust = entry
+            .path()
+            .extension()
+            .map(|ext| ext == "rs")
+            .unwrap_or(false);
+        if !entry.file_type().is_file() || !is_rust {
+            continue;
+        }
+        let content = match fs::read_to_string(entry.path()) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        if let Ok(tokens) = TokenStream::from_str(&content) {
+            count_in_tokens(tokens, &mut count);
+        }
+    }
+    Ok(count)
+}
@@ -0,0 +1,83 @@
+//! Cross-checking sources vendored with `cargo vendor`
+//!
+//! `cargo vendor` copies every package to `vendor/<name>` (or
+//! `vendor/<name>-<version>` if more versions are used), and records
+//! checksums of the `.crate` file and of every file in `.cargo-checksum.json`.
+use crate::prelude::*;
+use sha2::Digest;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub const CHECKSUM_FILE: &str = ".cargo-checksum.json";
+
+fn manifest_version(pkg_dir: &Path) -> Result<Option<String>> {
+    let manifest: toml::Value = toml::from_str(&fs::read_to_string(pkg_dir.join("Cargo.toml"))?)?;
+    Ok(manifest
+        .get("package")
+        .and_then(|package| package.get("version"))
+        .and_then(|version| version.as_str())
+        .map(ToOwned::to_owned))
+}
+
+/// Directory with the vendored copy of a package in `vendor_dir`, if any
+pub fn find_package_dir(vendor_dir: &Path, name: &str, version: &str) -> Result<Option<PathBuf>> {
+    let versioned = vendor_dir.join(format!("{}-{}", name, version));
+    if versioned.is_dir() {
+        return Ok(Some(versioned));
+    }
+    let unversioned = vendor_dir.join(name);
+    if unversioned.join("Cargo.toml").is_file()
+        && manifest_version(&unversioned)?.as_deref() == Some(version)
+    {
+        return Ok(Some(unversioned));
+    }
+    Ok(None)
+}
+
+/// Check the vendored copy in `pkg_dir` against its `.cargo-checksum.json`,
+/// and against the registry `checksum` of the package
+///
+/// Returns descriptions of all problems found.
+pub fn check_package_dir(pkg_dir: &Path, checksum: Option<&str>) -> Result<Vec<String>> {
+    let checksum_path = pkg_dir.join(CHECKSUM_FILE);
+    if !checksum_path.exists() {
+        return Ok(vec![format!("no {}", CHECKSUM_FILE)]);
+    }
+    let recorded: serde_json::Value = serde_json::from_str(&fs::read_to_string(&checksum_path)?)?;
+
+    let mut problems = vec![];
+    if let Some(checksum) = checksum {
+        if recorded.get("package").and_then(|p| p.as_str()) != Some(checksum) {
+            problems.push("vendored from a different .crate than the registry one".to_owned());
+        }
+    }
+    if let Some(files) = recorded.get("files").and_then(|f| f.as_object()) {
+        for (file, file_checksum) in files {
+            let path = pkg_dir.join(file);
+            if !path.exists() {
+                problems.push(format!("{} is missing", file));
+                continue;
+            }
+            let mut hasher = sha2::Sha256::default();
+            hasher.input(&fs::read(&path)?);
+            if Some(format!("{:x}", hasher.result()).as_str()) != file_checksum.as_str() {
+                problems.push(format!("{} was modified", file));
+            }
+        }
+        for entry in walkdir::WalkDir::new(pkg_dir) {
+            let entry = entry?;
+            let rel_path = entry.path().strip_prefix(pkg_dir)?;
+            let file = rel_path
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if entry.file_type().is_file() && file != CHECKSUM_FILE && !files.contains_key(&file) {
+                problems.push(format!("{} was added", file));
+            }
+        }
+    }
+    Ok(problems)
+}
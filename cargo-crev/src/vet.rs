@@ -0,0 +1,202 @@
+//! Exporting package reviews as cargo-vet audits
+use crate::prelude::*;
+use crev_data::proof::review::{self, Common, Rating};
+use std::collections::BTreeMap;
+
+/// cargo-vet criteria for reviews of every rating
+///
+/// Reviews rated negative or dangerous become violations of their criteria.
+pub type CriteriaMap = BTreeMap<Rating, String>;
+
+pub fn default_criteria_map() -> CriteriaMap {
+    let mut map = CriteriaMap::new();
+    map.insert(Rating::Strong, "safe-to-deploy".into());
+    map.insert(Rating::Positive, "safe-to-deploy".into());
+    map.insert(Rating::Neutral, "safe-to-run".into());
+    map.insert(Rating::Negative, "safe-to-deploy".into());
+    map.insert(Rating::Dangerous, "safe-to-run".into());
+    map
+}
+
+fn parse_rating(s: &str) -> Result<Rating> {
+    Ok(match s {
+        "strong" => Rating::Strong,
+        "positive" => Rating::Positive,
+        "neutral" => Rating::Neutral,
+        "negative" => Rating::Negative,
+        "dangerous" => Rating::Dangerous,
+        _ => bail!("Unknown rating: {}", s),
+    })
+}
+
+/// Change `map` according to `<rating>=<criteria>` mappings
+///
+/// An empty criteria leaves reviews of the rating out.
+pub fn apply_criteria_mappings(map: &mut CriteriaMap, mappings: &[String]) -> Result<()> {
+    for mapping in mappings {
+        let mut parts = mapping.splitn(2, '=');
+        let rating = parse_rating(parts.next().unwrap_or(""))?;
+        let criteria = parts
+            .next()
+            .ok_or_else(|| format_err!("Expected `<rating>=<criteria>`, got `{}`", mapping))?;
+        if criteria.is_empty() {
+            map.remove(&rating);
+        } else {
+            map.insert(rating, criteria.to_owned());
+        }
+    }
+    Ok(())
+}
+
+/// Highest rating (neutral or better) mapped to any of `criteria`
+pub fn rating_for_criteria(map: &CriteriaMap, criteria: &[String]) -> Option<Rating> {
+    map.iter()
+        .filter(|(rating, mapped)| **rating >= Rating::Neutral && criteria.contains(mapped))
+        .map(|(rating, _)| rating.clone())
+        .max()
+}
+
+/// An entry of cargo-vet `audits.toml`
+pub struct Audit {
+    pub name: String,
+    pub who: Vec<String>,
+    pub criteria: Vec<String>,
+    /// Audited version, for full audits
+    pub version: Option<String>,
+    /// `<from> -> <to>`, for audits of changes between versions
+    pub delta: Option<String>,
+    /// Versions failing the criteria, for violations
+    pub violation: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// Strings of `key`, which can be a single string or a list of them
+fn get_strs(table: &toml::Value, key: &str) -> Vec<String> {
+    match table.get(key) {
+        Some(toml::Value::String(s)) => vec![s.clone()],
+        Some(toml::Value::Array(list)) => list
+            .iter()
+            .filter_map(toml::Value::as_str)
+            .map(ToOwned::to_owned)
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn get_str(table: &toml::Value, key: &str) -> Option<String> {
+    table
+        .get(key)
+        .and_then(toml::Value::as_str)
+        .map(ToOwned::to_owned)
+}
+
+/// Parse the audits of cargo-vet `audits.toml`
+pub fn parse_audits_toml(content: &str) -> Result<Vec<Audit>> {
+    let root: toml::Value = toml::from_str(content)?;
+    let mut audits = vec![];
+    let packages = match root.get("audits").and_then(toml::Value::as_table) {
+        Some(packages) => packages,
+        None => return Ok(audits),
+    };
+    for (name, entries) in packages {
+        let entries = entries
+            .as_array()
+            .ok_or_else(|| format_err!("Invalid audits of {}", name))?;
+        for entry in entries {
+            audits.push(Audit {
+                name: name.clone(),
+                who: get_strs(entry, "who"),
+                criteria: get_strs(entry, "criteria"),
+                version: get_str(entry, "version"),
+                delta: get_str(entry, "delta"),
+                violation: get_str(entry, "violation"),
+                notes: get_str(entry, "notes"),
+            });
+        }
+    }
+    Ok(audits)
+}
+
+impl Audit {
+    /// Comment of the review imported from the audit
+    pub fn review_comment(&self) -> String {
+        let mut comment = format!(
+            "Imported from a cargo-vet audit by {} (criteria: {}).",
+            if self.who.is_empty() {
+                "unknown".to_owned()
+            } else {
+                self.who.join(", ")
+            },
+            self.criteria.join(", ")
+        );
+        if let Some(notes) = &self.notes {
+            comment.push_str("\n\n");
+            comment.push_str(notes.trim());
+        }
+        comment
+    }
+}
+
+/// `audits.toml` with an audit for each of `reviews`
+///
+/// Only the newest review of every package version is used. Positive
+/// reviews asserting criteria are exported with these criteria.
+pub fn to_audits_toml(
+    reviews: &[review::Package],
+    who: &str,
+    criteria_map: &CriteriaMap,
+) -> Result<String> {
+    let mut newest: BTreeMap<(&str, &str), &review::Package> = BTreeMap::new();
+    for review in reviews {
+        let key = (
+            review.package.name.as_str(),
+            review.package.version.as_str(),
+        );
+        match newest.get(&key) {
+            Some(other) if other.date > review.date => {}
+            _ => {
+                newest.insert(key, review);
+            }
+        }
+    }
+
+    let mut audits = toml::value::Table::new();
+    for ((name, version), review) in newest {
+        let rating = &review.review().rating;
+        let criteria = match criteria_map.get(rating) {
+            Some(criteria) => criteria,
+            None => continue,
+        };
+        let mut audit = toml::value::Table::new();
+        audit.insert("who".into(), who.into());
+        // criteria asserted in the review take precedence over the mapping
+        if *rating >= Rating::Neutral && !review.review().criteria.is_empty() {
+            audit.insert("criteria".into(), review.review().criteria.clone().into());
+        } else {
+            audit.insert("criteria".into(), criteria.clone().into());
+        }
+        if *rating < Rating::Neutral {
+            let violation = match &review.applies_to {
+                Some(applies_to) => applies_to.clone(),
+                None => format!("={}", version),
+            };
+            audit.insert("violation".into(), violation.into());
+        } else {
+            audit.insert("version".into(), version.into());
+        }
+        if !review.comment.is_empty() {
+            audit.insert("notes".into(), review.comment.trim().into());
+        }
+        match audits
+            .entry(name.to_owned())
+            .or_insert_with(|| toml::Value::Array(vec![]))
+        {
+            toml::Value::Array(entries) => entries.push(audit.into()),
+            _ => unreachable!(),
+        }
+    }
+
+    let mut root = toml::value::Table::new();
+    root.insert("audits".into(), audits.into());
+    Ok(toml::to_string(&toml::Value::Table(root))?)
+}
@@ -0,0 +1,94 @@
+//! `cargo crev watch`: re-verify dependencies whenever something changes
+use crate::prelude::*;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+/// Verification status of every dependency, by name and version
+pub type Statuses = BTreeMap<(String, String), crev_lib::VerificationStatus>;
+
+/// Summary of the state of `paths`, changing whenever a file is modified,
+/// added or removed
+///
+/// Missing paths are skipped, so that they can be created later.
+#[derive(PartialEq, Eq)]
+pub struct Stamp {
+    latest: Option<SystemTime>,
+    files: usize,
+}
+
+impl Stamp {
+    pub fn of(paths: &[PathBuf]) -> Result<Self> {
+        let mut stamp = Stamp {
+            latest: None,
+            files: 0,
+        };
+        for path in paths.iter().filter(|path| path.exists()) {
+            for entry in walkdir::WalkDir::new(path) {
+                let entry = entry?;
+                if !entry.file_type().is_file() {
+                    continue;
+                }
+                let modified = entry.metadata()?.modified()?;
+                stamp.files += 1;
+                stamp.latest = stamp.latest.max(Some(modified));
+            }
+        }
+        Ok(stamp)
+    }
+}
+
+/// Change of the verification status of a dependency
+pub struct Change {
+    pub name: String,
+    pub version: String,
+    /// `None` if the dependency was added
+    pub old: Option<String>,
+    /// `None` if the dependency was removed
+    pub new: Option<String>,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}: ", self.name, self.version)?;
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => write!(f, "{} -> {}", old, new),
+            (None, Some(new)) => write!(f, "added, {}", new),
+            (_, None) => write!(f, "removed"),
+        }
+    }
+}
+
+/// Dependencies with a different status in `new` than in `old`
+pub fn changes(old: &Statuses, new: &Statuses) -> Vec<Change> {
+    let change =
+        |(name, version): &(String, String), old: Option<String>, new: Option<String>| Change {
+            name: name.clone(),
+            version: version.clone(),
+            old,
+            new,
+        };
+    let mut changes = vec![];
+    for (pkg, status) in new {
+        match old.get(pkg) {
+            Some(old_status) if old_status == status => {}
+            old_status => changes.push(change(
+                pkg,
+                old_status.map(ToString::to_string),
+                Some(status.to_string()),
+            )),
+        }
+    }
+    for pkg in old.keys().filter(|pkg| !new.contains_key(pkg)) {
+        changes.push(change(pkg, old.get(pkg).map(ToString::to_string), None));
+    }
+    changes
+}
+
+/// Pause between checks for changes
+pub fn sleep(interval: u64) {
+    std::thread::sleep(Duration::from_secs(interval));
+}
@@ -0,0 +1,202 @@
+//! Explaining the verification status of a dependency
+use crate::prelude::*;
+use crate::{opts, rustsec, Repo, PROJECT_SOURCE_CRATES_IO};
+use crev_data::{
+    proof::review::{Common, Rating},
+    Id,
+};
+use crev_lib::{trustdb::TrustDB, Local, VerificationStatus};
+
+/// Id with its proof repository URL, if known
+pub fn describe_id(db: &TrustDB, id: &Id) -> String {
+    match db.lookup_url(id) {
+        Some(url) => format!("{} ({})", id, url.url),
+        None => id.to_string(),
+    }
+}
+
+/// Chain of trust like `you -(high)-> A -(member)-> B`
+fn describe_trust_path(db: &TrustDB, path: &[Id]) -> String {
+    let mut s = "you".to_owned();
+    for pair in path.windows(2) {
+        let link = match db.get_trust_level(&pair[0], &pair[1]) {
+            Some(level) => level.to_string(),
+            None => "member".into(),
+        };
+        s.push_str(&format!(" -({})-> {}", link, pair[1]));
+    }
+    s
+}
+
+fn print_rule(passed: bool, rule: &str) {
+    println!("  [{}] {}", if passed { "pass" } else { "FAIL" }, rule);
+}
+
+pub fn explain(args: &opts::Why) -> Result<()> {
+    let local = Local::auto_open()?;
+    let config = local.load_user_config()?;
+    let (db, trust_set) = local.load_db(&args.trust_params.clone().into())?;
+
+    let mut repo = Repo::auto_open_cwd()?;
+    repo.set_features(&args.features);
+    let (pkg_dir, version) =
+        repo.find_dependency_dir(&args.crate_.name, args.crate_.version.as_deref())?;
+    let name = &args.crate_.name;
+    let version = version.to_string();
+
+    let digests = crate::get_package_digests(&db, &trust_set, &pkg_dir, name, &version, &config)?;
+    let result = crate::verify_package(&db, &trust_set, &digests, name, &version);
+    println!("{} {}: {}", name, version, result);
+    println!("Sources: {}", pkg_dir.display());
+    for (digest_type, digest) in &digests {
+        println!("Digest ({}): {}", digest_type, digest);
+    }
+
+    let reviews: Vec<_> = db
+        .get_package_reviews_for_package(PROJECT_SOURCE_CRATES_IO, Some(name), Some(&version))
+        .collect();
+    // reviews of other versions are there because they apply to this one
+    let matching: Vec<_> = reviews
+        .iter()
+        .filter(|review| {
+            review.package.version != version
+                || digests.iter().any(|(digest_type, digest)| {
+                    review.package.digest_type == digest_type.as_str()
+                        && review.package.digest == digest.as_slice()
+                })
+        })
+        .collect();
+
+    println!();
+    println!(
+        "Reviews of this version: {} ({} matching the digest or applying to it)",
+        reviews.len(),
+        matching.len()
+    );
+    let verifying = db.get_verifying_reviewers(
+        &digests,
+        PROJECT_SOURCE_CRATES_IO,
+        name,
+        &version,
+        &trust_set,
+    );
+    for review in &matching {
+        let id = &review.from.id;
+        let review_value = review.review();
+        println!(
+            "- {:?} review by {}",
+            review_value.rating,
+            describe_id(&db, id)
+        );
+        if review.package.version != version {
+            println!(
+                "  of version {}, applying to {}",
+                review.package.version,
+                review.applies_to.as_deref().unwrap_or("")
+            );
+        }
+        println!(
+            "  on {}, thoroughness: {}, understanding: {}",
+            review.date.format("%Y-%m-%d"),
+            review_value.thoroughness,
+            review_value.understanding
+        );
+        match trust_set.get_path(id) {
+            Some(path) => println!("  trusted: {}", describe_trust_path(&db, &path)),
+            None => println!("  not in your trust set"),
+        }
+        let is_superseded = matching
+            .iter()
+            .any(|other| other.from.id == *id && other.date > review.date);
+        if is_superseded {
+            println!("  superseded by a more recent review of the same author");
+        } else if verifying.contains(id) {
+            println!("  determines the result");
+        }
+    }
+    let mismatching = reviews.len() - matching.len();
+    if mismatching > 0 {
+        println!(
+            "{} review(s) of this version don't match the digest: the sources differ \
+             from the reviewed ones, or were digested with different options",
+            mismatching
+        );
+    }
+
+    let advisories =
+        db.get_advisories_for_version(PROJECT_SOURCE_CRATES_IO, name, &version, &trust_set);
+
+    println!();
+    println!("Rules:");
+    print_rule(
+        db.verify_package_reviews(
+            &digests,
+            PROJECT_SOURCE_CRATES_IO,
+            name,
+            &version,
+            &trust_set,
+        ) != VerificationStatus::Flagged,
+        "no negative reviews from your trust set",
+    );
+    let issues = db.get_issues_for_version(PROJECT_SOURCE_CRATES_IO, name, &version, &trust_set);
+    print_rule(
+        issues.is_empty(),
+        "no issues affecting this version from your trust set",
+    );
+    for issue in &issues {
+        println!(
+            "    {} ({}) reported by {}",
+            issue.versions,
+            issue.severity,
+            describe_id(&db, &issue.from.id)
+        );
+    }
+    print_rule(
+        matching.iter().any(|review| {
+            trust_set.contains(&review.from.id) && Rating::Neutral <= review.review().rating
+        }),
+        "at least one positive review from your trust set",
+    );
+    print_rule(
+        advisories.is_empty(),
+        "no advisories affecting this version from your trust set",
+    );
+    for review in &advisories {
+        println!("    reported by {}", describe_id(&db, &review.from.id));
+    }
+    let rustsec_db = if args.no_rustsec {
+        None
+    } else {
+        rustsec::AdvisoryDb::load(&local)?
+    };
+    if let Some(rustsec_db) = rustsec_db {
+        let rustsec_advisories = rustsec_db.get_advisories(name, &version.parse()?);
+        print_rule(
+            rustsec_advisories.is_empty(),
+            "no RustSec advisories affecting this version",
+        );
+        for advisory in rustsec_advisories {
+            println!("    {}", advisory.id);
+        }
+    }
+    if let Some(checksum) = repo
+        .registry_checksums()?
+        .iter()
+        .find_map(|(pkg_id, checksum)| {
+            if pkg_id.name().as_str() == name && pkg_id.version().to_string() == version {
+                Some(checksum.clone())
+            } else {
+                None
+            }
+        })
+    {
+        let problem =
+            crate::registry::check_package_dir(&pkg_dir, &checksum, &crate::cargo_ignore_list())?;
+        print_rule(problem.is_none(), "sources match the registry checksum");
+        if let Some(problem) = problem {
+            println!("    {}", problem);
+        }
+    }
+
+    Ok(())
+}
@@ -105,6 +105,11 @@ pub fn read_new_passphrase() -> io::Result<String> {
     }
 }
 
+pub fn read_ssh_key_passphrase(path: &Path) -> io::Result<String> {
+    eprint!("Enter passphrase of {}: ", path.display());
+    rpassword::read_password()
+}
+
 pub fn read_file_to_string(path: &Path) -> io::Result<String> {
     let mut file = fs::File::open(&path)?;
     let mut res = String::new();
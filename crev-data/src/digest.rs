@@ -1,7 +1,42 @@
+use crate::Result;
 use std::fmt;
 
+/// Algorithm used to calculate a (recursive) digest
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DigestType {
+    #[default]
+    Blake2b,
+    Sha256,
+}
+
+impl DigestType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DigestType::Blake2b => "blake2b",
+            DigestType::Sha256 => "sha256",
+        }
+    }
+}
+
+impl std::str::FromStr for DigestType {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "blake2b" => DigestType::Blake2b,
+            "sha256" => DigestType::Sha256,
+            _ => bail!("Unknown digest type: {}", s),
+        })
+    }
+}
+
+impl fmt::Display for DigestType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
-#[derive(Eq, PartialEq)]
+#[derive(Clone, Eq, PartialEq)]
 pub struct Digest(Vec<u8>);
 
 impl Digest {
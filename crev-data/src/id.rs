@@ -7,6 +7,9 @@ use ed25519_dalek::{self, PublicKey, SecretKey};
 use rand::OsRng;
 use std::fmt;
 
+/// Signed by an SSH key, to derive a CrevID from the signature
+pub const SSH_KEY_SEED_MESSAGE: &[u8] = b"crev id from ssh key\0";
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum IdType {
     #[serde(rename = "crev")]
@@ -115,6 +118,19 @@ impl OwnId {
             .build()
             .map_err(|e| format_err!("{}", e))?)
     }
+
+    pub fn create_membership_proof(
+        &self,
+        members: Vec<PubId>,
+        status: proof::membership::MembershipStatus,
+    ) -> Result<proof::Membership> {
+        proof::MembershipBuilder::default()
+            .from(self.id.clone())
+            .status(status)
+            .members(members)
+            .build()
+            .map_err(|e| format_err!("{}", e))
+    }
 }
 
 impl AsRef<Id> for OwnId {
@@ -159,6 +175,30 @@ impl OwnId {
         &self.id
     }
 
+    /// Id seeded from an ed25519 SSH key (see `ssh::read_ed25519_seed`)
+    ///
+    /// Same as `from_ssh_signature`, with the signature made from the key file.
+    pub fn from_ssh_key(url: Url, key: &str, passphrase: &str) -> Result<Self> {
+        let seed = crate::ssh::read_ed25519_seed(key, passphrase)?;
+        let signature = crate::ssh::sign_ed25519(&seed, SSH_KEY_SEED_MESSAGE)?;
+        Self::from_ssh_signature(url, &signature)
+    }
+
+    /// Id seeded from the signature of `SSH_KEY_SEED_MESSAGE` by an ed25519
+    /// SSH key, eg. made by `ssh-agent`
+    ///
+    /// Ed25519 signatures are deterministic, so the same SSH key always gives
+    /// the same Id, from the key file or through the agent. Only the key can
+    /// make the signature; the Id and SSH don't share a secret.
+    pub fn from_ssh_signature(url: Url, signature: &[u8]) -> Result<Self> {
+        if signature.len() != 64 {
+            bail!("Not an ed25519 signature");
+        }
+        let mut input = SSH_KEY_SEED_MESSAGE.to_vec();
+        input.extend_from_slice(signature);
+        Self::new(url, crev_common::blake2b256sum(&input))
+    }
+
     pub fn generate_for_git_url(url: &str) -> Self {
         Self::generate(Url::new_git(url.to_owned()))
     }
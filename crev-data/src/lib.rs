@@ -18,11 +18,12 @@ pub mod id;
 pub mod level;
 mod prelude;
 pub mod proof;
+pub mod ssh;
 pub mod url;
 pub mod util;
 
 pub use crate::{
-    digest::Digest,
+    digest::{Digest, DigestType},
     id::{Id, OwnId, PubId},
     level::Level,
     proof::review::Review,
@@ -0,0 +1,130 @@
+use crate::{id, proof, Result};
+use chrono::{self, prelude::*};
+use crev_common::{
+    self,
+    serde::{as_rfc3339_fixed, from_rfc3339_fixed},
+};
+use serde_yaml;
+use std::fmt;
+
+const BEGIN_BLOCK: &str = "-----BEGIN CREV ALTERNATIVE-----";
+const BEGIN_SIGNATURE: &str = "-----BEGIN CREV ALTERNATIVE SIGNATURE-----";
+const END_BLOCK: &str = "-----END CREV ALTERNATIVE-----";
+
+const CURRENT_ALTERNATIVE_PROOF_SERIALIZATION_VERSION: i64 = 0;
+
+fn cur_version() -> i64 {
+    CURRENT_ALTERNATIVE_PROOF_SERIALIZATION_VERSION
+}
+
+/// Body of an Alternative Proof
+///
+/// Recommends replacing a package with another one (from the same source).
+#[derive(Clone, Debug, Builder, Serialize, Deserialize)]
+pub struct Alternative {
+    #[builder(default = "cur_version()")]
+    version: i64,
+    #[builder(default = "crev_common::now()")]
+    #[serde(
+        serialize_with = "as_rfc3339_fixed",
+        deserialize_with = "from_rfc3339_fixed"
+    )]
+    pub date: chrono::DateTime<FixedOffset>,
+    pub from: crate::PubId,
+    pub source: String,
+    pub name: String,
+    /// Name of the recommended package
+    pub alternative: String,
+    /// Reason for the recommendation
+    #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub comment: String,
+}
+
+impl Alternative {
+    pub fn apply_draft(&self, draft: AlternativeDraft) -> Alternative {
+        let mut copy = self.clone();
+        copy.alternative = draft.alternative;
+        copy.comment = draft.comment;
+        copy
+    }
+}
+
+/// Like `Alternative` but serializes for interactive editing
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AlternativeDraft {
+    alternative: String,
+    #[serde(default = "Default::default")]
+    comment: String,
+}
+
+impl From<Alternative> for AlternativeDraft {
+    fn from(alternative: Alternative) -> Self {
+        AlternativeDraft {
+            alternative: alternative.alternative,
+            comment: alternative.comment,
+        }
+    }
+}
+
+impl fmt::Display for Alternative {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crev_common::serde::write_as_headerless_yaml(self, f)
+    }
+}
+
+impl fmt::Display for AlternativeDraft {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crev_common::serde::write_as_headerless_yaml(self, f)
+    }
+}
+
+impl Alternative {
+    pub(crate) const BEGIN_BLOCK: &'static str = BEGIN_BLOCK;
+    pub(crate) const BEGIN_SIGNATURE: &'static str = BEGIN_SIGNATURE;
+    pub(crate) const END_BLOCK: &'static str = END_BLOCK;
+}
+
+impl proof::ContentCommon for Alternative {
+    fn date(&self) -> &chrono::DateTime<FixedOffset> {
+        &self.date
+    }
+
+    fn author(&self) -> &crate::PubId {
+        &self.from
+    }
+
+    fn version(&self) -> i64 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: i64) {
+        self.version = version;
+    }
+
+    fn current_version(&self) -> i64 {
+        cur_version()
+    }
+
+    fn draft_title(&self) -> String {
+        format!("Alternative to {}", self.name)
+    }
+}
+
+impl Alternative {
+    pub fn parse(s: &str) -> Result<Self> {
+        let proof: Self = serde_yaml::from_str(s)?;
+        proof::check_version(proof.version, cur_version())?;
+        Ok(proof)
+    }
+
+    pub fn sign_by(self, id: &id::OwnId) -> Result<proof::Proof> {
+        super::Content::from(self).sign_by(id)
+    }
+}
+
+impl AlternativeDraft {
+    pub fn parse(s: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(s)?)
+    }
+}
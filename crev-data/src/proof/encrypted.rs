@@ -0,0 +1,249 @@
+//! Proofs readable only by a set of recipient Ids
+//!
+//! A signed proof is encrypted with a random key, which in turn is encrypted
+//! to every recipient like `age` does for `ssh-ed25519` keys: with a key
+//! agreement between an ephemeral X25519 key and the Montgomery form of the
+//! Ed25519 key of the recipient's Id. Recipients get the original proof,
+//! with the original signature, back.
+use crate::{id, proof, Id, Result};
+use blake2::{self, Digest};
+use chrono::{self, prelude::*};
+use crev_common::{
+    self,
+    serde::{as_base64, as_rfc3339_fixed, from_base64, from_rfc3339_fixed},
+};
+use curve25519_dalek::{
+    constants::X25519_BASEPOINT, edwards::CompressedEdwardsY, montgomery::MontgomeryPoint,
+    scalar::Scalar,
+};
+use miscreant::aead::{self, Algorithm};
+use rand::{OsRng, RngCore};
+use serde_yaml;
+use std::fmt;
+
+const BEGIN_BLOCK: &str = "-----BEGIN CREV ENCRYPTED-----";
+const BEGIN_SIGNATURE: &str = "-----BEGIN CREV ENCRYPTED SIGNATURE-----";
+const END_BLOCK: &str = "-----END CREV ENCRYPTED-----";
+
+const CURRENT_ENCRYPTED_PROOF_SERIALIZATION_VERSION: i64 = 0;
+
+fn cur_version() -> i64 {
+    CURRENT_ENCRYPTED_PROOF_SERIALIZATION_VERSION
+}
+
+/// Domain separation of the keys sealing the proof key
+const WRAP_KEY_LABEL: &[u8] = b"crev-encrypted-proof-v0";
+
+/// The proof key, sealed for one recipient
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Recipient {
+    #[serde(flatten)]
+    pub id: Id,
+    /// Public X25519 key, generated for this recipient only
+    #[serde(
+        rename = "ephemeral-key",
+        serialize_with = "as_base64",
+        deserialize_with = "from_base64"
+    )]
+    pub ephemeral_key: Vec<u8>,
+    #[serde(
+        rename = "sealed-key",
+        serialize_with = "as_base64",
+        deserialize_with = "from_base64"
+    )]
+    pub sealed_key: Vec<u8>,
+}
+
+/// Body of an Encrypted Proof
+///
+/// Anyone can see who published it and for whom, but only the recipients
+/// can read the proof inside.
+#[derive(Clone, Debug, Builder, Serialize, Deserialize)]
+pub struct Encrypted {
+    #[builder(default = "cur_version()")]
+    version: i64,
+    #[builder(default = "crev_common::now()")]
+    #[serde(
+        serialize_with = "as_rfc3339_fixed",
+        deserialize_with = "from_rfc3339_fixed"
+    )]
+    pub date: chrono::DateTime<FixedOffset>,
+    pub from: crate::PubId,
+    pub recipients: Vec<Recipient>,
+    /// The signed proof, sealed with the proof key
+    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
+    pub ciphertext: Vec<u8>,
+}
+
+impl fmt::Display for Encrypted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crev_common::serde::write_as_headerless_yaml(self, f)
+    }
+}
+
+impl Encrypted {
+    pub(crate) const BEGIN_BLOCK: &'static str = BEGIN_BLOCK;
+    pub(crate) const BEGIN_SIGNATURE: &'static str = BEGIN_SIGNATURE;
+    pub(crate) const END_BLOCK: &'static str = END_BLOCK;
+}
+
+impl proof::ContentCommon for Encrypted {
+    fn date(&self) -> &chrono::DateTime<FixedOffset> {
+        &self.date
+    }
+
+    fn author(&self) -> &crate::PubId {
+        &self.from
+    }
+
+    fn version(&self) -> i64 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: i64) {
+        self.version = version;
+    }
+
+    fn current_version(&self) -> i64 {
+        cur_version()
+    }
+
+    fn draft_title(&self) -> String {
+        "Encrypted proof".to_owned()
+    }
+}
+
+/// X25519 public key of an Id: the Montgomery form of its Ed25519 key
+fn public_key_of(id: &Id) -> Result<MontgomeryPoint> {
+    match id {
+        Id::Crev { id: bytes } => {
+            if bytes.len() != 32 {
+                bail!("Invalid Id {}", id);
+            }
+            let mut compressed = [0u8; 32];
+            compressed.copy_from_slice(bytes);
+            Ok(CompressedEdwardsY(compressed)
+                .decompress()
+                .ok_or_else(|| format_err!("Invalid Id {}", id))?
+                .to_montgomery())
+        }
+    }
+}
+
+/// X25519 secret key of an own Id: the scalar of its Ed25519 key
+fn secret_key_of(id: &id::OwnId) -> Scalar {
+    let expanded = id.keypair.secret.expand::<blake2::Blake2b>().to_bytes();
+    let mut bits = [0u8; 32];
+    bits.copy_from_slice(&expanded[..32]);
+    Scalar::from_bits(bits)
+}
+
+/// Key sealing the proof key for `recipient`, from the result of the key agreement
+fn wrap_key(
+    shared: &MontgomeryPoint,
+    ephemeral: &MontgomeryPoint,
+    recipient: &MontgomeryPoint,
+) -> Vec<u8> {
+    let mut hasher = blake2::Blake2b::new();
+    hasher.input(WRAP_KEY_LABEL);
+    hasher.input(shared.as_bytes());
+    hasher.input(ephemeral.as_bytes());
+    hasher.input(recipient.as_bytes());
+    hasher.result().to_vec()
+}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>> {
+    let mut bytes = vec![0u8; len];
+    OsRng::new()?.fill_bytes(&mut bytes);
+    Ok(bytes)
+}
+
+impl Encrypted {
+    pub fn parse(s: &str) -> Result<Self> {
+        let proof: Self = serde_yaml::from_str(s)?;
+        proof::check_version(proof.version, cur_version())?;
+        Ok(proof)
+    }
+
+    pub fn sign_by(self, id: &id::OwnId) -> Result<proof::Proof> {
+        super::Content::from(self).sign_by(id)
+    }
+
+    /// Encrypt `proof` (signed by `from`) to `recipients`
+    pub fn encrypt(proof: &proof::Proof, from: crate::PubId, recipients: &[Id]) -> Result<Self> {
+        if proof.content.author_id() != from.id {
+            bail!("Only own proofs can be encrypted");
+        }
+        if recipients.is_empty() {
+            bail!("No recipients given");
+        }
+
+        // the proof key is used only once, so no nonce is needed
+        let proof_key = random_bytes(64)?;
+        let ciphertext =
+            aead::Aes256Siv::new(&proof_key).seal(&[], &[], proof.to_string().as_bytes());
+
+        let mut sealed_recipients = vec![];
+        for id in recipients {
+            let recipient_key = public_key_of(id)?;
+            let mut ephemeral_secret = [0u8; 32];
+            ephemeral_secret.copy_from_slice(&random_bytes(32)?);
+            let ephemeral_secret = Scalar::from_bits(ephemeral_secret);
+            let ephemeral_key = X25519_BASEPOINT * ephemeral_secret;
+            let shared = recipient_key * ephemeral_secret;
+            let key = wrap_key(&shared, &ephemeral_key, &recipient_key);
+            sealed_recipients.push(Recipient {
+                id: id.clone(),
+                ephemeral_key: ephemeral_key.as_bytes().to_vec(),
+                sealed_key: aead::Aes256Siv::new(&key).seal(&[], &[], &proof_key),
+            });
+        }
+
+        EncryptedBuilder::default()
+            .from(from)
+            .recipients(sealed_recipients)
+            .ciphertext(ciphertext)
+            .build()
+            .map_err(|e| format_err!("{}", e))
+    }
+
+    pub fn is_encrypted_to(&self, id: &Id) -> bool {
+        self.recipients.iter().any(|recipient| recipient.id == *id)
+    }
+
+    /// Decrypt the proof inside as one of the recipients, checking its signature
+    pub fn decrypt(&self, id: &id::OwnId) -> Result<proof::Proof> {
+        let recipient = self
+            .recipients
+            .iter()
+            .find(|recipient| recipient.id == id.id.id)
+            .ok_or_else(|| format_err!("Proof is not encrypted to {}", id.id.id))?;
+        if recipient.ephemeral_key.len() != 32 {
+            bail!("Invalid ephemeral key");
+        }
+        let mut ephemeral_key = [0u8; 32];
+        ephemeral_key.copy_from_slice(&recipient.ephemeral_key);
+        let ephemeral_key = MontgomeryPoint(ephemeral_key);
+
+        let secret_key = secret_key_of(id);
+        let shared = ephemeral_key * secret_key;
+        let key = wrap_key(&shared, &ephemeral_key, &public_key_of(&id.id.id)?);
+        let proof_key = aead::Aes256Siv::new(&key)
+            .open(&[], &[], &recipient.sealed_key)
+            .map_err(|_| format_err!("Can't decrypt the proof key"))?;
+        let plaintext = aead::Aes256Siv::new(&proof_key)
+            .open(&[], &[], &self.ciphertext)
+            .map_err(|_| format_err!("Can't decrypt the proof"))?;
+
+        let mut proofs = proof::Proof::parse(plaintext.as_slice())?;
+        if proofs.len() != 1 {
+            bail!("Encrypted proof should contain exactly one proof");
+        }
+        let proof = proofs.remove(0);
+        proof.verify()?;
+        if proof.content.author_id() != self.from.id {
+            bail!("Encrypted proof contains a proof of another Id");
+        }
+        Ok(proof)
+    }
+}
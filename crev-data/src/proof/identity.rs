@@ -0,0 +1,186 @@
+use crate::{id, proof, Result};
+use chrono::{self, prelude::*};
+use crev_common::{
+    self,
+    serde::{as_rfc3339_fixed, from_rfc3339_fixed},
+};
+use serde_yaml;
+use std::fmt;
+
+const BEGIN_BLOCK: &str = "-----BEGIN CREV IDENTITY CLAIM-----";
+const BEGIN_SIGNATURE: &str = "-----BEGIN CREV IDENTITY CLAIM SIGNATURE-----";
+const END_BLOCK: &str = "-----END CREV IDENTITY CLAIM-----";
+
+const CURRENT_IDENTITY_CLAIM_PROOF_SERIALIZATION_VERSION: i64 = 0;
+
+fn cur_version() -> i64 {
+    CURRENT_IDENTITY_CLAIM_PROOF_SERIALIZATION_VERSION
+}
+
+pub const SERVICE_GITHUB: &str = "github";
+/// crates.io logins are GitHub logins, so these are verified the same way
+pub const SERVICE_CRATES_IO: &str = "crates-io";
+
+/// Account on an external service
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Account {
+    /// `github` or `crates-io`
+    pub service: String,
+    pub name: String,
+    /// Published by the account, containing the Id (eg. a gist)
+    #[serde(rename = "proof-url")]
+    pub proof_url: String,
+}
+
+impl Account {
+    /// Where to download the content of `proof_url` from
+    ///
+    /// `None` if `proof_url` doesn't belong to the account, so it can't
+    /// verify the claim.
+    pub fn raw_proof_url(&self) -> Option<String> {
+        if self.service != SERVICE_GITHUB && self.service != SERVICE_CRATES_IO {
+            return None;
+        }
+        let url = self.proof_url.trim_end_matches('/');
+        if !url.starts_with("https://") {
+            return None;
+        }
+        let without_scheme = &url["https://".len()..];
+        let (host, path) = without_scheme.split_once('/')?;
+        let (owner, rest) = path.split_once('/')?;
+        if !owner.eq_ignore_ascii_case(&self.name) {
+            return None;
+        }
+        match host {
+            "gist.github.com" => Some(format!(
+                "https://gist.githubusercontent.com/{}/{}/raw",
+                owner, rest
+            )),
+            "github.com" => {
+                // `<repo>/blob/<branch>/<path>`
+                let mut parts = rest.splitn(3, '/');
+                let repo = parts.next()?;
+                if parts.next()? != "blob" {
+                    return None;
+                }
+                Some(format!(
+                    "https://raw.githubusercontent.com/{}/{}/{}",
+                    owner,
+                    repo,
+                    parts.next()?
+                ))
+            }
+            "gist.githubusercontent.com" | "raw.githubusercontent.com" => Some(url.to_owned()),
+            _ => None,
+        }
+    }
+}
+
+/// Body of an Identity Claim Proof
+///
+/// Links an Id to accounts on external services. A claim is verified
+/// only if the account published the Id too, at `proof_url`.
+#[derive(Clone, Debug, Builder, Serialize, Deserialize)]
+pub struct IdentityClaim {
+    #[builder(default = "cur_version()")]
+    version: i64,
+    #[builder(default = "crev_common::now()")]
+    #[serde(
+        serialize_with = "as_rfc3339_fixed",
+        deserialize_with = "from_rfc3339_fixed"
+    )]
+    pub date: chrono::DateTime<FixedOffset>,
+    pub from: crate::PubId,
+    pub accounts: Vec<Account>,
+    #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub comment: String,
+}
+
+impl IdentityClaim {
+    pub fn apply_draft(&self, draft: IdentityClaimDraft) -> IdentityClaim {
+        let mut copy = self.clone();
+        copy.accounts = draft.accounts;
+        copy.comment = draft.comment;
+        copy
+    }
+}
+
+/// Like `IdentityClaim` but serializes for interactive editing
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IdentityClaimDraft {
+    accounts: Vec<Account>,
+    #[serde(default = "Default::default")]
+    comment: String,
+}
+
+impl From<IdentityClaim> for IdentityClaimDraft {
+    fn from(claim: IdentityClaim) -> Self {
+        IdentityClaimDraft {
+            accounts: claim.accounts,
+            comment: claim.comment,
+        }
+    }
+}
+
+impl fmt::Display for IdentityClaim {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crev_common::serde::write_as_headerless_yaml(self, f)
+    }
+}
+
+impl fmt::Display for IdentityClaimDraft {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crev_common::serde::write_as_headerless_yaml(self, f)
+    }
+}
+
+impl IdentityClaim {
+    pub(crate) const BEGIN_BLOCK: &'static str = BEGIN_BLOCK;
+    pub(crate) const BEGIN_SIGNATURE: &'static str = BEGIN_SIGNATURE;
+    pub(crate) const END_BLOCK: &'static str = END_BLOCK;
+}
+
+impl proof::ContentCommon for IdentityClaim {
+    fn date(&self) -> &chrono::DateTime<FixedOffset> {
+        &self.date
+    }
+
+    fn author(&self) -> &crate::PubId {
+        &self.from
+    }
+
+    fn version(&self) -> i64 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: i64) {
+        self.version = version;
+    }
+
+    fn current_version(&self) -> i64 {
+        cur_version()
+    }
+
+    fn draft_title(&self) -> String {
+        "Identity claim".to_owned()
+    }
+}
+
+impl IdentityClaim {
+    pub fn parse(s: &str) -> Result<Self> {
+        let proof: Self = serde_yaml::from_str(s)?;
+        proof::check_version(proof.version, cur_version())?;
+        Ok(proof)
+    }
+
+    pub fn sign_by(self, id: &id::OwnId) -> Result<proof::Proof> {
+        super::Content::from(self).sign_by(id)
+    }
+}
+
+impl IdentityClaimDraft {
+    pub fn parse(s: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(s)?)
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{id, proof, Level, Result};
+use chrono::{self, prelude::*};
+use crev_common::{
+    self,
+    serde::{as_rfc3339_fixed, from_rfc3339_fixed},
+};
+use serde_yaml;
+use std::fmt;
+
+const BEGIN_BLOCK: &str = "-----BEGIN CREV ISSUE-----";
+const BEGIN_SIGNATURE: &str = "-----BEGIN CREV ISSUE SIGNATURE-----";
+const END_BLOCK: &str = "-----END CREV ISSUE-----";
+
+const CURRENT_ISSUE_PROOF_SERIALIZATION_VERSION: i64 = 0;
+
+fn cur_version() -> i64 {
+    CURRENT_ISSUE_PROOF_SERIALIZATION_VERSION
+}
+
+/// Body of an Issue Proof
+///
+/// Reports a problem affecting a range of versions of a package, eg.
+/// found before any fixed version exists (unlike an `Advisory`, which is
+/// attached to the review of the fixed version).
+#[derive(Clone, Debug, Builder, Serialize, Deserialize)]
+pub struct Issue {
+    #[builder(default = "cur_version()")]
+    version: i64,
+    #[builder(default = "crev_common::now()")]
+    #[serde(
+        serialize_with = "as_rfc3339_fixed",
+        deserialize_with = "from_rfc3339_fixed"
+    )]
+    pub date: chrono::DateTime<FixedOffset>,
+    pub from: crate::PubId,
+    pub source: String,
+    pub name: String,
+    /// Affected versions, as a semver requirement (eg. `>=1.0.0, <1.2.3`)
+    pub versions: String,
+    /// Ids of the problem in other databases (eg. `RUSTSEC-2019-0001`, `CVE-2019-1234`)
+    #[serde(skip_serializing_if = "Vec::is_empty", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub ids: Vec<String>,
+    #[serde(default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub severity: Level,
+    #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub comment: String,
+}
+
+impl Issue {
+    pub fn apply_draft(&self, draft: IssueDraft) -> Issue {
+        let mut copy = self.clone();
+        copy.versions = draft.versions;
+        copy.ids = draft.ids;
+        copy.severity = draft.severity;
+        copy.comment = draft.comment;
+        copy
+    }
+}
+
+/// Like `Issue` but serializes for interactive editing
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IssueDraft {
+    versions: String,
+    #[serde(default = "Default::default")]
+    ids: Vec<String>,
+    #[serde(default = "Default::default")]
+    severity: Level,
+    #[serde(default = "Default::default")]
+    comment: String,
+}
+
+impl From<Issue> for IssueDraft {
+    fn from(issue: Issue) -> Self {
+        IssueDraft {
+            versions: issue.versions,
+            ids: issue.ids,
+            severity: issue.severity,
+            comment: issue.comment,
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crev_common::serde::write_as_headerless_yaml(self, f)
+    }
+}
+
+impl fmt::Display for IssueDraft {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crev_common::serde::write_as_headerless_yaml(self, f)
+    }
+}
+
+impl Issue {
+    pub(crate) const BEGIN_BLOCK: &'static str = BEGIN_BLOCK;
+    pub(crate) const BEGIN_SIGNATURE: &'static str = BEGIN_SIGNATURE;
+    pub(crate) const END_BLOCK: &'static str = END_BLOCK;
+}
+
+impl proof::ContentCommon for Issue {
+    fn date(&self) -> &chrono::DateTime<FixedOffset> {
+        &self.date
+    }
+
+    fn author(&self) -> &crate::PubId {
+        &self.from
+    }
+
+    fn version(&self) -> i64 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: i64) {
+        self.version = version;
+    }
+
+    fn current_version(&self) -> i64 {
+        cur_version()
+    }
+
+    fn draft_title(&self) -> String {
+        format!("Issue in {} {}", self.name, self.versions)
+    }
+}
+
+impl Issue {
+    pub fn parse(s: &str) -> Result<Self> {
+        let proof: Self = serde_yaml::from_str(s)?;
+        proof::check_version(proof.version, cur_version())?;
+        Ok(proof)
+    }
+
+    pub fn sign_by(self, id: &id::OwnId) -> Result<proof::Proof> {
+        super::Content::from(self).sign_by(id)
+    }
+}
+
+impl IssueDraft {
+    pub fn parse(s: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(s)?)
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{id, proof, Result};
+use chrono::{self, prelude::*};
+use crev_common::{
+    self,
+    serde::{as_rfc3339_fixed, from_rfc3339_fixed},
+};
+use serde_yaml;
+use std::fmt;
+
+const BEGIN_BLOCK: &str = "-----BEGIN CREV MEMBERSHIP-----";
+const BEGIN_SIGNATURE: &str = "-----BEGIN CREV MEMBERSHIP SIGNATURE-----";
+const END_BLOCK: &str = "-----END CREV MEMBERSHIP-----";
+
+const CURRENT_MEMBERSHIP_PROOF_SERIALIZATION_VERSION: i64 = 0;
+
+fn cur_version() -> i64 {
+    CURRENT_MEMBERSHIP_PROOF_SERIALIZATION_VERSION
+}
+
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MembershipStatus {
+    #[default]
+    Member,
+    Revoked,
+}
+
+impl fmt::Display for MembershipStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use self::MembershipStatus::*;
+        f.write_str(match self {
+            Member => "member",
+            Revoked => "revoked",
+        })
+    }
+}
+
+/// Body of a Membership Proof
+///
+/// Issued by an organization Id, to vouch for Ids of its members.
+/// Anyone trusting the organization trusts its members just as much.
+#[derive(Clone, Debug, Builder, Serialize, Deserialize)]
+pub struct Membership {
+    #[builder(default = "cur_version()")]
+    version: i64,
+    #[builder(default = "crev_common::now()")]
+    #[serde(
+        serialize_with = "as_rfc3339_fixed",
+        deserialize_with = "from_rfc3339_fixed"
+    )]
+    pub date: chrono::DateTime<FixedOffset>,
+    pub from: crate::PubId,
+    pub members: Vec<crate::PubId>,
+    #[builder(default = "Default::default()")]
+    pub status: MembershipStatus,
+    #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    comment: String,
+}
+
+impl Membership {
+    pub fn apply_draft(&self, draft: MembershipDraft) -> Membership {
+        let mut copy = self.clone();
+        copy.status = draft.status;
+        copy.comment = draft.comment;
+        copy
+    }
+}
+
+/// Like `Membership` but serializes for interactive editing
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MembershipDraft {
+    pub status: MembershipStatus,
+    #[serde(default = "Default::default")]
+    comment: String,
+}
+
+impl From<Membership> for MembershipDraft {
+    fn from(membership: Membership) -> Self {
+        MembershipDraft {
+            status: membership.status,
+            comment: membership.comment,
+        }
+    }
+}
+
+impl fmt::Display for Membership {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crev_common::serde::write_as_headerless_yaml(self, f)
+    }
+}
+
+impl fmt::Display for MembershipDraft {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crev_common::serde::write_as_headerless_yaml(self, f)
+    }
+}
+
+impl Membership {
+    pub(crate) const BEGIN_BLOCK: &'static str = BEGIN_BLOCK;
+    pub(crate) const BEGIN_SIGNATURE: &'static str = BEGIN_SIGNATURE;
+    pub(crate) const END_BLOCK: &'static str = END_BLOCK;
+}
+
+impl proof::ContentCommon for Membership {
+    fn date(&self) -> &chrono::DateTime<FixedOffset> {
+        &self.date
+    }
+
+    fn author(&self) -> &crate::PubId {
+        &self.from
+    }
+
+    fn version(&self) -> i64 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: i64) {
+        self.version = version;
+    }
+
+    fn current_version(&self) -> i64 {
+        cur_version()
+    }
+
+    fn draft_title(&self) -> String {
+        format!("Membership of {} Ids", self.members.len())
+    }
+}
+
+impl Membership {
+    pub fn parse(s: &str) -> Result<Self> {
+        let proof: Self = serde_yaml::from_str(s)?;
+        proof::check_version(proof.version, cur_version())?;
+        Ok(proof)
+    }
+
+    pub fn sign_by(self, id: &id::OwnId) -> Result<proof::Proof> {
+        super::Content::from(self).sign_by(id)
+    }
+}
+
+impl MembershipDraft {
+    pub fn parse(s: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(s)?)
+    }
+}
@@ -5,12 +5,22 @@ use chrono::{self, prelude::*};
 use crev_common;
 use std::{default, fmt, fs, io, mem, path::Path};
 
+pub mod alternative;
+pub mod encrypted;
+pub mod identity;
+pub mod issue;
+pub mod membership;
 pub mod package_info;
 pub mod review;
 pub mod revision;
+pub mod strict;
 pub mod trust;
+pub mod usage;
 
-pub use self::{package_info::*, revision::*, trust::*};
+pub use self::{
+    alternative::*, encrypted::*, identity::*, issue::*, membership::*, package_info::*,
+    revision::*, trust::*, usage::*,
+};
 
 use crate::Result;
 
@@ -30,14 +40,48 @@ pub trait ContentCommon {
         self.author().url.clone()
     }
 
+    /// Format version the proof was written in
+    fn version(&self) -> i64;
+
+    fn set_version(&mut self, version: i64);
+
+    /// Format version new proofs of this type are written in
+    fn current_version(&self) -> i64;
+
+    /// Was the proof written in an older format version
+    fn is_outdated(&self) -> bool {
+        self.version() < self.current_version()
+    }
+
     fn draft_title(&self) -> String;
 }
 
+/// Check that a proof in format `version` can be understood
+///
+/// Older versions are fine (and can be migrated), but proofs written
+/// by a newer version of `crev` could be misinterpreted.
+pub(crate) fn check_version(version: i64, current: i64) -> Result<()> {
+    if version > current {
+        bail!(
+            "Proof format version {} is newer than the supported version {}; please upgrade",
+            version,
+            current
+        );
+    }
+    Ok(())
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum ProofType {
     Code,
     Package,
     Trust,
+    Membership,
+    Issue,
+    Alternative,
+    IdentityClaim,
+    Encrypted,
+    Usage,
 }
 
 impl ProofType {
@@ -46,6 +90,12 @@ impl ProofType {
             ProofType::Code => review::Code::BEGIN_BLOCK,
             ProofType::Package => review::Package::BEGIN_BLOCK,
             ProofType::Trust => Trust::BEGIN_BLOCK,
+            ProofType::Membership => Membership::BEGIN_BLOCK,
+            ProofType::Issue => Issue::BEGIN_BLOCK,
+            ProofType::Alternative => Alternative::BEGIN_BLOCK,
+            ProofType::IdentityClaim => IdentityClaim::BEGIN_BLOCK,
+            ProofType::Encrypted => Encrypted::BEGIN_BLOCK,
+            ProofType::Usage => Usage::BEGIN_BLOCK,
         }
     }
     fn begin_signature(&self) -> &'static str {
@@ -53,6 +103,12 @@ impl ProofType {
             ProofType::Code => review::Code::BEGIN_SIGNATURE,
             ProofType::Package => review::Package::BEGIN_SIGNATURE,
             ProofType::Trust => Trust::BEGIN_SIGNATURE,
+            ProofType::Membership => Membership::BEGIN_SIGNATURE,
+            ProofType::Issue => Issue::BEGIN_SIGNATURE,
+            ProofType::Alternative => Alternative::BEGIN_SIGNATURE,
+            ProofType::IdentityClaim => IdentityClaim::BEGIN_SIGNATURE,
+            ProofType::Encrypted => Encrypted::BEGIN_SIGNATURE,
+            ProofType::Usage => Usage::BEGIN_SIGNATURE,
         }
     }
     fn end_block(&self) -> &'static str {
@@ -60,6 +116,12 @@ impl ProofType {
             ProofType::Code => review::Code::END_BLOCK,
             ProofType::Package => review::Package::END_BLOCK,
             ProofType::Trust => Trust::END_BLOCK,
+            ProofType::Membership => Membership::END_BLOCK,
+            ProofType::Issue => Issue::END_BLOCK,
+            ProofType::Alternative => Alternative::END_BLOCK,
+            ProofType::IdentityClaim => IdentityClaim::END_BLOCK,
+            ProofType::Encrypted => Encrypted::END_BLOCK,
+            ProofType::Usage => Usage::END_BLOCK,
         }
     }
 }
@@ -81,6 +143,12 @@ pub(crate) struct Serialized {
 #[derive(Debug, Clone)]
 pub enum Content {
     Trust(Trust),
+    Membership(Membership),
+    Issue(Issue),
+    Alternative(Alternative),
+    IdentityClaim(IdentityClaim),
+    Encrypted(Encrypted),
+    Usage(Usage),
     Package(review::Package),
     Code(review::Code),
 }
@@ -90,6 +158,12 @@ impl fmt::Display for Content {
         use self::Content::*;
         match self {
             Trust(trust) => trust.fmt(f),
+            Membership(membership) => membership.fmt(f),
+            Issue(issue) => issue.fmt(f),
+            Alternative(alternative) => alternative.fmt(f),
+            IdentityClaim(claim) => claim.fmt(f),
+            Encrypted(encrypted) => encrypted.fmt(f),
+            Usage(usage) => usage.fmt(f),
             Code(code) => code.fmt(f),
             Package(package) => package.fmt(f),
         }
@@ -114,11 +188,53 @@ impl From<Trust> for Content {
     }
 }
 
+impl From<Membership> for Content {
+    fn from(membership: Membership) -> Self {
+        Content::Membership(membership)
+    }
+}
+
+impl From<Issue> for Content {
+    fn from(issue: Issue) -> Self {
+        Content::Issue(issue)
+    }
+}
+
+impl From<Alternative> for Content {
+    fn from(alternative: Alternative) -> Self {
+        Content::Alternative(alternative)
+    }
+}
+
+impl From<IdentityClaim> for Content {
+    fn from(claim: IdentityClaim) -> Self {
+        Content::IdentityClaim(claim)
+    }
+}
+
+impl From<Encrypted> for Content {
+    fn from(encrypted: Encrypted) -> Self {
+        Content::Encrypted(encrypted)
+    }
+}
+
+impl From<Usage> for Content {
+    fn from(usage: Usage) -> Self {
+        Content::Usage(usage)
+    }
+}
+
 impl Content {
     pub fn draft_title(&self) -> String {
         use self::Content::*;
         match self {
             Trust(trust) => trust.draft_title(),
+            Membership(membership) => membership.draft_title(),
+            Issue(issue) => issue.draft_title(),
+            Alternative(alternative) => alternative.draft_title(),
+            IdentityClaim(claim) => claim.draft_title(),
+            Encrypted(encrypted) => encrypted.draft_title(),
+            Usage(usage) => usage.draft_title(),
             Code(review) => review.draft_title(),
             Package(review) => review.draft_title(),
         }
@@ -128,6 +244,12 @@ impl Content {
             ProofType::Code => Content::Code(review::Code::parse(&s)?),
             ProofType::Package => Content::Package(review::Package::parse(&s)?),
             ProofType::Trust => Content::Trust(Trust::parse(&s)?),
+            ProofType::Membership => Content::Membership(Membership::parse(s)?),
+            ProofType::Issue => Content::Issue(Issue::parse(s)?),
+            ProofType::Alternative => Content::Alternative(Alternative::parse(s)?),
+            ProofType::IdentityClaim => Content::IdentityClaim(IdentityClaim::parse(s)?),
+            ProofType::Encrypted => Content::Encrypted(Encrypted::parse(s)?),
+            ProofType::Usage => Content::Usage(Usage::parse(s)?),
         })
     }
 
@@ -142,6 +264,18 @@ impl Content {
             Content::Trust(trust) => {
                 Content::Trust(trust.apply_draft(TrustDraft::parse(&s)?.into()))
             }
+            Content::Membership(membership) => {
+                Content::Membership(membership.apply_draft(MembershipDraft::parse(s)?))
+            }
+            Content::Issue(issue) => Content::Issue(issue.apply_draft(IssueDraft::parse(s)?)),
+            Content::Alternative(alternative) => {
+                Content::Alternative(alternative.apply_draft(AlternativeDraft::parse(s)?))
+            }
+            Content::IdentityClaim(claim) => {
+                Content::IdentityClaim(claim.apply_draft(IdentityClaimDraft::parse(s)?))
+            }
+            Content::Encrypted(_) => bail!("Encrypted proofs can't be edited"),
+            Content::Usage(usage) => Content::Usage(usage.apply_draft(UsageDraft::parse(s)?)),
         })
     }
     pub fn sign_by(&self, id: &crate::id::OwnId) -> Result<Proof> {
@@ -155,10 +289,28 @@ impl Content {
         })
     }
 
+    /// The proof with an empty signature, to be signed elsewhere
+    ///
+    /// See `Proof::detached_signature_by` and `Proof::with_signature`.
+    pub fn to_unsigned_string(&self) -> String {
+        Serialized {
+            body: self.to_string(),
+            signature: String::new(),
+            type_: self.proof_type(),
+        }
+        .to_string()
+    }
+
     pub fn proof_type(&self) -> ProofType {
         use self::Content::*;
         match self {
             Trust(_trust) => ProofType::Trust,
+            Membership(_membership) => ProofType::Membership,
+            Issue(_issue) => ProofType::Issue,
+            Alternative(_alternative) => ProofType::Alternative,
+            IdentityClaim(_claim) => ProofType::IdentityClaim,
+            Encrypted(_encrypted) => ProofType::Encrypted,
+            Usage(_usage) => ProofType::Usage,
             Code(_review) => ProofType::Code,
             Package(_review) => ProofType::Package,
         }
@@ -168,6 +320,12 @@ impl Content {
         use self::Content::*;
         match self {
             Trust(trust) => trust.date(),
+            Membership(membership) => membership.date(),
+            Issue(issue) => issue.date(),
+            Alternative(alternative) => alternative.date(),
+            IdentityClaim(claim) => claim.date(),
+            Encrypted(encrypted) => encrypted.date(),
+            Usage(usage) => usage.date(),
             Code(review) => review.date(),
             Package(review) => review.date(),
         }
@@ -177,6 +335,12 @@ impl Content {
         use self::Content::*;
         match self {
             Trust(trust) => trust.author_id(),
+            Membership(membership) => membership.author_id(),
+            Issue(issue) => issue.author_id(),
+            Alternative(alternative) => alternative.author_id(),
+            IdentityClaim(claim) => claim.author_id(),
+            Encrypted(encrypted) => encrypted.author_id(),
+            Usage(usage) => usage.author_id(),
             Code(review) => review.author_id(),
             Package(review) => review.author_id(),
         }
@@ -186,15 +350,64 @@ impl Content {
         use self::Content::*;
         match self {
             Trust(trust) => trust.author_url(),
+            Membership(membership) => membership.author_url(),
+            Issue(issue) => issue.author_url(),
+            Alternative(alternative) => alternative.author_url(),
+            IdentityClaim(claim) => claim.author_url(),
+            Encrypted(encrypted) => encrypted.author_url(),
+            Usage(usage) => usage.author_url(),
             Code(review) => review.author_url(),
             Package(review) => review.author_url(),
         }
     }
 
+    pub fn is_outdated(&self) -> bool {
+        use self::Content::*;
+        match self {
+            Trust(trust) => trust.is_outdated(),
+            Membership(membership) => membership.is_outdated(),
+            Issue(issue) => issue.is_outdated(),
+            Alternative(alternative) => alternative.is_outdated(),
+            IdentityClaim(claim) => claim.is_outdated(),
+            Encrypted(encrypted) => encrypted.is_outdated(),
+            Usage(usage) => usage.is_outdated(),
+            Code(review) => review.is_outdated(),
+            Package(review) => review.is_outdated(),
+        }
+    }
+
+    /// Copy of the content upgraded to the current format version
+    pub fn migrate(&self) -> Content {
+        use self::Content::*;
+        let mut copy = self.clone();
+        {
+            let common: &mut dyn ContentCommon = match &mut copy {
+                Trust(trust) => trust,
+                Membership(membership) => membership,
+                Issue(issue) => issue,
+                Alternative(alternative) => alternative,
+                IdentityClaim(claim) => claim,
+                Encrypted(encrypted) => encrypted,
+                Usage(usage) => usage,
+                Code(review) => review,
+                Package(review) => review,
+            };
+            let version = common.current_version();
+            common.set_version(version);
+        }
+        copy
+    }
+
     pub fn to_draft_string(&self) -> String {
         use self::Content::*;
         match self.clone() {
             Trust(trust) => format!("{}", TrustDraft::from(trust)),
+            Membership(membership) => format!("{}", MembershipDraft::from(membership)),
+            Issue(issue) => format!("{}", IssueDraft::from(issue)),
+            Alternative(alternative) => format!("{}", AlternativeDraft::from(alternative)),
+            IdentityClaim(claim) => format!("{}", IdentityClaimDraft::from(claim)),
+            Encrypted(encrypted) => format!("{}", encrypted),
+            Usage(usage) => format!("{}", UsageDraft::from(usage)),
             Code(review) => format!("{}", review::CodeDraft::from(review)),
             Package(review) => format!("{}", review::PackageDraft::from(review)),
         }
@@ -252,11 +465,32 @@ impl Serialized {
                 ProofType::Code => Content::Code(review::Code::parse(&self.body)?),
                 ProofType::Package => Content::Package(review::Package::parse(&self.body)?),
                 ProofType::Trust => Content::Trust(Trust::parse(&self.body)?),
+                ProofType::Membership => Content::Membership(Membership::parse(&self.body)?),
+                ProofType::Issue => Content::Issue(Issue::parse(&self.body)?),
+                ProofType::Alternative => Content::Alternative(Alternative::parse(&self.body)?),
+                ProofType::IdentityClaim => {
+                    Content::IdentityClaim(IdentityClaim::parse(&self.body)?)
+                }
+                ProofType::Encrypted => Content::Encrypted(Encrypted::parse(&self.body)?),
+                ProofType::Usage => Content::Usage(Usage::parse(&self.body)?),
             },
         })
     }
 
     pub fn parse(reader: impl io::BufRead) -> Result<Vec<Self>> {
+        Ok(Self::parse_with_lines(reader)
+            .map_err(|(line, e)| format_err!("Line {}: {}", line, e))?
+            .into_iter()
+            .map(|(_line, serialized)| serialized)
+            .collect())
+    }
+
+    /// Like `parse`, but also returns the line number where each proof begins
+    ///
+    /// On failure, returns the line number of the problem.
+    pub(crate) fn parse_with_lines(
+        reader: impl io::BufRead,
+    ) -> std::result::Result<Vec<(usize, Self)>, (usize, failure::Error)> {
         #[derive(PartialEq, Eq)]
         enum Stage {
             None,
@@ -275,7 +509,9 @@ impl Serialized {
             body: String,
             signature: String,
             type_: ProofType,
-            proofs: Vec<Serialized>,
+            line_number: usize,
+            begin_line_number: usize,
+            proofs: Vec<(usize, Serialized)>,
         }
 
         impl default::Default for State {
@@ -285,6 +521,8 @@ impl Serialized {
                     body: Default::default(),
                     signature: Default::default(),
                     type_: ProofType::Trust, // whatever
+                    line_number: 0,
+                    begin_line_number: 0,
                     proofs: vec![],
                 }
             }
@@ -292,9 +530,11 @@ impl Serialized {
 
         impl State {
             fn process_line(&mut self, line: &str) -> Result<()> {
+                self.line_number += 1;
                 match self.stage {
                     Stage::None => {
                         let line = line.trim();
+                        self.begin_line_number = self.line_number;
                         if line.is_empty() {
                         } else if line == ProofType::Code.begin_block() {
                             self.type_ = ProofType::Code;
@@ -305,6 +545,24 @@ impl Serialized {
                         } else if line == ProofType::Package.begin_block() {
                             self.type_ = ProofType::Package;
                             self.stage = Stage::Body;
+                        } else if line == ProofType::Membership.begin_block() {
+                            self.type_ = ProofType::Membership;
+                            self.stage = Stage::Body;
+                        } else if line == ProofType::Issue.begin_block() {
+                            self.type_ = ProofType::Issue;
+                            self.stage = Stage::Body;
+                        } else if line == ProofType::Alternative.begin_block() {
+                            self.type_ = ProofType::Alternative;
+                            self.stage = Stage::Body;
+                        } else if line == ProofType::IdentityClaim.begin_block() {
+                            self.type_ = ProofType::IdentityClaim;
+                            self.stage = Stage::Body;
+                        } else if line == ProofType::Encrypted.begin_block() {
+                            self.type_ = ProofType::Encrypted;
+                            self.stage = Stage::Body;
+                        } else if line == ProofType::Usage.begin_block() {
+                            self.type_ = ProofType::Usage;
+                            self.stage = Stage::Body;
                         } else {
                             bail!("Parsing error when looking for start of code review proof");
                         }
@@ -323,11 +581,14 @@ impl Serialized {
                     Stage::Signature => {
                         if line.trim() == self.type_.end_block() {
                             self.stage = Stage::None;
-                            self.proofs.push(Serialized {
-                                body: mem::replace(&mut self.body, String::new()),
-                                signature: mem::replace(&mut self.signature, String::new()),
-                                type_: self.type_,
-                            });
+                            self.proofs.push((
+                                self.begin_line_number,
+                                Serialized {
+                                    body: mem::replace(&mut self.body, String::new()),
+                                    signature: mem::replace(&mut self.signature, String::new()),
+                                    type_: self.type_,
+                                },
+                            ));
                         } else {
                             self.signature += line;
                             self.signature += "\n";
@@ -340,7 +601,7 @@ impl Serialized {
                 Ok(())
             }
 
-            fn finish(self) -> Result<Vec<Serialized>> {
+            fn finish(self) -> Result<Vec<(usize, Serialized)>> {
                 if self.stage != Stage::None {
                     bail!("Unexpected EOF while parsing");
                 }
@@ -351,10 +612,14 @@ impl Serialized {
         let mut state: State = Default::default();
 
         for line in reader.lines() {
-            state.process_line(&line?)?;
+            let line = line.map_err(|e| (state.line_number + 1, e.into()))?;
+            state
+                .process_line(&line)
+                .map_err(|e| (state.line_number, e))?;
         }
 
-        state.finish()
+        let line_number = state.line_number;
+        state.finish().map_err(|e| (line_number, e))
     }
 }
 
@@ -372,6 +637,22 @@ impl Proof {
         Ok(v)
     }
 
+    /// Like `parse_from`, but each proof's content is parsed separately
+    ///
+    /// A proof that can't be understood (e.g. it was written in a newer
+    /// format version) doesn't fail the others in the same file.
+    pub fn parse_each_from(path: &Path) -> Result<Vec<Result<Self>>> {
+        let file = fs::File::open(path)?;
+        Self::parse_each(io::BufReader::new(file))
+    }
+
+    pub fn parse_each(reader: impl io::BufRead) -> Result<Vec<Result<Self>>> {
+        Ok(Serialized::parse(reader)?
+            .iter()
+            .map(Serialized::to_parsed)
+            .collect())
+    }
+
     pub fn signature(&self) -> &str {
         self.signature.trim()
     }
@@ -382,6 +663,30 @@ impl Proof {
 
         Ok(())
     }
+
+    /// Was the proof written without a signature (`Content::to_unsigned_string`)
+    pub fn is_unsigned(&self) -> bool {
+        self.signature().is_empty()
+    }
+
+    /// Signature of the body by `id`, eg. made on a machine the proof was
+    /// moved to unsigned
+    pub fn detached_signature_by(&self, id: &crate::id::OwnId) -> Result<String> {
+        if id.id.id != self.content.author_id() {
+            bail!("The proof is from another Id: {}", self.content.author_id());
+        }
+        Ok(crev_common::base64_encode(&id.sign(self.body.as_bytes())))
+    }
+
+    /// The proof with a `signature` made elsewhere, if it's valid
+    pub fn with_signature(self, signature: &str) -> Result<Self> {
+        let proof = Proof {
+            signature: signature.trim().to_owned(),
+            ..self
+        };
+        proof.verify()?;
+        Ok(proof)
+    }
 }
 
 fn equals_default_digest_type(s: &str) -> bool {
@@ -389,7 +694,7 @@ fn equals_default_digest_type(s: &str) -> bool {
 }
 
 pub fn default_digest_type() -> String {
-    "blake2b".into()
+    crate::DigestType::default().to_string()
 }
 
 fn equals_default_revision_type(s: &str) -> bool {
@@ -26,4 +26,21 @@ pub struct PackageInfo {
         default = "proof::default_digest_type"
     )]
     pub digest_type: String,
+    /// Paths excluded when calculating `digest`, in addition to the defaults
+    #[serde(
+        rename = "digest-ignore",
+        skip_serializing_if = "Vec::is_empty",
+        default = "Default::default"
+    )]
+    #[builder(default = "Default::default()")]
+    pub digest_ignore: Vec<String>,
+    /// SHA-256 of the package archive, as recorded by the registry
+    /// (crates.io) at review time
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default = "Default::default()")]
+    pub checksum: Option<String>,
+    /// Source repository declared in the package manifest at review time
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default = "Default::default()")]
+    pub repository: Option<String>,
 }
@@ -10,7 +10,7 @@ const BEGIN_BLOCK: &str = "-----BEGIN CODE REVIEW-----";
 const BEGIN_SIGNATURE: &str = "-----BEGIN CODE REVIEW SIGNATURE-----";
 const END_BLOCK: &str = "-----END CODE REVIEW-----";
 
-const CURRENT_CODE_REVIEW_PROOF_SERIALIZATION_VERSION: i64 = -1;
+const CURRENT_CODE_REVIEW_PROOF_SERIALIZATION_VERSION: i64 = 0;
 
 fn cur_version() -> i64 {
     CURRENT_CODE_REVIEW_PROOF_SERIALIZATION_VERSION
@@ -99,6 +99,18 @@ impl proof::ContentCommon for Code {
         &self.from
     }
 
+    fn version(&self) -> i64 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: i64) {
+        self.version = version;
+    }
+
+    fn current_version(&self) -> i64 {
+        cur_version()
+    }
+
     fn draft_title(&self) -> String {
         format!(
             "Code Review of {} files of {} {}",
@@ -117,7 +129,9 @@ impl super::Common for Code {
 
 impl Code {
     pub fn parse(s: &str) -> Result<Self> {
-        Ok(serde_yaml::from_str(&s)?)
+        let proof: Self = serde_yaml::from_str(&s)?;
+        proof::check_version(proof.version, cur_version())?;
+        Ok(proof)
     }
 
     pub fn sign_by(self, id: &id::OwnId) -> Result<proof::Proof> {
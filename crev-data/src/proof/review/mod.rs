@@ -35,6 +35,11 @@ pub struct Review {
     pub understanding: Level,
     #[builder(default = "Default::default()")]
     pub rating: Rating,
+    /// Properties the reviewer asserts (eg. `safe-to-deploy`,
+    /// `crypto-reviewed`, `no-unsafe`); see `verify deps --criteria`
+    #[serde(skip_serializing_if = "Vec::is_empty", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub criteria: Vec<String>,
 }
 
 impl Default for Review {
@@ -43,10 +48,66 @@ impl Default for Review {
             thoroughness: Level::Low,
             understanding: Level::Medium,
             rating: Rating::Positive,
+            criteria: vec![],
         }
     }
 }
 
+/// Versions affected by an `Advisory`
+///
+/// Relative to the reviewed version, which is the one fixing the problem.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AdvisoryRange {
+    /// All earlier versions
+    All,
+    /// Earlier versions with the same major version
+    Major,
+    /// Earlier versions with the same major and minor version
+    Minor,
+}
+
+impl Default for AdvisoryRange {
+    fn default() -> Self {
+        AdvisoryRange::All
+    }
+}
+
+/// Warning about a problem (eg. a security vulnerability) in earlier versions
+///
+/// Attached to the review of the version that fixes the problem.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct Advisory {
+    /// Ids of the problem in other databases (eg. `RUSTSEC-2019-0001`, `CVE-2019-1234`)
+    #[serde(skip_serializing_if = "Vec::is_empty", default = "Default::default")]
+    pub ids: Vec<String>,
+    #[serde(default = "Default::default")]
+    pub severity: Level,
+    #[serde(default = "Default::default")]
+    pub range: AdvisoryRange,
+}
+
+/// Amount of `unsafe` Rust code observed at the time of a review
+///
+/// Allows comparing with later versions, to see how much of it was added.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct UnsafeCount {
+    /// `unsafe fn` items
+    pub functions: u64,
+    /// `unsafe { ... }` blocks
+    pub blocks: u64,
+    /// `unsafe impl` items
+    pub impls: u64,
+    /// `unsafe trait` items
+    pub traits: u64,
+}
+
+impl UnsafeCount {
+    pub fn total(&self) -> u64 {
+        self.functions + self.blocks + self.impls + self.traits
+    }
+}
+
 impl Review {
     pub fn new_positive() -> Self {
         Default::default()
@@ -56,6 +117,7 @@ impl Review {
             thoroughness: Level::Low,
             understanding: Level::Medium,
             rating: Rating::Negative,
+            criteria: vec![],
         }
     }
 }
@@ -11,7 +11,7 @@ const BEGIN_BLOCK: &str = "-----BEGIN CREV PACKAGE REVIEW-----";
 const BEGIN_SIGNATURE: &str = "-----BEGIN CREV PACKAGE REVIEW SIGNATURE-----";
 const END_BLOCK: &str = "-----END CREV PACKAGE REVIEW-----";
 
-const CURRENT_PACKAGE_REVIEW_PROOF_SERIALIZATION_VERSION: i64 = -1;
+const CURRENT_PACKAGE_REVIEW_PROOF_SERIALIZATION_VERSION: i64 = 0;
 
 fn cur_version() -> i64 {
     CURRENT_PACKAGE_REVIEW_PROOF_SERIALIZATION_VERSION
@@ -34,15 +34,36 @@ pub struct Package {
     pub package: proof::PackageInfo,
     #[builder(default = "Default::default()")]
     review: super::Review,
+    #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub advisory: Option<super::Advisory>,
+    #[serde(
+        rename = "unsafe-count",
+        skip_serializing_if = "Option::is_none",
+        default = "Default::default"
+    )]
+    #[builder(default = "Default::default()")]
+    pub unsafe_count: Option<super::UnsafeCount>,
+    /// Other versions the review applies to as well, as a semver requirement
+    /// (eg. `>=1.2.0, <1.3.0` if they didn't change since 1.2.0)
+    #[serde(
+        rename = "applies-to",
+        skip_serializing_if = "Option::is_none",
+        default = "Default::default"
+    )]
+    #[builder(default = "Default::default()")]
+    pub applies_to: Option<String>,
     #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
     #[builder(default = "Default::default()")]
-    comment: String,
+    pub comment: String,
 }
 
 impl Package {
     pub fn apply_draft(&self, draft: PackageDraft) -> Package {
         let mut copy = self.clone();
         copy.review = draft.review;
+        copy.advisory = draft.advisory;
+        copy.applies_to = draft.applies_to;
         copy.comment = draft.comment;
         copy
     }
@@ -52,6 +73,14 @@ impl Package {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PackageDraft {
     review: super::Review,
+    #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
+    advisory: Option<super::Advisory>,
+    #[serde(
+        rename = "applies-to",
+        skip_serializing_if = "Option::is_none",
+        default = "Default::default"
+    )]
+    applies_to: Option<String>,
     #[serde(default = "Default::default")]
     comment: String,
 }
@@ -60,6 +89,8 @@ impl From<Package> for PackageDraft {
     fn from(package: Package) -> Self {
         PackageDraft {
             review: package.review,
+            advisory: package.advisory,
+            applies_to: package.applies_to,
             comment: package.comment,
         }
     }
@@ -80,6 +111,18 @@ impl proof::ContentCommon for Package {
         &self.from
     }
 
+    fn version(&self) -> i64 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: i64) {
+        self.version = version;
+    }
+
+    fn current_version(&self) -> i64 {
+        cur_version()
+    }
+
     fn draft_title(&self) -> String {
         format!(
             "Package Review of {} {}",
@@ -96,7 +139,9 @@ impl super::Common for Package {
 
 impl Package {
     pub fn parse(s: &str) -> Result<Self> {
-        Ok(serde_yaml::from_str(&s)?)
+        let proof: Self = serde_yaml::from_str(&s)?;
+        proof::check_version(proof.version, cur_version())?;
+        Ok(proof)
     }
 
     pub fn sign_by(self, id: &id::OwnId) -> Result<proof::Proof> {
@@ -0,0 +1,497 @@
+//! Strict proof parsing
+//!
+//! Normal parsing is lenient: it stops at the first problem and doesn't
+//! say where it is. Strict parsing validates every proof against the schema
+//! of its type and reports all problems with their line and column.
+
+use super::{Proof, ProofType, Serialized};
+use crate::Result;
+use std::{fmt, io};
+use yaml_rust::{
+    parser::{MarkedEventReceiver, Parser},
+    scanner::Marker,
+    Event,
+};
+
+/// A problem found in a proof file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    /// Line number, starting at 1
+    pub line: usize,
+    /// Column number, starting at 1
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Shape {
+    Scalar,
+    Scalars,
+    Map(&'static [Field]),
+    List(&'static [Field]),
+}
+
+struct Field {
+    name: &'static str,
+    required: bool,
+    shape: Shape,
+}
+
+const fn required(name: &'static str, shape: Shape) -> Field {
+    Field {
+        name,
+        required: true,
+        shape,
+    }
+}
+
+const fn optional(name: &'static str, shape: Shape) -> Field {
+    Field {
+        name,
+        required: false,
+        shape,
+    }
+}
+
+use self::Shape::*;
+
+const PUB_ID: &[Field] = &[
+    required("id-type", Scalar),
+    required("id", Scalar),
+    required("url", Scalar),
+    optional("url-type", Scalar),
+];
+
+const PACKAGE_INFO: &[Field] = &[
+    optional("id-type", Scalar),
+    optional("id", Scalar),
+    optional("url", Scalar),
+    optional("url-type", Scalar),
+    required("source", Scalar),
+    required("name", Scalar),
+    required("version", Scalar),
+    optional("revision", Scalar),
+    optional("revision-type", Scalar),
+    required("digest", Scalar),
+    optional("digest_type", Scalar),
+    optional("digest-ignore", Scalars),
+    optional("checksum", Scalar),
+    optional("repository", Scalar),
+];
+
+const REVIEW: &[Field] = &[
+    required("thoroughness", Scalar),
+    required("understanding", Scalar),
+    required("rating", Scalar),
+    optional("criteria", Scalars),
+];
+
+const ADVISORY: &[Field] = &[
+    optional("ids", Scalars),
+    optional("severity", Scalar),
+    optional("range", Scalar),
+];
+
+const UNSAFE_COUNT: &[Field] = &[
+    required("functions", Scalar),
+    required("blocks", Scalar),
+    required("impls", Scalar),
+    required("traits", Scalar),
+];
+
+const CODE_FILE: &[Field] = &[
+    required("path", Scalar),
+    required("digest", Scalar),
+    optional("digest-type", Scalar),
+];
+
+const TRUST: &[Field] = &[
+    required("version", Scalar),
+    required("date", Scalar),
+    required("from", Map(PUB_ID)),
+    required("ids", List(PUB_ID)),
+    required("trust", Scalar),
+    optional("comment", Scalar),
+];
+
+const MEMBERSHIP: &[Field] = &[
+    required("version", Scalar),
+    required("date", Scalar),
+    required("from", Map(PUB_ID)),
+    required("members", List(PUB_ID)),
+    required("status", Scalar),
+    optional("comment", Scalar),
+];
+
+const ISSUE: &[Field] = &[
+    required("version", Scalar),
+    required("date", Scalar),
+    required("from", Map(PUB_ID)),
+    required("source", Scalar),
+    required("name", Scalar),
+    required("versions", Scalar),
+    optional("ids", Scalars),
+    optional("severity", Scalar),
+    optional("comment", Scalar),
+];
+
+const ALTERNATIVE: &[Field] = &[
+    required("version", Scalar),
+    required("date", Scalar),
+    required("from", Map(PUB_ID)),
+    required("source", Scalar),
+    required("name", Scalar),
+    required("alternative", Scalar),
+    optional("comment", Scalar),
+];
+
+const ACCOUNT: &[Field] = &[
+    required("service", Scalar),
+    required("name", Scalar),
+    required("proof-url", Scalar),
+];
+
+const IDENTITY_CLAIM: &[Field] = &[
+    required("version", Scalar),
+    required("date", Scalar),
+    required("from", Map(PUB_ID)),
+    required("accounts", List(ACCOUNT)),
+    optional("comment", Scalar),
+];
+
+const RECIPIENT: &[Field] = &[
+    required("id-type", Scalar),
+    required("id", Scalar),
+    required("ephemeral-key", Scalar),
+    required("sealed-key", Scalar),
+];
+
+const ENCRYPTED: &[Field] = &[
+    required("version", Scalar),
+    required("date", Scalar),
+    required("from", Map(PUB_ID)),
+    required("recipients", List(RECIPIENT)),
+    required("ciphertext", Scalar),
+];
+
+const USAGE: &[Field] = &[
+    required("version", Scalar),
+    required("date", Scalar),
+    required("from", Map(PUB_ID)),
+    required("source", Scalar),
+    required("name", Scalar),
+    required("package-version", Scalar),
+    optional("comment", Scalar),
+];
+
+const PACKAGE_REVIEW: &[Field] = &[
+    required("version", Scalar),
+    required("date", Scalar),
+    required("from", Map(PUB_ID)),
+    required("package", Map(PACKAGE_INFO)),
+    required("review", Map(REVIEW)),
+    optional("advisory", Map(ADVISORY)),
+    optional("unsafe-count", Map(UNSAFE_COUNT)),
+    optional("applies-to", Scalar),
+    optional("comment", Scalar),
+];
+
+const CODE_REVIEW: &[Field] = &[
+    required("version", Scalar),
+    required("date", Scalar),
+    required("from", Map(PUB_ID)),
+    required("package", Map(PACKAGE_INFO)),
+    required("thoroughness", Scalar),
+    required("understanding", Scalar),
+    required("rating", Scalar),
+    optional("criteria", Scalars),
+    optional("comment", Scalar),
+    optional("files", List(CODE_FILE)),
+];
+
+fn schema_for(type_: ProofType) -> &'static [Field] {
+    match type_ {
+        ProofType::Code => CODE_REVIEW,
+        ProofType::Package => PACKAGE_REVIEW,
+        ProofType::Trust => TRUST,
+        ProofType::Membership => MEMBERSHIP,
+        ProofType::Issue => ISSUE,
+        ProofType::Alternative => ALTERNATIVE,
+        ProofType::IdentityClaim => IDENTITY_CLAIM,
+        ProofType::Encrypted => ENCRYPTED,
+        ProofType::Usage => USAGE,
+    }
+}
+
+/// A YAML node along with its position (relative to the proof body)
+struct Node {
+    line: usize,
+    column: usize,
+    value: Value,
+}
+
+enum Value {
+    Scalar(String),
+    List(Vec<Node>),
+    Map(Vec<(Node, Node)>),
+    Alias,
+}
+
+enum Frame {
+    List(Node),
+    Map(Node, Option<Node>),
+}
+
+/// Builds a tree of `Node`s from `yaml_rust` parser events
+#[derive(Default)]
+struct TreeBuilder {
+    stack: Vec<Frame>,
+    root: Option<Node>,
+}
+
+impl TreeBuilder {
+    fn push_node(&mut self, node: Node) {
+        match self.stack.last_mut() {
+            None => self.root = Some(node),
+            Some(Frame::List(list)) => {
+                if let Value::List(ref mut items) = list.value {
+                    items.push(node);
+                }
+            }
+            Some(Frame::Map(map, key)) => match key.take() {
+                None => *key = Some(node),
+                Some(k) => {
+                    if let Value::Map(ref mut entries) = map.value {
+                        entries.push((k, node));
+                    }
+                }
+            },
+        }
+    }
+}
+
+impl MarkedEventReceiver for TreeBuilder {
+    fn on_event(&mut self, ev: Event, mark: Marker) {
+        let node = |value| Node {
+            line: mark.line(),
+            column: mark.col() + 1,
+            value,
+        };
+        match ev {
+            Event::Scalar(s, ..) => self.push_node(node(Value::Scalar(s))),
+            Event::Alias(_) => self.push_node(node(Value::Alias)),
+            Event::SequenceStart(_) => self.stack.push(Frame::List(node(Value::List(vec![])))),
+            Event::MappingStart(_) => self.stack.push(Frame::Map(node(Value::Map(vec![])), None)),
+            Event::SequenceEnd | Event::MappingEnd => {
+                let node = match self.stack.pop() {
+                    Some(Frame::List(node)) | Some(Frame::Map(node, _)) => node,
+                    None => return,
+                };
+                self.push_node(node);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Strips the position suffix that `yaml_rust` and `serde_yaml` add to messages
+fn strip_position(message: &str) -> String {
+    match message.find(" at line ") {
+        Some(i) => message[..i].to_owned(),
+        None => message.to_owned(),
+    }
+}
+
+/// Checks proofs strictly, collecting all issues
+struct Linter {
+    issues: Vec<Issue>,
+    /// Line number of the first line of the proof body
+    body_line: usize,
+}
+
+impl Linter {
+    fn issue(&mut self, line: usize, column: usize, message: String) {
+        self.issues.push(Issue {
+            line: self.body_line + line - 1,
+            column,
+            message,
+        });
+    }
+
+    fn check_shape(&mut self, node: &Node, shape: Shape, name: &str) {
+        match (shape, &node.value) {
+            (_, Value::Alias) => self.issue(
+                node.line,
+                node.column,
+                "aliases are not allowed in proofs".into(),
+            ),
+            (Shape::Scalar, Value::Scalar(_)) => {}
+            (Shape::Scalars, Value::List(items)) => {
+                for item in items {
+                    self.check_shape(item, Shape::Scalar, name);
+                }
+            }
+            (Shape::Map(fields), Value::Map(_)) => self.check_map(node, fields),
+            (Shape::List(fields), Value::List(items)) => {
+                for item in items {
+                    self.check_shape(item, Shape::Map(fields), name);
+                }
+            }
+            (Shape::Scalar, _) => self.issue(
+                node.line,
+                node.column,
+                format!("`{}` should be a single value", name),
+            ),
+            (Shape::Map(_), _) => self.issue(
+                node.line,
+                node.column,
+                format!("`{}` should be a mapping", name),
+            ),
+            (Shape::List(_), _) | (Shape::Scalars, _) => self.issue(
+                node.line,
+                node.column,
+                format!("`{}` should be a list", name),
+            ),
+        }
+    }
+
+    fn check_map(&mut self, node: &Node, fields: &'static [Field]) {
+        let entries = match node.value {
+            Value::Map(ref entries) => entries,
+            _ => return,
+        };
+
+        let mut seen = vec![];
+        for (key, value) in entries {
+            let name = match key.value {
+                Value::Scalar(ref name) => name.as_str(),
+                _ => {
+                    self.issue(key.line, key.column, "field name must be a string".into());
+                    continue;
+                }
+            };
+            if seen.contains(&name) {
+                self.issue(key.line, key.column, format!("duplicate field `{}`", name));
+                continue;
+            }
+            seen.push(name);
+
+            match fields.iter().find(|field| field.name == name) {
+                Some(field) => self.check_shape(value, field.shape, name),
+                None => self.issue(key.line, key.column, format!("unknown field `{}`", name)),
+            }
+        }
+
+        for field in fields {
+            if field.required && !seen.contains(&field.name) {
+                self.issue(
+                    node.line,
+                    node.column,
+                    format!("missing field `{}`", field.name),
+                );
+            }
+        }
+    }
+
+    fn check_body(&mut self, serialized: &Serialized) -> Option<Proof> {
+        let mut builder = TreeBuilder::default();
+        if let Err(e) = Parser::new(serialized.body.chars()).load(&mut builder, false) {
+            let marker = e.marker();
+            self.issue(
+                marker.line(),
+                marker.col() + 1,
+                strip_position(&e.to_string()),
+            );
+            return None;
+        }
+
+        let root = match builder.root {
+            Some(root) => root,
+            None => {
+                self.issue(1, 1, "empty proof".into());
+                return None;
+            }
+        };
+
+        let issue_count = self.issues.len();
+        self.check_shape(&root, Shape::Map(schema_for(serialized.type_)), "proof");
+        if self.issues.len() != issue_count {
+            return None;
+        }
+
+        // the shape is right, but values might still be invalid
+        match serialized.to_parsed() {
+            Ok(proof) => Some(proof),
+            Err(e) => {
+                let (line, column) = e
+                    .downcast_ref::<serde_yaml::Error>()
+                    .and_then(|e| e.location())
+                    .map(|location| (location.line(), location.column()))
+                    .unwrap_or((1, 1));
+                self.issue(line, column, strip_position(&e.to_string()));
+                None
+            }
+        }
+    }
+}
+
+/// Strictly check all proofs in `reader`
+///
+/// Returns all proofs that passed, and all issues found.
+pub fn parse(reader: impl io::BufRead) -> (Vec<Proof>, Vec<Issue>) {
+    let mut linter = Linter {
+        issues: vec![],
+        body_line: 1,
+    };
+
+    let serialized = match Serialized::parse_with_lines(reader) {
+        Ok(serialized) => serialized,
+        Err((line, e)) => {
+            linter.issues.push(Issue {
+                line,
+                column: 1,
+                message: e.to_string(),
+            });
+            return (vec![], linter.issues);
+        }
+    };
+
+    let mut proofs = vec![];
+    for (line, serialized) in serialized {
+        // bodies start right after the `BEGIN` line
+        linter.body_line = line + 1;
+        if let Some(proof) = linter.check_body(&serialized) {
+            if let Err(e) = proof.verify() {
+                linter.issues.push(Issue {
+                    line: line + 1 + serialized.body.lines().count(),
+                    column: 1,
+                    message: format!("invalid signature: {}", e),
+                });
+            } else {
+                proofs.push(proof);
+            }
+        }
+    }
+
+    (proofs, linter.issues)
+}
+
+impl Proof {
+    /// Like `parse`, but validating proofs against their schema and signatures
+    ///
+    /// Fails with the position of the first problem. Use `strict::parse`
+    /// to get all of them.
+    pub fn parse_strict(reader: impl io::BufRead) -> Result<Vec<Self>> {
+        let (proofs, issues) = parse(reader);
+        if let Some(issue) = issues.into_iter().next() {
+            bail!("{}", issue);
+        }
+        Ok(proofs)
+    }
+}
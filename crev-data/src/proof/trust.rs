@@ -11,7 +11,7 @@ const BEGIN_BLOCK: &str = "-----BEGIN CREV TRUST -----";
 const BEGIN_SIGNATURE: &str = "-----BEGIN CREV TRUST SIGNATURE-----";
 const END_BLOCK: &str = "-----END CREV TRUST-----";
 
-const CURRENT_TRUST_PROOF_SERIALIZATION_VERSION: i64 = -1;
+const CURRENT_TRUST_PROOF_SERIALIZATION_VERSION: i64 = 0;
 
 fn cur_version() -> i64 {
     CURRENT_TRUST_PROOF_SERIALIZATION_VERSION
@@ -46,8 +46,9 @@ impl fmt::Display for TrustLevel {
     }
 }
 
-impl TrustLevel {
-    #[allow(unused)]
+impl std::str::FromStr for TrustLevel {
+    type Err = failure::Error;
+
     fn from_str(s: &str) -> Result<TrustLevel> {
         Ok(match s {
             "distrust" => TrustLevel::Distrust,
@@ -133,6 +134,18 @@ impl proof::ContentCommon for Trust {
         &self.from
     }
 
+    fn version(&self) -> i64 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: i64) {
+        self.version = version;
+    }
+
+    fn current_version(&self) -> i64 {
+        cur_version()
+    }
+
     fn draft_title(&self) -> String {
         format!("Trust of {} Ids", self.ids.len())
     }
@@ -140,7 +153,9 @@ impl proof::ContentCommon for Trust {
 
 impl Trust {
     pub fn parse(s: &str) -> Result<Self> {
-        Ok(serde_yaml::from_str(&s)?)
+        let proof: Self = serde_yaml::from_str(&s)?;
+        proof::check_version(proof.version, cur_version())?;
+        Ok(proof)
     }
 
     pub fn sign_by(self, id: &id::OwnId) -> Result<proof::Proof> {
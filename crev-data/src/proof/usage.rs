@@ -0,0 +1,127 @@
+use crate::{id, proof, Result};
+use chrono::{self, prelude::*};
+use crev_common::{
+    self,
+    serde::{as_rfc3339_fixed, from_rfc3339_fixed},
+};
+use serde_yaml;
+use std::fmt;
+
+const BEGIN_BLOCK: &str = "-----BEGIN CREV USAGE-----";
+const BEGIN_SIGNATURE: &str = "-----BEGIN CREV USAGE SIGNATURE-----";
+const END_BLOCK: &str = "-----END CREV USAGE-----";
+
+const CURRENT_USAGE_PROOF_SERIALIZATION_VERSION: i64 = 0;
+
+fn cur_version() -> i64 {
+    CURRENT_USAGE_PROOF_SERIALIZATION_VERSION
+}
+
+/// Body of a Usage Proof
+///
+/// States that the author depends on a package version in production.
+/// Unlike a review, it doesn't claim anything about the code.
+#[derive(Clone, Debug, Builder, Serialize, Deserialize)]
+pub struct Usage {
+    #[builder(default = "cur_version()")]
+    version: i64,
+    #[builder(default = "crev_common::now()")]
+    #[serde(
+        serialize_with = "as_rfc3339_fixed",
+        deserialize_with = "from_rfc3339_fixed"
+    )]
+    pub date: chrono::DateTime<FixedOffset>,
+    pub from: crate::PubId,
+    pub source: String,
+    pub name: String,
+    #[serde(rename = "package-version")]
+    pub package_version: String,
+    #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub comment: String,
+}
+
+impl Usage {
+    pub fn apply_draft(&self, draft: UsageDraft) -> Usage {
+        let mut copy = self.clone();
+        copy.comment = draft.comment;
+        copy
+    }
+}
+
+/// Like `Usage` but serializes for interactive editing
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UsageDraft {
+    #[serde(default = "Default::default")]
+    comment: String,
+}
+
+impl From<Usage> for UsageDraft {
+    fn from(usage: Usage) -> Self {
+        UsageDraft {
+            comment: usage.comment,
+        }
+    }
+}
+
+impl fmt::Display for Usage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crev_common::serde::write_as_headerless_yaml(self, f)
+    }
+}
+
+impl fmt::Display for UsageDraft {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crev_common::serde::write_as_headerless_yaml(self, f)
+    }
+}
+
+impl Usage {
+    pub(crate) const BEGIN_BLOCK: &'static str = BEGIN_BLOCK;
+    pub(crate) const BEGIN_SIGNATURE: &'static str = BEGIN_SIGNATURE;
+    pub(crate) const END_BLOCK: &'static str = END_BLOCK;
+}
+
+impl proof::ContentCommon for Usage {
+    fn date(&self) -> &chrono::DateTime<FixedOffset> {
+        &self.date
+    }
+
+    fn author(&self) -> &crate::PubId {
+        &self.from
+    }
+
+    fn version(&self) -> i64 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: i64) {
+        self.version = version;
+    }
+
+    fn current_version(&self) -> i64 {
+        cur_version()
+    }
+
+    fn draft_title(&self) -> String {
+        format!("Usage of {} {}", self.name, self.package_version)
+    }
+}
+
+impl Usage {
+    pub fn parse(s: &str) -> Result<Self> {
+        let proof: Self = serde_yaml::from_str(s)?;
+        proof::check_version(proof.version, cur_version())?;
+        Ok(proof)
+    }
+
+    pub fn sign_by(self, id: &id::OwnId) -> Result<proof::Proof> {
+        super::Content::from(self).sign_by(id)
+    }
+}
+
+impl UsageDraft {
+    pub fn parse(s: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(s)?)
+    }
+}
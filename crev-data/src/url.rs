@@ -1,3 +1,6 @@
+use crate::Result;
+use std::path::{Component, Path};
+
 #[derive(Clone, Debug, Builder, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Url {
     pub url: String,
@@ -17,6 +20,16 @@ impl Url {
         }
     }
 
+    /// Git repository part of the URL (see `split_subdir`)
+    pub fn repo_url(&self) -> &str {
+        split_fragment(&self.url).0
+    }
+
+    /// Directory of the repository the proofs are in (see `split_subdir`)
+    pub fn subdir(&self) -> Result<Option<&str>> {
+        Ok(split_subdir(&self.url)?.1)
+    }
+
     pub fn digest(&self) -> crate::Digest {
         let digest = crev_common::blake2b256sum(self.url.to_ascii_lowercase().as_bytes());
         crate::Digest::from_vec(digest)
@@ -30,3 +43,32 @@ pub(crate) fn equals_default_url_type(s: &str) -> bool {
 pub(crate) fn default_url_type() -> String {
     "git".into()
 }
+
+fn split_fragment(url: &str) -> (&str, Option<&str>) {
+    match url.find('#') {
+        Some(i) => (&url[..i], Some(&url[i + 1..])),
+        None => (url, None),
+    }
+}
+
+/// Split a proof repository URL into the git repository URL, and the
+/// directory inside it that keeps the proofs, if any
+///
+/// Proofs can be kept in a directory of an existing repository (eg. with
+/// dotfiles) with URLs like `https://github.com/user/dotfiles#crev/proofs`.
+/// URLs come from foreign proofs, so the directory must be a relative path
+/// inside the repository: absolute paths and `..` are rejected.
+pub fn split_subdir(url: &str) -> Result<(&str, Option<&str>)> {
+    let (repo_url, subdir) = split_fragment(url);
+    let subdir = match subdir.map(|subdir| subdir.trim_end_matches('/')) {
+        None | Some("") => return Ok((repo_url, None)),
+        Some(subdir) => subdir,
+    };
+    let is_relative = Path::new(subdir)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !is_relative || subdir.starts_with('/') || subdir.contains('\\') {
+        bail!("Invalid proof directory `{}` in {}", subdir, url);
+    }
+    Ok((repo_url, Some(subdir)))
+}
@@ -0,0 +1,119 @@
+//! Bundles of proofs, to move them where git can't reach
+//!
+//! A bundle is a single text file: a header signed by the Id that made it,
+//! followed by the proofs. Every proof is signed by its author anyway; the
+//! header records a digest of all of them, so that a bundle can't lose or
+//! gain proofs on the way without its signature failing.
+use crate::Result;
+use chrono::{self, prelude::*};
+use crev_common::serde::{as_base64, as_rfc3339_fixed, from_base64, from_rfc3339_fixed};
+use crev_data::{id::OwnId, proof, PubId};
+use serde_yaml;
+use std::fmt;
+
+const BEGIN_BLOCK: &str = "-----BEGIN CREV BUNDLE-----";
+const BEGIN_SIGNATURE: &str = "-----BEGIN CREV BUNDLE SIGNATURE-----";
+const END_BLOCK: &str = "-----END CREV BUNDLE-----";
+
+const CURRENT_BUNDLE_VERSION: i64 = 0;
+
+fn cur_version() -> i64 {
+    CURRENT_BUNDLE_VERSION
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Header {
+    version: i64,
+    #[serde(
+        serialize_with = "as_rfc3339_fixed",
+        deserialize_with = "from_rfc3339_fixed"
+    )]
+    pub date: chrono::DateTime<FixedOffset>,
+    pub from: PubId,
+    #[serde(rename = "proof-count")]
+    pub proof_count: usize,
+    /// Digest of the proofs following the header
+    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
+    digest: Vec<u8>,
+}
+
+impl fmt::Display for Header {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crev_common::serde::write_as_headerless_yaml(self, f)
+    }
+}
+
+/// A verified bundle
+pub struct Bundle {
+    pub header: Header,
+    pub proofs: Vec<proof::Proof>,
+}
+
+/// Bundle of `proofs`, signed by `id`
+pub fn create(id: &OwnId, proofs: &[proof::Proof]) -> Result<String> {
+    let body: String = proofs.iter().map(ToString::to_string).collect();
+    let header = Header {
+        version: cur_version(),
+        date: crev_common::now(),
+        from: id.id.clone(),
+        proof_count: proofs.len(),
+        digest: crev_common::blake2b256sum(body.as_bytes()),
+    }
+    .to_string();
+    let signature = crev_common::base64_encode(&id.sign(header.as_bytes()));
+    Ok(format!(
+        "{}\n{}{}\n{}\n{}\n{}",
+        BEGIN_BLOCK, header, BEGIN_SIGNATURE, signature, END_BLOCK, body
+    ))
+}
+
+impl Bundle {
+    /// Parse a bundle, and verify its signature and proofs
+    pub fn parse(s: &str) -> Result<Self> {
+        let s = s.trim_start();
+        if !s.starts_with(BEGIN_BLOCK) {
+            bail!("Not a crev bundle");
+        }
+        let rest = s[BEGIN_BLOCK.len()..].trim_start_matches(['\r', '\n']);
+        let (header_str, rest) = split_at_line(rest, BEGIN_SIGNATURE)?;
+        let (signature, body) = split_at_line(rest, END_BLOCK)?;
+
+        let header: Header = serde_yaml::from_str(header_str)?;
+        if header.version > cur_version() {
+            bail!("Bundle format version {} is not supported", header.version);
+        }
+        header
+            .from
+            .id
+            .verify_signature(header_str.as_bytes(), signature.trim())
+            .map_err(|e| format_err!("Invalid bundle signature: {}", e))?;
+        if crev_common::blake2b256sum(body.as_bytes()) != header.digest {
+            bail!("Proofs of the bundle were modified after it was signed");
+        }
+
+        let proofs = proof::Proof::parse(body.as_bytes())?;
+        if proofs.len() != header.proof_count {
+            bail!(
+                "Bundle should have {} proofs, but has {}",
+                header.proof_count,
+                proofs.len()
+            );
+        }
+        for proof in &proofs {
+            proof.verify()?;
+        }
+        Ok(Self { header, proofs })
+    }
+}
+
+/// Split `s` around the first line equal to `marker`
+fn split_at_line<'a>(s: &'a str, marker: &str) -> Result<(&'a str, &'a str)> {
+    let mut pos = 0;
+    for line in s.split_inclusive('\n') {
+        if line.trim_end() == marker {
+            return Ok((&s[..pos], &s[pos + line.len()..]));
+        }
+        pos += line.len();
+    }
+    bail!("Truncated bundle: {} not found", marker)
+}
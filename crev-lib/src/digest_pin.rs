@@ -0,0 +1,148 @@
+//! Digests of package versions seen on this machine
+//!
+//! A published version of a crate never changes, so neither should its
+//! digest. The first digest calculated for every version is pinned, and
+//! a different one later means the sources were modified: in the local
+//! registry cache, or by re-publishing upstream.
+use crate::{util, DigestOptions, Result, SymlinkPolicy};
+use chrono::{self, prelude::*};
+use crev_common::serde::{as_rfc3339_fixed, from_rfc3339_fixed};
+use serde_yaml;
+use std::{collections::BTreeMap, path::Path};
+
+/// Name of the file keeping pinned digests, in the user data directory
+pub const DIGEST_PINS_FILE_NAME: &str = "digest-pins.yaml";
+
+/// Settings a digest was calculated with
+///
+/// Digests calculated with other settings differ anyway, so they
+/// are pinned separately.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PinSettings {
+    /// `digest-ignore` of the config, sorted
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub ignore: Vec<String>,
+    /// As in `digest-symlinks` of the config
+    pub symlinks: String,
+    #[serde(rename = "empty-dirs")]
+    pub empty_dirs: bool,
+    #[serde(rename = "executable-bit")]
+    pub executable_bit: bool,
+}
+
+impl PinSettings {
+    pub fn new(ignore: &[String], options: &DigestOptions) -> Self {
+        let mut ignore = ignore.to_vec();
+        ignore.sort();
+        let symlinks = match options.symlinks {
+            SymlinkPolicy::Target => "target",
+            SymlinkPolicy::Follow => "follow",
+            SymlinkPolicy::Skip => "skip",
+            SymlinkPolicy::Reject => "reject",
+        };
+        PinSettings {
+            ignore,
+            symlinks: symlinks.to_owned(),
+            empty_dirs: options.empty_dirs,
+            executable_bit: options.executable_bit,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Pin {
+    pub digest: String,
+    pub settings: PinSettings,
+    #[serde(
+        serialize_with = "as_rfc3339_fixed",
+        deserialize_with = "from_rfc3339_fixed"
+    )]
+    pub date: chrono::DateTime<FixedOffset>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DigestPins {
+    /// Pins by source (eg. a git repository at a commit), crate name and version
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pins: BTreeMap<String, BTreeMap<String, BTreeMap<String, Vec<Pin>>>>,
+    #[serde(skip)]
+    changed: bool,
+}
+
+impl DigestPins {
+    /// Load pins from `path`; a missing file means no pins
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        serde_yaml::from_str(&util::read_file_to_string(path)?)
+            .map_err(|e| format_err!("Can't parse {}: {}", path.display(), e))
+    }
+
+    pub fn store(&self, path: &Path) -> Result<()> {
+        util::store_str_to_file(path, &serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Were any pins added or replaced since loading
+    pub fn is_changed(&self) -> bool {
+        self.changed
+    }
+
+    fn pins_mut(&mut self, source: &str, name: &str, version: &str) -> &mut Vec<Pin> {
+        self.pins
+            .entry(source.to_owned())
+            .or_default()
+            .entry(name.to_owned())
+            .or_default()
+            .entry(version.to_owned())
+            .or_default()
+    }
+
+    /// Check `digest` of a crate version from `source` against the pinned one
+    ///
+    /// Pins the digest if it's the first one seen (with these `settings`).
+    /// Returns the pin if the digest differs from it; the pin stays.
+    pub fn check(
+        &mut self,
+        source: &str,
+        name: &str,
+        version: &str,
+        digest: &str,
+        settings: &PinSettings,
+    ) -> Option<Pin> {
+        let pins = self.pins_mut(source, name, version);
+        match pins.iter().find(|pin| pin.settings == *settings) {
+            Some(pin) if pin.digest == digest => None,
+            Some(pin) => Some(pin.clone()),
+            None => {
+                pins.push(Pin {
+                    digest: digest.to_owned(),
+                    settings: settings.clone(),
+                    date: crev_common::now(),
+                });
+                self.changed = true;
+                None
+            }
+        }
+    }
+
+    /// Pin `digest` of a crate version, replacing the pin it had
+    pub fn repin(
+        &mut self,
+        source: &str,
+        name: &str,
+        version: &str,
+        digest: &str,
+        settings: &PinSettings,
+    ) {
+        let pins = self.pins_mut(source, name, version);
+        pins.retain(|pin| pin.settings != *settings);
+        pins.push(Pin {
+            digest: digest.to_owned(),
+            settings: settings.clone(),
+            date: crev_common::now(),
+        });
+        self.changed = true;
+    }
+}
@@ -0,0 +1,79 @@
+//! Reporting progress of fetching proof repositories, and cancelling it
+//!
+//! Fetching can take long, so frontends can run it in a background thread
+//! with their own `FetchObserver`, eg. `Local::fetch_trusted_with`.
+use crev_data::Id;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Something that happened while fetching
+pub enum FetchEvent<'a> {
+    /// Fetching a proof repository started
+    Started { url: &'a str },
+    /// Objects of a proof repository were received, out of `total_objects`
+    ///
+    /// Not reported when updating shallow clones with `git` binary.
+    Progress {
+        url: &'a str,
+        received_objects: usize,
+        total_objects: usize,
+    },
+    /// Fetching a proof repository is done; `error` if it failed
+    Finished {
+        url: &'a str,
+        error: Option<&'a failure::Error>,
+    },
+    /// There's no known proof repository for an Id that was to be fetched
+    NoUrl { id: &'a Id },
+}
+
+/// Receives `FetchEvent`s, and can cancel fetching
+pub trait FetchObserver {
+    fn on_event(&self, event: &FetchEvent<'_>);
+
+    /// Checked before fetching each proof repository
+    ///
+    /// Once it returns `true`, fetching stops with an error.
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+/// Logs problems, like the command line tools do
+pub struct StderrFetchObserver;
+
+impl FetchObserver for StderrFetchObserver {
+    fn on_event(&self, event: &FetchEvent<'_>) {
+        match event {
+            FetchEvent::Started { .. } | FetchEvent::Progress { .. } => {}
+            FetchEvent::Finished { url, error } => match error {
+                Some(e) => error!("Couldn't fetch {}: {}", url, e),
+                None => debug!("Fetched {}", url),
+            },
+            FetchEvent::NoUrl { id } => warn!("No URL for {}", id),
+        }
+    }
+}
+
+/// Flag to cancel fetching from another thread
+///
+/// Clones share the flag, so one can be kept by the frontend while
+/// the other is used by the `FetchObserver`.
+#[derive(Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,88 @@
+//! User-configured commands run around proof lifecycle events
+//!
+//! Set in `hooks` of the user config, eg.
+//! `post-proof-created: notify-send "New crev proof: $CREV_PROOF_PATH"`.
+//! Commands are run by the shell, in the own proof repository, with
+//! details of the event in `CREV_*` environment variables.
+use crate::Result;
+use crev_data::proof::{self, Content};
+use std::{path::Path, process};
+
+/// After a proof was created and stored in the own proof repository
+pub const POST_PROOF_CREATED: &str = "post-proof-created";
+/// After fetching proof repositories of other Ids
+pub const POST_FETCH: &str = "post-fetch";
+/// Before pushing the own proof repository; failing aborts the push
+pub const PRE_PUSH: &str = "pre-push";
+/// When `cargo crev watch` sees the verification status of a dependency change
+pub const STATUS_CHANGED: &str = "status-changed";
+
+/// Environment describing a `proof` stored at `path`
+pub fn proof_env(proof: &proof::Proof, path: &Path) -> Vec<(&'static str, String)> {
+    let mut env = vec![
+        ("CREV_PROOF_PATH", path.display().to_string()),
+        (
+            "CREV_PROOF_TYPE",
+            proof_type_name(&proof.content).to_owned(),
+        ),
+        ("CREV_PROOF_AUTHOR", proof.content.author_id().to_string()),
+        ("CREV_PROOF_SIGNATURE", proof.signature.clone()),
+    ];
+    if let Content::Package(review) = &proof.content {
+        env.push(("CREV_PACKAGE_SOURCE", review.package.source.clone()));
+        env.push(("CREV_PACKAGE_NAME", review.package.name.clone()));
+        env.push(("CREV_PACKAGE_VERSION", review.package.version.clone()));
+    }
+    env
+}
+
+/// Name of the type of proof, as in `CREV_PROOF_TYPE`
+pub fn proof_type_name(content: &Content) -> &'static str {
+    match content {
+        Content::Trust(_) => "trust",
+        Content::Membership(_) => "membership",
+        Content::Issue(_) => "issue",
+        Content::Alternative(_) => "alternative",
+        Content::IdentityClaim(_) => "identity-claim",
+        Content::Encrypted(_) => "encrypted",
+        Content::Usage(_) => "usage",
+        Content::Code(_) => "code-review",
+        Content::Package(_) => "package-review",
+    }
+}
+
+#[cfg(unix)]
+fn shell_command(cmd: &str) -> process::Command {
+    let mut command = process::Command::new("sh");
+    command.arg("-c").arg(cmd);
+    command
+}
+
+#[cfg(windows)]
+fn shell_command(cmd: &str) -> process::Command {
+    let mut command = process::Command::new("cmd");
+    command.arg("/C").arg(cmd);
+    command
+}
+
+/// Run `cmd` of the hook called `name` in `dir`
+///
+/// A failing `pre-` hook is an error, so that the operation is aborted;
+/// failures of other hooks are only reported.
+pub fn run(name: &str, cmd: &str, dir: &Path, env: &[(&str, String)]) -> Result<()> {
+    let res = shell_command(cmd)
+        .current_dir(dir)
+        .env("CREV_HOOK", name)
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .status();
+    let problem = match res {
+        Ok(status) if status.success() => return Ok(()),
+        Ok(status) => format!("`{}` hook returned {}", name, status),
+        Err(e) => format!("Could not run `{}` hook: {}", name, e),
+    };
+    if name.starts_with("pre-") {
+        bail!("{}", problem);
+    }
+    warn!("{}", problem);
+    Ok(())
+}
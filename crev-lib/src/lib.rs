@@ -5,18 +5,37 @@ use common_failures::prelude::*;
 
 #[macro_use]
 extern crate failure;
+#[macro_use]
+extern crate log;
 
+pub mod bundle;
+pub mod digest_pin;
+pub mod fetch;
+pub mod hooks;
 pub mod id;
 pub mod local;
+pub mod local_review;
+pub mod lock;
+pub mod note;
+pub mod policy;
 pub mod proof;
+pub mod proof_index;
 pub mod repo;
+pub mod repo_index;
+pub mod secret;
+pub mod ssh_agent;
 pub mod staging;
+pub mod transparency;
+pub mod trust_list;
 pub mod trustdb;
 pub mod util;
+pub mod verification_cache;
 
 pub use self::local::Local;
-use crev_data::Digest;
+pub use self::secret::SecretProvider;
 use crev_data::Id;
+use crev_data::{Digest, DigestType};
+pub use crev_recursive_digest::{DigestOptions, SymlinkPolicy};
 use std::convert::AsRef;
 use std::{
     collections::HashSet,
@@ -35,6 +54,7 @@ pub trait ProofStore {
 /// Result of verification
 ///
 /// Not named `Result` to avoid confusion with `Result` type.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum VerificationStatus {
     Verified,
     Unknown,
@@ -74,15 +94,14 @@ impl fmt::Display for VerificationStatus {
     }
 }
 
-pub fn dir_or_git_repo_verify<H1, H2>(
+pub fn dir_or_git_repo_verify<H1>(
     path: &Path,
     ignore_list: &HashSet<PathBuf, H1>,
     db: &trustdb::TrustDB,
-    trusted_set: &HashSet<Id, H2>,
+    trusted_set: &trustdb::TrustSet,
 ) -> Result<crate::VerificationStatus>
 where
     H1: std::hash::BuildHasher + std::default::Default,
-    H2: std::hash::BuildHasher + std::default::Default,
 {
     let digest = if path.join(".git").exists() {
         get_recursive_digest_for_git_dir(path, ignore_list)?
@@ -96,33 +115,77 @@ where
     Ok(db.verify_digest(&digest, trusted_set))
 }
 
-pub fn dir_verify<H1, H2>(
+pub fn dir_verify<H1>(
     path: &Path,
     ignore_list: &HashSet<PathBuf, H1>,
     db: &trustdb::TrustDB,
-    trusted_set: &HashSet<Id, H2>,
+    trusted_set: &trustdb::TrustSet,
 ) -> Result<crate::VerificationStatus>
 where
     H1: std::hash::BuildHasher + std::default::Default,
-    H2: std::hash::BuildHasher + std::default::Default,
 {
-    let digest = Digest::from_vec(crev_recursive_digest::get_recursive_digest_for_dir::<
-        crev_common::Blake2b256,
-        H1,
-    >(path, ignore_list)?);
-    Ok(db.verify_digest(&digest, trusted_set))
+    let digests = get_dir_digests(path, ignore_list, &DigestOptions::default(), db)?;
+    Ok(db.verify_digests(&digests, trusted_set))
 }
 
 pub fn get_dir_digest<H1>(path: &Path, ignore_list: &HashSet<PathBuf, H1>) -> Result<Digest>
 where
     H1: std::hash::BuildHasher + std::default::Default,
 {
-    Ok(Digest::from_vec(
-        crev_recursive_digest::get_recursive_digest_for_dir::<crev_common::Blake2b256, H1>(
-            path,
-            ignore_list,
-        )?,
-    ))
+    get_dir_digest_of_type(
+        path,
+        ignore_list,
+        DigestType::Blake2b,
+        &DigestOptions::default(),
+    )
+}
+
+pub fn get_dir_digest_of_type<H1>(
+    path: &Path,
+    ignore_list: &HashSet<PathBuf, H1>,
+    digest_type: DigestType,
+    options: &DigestOptions,
+) -> Result<Digest>
+where
+    H1: std::hash::BuildHasher + std::default::Default,
+{
+    Ok(Digest::from_vec(match digest_type {
+        DigestType::Blake2b => crev_recursive_digest::get_recursive_digest_for_dir_with_options::<
+            crev_common::Blake2b256,
+            H1,
+        >(path, ignore_list, options)?,
+        DigestType::Sha256 => crev_recursive_digest::get_recursive_digest_for_dir_with_options::<
+            sha2::Sha256,
+            H1,
+        >(path, ignore_list, options)?,
+    }))
+}
+
+/// Digests of `path` of every type used by reviews in `db`
+///
+/// The default (blake2b) one is always calculated, and comes first.
+pub fn get_dir_digests<H1>(
+    path: &Path,
+    ignore_list: &HashSet<PathBuf, H1>,
+    options: &DigestOptions,
+    db: &trustdb::TrustDB,
+) -> Result<Vec<(DigestType, Digest)>>
+where
+    H1: std::hash::BuildHasher + std::default::Default,
+{
+    let mut digests = vec![(
+        DigestType::Blake2b,
+        get_dir_digest_of_type(path, ignore_list, DigestType::Blake2b, options)?,
+    )];
+    for digest_type in db.get_digest_types() {
+        if digest_type != DigestType::Blake2b {
+            digests.push((
+                digest_type,
+                get_dir_digest_of_type(path, ignore_list, digest_type, options)?,
+            ));
+        }
+    }
+    Ok(digests)
 }
 
 pub fn show_current_id() -> Result<()> {
@@ -195,10 +258,20 @@ where
     ))
 }
 
+/// SSH key to derive a new CrevID from
+pub enum SshKey<'a> {
+    /// Private key file, possibly protected by a passphrase
+    File(&'a Path),
+    /// Key held by `ssh-agent`; a public key file picks one if it holds several
+    Agent(Option<&'a Path>),
+}
+
 pub fn generate_id(
     url: Option<String>,
     github_username: Option<String>,
     use_https_push: bool,
+    ssh_key: Option<SshKey<'_>>,
+    secrets: &dyn SecretProvider,
 ) -> Result<()> {
     let url = match (url, github_username) {
         (Some(url), None) => url,
@@ -214,10 +287,52 @@ pub fn generate_id(
     let local = Local::auto_create_or_open()?;
     local.clone_proof_dir_from_git(&url, use_https_push)?;
 
-    let id = crev_data::id::OwnId::generate(crev_data::Url::new_git(url.clone()));
-    eprintln!("CrevID will be protected by a passphrase.");
-    eprintln!("There's no way to recover your CrevID if you forget your passphrase.");
-    let passphrase = crev_common::read_new_passphrase()?;
+    let read_key = |path: &Path| {
+        util::read_file_to_string(path)
+            .map_err(|e| format_err!("Can't read {}: {}", path.display(), e))
+    };
+    let id = match ssh_key {
+        Some(SshKey::File(path)) => {
+            let key = read_key(path)?;
+            let passphrase = if crev_data::ssh::is_encrypted(&key)? {
+                secrets.read_ssh_key_passphrase(path)?
+            } else {
+                String::new()
+            };
+            let id = crev_data::id::OwnId::from_ssh_key(
+                crev_data::Url::new_git(url.clone()),
+                &key,
+                &passphrase,
+            )?;
+            eprintln!("CrevID will be protected by a passphrase.");
+            eprintln!(
+                "If you forget it, the same CrevID can be created again from {}.",
+                path.display()
+            );
+            id
+        }
+        Some(SshKey::Agent(public_key_path)) => {
+            let public_key = public_key_path.map(read_key).transpose()?;
+            let signature = ssh_agent::sign_ed25519(
+                public_key.as_deref(),
+                crev_data::id::SSH_KEY_SEED_MESSAGE,
+            )?;
+            let id = crev_data::id::OwnId::from_ssh_signature(
+                crev_data::Url::new_git(url.clone()),
+                &signature,
+            )?;
+            eprintln!("CrevID will be protected by a passphrase.");
+            eprintln!("If you forget it, the same CrevID can be created again with the SSH key.");
+            id
+        }
+        None => {
+            let id = crev_data::id::OwnId::generate(crev_data::Url::new_git(url.clone()));
+            eprintln!("CrevID will be protected by a passphrase.");
+            eprintln!("There's no way to recover your CrevID if you forget your passphrase.");
+            id
+        }
+    };
+    let passphrase = secrets.read_new_passphrase()?;
     let locked = id::LockedId::from_own_id(&id, &passphrase)?;
 
     local.save_locked_id(&locked)?;
@@ -1,34 +1,109 @@
 use crate::ProofStore;
 use crate::{
+    fetch::{FetchEvent, FetchObserver, StderrFetchObserver},
+    hooks,
     id::{self, LockedId},
-    trustdb,
+    lock::FileLock,
+    proof_index::{self, ProofIndex},
+    repo_index::{self, RepoIndex},
+    secret::SecretProvider,
+    transparency, trust_list, trustdb,
     util::{self, APP_INFO},
+    verification_cache::{self, VerificationCache},
     Result,
 };
 use app_dirs::{app_root, AppDataType};
 use crev_common;
-use crev_data::{id::OwnId, proof, proof::trust::TrustLevel, Id, PubId, Url};
+use crev_data::{
+    id::OwnId,
+    proof::{self, membership::MembershipStatus, trust::TrustLevel},
+    Id, PubId, Url,
+};
 use default::default;
-use failure::ResultExt;
 use git2;
 use resiter_dpc_tmp::*;
 use serde_yaml;
-use std::cell::RefCell;
+use std::{cell::RefCell, rc::Rc};
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     ffi::OsString,
     fs,
-    io::Write,
+    io::{self, Write},
     path::{Path, PathBuf},
+    process,
 };
 
 const CURRENT_USER_CONFIG_SERIALIZATION_VERSION: i64 = -1;
 
+/// Environment variable overriding where all crev state is kept
+///
+/// Config, data (proofs) and cache go to its `config`, `data` and `cache`
+/// subdirectories, eg. to isolate CI jobs, tests or accounts.
+pub const CREV_HOME_ENV: &str = "CREV_HOME";
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct UserConfig {
     pub version: i64,
     #[serde(rename = "current-id")]
     pub current_id: Option<Id>,
+    /// Fetch proofs of trusted Ids before every verification
+    #[serde(rename = "fetch-before-verify", default)]
+    pub fetch_before_verify: bool,
+    /// Sign commits in own proof repository with the current Id
+    #[serde(rename = "sign-commits", default)]
+    pub sign_commits: bool,
+    /// Commit and push own proofs right after creating them
+    #[serde(rename = "auto-publish", default)]
+    pub auto_publish: bool,
+    /// Paths to exclude when calculating digests of packages (eg. big generated
+    /// or vendored files), in addition to the defaults; recorded in reviews
+    #[serde(rename = "digest-ignore", default)]
+    pub digest_ignore: Vec<String>,
+    /// How to digest symlinks in packages: `target` (default), `follow`,
+    /// `skip` or `reject`
+    #[serde(rename = "digest-symlinks", default)]
+    pub digest_symlinks: Option<String>,
+    /// Leave out directories without any files when calculating digests
+    #[serde(rename = "digest-skip-empty-dirs", default)]
+    pub digest_skip_empty_dirs: bool,
+    /// Include the executable bit of files when calculating digests
+    #[serde(rename = "digest-executable-bit", default)]
+    pub digest_executable_bit: bool,
+    /// Command to open crate sources with (eg. `code`), with the directory
+    /// as the last argument; `$VISUAL`/`$EDITOR` by default
+    #[serde(rename = "open-cmd", default)]
+    pub open_cmd: Option<String>,
+    /// Command to run `open-cmd` in when opening a crate in a sandbox;
+    /// `{dir}` is replaced with the directory of the copied sources
+    #[serde(rename = "sandbox-cmd", default)]
+    pub sandbox_cmd: Option<String>,
+    /// Command to compare an earlier reviewed version of a crate with the
+    /// one being reviewed (eg. `meld`), with both directories as the last
+    /// arguments; a unified diff is printed by default
+    #[serde(rename = "diff-cmd", default)]
+    pub diff_cmd: Option<String>,
+    /// Commands to run around events like creating proofs (see `hooks` module)
+    #[serde(rename = "hooks", default)]
+    pub hooks: BTreeMap<String, String>,
+    /// Ids whose reviews and trust are left out of verification,
+    /// without publishing distrust of them
+    #[serde(rename = "ignored-ids", default)]
+    pub ignored_ids: Vec<String>,
+    /// Other proof repositories for some of own proofs (eg. reviews of
+    /// internal crates go to a company git server); the first matching
+    /// route is used, and proofs matching none go to the Id's repository
+    #[serde(rename = "proof-routes", default)]
+    pub proof_routes: Vec<ProofRoute>,
+    /// Transparency log to anchor own proofs in (eg. `https://rekor.sigstore.dev`)
+    #[serde(rename = "transparency-log", default)]
+    pub transparency_log: Option<String>,
+    /// Public key (PEM) the `transparency-log` signs its tree heads with
+    #[serde(rename = "transparency-log-key", default)]
+    pub transparency_log_key: Option<String>,
+    /// Named sets of trust parameters, eg. to compare verification under
+    /// them with `verify compare`
+    #[serde(rename = "trust-profiles", default)]
+    pub trust_profiles: BTreeMap<String, TrustProfile>,
 }
 
 impl Default for UserConfig {
@@ -36,6 +111,22 @@ impl Default for UserConfig {
         Self {
             version: CURRENT_USER_CONFIG_SERIALIZATION_VERSION,
             current_id: None,
+            fetch_before_verify: false,
+            sign_commits: false,
+            auto_publish: false,
+            digest_ignore: vec![],
+            digest_symlinks: None,
+            digest_skip_empty_dirs: false,
+            digest_executable_bit: false,
+            open_cmd: None,
+            sandbox_cmd: None,
+            diff_cmd: None,
+            hooks: BTreeMap::new(),
+            ignored_ids: vec![],
+            proof_routes: vec![],
+            transparency_log: None,
+            transparency_log_key: None,
+            trust_profiles: BTreeMap::new(),
         }
     }
 }
@@ -46,6 +137,197 @@ impl UserConfig {
             .as_ref()
             .ok_or_else(|| format_err!("Current Id not set"))
     }
+
+    /// `params` with the `ignored-ids` added to the ones ignored already
+    pub fn apply_ignored_ids(
+        &self,
+        mut params: trustdb::TrustDistanceParams,
+    ) -> Result<trustdb::TrustDistanceParams> {
+        for id in &self.ignored_ids {
+            params.ignored_ids.insert(Id::crevid_from_str(id)?);
+        }
+        Ok(params)
+    }
+
+    /// `params` changed by the trust profile `name`
+    ///
+    /// `default` is the name of the profile changing nothing.
+    pub fn apply_trust_profile(
+        &self,
+        name: &str,
+        params: trustdb::TrustDistanceParams,
+    ) -> Result<trustdb::TrustDistanceParams> {
+        if name == "default" {
+            return Ok(params);
+        }
+        match self.trust_profiles.get(name) {
+            Some(profile) => Ok(profile.apply(params)),
+            None => bail!(
+                "Unknown trust profile `{}`; see `trust-profiles` in the config",
+                name
+            ),
+        }
+    }
+
+    /// The transparency log pinned in the config, to anchor proofs in and
+    /// check their anchors with
+    pub fn transparency_log(&self) -> Result<transparency::Log> {
+        match (&self.transparency_log, &self.transparency_log_key) {
+            (Some(url), Some(key)) => transparency::Log::new(url, key),
+            _ => bail!(
+                "No transparency log; set `transparency-log` and `transparency-log-key` in the config"
+            ),
+        }
+    }
+
+    /// Options to calculate digests of packages with
+    ///
+    /// Anything other than the defaults makes digests differ from the ones
+    /// calculated by others, so reviews will match only if their authors
+    /// used the same settings.
+    pub fn digest_options(&self) -> Result<crate::DigestOptions> {
+        let symlinks = match self.digest_symlinks.as_deref() {
+            None | Some("target") => crate::SymlinkPolicy::Target,
+            Some("follow") => crate::SymlinkPolicy::Follow,
+            Some("skip") => crate::SymlinkPolicy::Skip,
+            Some("reject") => crate::SymlinkPolicy::Reject,
+            Some(other) => bail!("Unknown `digest-symlinks` setting: {}", other),
+        };
+        Ok(crate::DigestOptions {
+            symlinks,
+            empty_dirs: !self.digest_skip_empty_dirs,
+            executable_bit: self.digest_executable_bit,
+        })
+    }
+}
+
+/// Trust parameters of `trust-profiles` in the config
+///
+/// Named like the options of `verify`; the ones left out keep their values.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TrustProfile {
+    #[serde(
+        rename = "trust-depth",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub depth: Option<u64>,
+    #[serde(rename = "high-cost", skip_serializing_if = "Option::is_none", default)]
+    pub high_cost: Option<u64>,
+    #[serde(
+        rename = "medium-cost",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub medium_cost: Option<u64>,
+    #[serde(rename = "low-cost", skip_serializing_if = "Option::is_none", default)]
+    pub low_cost: Option<u64>,
+    #[serde(
+        rename = "weakest-link",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub weakest_link: Option<bool>,
+    #[serde(rename = "min-trust", skip_serializing_if = "Option::is_none", default)]
+    pub min_trust: Option<TrustLevel>,
+    /// In days
+    #[serde(
+        rename = "max-review-age",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub max_review_age: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub criteria: Option<Vec<String>>,
+}
+
+impl TrustProfile {
+    pub fn apply(&self, mut params: trustdb::TrustDistanceParams) -> trustdb::TrustDistanceParams {
+        if let Some(depth) = self.depth {
+            params.max_distance = depth;
+        }
+        if let Some(cost) = self.high_cost {
+            params.high_trust_distance = cost;
+        }
+        if let Some(cost) = self.medium_cost {
+            params.medium_trust_distance = cost;
+        }
+        if let Some(cost) = self.low_cost {
+            params.low_trust_distance = cost;
+        }
+        if let Some(weakest_link) = self.weakest_link {
+            params.cap_by_weakest_link = weakest_link;
+        }
+        if let Some(min_trust) = self.min_trust {
+            params.min_trust = min_trust;
+        }
+        if let Some(days) = self.max_review_age {
+            params.max_review_age = Some(chrono::Duration::days(days));
+        }
+        if let Some(criteria) = &self.criteria {
+            params.criteria = criteria.clone();
+        }
+        params
+    }
+}
+
+/// Rule sending own proofs to a proof repository other than the Id's one
+///
+/// Empty lists match anything; proofs not about a package (eg. trust)
+/// never match a route with `packages` or `sources`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProofRoute {
+    /// URL of the proof repository
+    pub url: String,
+    /// Types of proofs, as in `CREV_PROOF_TYPE` of hooks (eg. `package-review`)
+    #[serde(rename = "proof-types", skip_serializing_if = "Vec::is_empty", default)]
+    pub proof_types: Vec<String>,
+    /// Names of packages; a trailing `*` matches any suffix (eg. `acme-*`)
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub packages: Vec<String>,
+    /// Sources of packages (eg. `https://crates.io`)
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub sources: Vec<String>,
+    /// Ids to encrypt the proofs to, so only they (and the author) can
+    /// read them; stored unencrypted if empty
+    #[serde(rename = "encrypt-to", skip_serializing_if = "Vec::is_empty", default)]
+    pub encrypt_to: Vec<String>,
+}
+
+impl ProofRoute {
+    pub fn matches(&self, content: &proof::Content) -> bool {
+        use crev_data::proof::Content;
+
+        if !self.proof_types.is_empty()
+            && !self
+                .proof_types
+                .iter()
+                .any(|type_| type_ == hooks::proof_type_name(content))
+        {
+            return false;
+        }
+        if self.packages.is_empty() && self.sources.is_empty() {
+            return true;
+        }
+        let (source, name) = match content {
+            Content::Package(review) => (&review.package.source, &review.package.name),
+            Content::Code(review) => (&review.package.source, &review.package.name),
+            Content::Issue(issue) => (&issue.source, &issue.name),
+            Content::Alternative(alternative) => (&alternative.source, &alternative.name),
+            Content::Usage(usage) => (&usage.source, &usage.name),
+            _ => return false,
+        };
+        let name_matches = self.packages.is_empty()
+            || self.packages.iter().any(|pattern| {
+                if pattern.ends_with('*') {
+                    name.starts_with(&pattern[..pattern.len() - 1])
+                } else {
+                    name == pattern
+                }
+            });
+        let source_matches = self.sources.is_empty() || self.sources.contains(source);
+        name_matches && source_matches
+    }
 }
 
 #[derive(PartialEq, Debug, Default)]
@@ -81,8 +363,11 @@ pub fn parse_git_url_https(http_url: &str) -> Option<GitUrlComponents> {
     })
 }
 
-fn fetch_and_checkout_git_repo(repo: &git2::Repository) -> Result<()> {
-    repo.find_remote("origin")?.fetch(&["master"], None, None)?;
+fn fetch_and_checkout_git_repo(
+    repo: &git2::Repository,
+    on_progress: &mut dyn FnMut(usize, usize),
+) -> Result<()> {
+    util::git::fetch_origin(repo, "master", on_progress)?;
     repo.set_head("FETCH_HEAD")?;
     let mut opts = git2::build::CheckoutBuilder::new();
     opts.force();
@@ -90,6 +375,151 @@ fn fetch_and_checkout_git_repo(repo: &git2::Repository) -> Result<()> {
     Ok(())
 }
 
+fn is_git_binary_available() -> bool {
+    process::Command::new("git")
+        .arg("--version")
+        .stdout(process::Stdio::null())
+        .stderr(process::Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+fn run_git_in_dir(dir: &Path, args: &[&str]) -> Result<()> {
+    let status = process::Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .status()?;
+
+    if !status.success() {
+        bail!("`git {}` returned {}", args.join(" "), status);
+    }
+    Ok(())
+}
+
+/// Clone a proof repository, downloading only the most recent commit
+///
+/// We never need the history of proof repositories, so there's no
+/// point in downloading it. `libgit2` can't do shallow clones, so
+/// this uses `git` binary, and falls back to a full clone if it's missing.
+fn clone_git_repo_shallow(
+    url: &str,
+    dir: &Path,
+    on_progress: &mut dyn FnMut(usize, usize),
+) -> Result<()> {
+    if !is_git_binary_available() {
+        util::git::clone(url, dir, on_progress)?;
+        return Ok(());
+    }
+
+    let status = process::Command::new("git")
+        .arg("clone")
+        .arg("--quiet")
+        .arg("--depth=1")
+        .arg(url)
+        .arg(dir)
+        .status()?;
+
+    if !status.success() {
+        bail!("`git clone` of {} returned {}", url, status);
+    }
+    Ok(())
+}
+
+/// Fetch only the new commits into a shallow clone and check them out
+fn fetch_and_checkout_git_repo_shallow(dir: &Path) -> Result<()> {
+    run_git_in_dir(dir, &["fetch", "--quiet", "--depth=1", "origin", "master"])?;
+    run_git_in_dir(dir, &["reset", "--quiet", "--hard", "FETCH_HEAD"])?;
+    Ok(())
+}
+
+fn is_shallow_git_repo(dir: &Path) -> bool {
+    dir.join(".git").join("shallow").exists()
+}
+
+/// Fetch the whole history into a shallow clone
+///
+/// It stays a full clone afterwards, as only shallow clones are updated
+/// shallowly (see `fetch_or_clone_git_repo_with`).
+fn unshallow_git_repo(dir: &Path) -> Result<()> {
+    if !is_git_binary_available() {
+        bail!("`git` is needed to fetch the history of a shallow clone");
+    }
+    run_git_in_dir(dir, &["fetch", "--quiet", "--unshallow", "origin"])
+}
+
+/// File in `.git` of a fetched repository with the directory the proofs are in
+fn remote_subdir_marker_path(dir: &Path) -> PathBuf {
+    dir.join(".git").join("crev-subdir")
+}
+
+/// URL of the fetched repository in `dir`, as it was fetched with
+fn remote_git_url(dir: &Path) -> Result<String> {
+    let repo = git2::Repository::open(dir)?;
+    let remote = repo.find_remote("origin")?;
+    let url = remote
+        .url()
+        .ok_or_else(|| format_err!("origin has no url"))?;
+    Ok(match fs::read_to_string(remote_subdir_marker_path(dir)) {
+        Ok(subdir) => format!("{}#{}", url, subdir.trim()),
+        Err(_) => url.to_string(),
+    })
+}
+
+/// Pathspec of the proofs in the `git` repository of an own proof repository URL
+fn pathspec_for_url(url: &Url) -> Result<String> {
+    Ok(url.subdir()?.unwrap_or("*").to_owned())
+}
+
+/// Directory of a fetched repository in `dir` that keeps the proofs
+fn remote_proofs_path(dir: &Path) -> PathBuf {
+    match fs::read_to_string(remote_subdir_marker_path(dir)) {
+        Ok(subdir) => dir.join(subdir.trim()),
+        Err(_) => dir.to_owned(),
+    }
+}
+
+/// Clone git repository at `url` to `dir`, or update it if already there
+pub fn fetch_or_clone_git_repo(url: &str, dir: &Path) -> Result<()> {
+    fetch_or_clone_git_repo_with(url, dir, &mut |_, _| {})
+}
+
+/// Like `fetch_or_clone_git_repo`, passing the number of received
+/// and of all objects to `on_progress`
+///
+/// Shallow clones are only updated with `git` binary; without it they
+/// are replaced by full clones. For URLs with a directory (`repo#subdir`,
+/// see `crev_data::url::split_subdir`) the whole repository is fetched, but
+/// only the proofs in that directory are ever read.
+pub fn fetch_or_clone_git_repo_with(
+    url: &str,
+    dir: &Path,
+    on_progress: &mut dyn FnMut(usize, usize),
+) -> Result<()> {
+    let (url, subdir) = crev_data::url::split_subdir(url)?;
+    if dir.exists() && is_shallow_git_repo(dir) && !is_git_binary_available() {
+        fs::remove_dir_all(dir)?;
+    }
+    if dir.exists() {
+        eprintln!("Fetching {} to {}", url, dir.display());
+        if is_shallow_git_repo(dir) {
+            debug!("Updating shallow clone in {} with `git`", dir.display());
+            fetch_and_checkout_git_repo_shallow(dir)?
+        } else {
+            let repo = git2::Repository::open(dir)?;
+            fetch_and_checkout_git_repo(&repo, on_progress)?
+        }
+    } else {
+        eprintln!("Cloning {} to {}", url, dir.display());
+        clone_git_repo_shallow(url, dir, on_progress)?;
+    }
+    if let Some(subdir) = subdir {
+        fs::write(remote_subdir_marker_path(dir), subdir)?;
+    }
+
+    Ok(())
+}
+
 #[test]
 fn parse_git_url_https_test() {
     assert_eq!(
@@ -159,22 +589,98 @@ fn https_to_git_url_test() {
     );
 }
 
-/// Local config stored in `~/.config/crev`
+/// State of the own proof repository, compared to what was published
+#[derive(Debug, Default)]
+pub struct RepoStatus {
+    /// Checked out branch, if any
+    pub branch: Option<String>,
+    /// Remote branch the current one is pushed to, eg. `origin/master`
+    pub upstream: Option<String>,
+    /// Commits not pushed to the upstream yet
+    pub ahead: usize,
+    /// Commits on the upstream not pulled yet
+    pub behind: usize,
+    /// Proofs created, but not committed yet
+    pub uncommitted_proofs: usize,
+    /// Proofs committed, but not pushed to the upstream yet
+    pub unpushed_proofs: usize,
+    /// Uncommitted changes other than new proofs, eg. to `README.md`
+    pub other_changes: Vec<PathBuf>,
+}
+
+impl RepoStatus {
+    /// Is there anything to commit or push
+    pub fn is_published(&self) -> bool {
+        self.ahead == 0 && self.uncommitted_proofs == 0 && self.other_changes.is_empty()
+    }
+}
+
+/// Number of proofs in the content of a proof file
+fn count_proofs(content: &[u8]) -> usize {
+    proof::Proof::parse(content)
+        .map(|proofs| proofs.len())
+        .unwrap_or(0)
+}
+
+/// Number of proofs in the file at `path` of `tree`
+fn count_proofs_in_tree(
+    repo: &git2::Repository,
+    tree: Option<&git2::Tree<'_>>,
+    path: &Path,
+) -> Result<usize> {
+    let entry = match tree.and_then(|tree| tree.get_path(path).ok()) {
+        Some(entry) => entry,
+        None => return Ok(0),
+    };
+    let blob = entry.to_object(repo)?.peel_to_blob()?;
+    Ok(count_proofs(blob.content()))
+}
+
+/// Local config stored in `~/.config/crev`, proofs in `~/.local/share/crev`
+/// and cache in `~/.cache/crev` (or the platform's equivalents)
+///
+/// See also `CREV_HOME_ENV`.
 pub struct Local {
     root_path: PathBuf,
+    data_path: PathBuf,
     cache_path: PathBuf,
     cur_url: RefCell<Option<Url>>,
+    /// Held while writing to the store or running git in it
+    lock: FileLock,
+    verification_cache: Rc<RefCell<VerificationCache>>,
 }
 
 impl Local {
     #[allow(clippy::new_ret_no_self)]
     fn new() -> Result<Self> {
-        let root_path = app_root(AppDataType::UserConfig, &APP_INFO)?;
-        let cache_path = app_root(AppDataType::UserCache, &APP_INFO)?;
+        let (root_path, data_path, cache_path) = match std::env::var_os(CREV_HOME_ENV) {
+            Some(home) => {
+                let home = PathBuf::from(home);
+                (home.join("config"), home.join("data"), home.join("cache"))
+            }
+            None => {
+                let root_path = app_root(AppDataType::UserConfig, &APP_INFO)?;
+                // proofs used to be kept next to the config
+                let data_path = if root_path.join("proofs").exists() {
+                    root_path.clone()
+                } else {
+                    app_root(AppDataType::UserData, &APP_INFO)?
+                };
+                let cache_path = app_root(AppDataType::UserCache, &APP_INFO)?;
+                (root_path, data_path, cache_path)
+            }
+        };
+        let lock = FileLock::new(root_path.join("crev.lock"));
+        let verification_cache = Rc::new(RefCell::new(VerificationCache::new(
+            &cache_path.join(verification_cache::VERIFICATION_CACHE_FILE_NAME),
+        )));
         Ok(Self {
             root_path,
+            data_path,
             cache_path,
             cur_url: RefCell::new(None),
+            lock,
+            verification_cache,
         })
     }
 
@@ -243,7 +749,7 @@ impl Local {
     }
 
     pub fn user_proofs_path(&self) -> PathBuf {
-        self.root_path.join("proofs")
+        self.data_path.join("proofs")
     }
 
     fn id_path(&self, id: &Id) -> PathBuf {
@@ -273,6 +779,288 @@ impl Local {
         self.cache_path.join("remotes")
     }
 
+    /// Copies of the anchors of fetched repositories, by the name of their
+    /// directory in `cache_remotes_path`
+    fn seen_anchors_path(&self) -> PathBuf {
+        self.data_path.join("anchors")
+    }
+
+    /// Proofs imported from files (`import_proofs`), rather than fetched
+    fn imported_proofs_path(&self) -> PathBuf {
+        self.data_path.join("imported")
+    }
+
+    fn local_reviews_path(&self) -> PathBuf {
+        self.data_path
+            .join(crate::local_review::LOCAL_REVIEWS_FILE_NAME)
+    }
+
+    /// Package versions marked as reviewed on this machine only
+    pub fn load_local_reviews(&self) -> Result<crate::local_review::LocalReviews> {
+        crate::local_review::LocalReviews::load(&self.local_reviews_path())
+    }
+
+    pub fn store_local_reviews(&self, reviews: &crate::local_review::LocalReviews) -> Result<()> {
+        let _lock = self.lock.lock()?;
+        reviews.store(&self.local_reviews_path())
+    }
+
+    fn digest_pins_path(&self) -> PathBuf {
+        self.data_path
+            .join(crate::digest_pin::DIGEST_PINS_FILE_NAME)
+    }
+
+    /// Digests of crate versions seen before
+    pub fn load_digest_pins(&self) -> Result<crate::digest_pin::DigestPins> {
+        crate::digest_pin::DigestPins::load(&self.digest_pins_path())
+    }
+
+    pub fn store_digest_pins(&self, pins: &crate::digest_pin::DigestPins) -> Result<()> {
+        let _lock = self.lock.lock()?;
+        pins.store(&self.digest_pins_path())
+    }
+
+    fn notes_path(&self) -> PathBuf {
+        self.data_path.join(crate::note::NOTES_FILE_NAME)
+    }
+
+    /// Private notes about crates
+    pub fn load_notes(&self) -> Result<crate::note::Notes> {
+        crate::note::Notes::load(&self.notes_path())
+    }
+
+    pub fn store_notes(&self, notes: &crate::note::Notes) -> Result<()> {
+        let _lock = self.lock.lock()?;
+        notes.store(&self.notes_path())
+    }
+
+    /// Proofs of other Ids: fetched, imported and decrypted ones
+    fn foreign_proofs_iter(&self) -> Box<dyn Iterator<Item = proof::Proof>> {
+        self.foreign_proofs_iter_for(None)
+    }
+
+    /// Like `foreign_proofs_iter`, loading only proofs about `crates` (and
+    /// the general ones) from fetched repositories that publish an index
+    fn foreign_proofs_iter_for(
+        &self,
+        crates: Option<&HashSet<String>>,
+    ) -> Box<dyn Iterator<Item = proof::Proof>> {
+        let remote_dirs: Vec<_> = fs::read_dir(self.cache_remotes_path())
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect();
+        let remote_proofs: Vec<_> = remote_dirs
+            .iter()
+            .flat_map(|dir| self.remote_proofs_for(dir, crates))
+            .collect();
+        Box::new(
+            remote_proofs
+                .into_iter()
+                .chain(self.proofs_iter_for_path(self.imported_proofs_path()))
+                .chain(self.proofs_iter_for_path(self.decrypted_proofs_path())),
+        )
+    }
+
+    /// Proofs in all files under `path`, with valid signatures
+    fn proofs_iter_for_path(&self, path: PathBuf) -> Box<dyn Iterator<Item = proof::Proof>> {
+        proofs_iter_for_path(path, self.verification_cache.clone())
+    }
+
+    /// Index of the proofs of the fetched repository in `dir`
+    fn proof_index_path(&self, dir: &Path) -> PathBuf {
+        let name = dir.file_name().unwrap_or_default().to_string_lossy();
+        self.cache_path
+            .join("proof-index")
+            .join(format!("{}.cbor", name))
+    }
+
+    /// Proofs of the fetched repository in `dir`, from its index if it's up to date
+    ///
+    /// The index is updated if the repository is at another commit
+    /// than when it was indexed.
+    fn remote_proofs(&self, dir: &Path) -> Vec<proof::Proof> {
+        self.update_remote_proofs(dir).0
+    }
+
+    /// Proofs of the fetched repository in `dir`, and the ones among them
+    /// that are new since it was indexed last
+    ///
+    /// Only files changed since the indexed commit are read and verified;
+    /// all of them if there's no usable index.
+    fn update_remote_proofs(&self, dir: &Path) -> (Vec<proof::Proof>, Vec<proof::Proof>) {
+        let proofs_dir = remote_proofs_path(dir);
+        let head = match proof_index::git_head(dir) {
+            Some(head) => head,
+            None => {
+                let proofs: Vec<_> = self.proofs_iter_for_path(proofs_dir).collect();
+                return (proofs.clone(), proofs);
+            }
+        };
+        let index_path = self.proof_index_path(dir);
+        let index = ProofIndex::load(&index_path);
+        if let Some(proofs) = index.as_ref().and_then(|index| index.get(&head)) {
+            return (proofs, vec![]);
+        }
+        let changed =
+            index.and_then(
+                |index| match proof_index::changed_files(dir, &index.head, &head) {
+                    Ok(changed) => Some((index, changed)),
+                    Err(e) => {
+                        debug!("Can't diff {} since {}: {}", dir.display(), index.head, e);
+                        None
+                    }
+                },
+            );
+        let (mut index, paths) = match changed {
+            Some((index, changed)) => {
+                debug!(
+                    "Indexing {} files of {} changed since {}",
+                    changed.len(),
+                    dir.display(),
+                    index.head
+                );
+                let paths = changed.iter().map(|path| dir.join(path)).collect();
+                (index, paths)
+            }
+            None => {
+                debug!("Indexing all proofs of {} at {}", dir.display(), head);
+                let paths = proof_index::proof_files(&proofs_dir);
+                (ProofIndex::new(head.clone()), paths)
+            }
+        };
+
+        let mut new_proofs = vec![];
+        for path in paths {
+            let rel_path = match path.strip_prefix(dir) {
+                Ok(rel_path) => rel_path
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                Err(_) => continue,
+            };
+            let is_proof_file = path.starts_with(&proofs_dir)
+                && path.is_file()
+                && path.extension() == Some("crev".as_ref());
+            if is_proof_file {
+                let proofs: Vec<_> = self.proofs_iter_for_path(path).collect();
+                new_proofs.extend(index.set_file(&rel_path, &proofs));
+            } else {
+                index.remove_file(&rel_path);
+            }
+        }
+        debug!("{} new proofs in {}", new_proofs.len(), dir.display());
+        index.head = head;
+        if let Err(e) = index.store(&index_path) {
+            warn!("Couldn't store {}: {}", index_path.display(), e);
+        }
+        (index.proofs().unwrap_or_default(), new_proofs)
+    }
+
+    /// Proofs of the fetched repository in `dir` about `crates`, and the general ones
+    ///
+    /// Uses the index published in the repository to load only the files
+    /// with relevant proofs; without an up to date one (or with `crates`
+    /// being `None`), all proofs are loaded.
+    fn remote_proofs_for(&self, dir: &Path, crates: Option<&HashSet<String>>) -> Vec<proof::Proof> {
+        let crates = match crates {
+            Some(crates) => crates,
+            None => return self.remote_proofs(dir),
+        };
+        let proofs_dir = remote_proofs_path(dir);
+        let index = match RepoIndex::load(&proofs_dir) {
+            Ok(Some(index)) => index,
+            Ok(None) => return self.remote_proofs(dir),
+            Err(e) => {
+                warn!("Ignoring index of {}: {}", dir.display(), e);
+                return self.remote_proofs(dir);
+            }
+        };
+        match index.is_up_to_date(&proofs_dir) {
+            Ok(true) => {}
+            Ok(false) => {
+                info!("Ignoring outdated index of {}", dir.display());
+                return self.remote_proofs(dir);
+            }
+            Err(e) => {
+                warn!("Ignoring index of {}: {}", dir.display(), e);
+                return self.remote_proofs(dir);
+            }
+        }
+        index
+            .files_for(crates)
+            .into_iter()
+            .map(|rel_path| proofs_dir.join(rel_path))
+            .filter(|path| path.is_file())
+            .flat_map(|path| self.proofs_iter_for_path(path))
+            .collect()
+    }
+
+    /// Directories of all proofs loaded into the trust database
+    pub fn proof_paths(&self) -> Result<Vec<PathBuf>> {
+        let mut paths = vec![self.get_proofs_dir_path()?];
+        for route in self.load_user_config()?.proof_routes {
+            let path = self.get_proofs_dir_path_for_url(&Url::new_git(route.url))?;
+            if path.exists() && !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        paths.push(self.cache_remotes_path());
+        paths.push(self.imported_proofs_path());
+        Ok(paths)
+    }
+
+    /// Find a proof by its signature, among own and foreign proofs
+    pub fn find_proof(&self, signature: &str) -> Result<Option<proof::Proof>> {
+        Ok(self
+            .proofs_iter()?
+            .chain(self.foreign_proofs_iter())
+            .find(|proof| proof.signature == signature))
+    }
+
+    /// Import proofs moved around outside of proof repositories
+    ///
+    /// Proofs of the current Id are added to own proof repository, and the
+    /// ones of other Ids are kept aside, next to the config. Proofs that are
+    /// already known are skipped. Returns the number of imported proofs.
+    pub fn import_proofs(&self, proofs: Vec<proof::Proof>) -> Result<usize> {
+        let _lock = self.lock.lock()?;
+        let current_id = self.get_current_userid()?;
+        let mut known: HashSet<String> = self
+            .proofs_iter()?
+            .chain(self.foreign_proofs_iter())
+            .map(|proof| proof.signature)
+            .collect();
+
+        let mut imported = 0;
+        for proof in proofs {
+            proof.verify()?;
+            if !known.insert(proof.signature.clone()) {
+                continue;
+            }
+            let author = proof.content.author_id();
+            if author == current_id {
+                self.insert(&proof)?;
+            } else {
+                let path = self
+                    .imported_proofs_path()
+                    .join(format!("{}.proof.crev", author));
+                fs::create_dir_all(self.imported_proofs_path())?;
+                let mut file = fs::OpenOptions::new()
+                    .append(true)
+                    .create(true)
+                    .open(path)?;
+                file.write_all(proof.to_string().as_bytes())?;
+                file.write_all(b"\n")?;
+            }
+            imported += 1;
+        }
+        Ok(imported)
+    }
+
     pub fn load_user_config(&self) -> Result<UserConfig> {
         let path = self.user_config_path();
 
@@ -282,6 +1070,7 @@ impl Local {
     }
 
     pub fn store_user_config(&self, config: &UserConfig) -> Result<()> {
+        let _lock = self.lock.lock()?;
         let path = self.user_config_path();
 
         let config_str = serde_yaml::to_string(&config)?;
@@ -317,6 +1106,7 @@ impl Local {
     }
 
     pub fn save_locked_id(&self, id: &id::LockedId) -> Result<()> {
+        let _lock = self.lock.lock()?;
         let path = self.id_path(&id.to_pubid().id);
         fs::create_dir_all(&path.parent().expect("Not /"))?;
         id.save_to(&path)
@@ -328,23 +1118,26 @@ impl Local {
         git_https_url: &str,
         use_https_push: bool,
     ) -> Result<()> {
+        let _lock = self.lock.lock()?;
+        let repo_url = crev_data::url::split_subdir(git_https_url)?.0;
         let push_url = if use_https_push {
-            git_https_url.to_string()
+            repo_url.to_string()
         } else {
-            match https_to_git_url(git_https_url) {
+            match https_to_git_url(repo_url) {
                 Some(git_url) => git_url,
                 None => {
                     eprintln!("Could not deduce `ssh` push url. Call:");
                     eprintln!("cargo crev git remote set-url --push origin <url>");
                     eprintln!("manually, after id is generated.");
                     eprintln!("");
-                    git_https_url.to_string()
+                    repo_url.to_string()
                 }
             }
         };
 
         let proof_dir =
-            self.get_proofs_dir_path_for_url(&Url::new_git(git_https_url.to_owned()))?;
+            self.get_proofs_repo_path_for_url(&Url::new_git(git_https_url.to_owned()))?;
+        let (git_https_url, _subdir) = crev_data::url::split_subdir(git_https_url)?;
 
         if proof_dir.exists() {
             eprintln!(
@@ -356,7 +1149,7 @@ impl Local {
 
         self.ensure_proofs_root_exists()?;
 
-        match git2::Repository::clone(git_https_url, &proof_dir) {
+        match util::git::clone(git_https_url, &proof_dir, &mut |_, _| {}) {
             Ok(repo) => {
                 eprintln!("{} cloned to {}", git_https_url, proof_dir.display());
                 repo.remote_set_url("origin", &push_url)?;
@@ -399,31 +1192,169 @@ impl Local {
         Ok(())
     }
 
-    pub fn get_proofs_dir_path_for_url(&self, url: &Url) -> Result<PathBuf> {
+    /// Path of the `git` repository of own proofs at `url`
+    pub fn get_proofs_repo_path_for_url(&self, url: &Url) -> Result<PathBuf> {
         Ok(self.user_proofs_path().join(url.digest().to_string()))
     }
 
+    pub fn get_proofs_dir_path_for_url(&self, url: &Url) -> Result<PathBuf> {
+        let repo_path = self.get_proofs_repo_path_for_url(url)?;
+        Ok(match url.subdir()? {
+            Some(subdir) => repo_path.join(subdir),
+            None => repo_path,
+        })
+    }
+
+    /// Path of the `git` repository of own proofs
+    ///
+    /// Same as `get_proofs_dir_path`, unless the proofs are kept in
+    /// a directory of the repository (`repo#subdir` URL).
+    pub fn get_proofs_repo_path(&self) -> Result<PathBuf> {
+        self.get_proofs_repo_path_for_url(&self.get_cur_url()?)
+    }
+
     // Path where the `proofs` are stored under `git` repository
     pub fn get_proofs_dir_path(&self) -> Result<PathBuf> {
+        self.get_proofs_dir_path_for_url(&self.get_cur_url()?)
+    }
+
+    /// Pathspec of own proofs in their `git` repository
+    fn get_proofs_pathspec(&self) -> Result<String> {
+        pathspec_for_url(&self.get_cur_url()?)
+    }
+
+    /// URLs of all own proof repositories: the Id's one, and the ones
+    /// of `proof-routes` of the user config
+    pub fn get_own_proof_urls(&self) -> Result<Vec<Url>> {
+        let mut urls = vec![self.get_cur_url()?];
+        for route in self.load_user_config()?.proof_routes {
+            let url = Url::new_git(route.url);
+            if !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+        Ok(urls)
+    }
+
+    /// URL of the own proof repository to store `content` in
+    fn get_proof_url(&self, content: &proof::Content) -> Result<Url> {
+        Ok(match self.get_proof_route(content)? {
+            Some(route) => Url::new_git(route.url),
+            None => self.get_cur_url()?,
+        })
+    }
+
+    /// The first of `proof-routes` of the user config matching `content`
+    fn get_proof_route(&self, content: &proof::Content) -> Result<Option<ProofRoute>> {
         Ok(self
-            .root_path
-            .join("proofs")
-            .join(self.get_cur_url()?.digest().to_string()))
+            .load_user_config()?
+            .proof_routes
+            .into_iter()
+            .find(|route| route.matches(content)))
     }
 
-    pub fn build_trust_proof(
-        &self,
-        id_strings: Vec<String>,
-        passphrase: &str,
-        trust_or_distrust: crate::TrustOrDistrust,
-    ) -> Result<()> {
+    /// Store own `proof`, signed by `id`
+    ///
+    /// Proofs of routes with `encrypt-to` Ids are stored encrypted to these
+    /// Ids and `id` (see `proof::Encrypted`).
+    pub fn insert_own(&self, proof: &proof::Proof, id: &OwnId) -> Result<()> {
+        let route = match self.get_proof_route(&proof.content)? {
+            Some(route) => route,
+            None => return self.insert(proof),
+        };
+        if route.encrypt_to.is_empty() {
+            return self.insert(proof);
+        }
+
+        let mut recipients = vec![id.id.id.clone()];
+        for recipient in &route.encrypt_to {
+            let recipient = Id::crevid_from_str(recipient)?;
+            if !recipients.contains(&recipient) {
+                recipients.push(recipient);
+            }
+        }
+        let encrypted =
+            proof::Encrypted::encrypt(proof, id.id.clone(), &recipients)?.sign_by(id)?;
+        self.insert_at(&Url::new_git(route.url), &encrypted)
+    }
+
+    /// Where proofs decrypted by `decrypt_proofs` are kept, never in any proof repository
+    fn decrypted_proofs_path(&self) -> PathBuf {
+        self.data_path.join("decrypted")
+    }
+
+    /// Decrypt all own and fetched proofs encrypted to the current Id
+    ///
+    /// Decrypted proofs are used like fetched ones. Returns the number
+    /// of newly decrypted proofs.
+    pub fn decrypt_proofs(&self, passphrase: &str) -> Result<usize> {
+        let _lock = self.lock.lock()?;
+        let id = self.read_current_unlocked_id(passphrase)?;
+        let mut known: HashSet<String> = self
+            .proofs_iter_for_path(self.decrypted_proofs_path())
+            .map(|proof| proof.signature)
+            .collect();
+
+        let mut decrypted = 0;
+        for proof in self.proofs_iter()?.chain(self.foreign_proofs_iter()) {
+            let encrypted = match proof.content {
+                proof::Content::Encrypted(ref encrypted)
+                    if encrypted.is_encrypted_to(&id.id.id) =>
+                {
+                    encrypted
+                }
+                _ => continue,
+            };
+            if let Err(e) = proof.verify() {
+                warn!("Skipping encrypted proof {}: {}", proof.signature(), e);
+                continue;
+            }
+            let inner = match encrypted.decrypt(&id) {
+                Ok(inner) => inner,
+                Err(e) => {
+                    warn!("Couldn't decrypt proof {}: {}", proof.signature(), e);
+                    continue;
+                }
+            };
+            if !known.insert(inner.signature.clone()) {
+                continue;
+            }
+            let path = self
+                .decrypted_proofs_path()
+                .join(format!("{}.proof.crev", inner.content.author_id()));
+            fs::create_dir_all(self.decrypted_proofs_path())?;
+            let mut file = fs::OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(path)?;
+            file.write_all(inner.to_string().as_bytes())?;
+            file.write_all(b"\n")?;
+            decrypted += 1;
+        }
+        Ok(decrypted)
+    }
+
+    /// Own proof repositories that exist already, with their URLs
+    fn own_proof_repos(&self) -> Result<Vec<(Url, git2::Repository)>> {
+        let mut repos = vec![];
+        for url in self.get_own_proof_urls()? {
+            let path = self.get_proofs_repo_path_for_url(&url)?;
+            if path.exists() {
+                repos.push((url, git2::Repository::open(path)?));
+            }
+        }
+        Ok(repos)
+    }
+
+    /// Find `PubId`s (Ids with their URLs) for given Id strings
+    fn lookup_pub_ids(&self, id_strings: Vec<String>) -> Result<Vec<PubId>> {
         if id_strings.is_empty() {
             bail!("No ids given.");
         }
 
         let mut trustdb = trustdb::TrustDB::new();
-        trustdb.import_from_iter(self.proofs_iter()?);
-        trustdb.import_from_iter(proofs_iter_for_path(self.cache_remotes_path()));
+        trustdb.import_verified_from_iter(self.proofs_iter()?);
+        trustdb.import_verified_from_iter(self.foreign_proofs_iter());
         let mut pub_ids = vec![];
 
         for id_string in id_strings {
@@ -439,45 +1370,249 @@ impl Local {
             }
         }
 
-        let own_id = self.read_current_unlocked_id(&passphrase)?;
-
-        let trust = own_id.create_trust_proof(
-            pub_ids,
-            if trust_or_distrust.is_trust() {
-                TrustLevel::Medium
-            } else {
-                TrustLevel::Distrust
-            },
-        )?;
+        Ok(pub_ids)
+    }
 
-        let trust = util::edit_proof_content_iteractively(&trust.into())?;
+    pub fn build_trust_proof(
+        &self,
+        id_strings: Vec<String>,
+        passphrase: &str,
+        trust_or_distrust: crate::TrustOrDistrust,
+    ) -> Result<()> {
+        let own_id = self.read_current_unlocked_id(&passphrase)?;
+        let trust = self.edit_trust_proof(id_strings, own_id.id.clone(), trust_or_distrust)?;
 
         let proof = trust.sign_by(&own_id)?;
 
-        self.insert(&proof)?;
+        self.insert_own(&proof, &own_id)?;
         Ok(())
     }
 
-    pub fn fetch_url(&self, url: &str) -> Result<()> {
-        let _success = util::err_eprint_and_ignore(self.fetch_remote_git(url).compat());
-        Ok(())
+    /// Like `build_trust_proof`, but without signing (and storing) the proof
+    ///
+    /// No passphrase is needed, so the Id can be kept on another machine
+    /// that signs the proof (see `proof::Proof::detached_signature_by`).
+    pub fn build_unsigned_trust_proof(
+        &self,
+        id_strings: Vec<String>,
+        trust_or_distrust: crate::TrustOrDistrust,
+    ) -> Result<proof::Content> {
+        let from = self.read_current_locked_id()?.to_pubid();
+        self.edit_trust_proof(id_strings, from, trust_or_distrust)
     }
 
-    pub fn fetch_trusted(&self, trust_params: trustdb::TrustDistanceParams) -> Result<()> {
-        let mut already_fetched = HashSet::new();
-        let mut db = trustdb::TrustDB::new();
-        db.import_from_iter(self.proofs_iter()?);
-        db.import_from_iter(proofs_iter_for_path(self.cache_remotes_path()));
-        let user_config = self.load_user_config()?;
-        let user_id = user_config.get_current_userid()?;
-
-        let mut something_was_fetched = true;
-        while something_was_fetched {
-            something_was_fetched = false;
-            let trust_set =
-                db.calculate_trust_set(user_config.get_current_userid()?, &trust_params);
+    fn edit_trust_proof(
+        &self,
+        id_strings: Vec<String>,
+        from: PubId,
+        trust_or_distrust: crate::TrustOrDistrust,
+    ) -> Result<proof::Content> {
+        let pub_ids = self.lookup_pub_ids(id_strings)?;
 
-            for id in &trust_set {
+        let trust = proof::TrustBuilder::default()
+            .from(from)
+            .trust(if trust_or_distrust.is_trust() {
+                TrustLevel::Medium
+            } else {
+                TrustLevel::Distrust
+            })
+            .ids(pub_ids)
+            .build()
+            .map_err(|e| format_err!("{}", e))?;
+
+        util::edit_proof_content_iteractively(&trust.into())
+    }
+
+    /// Trust Ids from a trust list at `level`
+    ///
+    /// Proofs of every Id are fetched first, and Ids not found at their URL
+    /// are skipped. Returns the number of trusted Ids.
+    pub fn build_trust_proof_from_list(
+        &self,
+        entries: &[trust_list::Entry],
+        passphrase: &str,
+        level: TrustLevel,
+    ) -> Result<usize> {
+        for entry in entries {
+            if let Err(e) = self.fetch_url(&entry.url) {
+                error!("Couldn't fetch {}: {}", entry.url, e);
+            }
+        }
+
+        let mut trustdb = trustdb::TrustDB::new();
+        trustdb.import_verified_from_iter(self.proofs_iter()?);
+        trustdb.import_verified_from_iter(self.foreign_proofs_iter());
+        let mut pub_ids = vec![];
+        for entry in entries {
+            match trustdb.lookup_url(&entry.id) {
+                Some(url) if url.url == entry.url => {
+                    pub_ids.push(PubId::new(entry.id.clone(), url.to_owned()))
+                }
+                _ => eprintln!("Skipping {}: no proofs found at {}", entry.id, entry.url),
+            }
+        }
+        if pub_ids.is_empty() {
+            bail!("No Ids to trust.");
+        }
+        let count = pub_ids.len();
+
+        let own_id = self.read_current_unlocked_id(passphrase)?;
+        let trust = own_id.create_trust_proof(pub_ids, level)?;
+        let trust = util::edit_proof_content_iteractively(&trust.into())?;
+        self.insert_own(&trust.sign_by(&own_id)?, &own_id)?;
+        Ok(count)
+    }
+
+    /// Vouch for (or revoke) membership of Ids in the organization of the current Id
+    pub fn build_membership_proof(
+        &self,
+        id_strings: Vec<String>,
+        passphrase: &str,
+        status: MembershipStatus,
+    ) -> Result<()> {
+        let own_id = self.read_current_unlocked_id(passphrase)?;
+        let membership = self.edit_membership_proof(id_strings, own_id.id.clone(), status)?;
+        let proof = membership.sign_by(&own_id)?;
+
+        self.insert_own(&proof, &own_id)?;
+        Ok(())
+    }
+
+    /// Like `build_membership_proof`, but without signing (and storing) the
+    /// proof; see `build_unsigned_trust_proof`
+    pub fn build_unsigned_membership_proof(
+        &self,
+        id_strings: Vec<String>,
+        status: MembershipStatus,
+    ) -> Result<proof::Content> {
+        let from = self.read_current_locked_id()?.to_pubid();
+        self.edit_membership_proof(id_strings, from, status)
+    }
+
+    fn edit_membership_proof(
+        &self,
+        id_strings: Vec<String>,
+        from: PubId,
+        status: MembershipStatus,
+    ) -> Result<proof::Content> {
+        let pub_ids = self.lookup_pub_ids(id_strings)?;
+
+        let membership = proof::MembershipBuilder::default()
+            .from(from)
+            .status(status)
+            .members(pub_ids)
+            .build()
+            .map_err(|e| format_err!("{}", e))?;
+
+        util::edit_proof_content_iteractively(&membership.into())
+    }
+
+    pub fn fetch_url(&self, url: &str) -> Result<()> {
+        self.fetch_url_with(url, &StderrFetchObserver)
+    }
+
+    /// Like `fetch_url`, reporting progress to an `observer`
+    pub fn fetch_url_with(&self, url: &str, observer: &dyn FetchObserver) -> Result<()> {
+        if self.fetch_remote_git_observed(url, observer)?.is_some() {
+            let mut urls = self.load_requested_urls()?;
+            if urls.insert(url.to_owned()) {
+                self.store_requested_urls(&urls)?;
+            }
+        }
+        self.run_hook(
+            hooks::POST_FETCH,
+            &[
+                ("CREV_FETCH_SOURCE", "url".into()),
+                ("CREV_FETCH_URL", url.into()),
+            ],
+        )
+    }
+
+    /// Run the hook called `name` from the user config, if there's one
+    pub fn run_hook(&self, name: &str, env: &[(&str, String)]) -> Result<()> {
+        if let Some(cmd) = self.load_user_config()?.hooks.get(name) {
+            hooks::run(name, cmd, &self.get_proofs_dir_path()?, env)?;
+        }
+        Ok(())
+    }
+
+    /// Fetch a single proof repository, reporting to `observer`
+    ///
+    /// Failing to fetch is reported, not returned; only cancellation is
+    /// an error. Returns proofs new since the last fetch, or `None` if
+    /// fetching failed.
+    fn fetch_remote_git_observed(
+        &self,
+        url: &str,
+        observer: &dyn FetchObserver,
+    ) -> Result<Option<Vec<proof::Proof>>> {
+        if observer.is_cancelled() {
+            bail!("Fetching was cancelled");
+        }
+        observer.on_event(&FetchEvent::Started { url });
+        let dir = self.get_remote_git_cache_path(url);
+        let res = {
+            let _lock = self.lock.lock()?;
+            fetch_or_clone_git_repo_with(url, &dir, &mut |received_objects, total_objects| {
+                observer.on_event(&FetchEvent::Progress {
+                    url,
+                    received_objects,
+                    total_objects,
+                })
+            })
+        };
+        // index the proofs right away, instead of on the next use
+        let new_proofs = match res {
+            Ok(()) => Some(self.update_remote_proofs(&dir).1),
+            Err(_) => None,
+        };
+        observer.on_event(&FetchEvent::Finished {
+            url,
+            error: res.as_ref().err(),
+        });
+        Ok(new_proofs)
+    }
+
+    /// Fetch proof repositories of all trusted Ids
+    ///
+    /// Repositories fetched with `fetch_url` before (eg. other proof
+    /// repositories of trusted Ids, see `UserConfig::proof_routes`) are
+    /// fetched again too.
+    ///
+    /// With `prune_untrusted`, cached repositories other than the ones of
+    /// Ids in the trust set are removed afterwards (except the ones of Ids
+    /// adjacent to it, see `fetch_discovered`).
+    pub fn fetch_trusted(
+        &self,
+        trust_params: trustdb::TrustDistanceParams,
+        prune_untrusted: bool,
+    ) -> Result<()> {
+        self.fetch_trusted_with(trust_params, prune_untrusted, &StderrFetchObserver)
+    }
+
+    /// Like `fetch_trusted`, reporting progress to an `observer`
+    pub fn fetch_trusted_with(
+        &self,
+        trust_params: trustdb::TrustDistanceParams,
+        prune_untrusted: bool,
+        observer: &dyn FetchObserver,
+    ) -> Result<()> {
+        let mut already_fetched = HashSet::new();
+        let mut fetched_urls = HashSet::new();
+        let mut db = trustdb::TrustDB::new();
+        db.import_verified_from_iter(self.proofs_iter()?);
+        db.import_verified_from_iter(self.foreign_proofs_iter());
+        let user_config = self.load_user_config()?;
+        let user_id = user_config.get_current_userid()?;
+        let trust_params = user_config.apply_ignored_ids(trust_params)?;
+
+        let mut something_was_fetched = true;
+        while something_was_fetched {
+            something_was_fetched = false;
+            let trust_set =
+                db.calculate_trust_set(user_config.get_current_userid()?, &trust_params);
+
+            for id in trust_set.ids() {
                 if already_fetched.contains(id) {
                     continue;
                 } else {
@@ -486,27 +1621,96 @@ impl Local {
                 if user_id == id {
                     continue;
                 } else if let Some(url) = db.lookup_url(id) {
-                    let success =
-                        util::err_eprint_and_ignore(self.fetch_remote_git(&url.url).compat());
-                    if success {
+                    fetched_urls.insert(url.url.clone());
+                    if let Some(new_proofs) = self.fetch_remote_git_observed(&url.url, observer)? {
                         something_was_fetched = true;
-                        db.import_from_iter(proofs_iter_for_path(
-                            self.get_remote_git_cache_path(&url.url),
-                        ));
+                        db.import_verified_from_iter(new_proofs.into_iter());
                     }
                 } else {
-                    eprintln!("No URL for {}", id);
+                    observer.on_event(&FetchEvent::NoUrl { id });
                 }
             }
         }
+
+        // other proof repositories of trusted Ids (see `UserConfig::proof_routes`)
+        // are only known once fetched with `fetch_url`; keep them up to date
+        for url in self.load_requested_urls()? {
+            if !fetched_urls.contains(&url) && self.get_remote_git_cache_path(&url).exists() {
+                self.fetch_remote_git_observed(&url, observer)?;
+            }
+        }
+
+        let trust_set = db.calculate_trust_set(user_id, &trust_params);
+
+        // proof repositories of Ids trusted by the trust set, but outside of it,
+        // to be fetched on request with `fetch_discovered`
+        let adjacent_urls: BTreeSet<String> = db
+            .get_ids_adjacent_to(&trust_set)
+            .iter()
+            .filter_map(|id| db.lookup_url(id))
+            .map(|url| url.url.clone())
+            .collect();
+        let discovered: BTreeSet<&String> = adjacent_urls
+            .iter()
+            .filter(|url| !self.get_remote_git_cache_path(url).exists())
+            .collect();
+        util::store_str_to_file(
+            &self.discovered_urls_path(),
+            &serde_yaml::to_string(&discovered)?,
+        )?;
+
+        if prune_untrusted {
+            let mut trusted_urls: HashSet<String> = trust_set
+                .ids()
+                .filter_map(|id| db.lookup_url(id))
+                .map(|url| url.url.clone())
+                .collect();
+            // fetched with `fetch_discovered`, would only be rediscovered
+            trusted_urls.extend(adjacent_urls);
+            self.prune_remote_git_cache(&trusted_urls)?;
+
+            let mut requested_urls = self.load_requested_urls()?;
+            requested_urls.retain(|url| trusted_urls.contains(url));
+            self.store_requested_urls(&requested_urls)?;
+        }
+        self.run_hook(
+            hooks::POST_FETCH,
+            &[("CREV_FETCH_SOURCE", "trusted".into())],
+        )
+    }
+
+    /// Remove all cached remote proof repositories except the ones of `urls_to_keep`
+    pub fn prune_remote_git_cache(&self, urls_to_keep: &HashSet<String>) -> Result<()> {
+        let _lock = self.lock.lock()?;
+        let dirs_to_keep: HashSet<PathBuf> = urls_to_keep
+            .iter()
+            .map(|url| self.get_remote_git_cache_path(url))
+            .collect();
+
+        for entry in fs::read_dir(self.cache_remotes_path())? {
+            let path = entry?.path();
+            if !path.is_dir() || dirs_to_keep.contains(&path) {
+                continue;
+            }
+            info!("Pruning {}", path.display());
+            fs::remove_dir_all(&path)?;
+            let index_path = self.proof_index_path(&path);
+            if index_path.exists() {
+                fs::remove_file(index_path)?;
+            }
+        }
         Ok(())
     }
 
-    fn fetch_all_ids_recursively(&self, mut already_fetched_urls: HashSet<String>) -> Result<()> {
+    fn fetch_all_ids_recursively(
+        &self,
+        mut already_fetched_urls: HashSet<String>,
+        observer: &dyn FetchObserver,
+    ) -> Result<()> {
         let mut already_fetched = HashSet::new();
         let mut db = trustdb::TrustDB::new();
-        db.import_from_iter(self.proofs_iter()?);
-        db.import_from_iter(proofs_iter_for_path(self.cache_remotes_path()));
+        db.import_verified_from_iter(self.proofs_iter()?);
+        db.import_verified_from_iter(self.foreign_proofs_iter());
         let user_config = self.load_user_config()?;
         let user_id = user_config.get_current_userid()?;
 
@@ -531,43 +1735,98 @@ impl Local {
                         already_fetched_urls.insert(url.clone());
                     }
 
-                    let success = util::err_eprint_and_ignore(self.fetch_remote_git(&url).compat());
-                    if success {
+                    if let Some(new_proofs) = self.fetch_remote_git_observed(&url, observer)? {
                         something_was_fetched = true;
-                        db.import_from_iter(proofs_iter_for_path(
-                            self.get_remote_git_cache_path(&url),
-                        ));
+                        db.import_verified_from_iter(new_proofs.into_iter());
                     }
                 } else {
-                    eprintln!("No URL for {}", id);
+                    observer.on_event(&FetchEvent::NoUrl { id });
                 }
             }
         }
         Ok(())
     }
 
+    fn requested_urls_path(&self) -> PathBuf {
+        self.cache_path.join("requested-urls.yaml")
+    }
+
+    /// Proof repositories fetched with `fetch_url`, and not pruned since
+    fn load_requested_urls(&self) -> Result<BTreeSet<String>> {
+        let path = self.requested_urls_path();
+        if !path.exists() {
+            return Ok(default());
+        }
+        Ok(serde_yaml::from_str(&util::read_file_to_string(&path)?)?)
+    }
+
+    fn store_requested_urls(&self, urls: &BTreeSet<String>) -> Result<()> {
+        util::store_str_to_file(&self.requested_urls_path(), &serde_yaml::to_string(urls)?)?;
+        Ok(())
+    }
+
+    fn discovered_urls_path(&self) -> PathBuf {
+        self.cache_path.join("discovered-urls.yaml")
+    }
+
+    /// Proof repositories of Ids adjacent to the trust set (trusted by Ids
+    /// in it), found by the last `fetch_trusted`, and not fetched yet
+    pub fn load_discovered_urls(&self) -> Result<BTreeSet<String>> {
+        let path = self.discovered_urls_path();
+        if !path.exists() {
+            return Ok(default());
+        }
+        Ok(serde_yaml::from_str(&util::read_file_to_string(&path)?)?)
+    }
+
+    /// Fetch proof repositories found by the last `fetch_trusted`
+    /// (see `load_discovered_urls`)
+    pub fn fetch_discovered(&self) -> Result<()> {
+        self.fetch_discovered_with(&StderrFetchObserver)
+    }
+
+    /// Like `fetch_discovered`, reporting progress to an `observer`
+    pub fn fetch_discovered_with(&self, observer: &dyn FetchObserver) -> Result<()> {
+        for url in self.load_discovered_urls()? {
+            self.fetch_remote_git_observed(&url, observer)?;
+        }
+        util::store_str_to_file(
+            &self.discovered_urls_path(),
+            &serde_yaml::to_string(&BTreeSet::<String>::new())?,
+        )?;
+        self.run_hook(
+            hooks::POST_FETCH,
+            &[("CREV_FETCH_SOURCE", "discovered".into())],
+        )
+    }
+
     pub fn get_remote_git_cache_path(&self, url: &str) -> PathBuf {
         let digest = crev_common::blake2b256sum(url.as_bytes());
         let digest = crev_data::Digest::from_vec(digest);
         self.cache_remotes_path().join(digest.to_string())
     }
 
-    pub fn fetch_remote_git(&self, url: &str) -> Result<()> {
+    /// Directory of the fetched repository at `url` that keeps the proofs
+    pub fn get_remote_proofs_path(&self, url: &str) -> Result<PathBuf> {
         let dir = self.get_remote_git_cache_path(url);
+        Ok(match crev_data::url::split_subdir(url)?.1 {
+            Some(subdir) => dir.join(subdir),
+            None => dir,
+        })
+    }
 
-        if dir.exists() {
-            eprintln!("Fetching {} to {}", url, dir.display());
-            let repo = git2::Repository::open(dir)?;
-            fetch_and_checkout_git_repo(&repo)?
-        } else {
-            eprintln!("Cloning {} to {}", url, dir.display());
-            git2::Repository::clone(url, dir)?;
-        }
-
-        Ok(())
+    pub fn fetch_remote_git(&self, url: &str) -> Result<()> {
+        let _lock = self.lock.lock()?;
+        let dir = self.get_remote_git_cache_path(url);
+        fetch_or_clone_git_repo(url, &dir)
     }
 
     pub fn fetch_all(&self) -> Result<()> {
+        self.fetch_all_with(&StderrFetchObserver)
+    }
+
+    /// Like `fetch_all`, reporting progress to an `observer`
+    pub fn fetch_all_with(&self, observer: &dyn FetchObserver) -> Result<()> {
         let mut fetched_urls = HashSet::new();
         for entry in fs::read_dir(self.cache_remotes_path())? {
             let path = entry?.path();
@@ -580,37 +1839,357 @@ impl Local {
                 continue;
             }
 
-            let url = {
-                || -> Result<String> {
-                    let repo = repo.unwrap();
-                    let remote = repo.find_remote("origin")?;
-                    let url = remote
-                        .url()
-                        .ok_or_else(|| format_err!("origin has no url"))?;
-                    Ok(url.to_string())
-                }
-            }();
-
-            match url {
+            match remote_git_url(&path) {
                 Ok(url) => {
                     fetched_urls.insert(url.clone());
-                    let _success =
-                        util::err_eprint_and_ignore(self.fetch_remote_git(&url).compat());
+                    let _success = self.fetch_remote_git_observed(&url, observer)?;
                 }
                 Err(e) => {
-                    eprintln!("ERR: {} {}", path.display(), e);
+                    error!("{}: {}", path.display(), e);
                 }
             }
         }
 
-        self.fetch_all_ids_recursively(fetched_urls)?;
+        self.fetch_all_ids_recursively(fetched_urls, observer)?;
 
+        self.run_hook(hooks::POST_FETCH, &[("CREV_FETCH_SOURCE", "all".into())])
+    }
+
+    /// Commit all changes in own proof repositories, signing the commits with the current Id
+    pub fn commit_signed(&self, passphrase: &str, message: &str) -> Result<()> {
+        let _lock = self.lock.lock()?;
+        let id = self.read_current_unlocked_id(passphrase)?;
+        self.commit_changed_repos(|url, repo| {
+            let commit_id =
+                util::git::commit_signed_by(repo, &id, &pathspec_for_url(url)?, message)?;
+            eprintln!("Commit {} signed by {}", commit_id, id.id.id);
+            Ok(())
+        })
+    }
+
+    /// Commit all changes in own proof repositories, like `git commit -a`
+    pub fn commit(&self, message: &str) -> Result<()> {
+        let _lock = self.lock.lock()?;
+        self.commit_changed_repos(|url, repo| {
+            let commit_id = util::git::commit_all(repo, &pathspec_for_url(url)?, message)?;
+            eprintln!("Commit {}", commit_id);
+            Ok(())
+        })
+    }
+
+    /// Run `commit` on own proof repositories with changes; fails if there are none
+    ///
+    /// The index of proof files (see `repo_index`) is updated first.
+    fn commit_changed_repos(
+        &self,
+        mut commit: impl FnMut(&Url, &git2::Repository) -> Result<()>,
+    ) -> Result<()> {
+        let mut committed = false;
+        for (url, repo) in self.own_proof_repos()? {
+            self.update_repo_index(&url)?;
+            if util::git::diff_head(&repo, &pathspec_for_url(&url)?)?.is_empty() {
+                continue;
+            }
+            commit(&url, &repo)?;
+            committed = true;
+        }
+        if !committed {
+            bail!("Nothing to commit");
+        }
         Ok(())
     }
 
+    /// Rebuild the published index of the own proof repository at `url`, if it changed
+    fn update_repo_index(&self, url: &Url) -> Result<()> {
+        let proofs_dir = self.get_proofs_dir_path_for_url(url)?;
+        let index = RepoIndex::build(&proofs_dir)?;
+        if RepoIndex::load(&proofs_dir).ok().flatten().as_ref() != Some(&index) {
+            index.store(&proofs_dir)?;
+        }
+        self.proof_repo_git_add_path(url, Path::new(repo_index::INDEX_FILE_NAME))
+    }
+
+    /// Push own proof repositories to their `origin`, running the `pre-push` hook first
+    pub fn push(&self) -> Result<()> {
+        self.run_hook(hooks::PRE_PUSH, &[])?;
+        let _lock = self.lock.lock()?;
+        for (url, repo) in self.own_proof_repos()? {
+            util::git::push(&repo).map_err(|e| format_err!("Couldn't push {}: {}", url.url, e))?;
+        }
+        Ok(())
+    }
+
+    /// Fast-forward own proof repositories to their `origin`
+    pub fn pull(&self) -> Result<()> {
+        let _lock = self.lock.lock()?;
+        for (url, repo) in self.own_proof_repos()? {
+            util::git::pull(&repo).map_err(|e| format_err!("Couldn't pull {}: {}", url.url, e))?;
+        }
+        Ok(())
+    }
+
+    /// Uncommitted changes in own proof repositories, as a patch
+    pub fn diff(&self) -> Result<String> {
+        let mut patch = String::new();
+        for (url, repo) in self.own_proof_repos()? {
+            patch += &util::git::diff_head(&repo, &pathspec_for_url(&url)?)?;
+        }
+        Ok(patch)
+    }
+
+    /// Compare own proof repository with its working copy and upstream
+    ///
+    /// Proofs are only appended to proof files, so new proofs are counted
+    /// as the difference in the number of proofs in each changed file.
+    pub fn repo_status(&self) -> Result<RepoStatus> {
+        let repo = git2::Repository::open(self.get_proofs_repo_path()?)?;
+        let mut status = RepoStatus::default();
+
+        // there's no `HEAD` before the first commit
+        let head = repo.head().ok();
+        let head_tree = match &head {
+            Some(head) => Some(head.peel_to_tree()?),
+            None => None,
+        };
+
+        let mut status_opts = git2::StatusOptions::new();
+        status_opts
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .pathspec(self.get_proofs_pathspec()?);
+        for entry in repo.statuses(Some(&mut status_opts))?.iter() {
+            let path = match entry.path() {
+                Some(path) => PathBuf::from(path),
+                None => continue,
+            };
+            if path.extension() != Some("crev".as_ref()) {
+                status.other_changes.push(path);
+                continue;
+            }
+            let before = count_proofs_in_tree(&repo, head_tree.as_ref(), &path)?;
+            let now = match fs::read(repo.workdir().unwrap_or(Path::new("")).join(&path)) {
+                Ok(content) => count_proofs(&content),
+                Err(_) => 0,
+            };
+            if now > before {
+                status.uncommitted_proofs += now - before;
+            } else {
+                status.other_changes.push(path);
+            }
+        }
+
+        let head = match head {
+            Some(head) => head,
+            None => return Ok(status),
+        };
+        if !head.is_branch() {
+            return Ok(status);
+        }
+        status.branch = head.shorthand().map(ToOwned::to_owned);
+        let head_commit = head.peel_to_commit()?;
+        let branch = git2::Branch::wrap(head);
+        let upstream_commit = match branch.upstream() {
+            Ok(upstream) => {
+                status.upstream = upstream.name()?.map(ToOwned::to_owned);
+                Some(upstream.get().peel_to_commit()?)
+            }
+            // nothing was pushed yet
+            Err(_) => None,
+        };
+        let upstream_tree = match &upstream_commit {
+            Some(upstream_commit) => {
+                let (ahead, behind) =
+                    repo.graph_ahead_behind(head_commit.id(), upstream_commit.id())?;
+                status.ahead = ahead;
+                status.behind = behind;
+                Some(upstream_commit.tree()?)
+            }
+            None => {
+                let mut revwalk = repo.revwalk()?;
+                revwalk.push(head_commit.id())?;
+                status.ahead = revwalk.count();
+                None
+            }
+        };
+
+        let diff = repo.diff_tree_to_tree(upstream_tree.as_ref(), head_tree.as_ref(), None)?;
+        for delta in diff.deltas() {
+            if let Some(path) = delta.new_file().path() {
+                let before = count_proofs_in_tree(&repo, upstream_tree.as_ref(), path)?;
+                let now = count_proofs_in_tree(&repo, head_tree.as_ref(), path)?;
+                status.unpushed_proofs += now.saturating_sub(before);
+            }
+        }
+        Ok(status)
+    }
+
+    /// Commit all changes in own proof repository and push them
+    ///
+    /// The commit is signed if `sign-commits` is set in the config.
+    pub fn publish_proofs(&self, message: &str, secrets: &dyn SecretProvider) -> Result<()> {
+        if self.load_user_config()?.sign_commits {
+            self.commit_signed(&secrets.read_passphrase()?, message)?;
+        } else {
+            self.commit(message)?;
+        }
+        self.push()
+    }
+
+    fn verified_heads_path(&self) -> PathBuf {
+        self.cache_path.join("verified-heads.yaml")
+    }
+
+    fn load_verified_heads(&self) -> Result<BTreeMap<String, String>> {
+        let path = self.verified_heads_path();
+        if !path.exists() {
+            return Ok(default());
+        }
+        Ok(serde_yaml::from_str(&util::read_file_to_string(&path)?)?)
+    }
+
+    fn seen_proofs_path(&self) -> PathBuf {
+        self.cache_path.join("seen-proofs.yaml")
+    }
+
+    /// Proofs of other Ids that previous calls didn't return yet
+    ///
+    /// All current proofs are remembered as seen. The first call only
+    /// remembers them, and returns `None`.
+    pub fn take_new_foreign_proofs(&self) -> Result<Option<Vec<proof::Proof>>> {
+        let _lock = self.lock.lock()?;
+        let path = self.seen_proofs_path();
+        let seen: Option<HashSet<String>> = if path.exists() {
+            Some(serde_yaml::from_str(&util::read_file_to_string(&path)?)?)
+        } else {
+            None
+        };
+
+        let proofs: Vec<_> = self.foreign_proofs_iter().collect();
+        let signatures: BTreeSet<&str> = proofs.iter().map(|p| p.signature.as_str()).collect();
+        util::store_str_to_file(&path, &serde_yaml::to_string(&signatures)?)?;
+
+        Ok(seen.map(|seen| {
+            let mut returned = HashSet::new();
+            proofs
+                .into_iter()
+                .filter(|proof| {
+                    !seen.contains(&proof.signature) && returned.insert(proof.signature.clone())
+                })
+                .collect()
+        }))
+    }
+
+    /// Verify CrevID signatures of commits in fetched proof repositories
+    ///
+    /// All commits since the last successful verification must be signed
+    /// by an Id using this repository, and the last verified commit must
+    /// still be in the history (otherwise it was rewritten). Unsigned commits
+    /// fail the verification too. Shallow clones lack the history to check
+    /// this, so their whole history is fetched first. Returns `false` if any
+    /// problems were found.
+    pub fn verify_remote_git_commits(&self) -> Result<bool> {
+        let _lock = self.lock.lock()?;
+        use crate::util::git::CommitSignature;
+
+        let mut db = trustdb::TrustDB::new();
+        db.import_verified_from_iter(self.proofs_iter()?);
+        db.import_verified_from_iter(self.foreign_proofs_iter());
+
+        let mut verified_heads = self.load_verified_heads()?;
+        let mut all_ok = true;
+
+        for entry in fs::read_dir(self.cache_remotes_path())? {
+            let path = entry?.path();
+            let repo = match git2::Repository::open(&path) {
+                Ok(repo) => repo,
+                Err(_) => continue,
+            };
+            let url = match repo.find_remote("origin")?.url() {
+                Some(url) => url.to_string(),
+                None => continue,
+            };
+            let repo = if repo.is_shallow() {
+                if let Err(e) = unshallow_git_repo(&path) {
+                    all_ok = false;
+                    eprintln!("{}: shallow clone, can't verify it: {}", url, e);
+                    continue;
+                }
+                git2::Repository::open(&path)?
+            } else {
+                repo
+            };
+            // Ids can keep proofs in more than one repository (see
+            // `UserConfig::proof_routes`), so authors of its proofs use it too
+            let owner_ids: HashSet<Id> = db
+                .all_known_ids()
+                .into_iter()
+                .filter(|id| {
+                    db.lookup_url(id)
+                        .map(|u| u.repo_url() == url)
+                        .unwrap_or(false)
+                })
+                .chain(
+                    self.proofs_iter_for_path(remote_proofs_path(&path))
+                        .map(|proof| proof.content.author_id()),
+                )
+                .collect();
+
+            let head = repo.head()?.peel_to_commit()?.id();
+            let mut problems = vec![];
+
+            let mut revwalk = repo.revwalk()?;
+            revwalk.push(head)?;
+            if let Some(last) = verified_heads.get(&url) {
+                let last = git2::Oid::from_str(last)?;
+                if repo.find_commit(last).is_ok() {
+                    if last != head && !repo.graph_descendant_of(head, last)? {
+                        problems.push(format!("history rewritten since {}", last));
+                    }
+                    revwalk.hide(last)?;
+                } else {
+                    problems.push(format!("previously verified {} is gone", last));
+                }
+            }
+
+            let mut unsigned = 0;
+            for commit_id in revwalk {
+                let commit_id = commit_id?;
+                match util::git::verify_commit_signature(&repo, commit_id)? {
+                    CommitSignature::Valid(ref id) if owner_ids.contains(id) => {}
+                    CommitSignature::Valid(id) => {
+                        problems.push(format!("{} signed by a foreign Id {}", commit_id, id))
+                    }
+                    CommitSignature::Invalid => {
+                        problems.push(format!("{} has an invalid signature", commit_id))
+                    }
+                    CommitSignature::Missing => unsigned += 1,
+                }
+            }
+
+            if unsigned > 0 {
+                problems.push(format!("{} unsigned commits", unsigned));
+            }
+            if problems.is_empty() {
+                verified_heads.insert(url, head.to_string());
+            } else {
+                all_ok = false;
+                for problem in problems {
+                    eprintln!("{}: {}", url, problem);
+                }
+            }
+        }
+
+        util::store_str_to_file(
+            &self.verified_heads_path(),
+            &serde_yaml::to_string(&verified_heads)?,
+        )?;
+        Ok(all_ok)
+    }
+
     pub fn run_git(&self, args: Vec<OsString>) -> Result<std::process::ExitStatus> {
+        let _lock = self.lock.lock()?;
         let orig_dir = std::env::current_dir()?;
-        std::env::set_current_dir(self.get_proofs_dir_path()?)?;
+        std::env::set_current_dir(self.get_proofs_repo_path()?)?;
 
         use std::process::Command;
 
@@ -633,32 +2212,332 @@ impl Local {
     pub fn load_db(
         &self,
         params: &trustdb::TrustDistanceParams,
-    ) -> Result<(trustdb::TrustDB, HashSet<Id>)> {
-        let user_config = self.load_user_config()?;
+    ) -> Result<(trustdb::TrustDB, trustdb::TrustSet)> {
+        self.load_db_for(params, None)
+    }
+
+    /// Like `load_db`, with fetched proofs about crates other than `crates`
+    /// left out where the repositories' indices allow it
+    ///
+    /// Enough to verify dependencies named `crates`, and much faster
+    /// with large proof repositories.
+    pub fn load_db_for_crates(
+        &self,
+        params: &trustdb::TrustDistanceParams,
+        crates: &HashSet<String>,
+    ) -> Result<(trustdb::TrustDB, trustdb::TrustSet)> {
+        self.load_db_for(params, Some(crates))
+    }
+
+    fn load_db_for(
+        &self,
+        params: &trustdb::TrustDistanceParams,
+        crates: Option<&HashSet<String>>,
+    ) -> Result<(trustdb::TrustDB, trustdb::TrustSet)> {
         let mut db = trustdb::TrustDB::new();
-        db.import_from_iter(self.proofs_iter()?);
-        db.import_from_iter(proofs_iter_for_path(self.cache_remotes_path()));
-        let trusted_set = db.calculate_trust_set(user_config.get_current_userid()?, &params);
+        let is_in_time = |proof: &proof::Proof| params.is_in_time(proof);
+        db.import_verified_from_iter(self.proofs_iter()?.filter(is_in_time));
+        db.import_verified_from_iter(self.foreign_proofs_iter_for(crates).filter(is_in_time));
+        let trusted_set = self.calculate_trust_set(&db, params)?;
 
         Ok((db, trusted_set))
     }
 
+    /// Trust set of the current Id in `db`, with `ignored-ids` of the config
+    ///
+    /// `load_db` does that already; useful after changing `db`.
+    pub fn calculate_trust_set(
+        &self,
+        db: &trustdb::TrustDB,
+        params: &trustdb::TrustDistanceParams,
+    ) -> Result<trustdb::TrustSet> {
+        let user_config = self.load_user_config()?;
+        let params = user_config.apply_ignored_ids(params.clone())?;
+        Ok(db.calculate_trust_set(user_config.get_current_userid()?, &params))
+    }
+
     pub fn proof_dir_git_add_path(&self, rel_path: &Path) -> Result<()> {
-        let proof_dir = self.get_proofs_dir_path()?;
-        let repo = git2::Repository::init(&proof_dir)?;
+        self.proof_repo_git_add_path(&self.get_cur_url()?, rel_path)
+    }
+
+    /// Like `proof_dir_git_add_path`, in the own proof repository at `url`
+    fn proof_repo_git_add_path(&self, url: &Url, rel_path: &Path) -> Result<()> {
+        let _lock = self.lock.lock()?;
+        let repo = git2::Repository::init(&self.get_proofs_repo_path_for_url(url)?)?;
         let mut index = repo.index()?;
 
-        index.add_path(rel_path)?;
+        match url.subdir()? {
+            Some(subdir) => index.add_path(&Path::new(subdir).join(rel_path))?,
+            None => index.add_path(rel_path)?,
+        }
         index.write()?;
         Ok(())
     }
+
+    pub fn proof_dir_git_remove_path(&self, rel_path: &Path) -> Result<()> {
+        self.proof_repo_git_remove_path(&self.get_cur_url()?, rel_path)
+    }
+
+    /// Like `proof_dir_git_remove_path`, in the own proof repository at `url`
+    fn proof_repo_git_remove_path(&self, url: &Url, rel_path: &Path) -> Result<()> {
+        let _lock = self.lock.lock()?;
+        let repo = git2::Repository::init(&self.get_proofs_repo_path_for_url(url)?)?;
+        let mut index = repo.index()?;
+
+        match url.subdir()? {
+            Some(subdir) => index.remove_path(&Path::new(subdir).join(rel_path))?,
+            None => index.remove_path(rel_path)?,
+        }
+        index.write()?;
+        Ok(())
+    }
+
+    /// Strictly check proof files, returning all issues found
+    ///
+    /// Checks the local proof repository, and optionally all fetched ones.
+    pub fn lint_proofs(
+        &self,
+        include_remotes: bool,
+    ) -> Result<Vec<(PathBuf, proof::strict::Issue)>> {
+        let mut paths = vec![];
+        for url in self.get_own_proof_urls()? {
+            let proofs_dir = self.get_proofs_dir_path_for_url(&url)?;
+            if proofs_dir.exists() {
+                paths.extend(proof_file_paths_for_path(&proofs_dir)?);
+            }
+        }
+        if include_remotes {
+            paths.extend(proof_file_paths_for_path(&self.cache_remotes_path())?);
+        }
+
+        let mut issues = vec![];
+        for path in paths {
+            let file = fs::File::open(&path)?;
+            let (_proofs, file_issues) = proof::strict::parse(io::BufReader::new(file));
+            issues.extend(file_issues.into_iter().map(|issue| (path.clone(), issue)));
+        }
+        Ok(issues)
+    }
+
+    /// Own proofs not anchored in a transparency log yet, with their repository
+    pub fn unanchored_proofs(&self) -> Result<Vec<(Url, proof::Proof)>> {
+        let mut unanchored = vec![];
+        for url in self.get_own_proof_urls()? {
+            let proofs_dir = self.get_proofs_dir_path_for_url(&url)?;
+            let anchors = transparency::load_anchors(&proofs_dir)?;
+            unanchored.extend(
+                self.proofs_iter_for_path(proofs_dir)
+                    .filter(|proof| !anchors.contains_key(proof.signature()))
+                    .map(|proof| (url.clone(), proof)),
+            );
+        }
+        Ok(unanchored)
+    }
+
+    /// Record `anchors` of own proofs in the repository at `url`
+    pub fn add_anchors(&self, url: &Url, anchors: Vec<transparency::Anchor>) -> Result<()> {
+        let proofs_dir = self.get_proofs_dir_path_for_url(url)?;
+        transparency::store_anchors(&proofs_dir, anchors)?;
+        self.proof_repo_git_add_path(url, Path::new(transparency::ANCHORS_FILE_NAME))
+    }
+
+    /// Proof directories that can have anchors: own ones, and optionally
+    /// all fetched ones, with the file remembering the anchors seen in them
+    fn anchored_proof_dirs(
+        &self,
+        include_remotes: bool,
+    ) -> Result<Vec<(PathBuf, Option<PathBuf>)>> {
+        let mut dirs = vec![];
+        for url in self.get_own_proof_urls()? {
+            let proofs_dir = self.get_proofs_dir_path_for_url(&url)?;
+            if proofs_dir.exists() {
+                dirs.push((proofs_dir, None));
+            }
+        }
+        if include_remotes {
+            let seen_anchors_path = self.seen_anchors_path();
+            dirs.extend(
+                fs::read_dir(self.cache_remotes_path())
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .filter(|path| path.is_dir())
+                    .filter_map(|dir| {
+                        let seen =
+                            seen_anchors_path.join(format!("{}.yaml", dir.file_name()?.to_str()?));
+                        Some((remote_proofs_path(&dir), Some(seen)))
+                    }),
+            );
+        }
+        Ok(dirs)
+    }
+
+    /// Anchors of own, and optionally fetched, repositories, by directory
+    ///
+    /// Anchors of fetched repositories are added to the ones remembered
+    /// from before, and the ones remembered are returned too, so a proof
+    /// removed together with its anchor is still noticed.
+    pub fn load_anchors(
+        &self,
+        include_remotes: bool,
+    ) -> Result<Vec<(PathBuf, HashMap<String, transparency::Anchor>)>> {
+        let mut all = vec![];
+        for (dir, seen) in self.anchored_proof_dirs(include_remotes)? {
+            let mut anchors = transparency::load_anchors(&dir)?;
+            if let Some(seen) = seen {
+                transparency::store_anchors_file(&seen, anchors.values().cloned().collect())?;
+                anchors = transparency::load_anchors_file(&seen)?;
+            }
+            all.push((dir, anchors));
+        }
+        Ok(all)
+    }
+
+    /// Check anchors of proofs in own, and optionally fetched, repositories
+    ///
+    /// See `load_anchors` and `transparency::check_anchors`.
+    pub fn check_anchors(
+        &self,
+        log: &transparency::Log,
+        include_remotes: bool,
+    ) -> Result<Vec<(PathBuf, transparency::Issue)>> {
+        let mut issues = vec![];
+        for (dir, anchors) in self.load_anchors(include_remotes)? {
+            let proofs: Vec<_> = self.proofs_iter_for_path(dir.clone()).collect();
+            issues.extend(
+                transparency::check_anchors(log, anchors, &proofs)
+                    .into_iter()
+                    .map(|issue| (dir.clone(), issue)),
+            );
+        }
+        Ok(issues)
+    }
+
+    /// Rewrite own proof store, removing duplicated and superseded proofs
+    ///
+    /// See `crate::proof::compact`. Returns the number of removed proofs.
+    pub fn compact_proofs(&self) -> Result<usize> {
+        let mut removed = 0;
+        for url in self.get_own_proof_urls()? {
+            removed += self.compact_proofs_for_url(&url)?;
+        }
+        Ok(removed)
+    }
+
+    fn compact_proofs_for_url(&self, url: &Url) -> Result<usize> {
+        let _lock = self.lock.lock()?;
+        let proofs_dir = self.get_proofs_dir_path_for_url(url)?;
+        if !proofs_dir.exists() {
+            return Ok(0);
+        }
+        let mut paths = vec![];
+        let mut groups = vec![];
+        for path in proof_file_paths_for_path(&proofs_dir)? {
+            groups.push(proof::Proof::parse_from(&path)?);
+            paths.push(path);
+        }
+
+        let counts_before: Vec<_> = groups.iter().map(Vec::len).collect();
+        let compacted = crate::proof::compact(groups);
+
+        let mut removed = 0;
+        for ((path, count_before), proofs) in paths.iter().zip(counts_before).zip(compacted) {
+            if proofs.len() == count_before {
+                continue;
+            }
+            removed += count_before - proofs.len();
+
+            let rel_path = path.strip_prefix(&proofs_dir)?;
+            if proofs.is_empty() {
+                fs::remove_file(path)?;
+                self.proof_repo_git_remove_path(url, rel_path)?;
+            } else {
+                let content: String = proofs.iter().map(|proof| format!("{}\n", proof)).collect();
+                util::store_str_to_file(path, &content)?;
+                self.proof_repo_git_add_path(url, rel_path)?;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Upgrade own proofs written in older format versions
+    ///
+    /// Outdated proofs are re-signed with the current Id. Proofs that are
+    /// up to date, or signed by other Ids, are left untouched.
+    pub fn migrate_proofs(&self, passphrase: &str) -> Result<usize> {
+        let id = self.read_current_unlocked_id(passphrase)?;
+        let mut migrated = 0;
+        for url in self.get_own_proof_urls()? {
+            migrated += self.migrate_proofs_for_url(&url, &id)?;
+        }
+        Ok(migrated)
+    }
+
+    fn migrate_proofs_for_url(&self, url: &Url, id: &OwnId) -> Result<usize> {
+        let _lock = self.lock.lock()?;
+        let needs_migration = |proof: &proof::Proof| {
+            proof.content.is_outdated() && proof.content.author_id() == id.id.id
+        };
+
+        let proofs_dir = self.get_proofs_dir_path_for_url(url)?;
+        if !proofs_dir.exists() {
+            return Ok(0);
+        }
+        let mut migrated = 0;
+        for path in proof_file_paths_for_path(&proofs_dir)? {
+            let proofs = proof::Proof::parse_from(&path)?;
+            if !proofs.iter().any(&needs_migration) {
+                continue;
+            }
+
+            let mut content = String::new();
+            for proof in proofs {
+                let proof = if needs_migration(&proof) {
+                    migrated += 1;
+                    proof.content.migrate().sign_by(id)?
+                } else {
+                    proof
+                };
+                content += &format!("{}\n", proof);
+            }
+            util::store_str_to_file(&path, &content)?;
+            self.proof_repo_git_add_path(url, path.strip_prefix(&proofs_dir)?)?;
+        }
+
+        Ok(migrated)
+    }
 }
 
 impl ProofStore for Local {
     fn insert(&self, proof: &proof::Proof) -> Result<()> {
+        self.insert_at(&self.get_proof_url(&proof.content)?, proof)
+    }
+
+    fn proofs_iter(&self) -> Result<Box<Iterator<Item = proof::Proof>>> {
+        let mut paths = vec![];
+        for url in self.get_own_proof_urls()? {
+            paths.push(self.get_proofs_dir_path_for_url(&url)?);
+        }
+        let verification_cache = self.verification_cache.clone();
+        Ok(Box::new(paths.into_iter().flat_map(move |path| {
+            proofs_iter_for_path(path, verification_cache.clone())
+        })))
+    }
+}
+
+impl Local {
+    /// Store `proof` in the own proof repository at `url`
+    fn insert_at(&self, url: &Url, proof: &proof::Proof) -> Result<()> {
         let rel_store_path = self.get_proof_rel_store_path(proof);
-        let path = self.get_proofs_dir_path()?.join(&rel_store_path);
+        let path = self.get_proofs_dir_path_for_url(url)?.join(&rel_store_path);
 
+        // not held while running the hook, which might run crev again
+        let lock = self.lock.lock()?;
+        if !self.get_proofs_repo_path_for_url(url)?.exists() {
+            self.clone_proof_dir_from_git(&url.url, false)?;
+        }
         fs::create_dir_all(path.parent().expect("Not a root dir"))?;
         let mut file = fs::OpenOptions::new()
             .append(true)
@@ -671,17 +2550,37 @@ impl ProofStore for Local {
         file.flush()?;
         drop(file);
 
-        self.proof_dir_git_add_path(&rel_store_path)?;
+        self.proof_repo_git_add_path(url, &rel_store_path)?;
+        drop(lock);
+
+        self.run_hook(
+            hooks::POST_PROOF_CREATED,
+            &hooks::proof_env(
+                proof,
+                &self.get_proofs_dir_path_for_url(url)?.join(&rel_store_path),
+            ),
+        )?;
 
         Ok(())
     }
+}
 
-    fn proofs_iter(&self) -> Result<Box<Iterator<Item = proof::Proof>>> {
-        Ok(proofs_iter_for_path(self.get_proofs_dir_path()?))
+fn proof_file_paths_for_path(path: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = vec![];
+    for entry in walkdir::WalkDir::new(path) {
+        let path = entry?.into_path();
+        if path.is_file() && path.extension() == Some("crev".as_ref()) {
+            paths.push(path);
+        }
     }
+    paths.sort();
+    Ok(paths)
 }
 
-fn proofs_iter_for_path(path: PathBuf) -> Box<Iterator<Item = proof::Proof>> {
+fn proofs_iter_for_path(
+    path: PathBuf,
+    verification_cache: Rc<RefCell<VerificationCache>>,
+) -> Box<Iterator<Item = proof::Proof>> {
     use std::ffi::OsStr;
     let file_iter = walkdir::WalkDir::new(path)
         .into_iter()
@@ -700,14 +2599,18 @@ fn proofs_iter_for_path(path: PathBuf) -> Box<Iterator<Item = proof::Proof>> {
         });
 
     let proofs_iter = file_iter
-        .and_then_ok(|path| Ok(proof::Proof::parse_from(&path)?))
-        .flatten_ok()
-        .and_then_ok(|proof| {
-            proof.verify()?;
+        .flat_map(
+            |path| match path.and_then(|path| proof::Proof::parse_each_from(&path)) {
+                Ok(proofs) => proofs,
+                Err(e) => vec![Err(e)],
+            },
+        )
+        .and_then_ok(move |proof| {
+            verification_cache.borrow_mut().verify(&proof)?;
             Ok(proof)
         })
         .on_err(|e| {
-            eprintln!("Failed processing a proof: {}", e);
+            warn!("Failed processing a proof: {}", e);
         });
 
     Box::new(proofs_iter.oks())
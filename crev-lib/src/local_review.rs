@@ -0,0 +1,100 @@
+//! Local reviews: package versions marked as reviewed on this machine only
+//!
+//! Unlike review proofs, they are not signed and never published, so they
+//! count only for the verification of their owner. Useful for code that is
+//! not worth a public review, or for confidential projects.
+use crate::{util, Result};
+use chrono::{self, prelude::*};
+use crev_common::serde::{as_rfc3339_fixed, from_rfc3339_fixed};
+use crev_data::{Digest, DigestType};
+use serde_yaml;
+use std::path::Path;
+
+/// Name of the file keeping local reviews, in the user data directory
+pub const LOCAL_REVIEWS_FILE_NAME: &str = "local-reviews.yaml";
+
+/// A package version marked as reviewed
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LocalReview {
+    pub name: String,
+    pub version: String,
+    #[serde(rename = "digest-type")]
+    pub digest_type: String,
+    /// Digest of the reviewed sources (base64)
+    pub digest: String,
+    #[serde(
+        serialize_with = "as_rfc3339_fixed",
+        deserialize_with = "from_rfc3339_fixed"
+    )]
+    pub date: chrono::DateTime<FixedOffset>,
+    #[serde(skip_serializing_if = "String::is_empty", default)]
+    pub comment: String,
+}
+
+impl LocalReview {
+    pub fn new(
+        name: &str,
+        version: &str,
+        digest_type: DigestType,
+        digest: &Digest,
+        comment: &str,
+    ) -> Self {
+        Self {
+            name: name.to_owned(),
+            version: version.to_owned(),
+            digest_type: digest_type.as_str().to_owned(),
+            digest: digest.to_string(),
+            date: crev_common::now(),
+            comment: comment.to_owned(),
+        }
+    }
+
+    fn matches(&self, digests: &[(DigestType, Digest)]) -> bool {
+        digests.iter().any(|(digest_type, digest)| {
+            self.digest_type == digest_type.as_str() && self.digest == digest.to_string()
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LocalReviews {
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub reviews: Vec<LocalReview>,
+}
+
+impl LocalReviews {
+    /// Load local reviews from `path`; a missing file means no reviews
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        serde_yaml::from_str(&util::read_file_to_string(path)?)
+            .map_err(|e| format_err!("Can't parse {}: {}", path.display(), e))
+    }
+
+    pub fn store(&self, path: &Path) -> Result<()> {
+        util::store_str_to_file(path, &serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Add `review`, replacing the one of the same package version
+    pub fn add(&mut self, review: LocalReview) {
+        self.remove(&review.name, &review.version);
+        self.reviews.push(review);
+        self.reviews
+            .sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+    }
+
+    /// Remove the review of the package version; returns `false` if there was none
+    pub fn remove(&mut self, name: &str, version: &str) -> bool {
+        let len = self.reviews.len();
+        self.reviews
+            .retain(|review| review.name != name || review.version != version);
+        self.reviews.len() != len
+    }
+
+    /// Review of sources with any of `digests`, if any
+    pub fn find(&self, digests: &[(DigestType, Digest)]) -> Option<&LocalReview> {
+        self.reviews.iter().find(|review| review.matches(digests))
+    }
+}
@@ -0,0 +1,83 @@
+//! Advisory locking of the local store, between concurrent crev processes
+use crate::Result;
+use fs2::FileExt;
+use std::{
+    cell::{Cell, RefCell},
+    fs,
+    path::PathBuf,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How long to wait for other processes to release the lock
+pub const LOCK_TIMEOUT: Duration = Duration::from_secs(120);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Exclusive lock on a file, reentrant within the process
+///
+/// Other processes (but not other threads) are excluded while any `LockGuard`
+/// is alive.
+pub struct FileLock {
+    path: PathBuf,
+    file: RefCell<Option<fs::File>>,
+    depth: Cell<usize>,
+}
+
+impl FileLock {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            file: RefCell::new(None),
+            depth: Cell::new(0),
+        }
+    }
+
+    /// Wait until no other process holds the lock (up to `LOCK_TIMEOUT`), and take it
+    pub fn lock(&self) -> Result<LockGuard<'_>> {
+        if self.depth.get() == 0 {
+            if let Some(dir) = self.path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let file = fs::OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&self.path)?;
+            let start = Instant::now();
+            let mut reported = false;
+            while file.try_lock_exclusive().is_err() {
+                if start.elapsed() >= LOCK_TIMEOUT {
+                    bail!(
+                        "Timed out waiting for another crev process to release {}",
+                        self.path.display()
+                    );
+                }
+                if !reported {
+                    eprintln!("Waiting for another crev process to finish...");
+                    reported = true;
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+            *self.file.borrow_mut() = Some(file);
+        }
+        self.depth.set(self.depth.get() + 1);
+        Ok(LockGuard { lock: self })
+    }
+}
+
+/// Holds a `FileLock` until dropped
+pub struct LockGuard<'a> {
+    lock: &'a FileLock,
+}
+
+impl<'a> Drop for LockGuard<'a> {
+    fn drop(&mut self) {
+        let depth = self.lock.depth.get() - 1;
+        self.lock.depth.set(depth);
+        if depth == 0 {
+            if let Some(file) = self.lock.file.borrow_mut().take() {
+                let _ = file.unlock();
+            }
+        }
+    }
+}
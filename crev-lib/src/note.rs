@@ -0,0 +1,86 @@
+//! Private notes about crates, kept on this machine only
+use crate::{util, Result};
+use chrono::{self, prelude::*};
+use crev_common::serde::{as_rfc3339_fixed, from_rfc3339_fixed};
+use serde_yaml;
+use std::path::Path;
+
+/// Name of the file keeping notes, in the user data directory
+pub const NOTES_FILE_NAME: &str = "notes.yaml";
+
+/// A note about a crate version, or the crate in general
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Note {
+    pub name: String,
+    /// `None` for notes about all versions
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub version: Option<String>,
+    #[serde(
+        serialize_with = "as_rfc3339_fixed",
+        deserialize_with = "from_rfc3339_fixed"
+    )]
+    pub date: chrono::DateTime<FixedOffset>,
+    pub text: String,
+}
+
+impl Note {
+    pub fn new(name: &str, version: Option<&str>, text: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            version: version.map(ToOwned::to_owned),
+            date: crev_common::now(),
+            text: text.to_owned(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Notes {
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub notes: Vec<Note>,
+}
+
+impl Notes {
+    /// Load notes from `path`; a missing file means no notes
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        serde_yaml::from_str(&util::read_file_to_string(path)?)
+            .map_err(|e| format_err!("Can't parse {}: {}", path.display(), e))
+    }
+
+    pub fn store(&self, path: &Path) -> Result<()> {
+        util::store_str_to_file(path, &serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn add(&mut self, note: Note) {
+        self.notes.push(note);
+    }
+
+    /// Remove notes about the crate version (or the crate in general, for
+    /// `None`); returns the number of removed notes
+    pub fn remove(&mut self, name: &str, version: Option<&str>) -> usize {
+        let len = self.notes.len();
+        self.notes
+            .retain(|note| note.name != name || note.version.as_deref() != version);
+        len - self.notes.len()
+    }
+
+    /// Notes about the crate, relevant for `version`
+    ///
+    /// Notes about the crate in general are always included; with
+    /// `version` being `None`, so are notes about any version.
+    pub fn get(&self, name: &str, version: Option<&str>) -> Vec<&Note> {
+        self.notes
+            .iter()
+            .filter(|note| {
+                note.name == name
+                    && (note.version.is_none()
+                        || version.is_none()
+                        || note.version.as_deref() == version)
+            })
+            .collect()
+    }
+}
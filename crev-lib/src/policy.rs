@@ -0,0 +1,115 @@
+//! Project policy: decisions a project made about its own dependencies
+//!
+//! Kept in a `crev-policy.yaml` file next to the project's root manifest,
+//! so it can be committed and shared by everyone working on the project.
+use crate::{util, Result};
+use chrono::NaiveDate;
+use serde_yaml;
+use std::path::Path;
+
+pub const POLICY_FILE_NAME: &str = "crev-policy.yaml";
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// A dependency accepted despite not being verified
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Exception {
+    #[serde(rename = "crate")]
+    pub name: String,
+    pub version: String,
+    /// Why the risk was accepted
+    pub reason: String,
+    /// Last day (`YYYY-MM-DD`) the exception applies, if limited
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub expires: Option<String>,
+}
+
+impl Exception {
+    fn expiry_date(&self) -> Result<Option<NaiveDate>> {
+        Ok(match &self.expires {
+            Some(date) => Some(NaiveDate::parse_from_str(date, DATE_FORMAT).map_err(|e| {
+                format_err!(
+                    "Invalid expiry date `{}` of exception for {} {}: {}",
+                    date,
+                    self.name,
+                    self.version,
+                    e
+                )
+            })?),
+            None => None,
+        })
+    }
+
+    pub fn is_expired(&self, today: NaiveDate) -> bool {
+        match self.expiry_date() {
+            Ok(Some(expires)) => expires < today,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Policy {
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub exceptions: Vec<Exception>,
+    /// Licenses (SPDX identifiers) that dependencies must not be used under
+    #[serde(
+        rename = "denied-licenses",
+        skip_serializing_if = "Vec::is_empty",
+        default
+    )]
+    pub denied_licenses: Vec<String>,
+}
+
+impl Policy {
+    /// Load the policy from `path`; a missing file means an empty policy
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let policy: Self = serde_yaml::from_str(&util::read_file_to_string(path)?)
+            .map_err(|e| format_err!("Can't parse {}: {}", path.display(), e))?;
+        for exception in &policy.exceptions {
+            exception.expiry_date()?;
+        }
+        Ok(policy)
+    }
+
+    /// Load the policy of the project with the root dir `project_dir`
+    pub fn load_for_project(project_dir: &Path) -> Result<Self> {
+        Self::load(&project_dir.join(POLICY_FILE_NAME))
+    }
+
+    /// Exception for the given package version, if any (expired ones included)
+    pub fn find_exception(&self, name: &str, version: &str) -> Option<&Exception> {
+        self.exceptions
+            .iter()
+            .find(|exception| exception.name == name && exception.version == version)
+    }
+
+    /// Is a package with the `license` expression (eg. `MIT OR Apache-2.0`) denied
+    ///
+    /// A package is denied only if every alternative (`OR`, or the legacy `/`)
+    /// includes a denied license.
+    pub fn is_license_denied(&self, license: &str) -> bool {
+        if self.denied_licenses.is_empty() {
+            return false;
+        }
+        license
+            .split(" OR ")
+            .flat_map(|alternative| alternative.split('/'))
+            .all(|alternative| {
+                alternative
+                    .split(" AND ")
+                    .filter_map(|part| part.split(" WITH ").next())
+                    .map(|part| {
+                        part.trim_matches(|c: char| c == '(' || c == ')' || c.is_whitespace())
+                    })
+                    .any(|part| {
+                        self.denied_licenses
+                            .iter()
+                            .any(|denied| denied.eq_ignore_ascii_case(part))
+                    })
+            })
+    }
+}
@@ -1,10 +1,22 @@
 use chrono::prelude::*;
-use crev_data::proof::Content;
-use std::path::PathBuf;
+use crev_data::{
+    proof::{review, Content, Proof},
+    Id,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
 
 fn type_name(content: &Content) -> (&str, Option<&str>) {
     match content {
         Content::Trust(_) => ("trust", None),
+        Content::Membership(_) => ("membership", None),
+        Content::Issue(_) => ("issues", None),
+        Content::Alternative(_) => ("alternatives", None),
+        Content::IdentityClaim(_) => ("identity-claims", None),
+        Content::Encrypted(_) => ("encrypted", None),
+        Content::Usage(_) => ("usages", None),
         Content::Code(_) => ("reviews", Some("code")),
         Content::Package(_) => ("reviews", Some("packages")),
     }
@@ -32,3 +44,52 @@ pub(crate) fn rel_store_path(content: &Content) -> PathBuf {
     })
     .with_extension("proof.crev")
 }
+
+fn package_review_key(review: &review::Package) -> (Id, String, String, String) {
+    (
+        review.from.id.clone(),
+        review.package.source.clone(),
+        review.package.name.clone(),
+        review.package.version.clone(),
+    )
+}
+
+/// Remove duplicated proofs and superseded package reviews
+///
+/// Proofs are given grouped (typically: by file they are stored in),
+/// and returned in the same grouping and order, with removed proofs
+/// filtered out. A package review is superseded by a more recent review
+/// of the same package version by the same author. All other proofs
+/// are kept.
+pub(crate) fn compact(groups: Vec<Vec<Proof>>) -> Vec<Vec<Proof>> {
+    let mut latest_package_review = HashMap::new();
+    for proof in groups.iter().flatten() {
+        if let Content::Package(ref review) = proof.content {
+            let latest = latest_package_review
+                .entry(package_review_key(review))
+                .or_insert(review.date);
+            if *latest < review.date {
+                *latest = review.date;
+            }
+        }
+    }
+
+    let mut seen_signatures = HashSet::new();
+    groups
+        .into_iter()
+        .map(|group| {
+            group
+                .into_iter()
+                .filter(|proof| {
+                    if !seen_signatures.insert(proof.signature().to_owned()) {
+                        return false;
+                    }
+                    if let Content::Package(ref review) = proof.content {
+                        return latest_package_review[&package_review_key(review)] == review.date;
+                    }
+                    true
+                })
+                .collect()
+        })
+        .collect()
+}
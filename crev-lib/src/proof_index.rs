@@ -0,0 +1,123 @@
+//! Index of proofs of fetched proof repositories
+//!
+//! Parsing and verifying every proof file on every command gets slow as
+//! the number of proofs grows. Proofs of a fetched repository are verified
+//! once, and kept in an index file along with the commit they were read at.
+//! As long as the repository stays at that commit, its proofs are loaded
+//! from the index, without walking the repository and verifying signatures
+//! again. When it moves to another commit, only files changed in between
+//! are read again.
+use crate::Result;
+use crev_data::proof;
+use serde_cbor;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Proofs of a repository at a commit
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ProofIndex {
+    /// Commit (`HEAD`) of the repository the proofs were read at
+    pub head: String,
+    /// Verified proofs, serialized, by the file (relative to the repository) they are in
+    files: BTreeMap<String, Vec<String>>,
+}
+
+/// Commit the git repository in `dir` is at
+pub fn git_head(dir: &Path) -> Option<String> {
+    let repo = git2::Repository::open(dir).ok()?;
+    let head = repo.head().ok()?.target()?;
+    Some(head.to_string())
+}
+
+/// Files (relative to the repository) changed between commits `from` and `to`
+/// of the git repository in `dir`
+pub fn changed_files(dir: &Path, from: &str, to: &str) -> Result<Vec<String>> {
+    let repo = git2::Repository::open(dir)?;
+    let tree = |commit: &str| -> Result<git2::Tree<'_>> {
+        Ok(repo.find_commit(git2::Oid::from_str(commit)?)?.tree()?)
+    };
+    let diff = repo.diff_tree_to_tree(Some(&tree(from)?), Some(&tree(to)?), None)?;
+    let mut files = vec![];
+    for delta in diff.deltas() {
+        for file in &[delta.old_file(), delta.new_file()] {
+            if let Some(path) = file.path().and_then(Path::to_str) {
+                if !files.iter().any(|f| f == path) {
+                    files.push(path.to_owned());
+                }
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Proof files (`*.crev`) under `dir`
+pub fn proof_files(dir: &Path) -> Vec<PathBuf> {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .map(walkdir::DirEntry::into_path)
+        .filter(|path| path.is_file() && path.extension() == Some("crev".as_ref()))
+        .collect()
+}
+
+impl ProofIndex {
+    pub fn new(head: String) -> Self {
+        Self {
+            head,
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Read the index at `path`, if there is a valid one
+    ///
+    /// The index is just a cache; problems with it mean it has to be rebuilt.
+    pub fn load(path: &Path) -> Option<Self> {
+        let file = fs::File::open(path).ok()?;
+        serde_cbor::from_reader(file).ok()
+    }
+
+    pub fn store(&self, path: &Path) -> Result<()> {
+        crev_common::store_to_file_with(path, |mut file| serde_cbor::to_writer(&mut file, self))??;
+        Ok(())
+    }
+
+    /// Set the proofs of the file at `rel_path`; returns the ones it didn't have before
+    pub fn set_file(&mut self, rel_path: &str, proofs: &[proof::Proof]) -> Vec<proof::Proof> {
+        let old = self.files.remove(rel_path).unwrap_or_default();
+        let serialized: Vec<_> = proofs.iter().map(ToString::to_string).collect();
+        let new = proofs
+            .iter()
+            .zip(&serialized)
+            .filter(|(_, s)| !old.contains(s))
+            .map(|(proof, _)| proof.clone())
+            .collect();
+        if !serialized.is_empty() {
+            self.files.insert(rel_path.to_owned(), serialized);
+        }
+        new
+    }
+
+    pub fn remove_file(&mut self, rel_path: &str) {
+        self.files.remove(rel_path);
+    }
+
+    /// All proofs of the index
+    pub fn proofs(&self) -> Option<Vec<proof::Proof>> {
+        let mut proofs = vec![];
+        for serialized in self.files.values().flatten() {
+            proofs.extend(proof::Proof::parse(serialized.as_bytes()).ok()?);
+        }
+        Some(proofs)
+    }
+
+    /// Proofs of the index, if it's for the `head` commit
+    pub fn get(&self, head: &str) -> Option<Vec<proof::Proof>> {
+        if self.head != head {
+            return None;
+        }
+        self.proofs()
+    }
+}
@@ -0,0 +1,169 @@
+//! Index of a proof repository, published along with the proofs
+//!
+//! Lists the proof files with proofs about every crate, and the ones with
+//! proofs not about any particular crate (trust, memberships...). With it,
+//! whoever fetched the repository can load just the proofs relevant to
+//! their dependencies, instead of parsing all of them.
+//!
+//! Proofs can be added without updating the index (eg. by other tools), so
+//! it also records the size of every proof file; an index not matching the
+//! files is outdated, and shouldn't be used.
+use crate::Result;
+use crev_data::proof::{self, Content};
+use serde_yaml;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    path::{Component, Path, PathBuf},
+};
+
+/// Name of the index file, in the root of a proof directory
+pub const INDEX_FILE_NAME: &str = "crev-index.yaml";
+
+const CURRENT_INDEX_VERSION: i64 = 0;
+
+fn cur_version() -> i64 {
+    CURRENT_INDEX_VERSION
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepoIndex {
+    #[serde(default = "cur_version")]
+    version: i64,
+    /// Files with proofs not about any particular crate
+    #[serde(skip_serializing_if = "BTreeSet::is_empty", default)]
+    pub general: BTreeSet<String>,
+    /// Files with proofs about a crate, by crate name
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub crates: BTreeMap<String, BTreeSet<String>>,
+    /// Sizes of all proof files when indexed (proofs are appended to them)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub sizes: BTreeMap<String, u64>,
+}
+
+/// Name of the crate a proof is about, if any
+pub fn crate_name(content: &Content) -> Option<&str> {
+    match content {
+        Content::Package(review) => Some(&review.package.name),
+        Content::Code(review) => Some(&review.package.name),
+        Content::Issue(issue) => Some(&issue.name),
+        Content::Alternative(alternative) => Some(&alternative.name),
+        Content::Usage(usage) => Some(&usage.name),
+        Content::Trust(_)
+        | Content::Membership(_)
+        | Content::IdentityClaim(_)
+        | Content::Encrypted(_) => None,
+    }
+}
+
+/// `path` relative to the proof directory, if it stays in it
+fn parse_rel_path(path: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(path);
+    let stays_inside = path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    if stays_inside && path.extension() == Some("crev".as_ref()) {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+/// Proof files (`*.crev`) in `proofs_dir`, with their relative paths and sizes
+fn proof_files(proofs_dir: &Path) -> Result<Vec<(PathBuf, String, u64)>> {
+    let mut files = vec![];
+    for entry in walkdir::WalkDir::new(proofs_dir) {
+        let entry = entry?;
+        let path = entry.path();
+        if !path.is_file() || path.extension() != Some("crev".as_ref()) {
+            continue;
+        }
+        let rel_path = path
+            .strip_prefix(proofs_dir)?
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+            .join("/");
+        files.push((path.to_owned(), rel_path, entry.metadata()?.len()));
+    }
+    Ok(files)
+}
+
+impl RepoIndex {
+    /// Index proof files (`*.crev`) in `proofs_dir`
+    pub fn build(proofs_dir: &Path) -> Result<Self> {
+        let mut index = Self {
+            version: cur_version(),
+            ..Self::default()
+        };
+        for (path, rel_path, size) in proof_files(proofs_dir)? {
+            index.sizes.insert(rel_path.clone(), size);
+            for proof in proof::Proof::parse_each_from(&path)? {
+                let proof = match proof {
+                    Ok(proof) => proof,
+                    Err(e) => {
+                        warn!("Skipping a proof in {}: {}", path.display(), e);
+                        continue;
+                    }
+                };
+                match crate_name(&proof.content) {
+                    Some(name) => {
+                        index
+                            .crates
+                            .entry(name.to_owned())
+                            .or_default()
+                            .insert(rel_path.clone());
+                    }
+                    None => {
+                        index.general.insert(rel_path.clone());
+                    }
+                }
+            }
+        }
+        Ok(index)
+    }
+
+    /// Does the index still match the proof files in `proofs_dir`
+    ///
+    /// Only the sizes of the files are compared, without reading them.
+    pub fn is_up_to_date(&self, proofs_dir: &Path) -> Result<bool> {
+        let sizes: BTreeMap<String, u64> = proof_files(proofs_dir)?
+            .into_iter()
+            .map(|(_, rel_path, size)| (rel_path, size))
+            .collect();
+        Ok(sizes == self.sizes)
+    }
+
+    /// Read the index in `proofs_dir`, if there is one
+    pub fn load(proofs_dir: &Path) -> Result<Option<Self>> {
+        let path = proofs_dir.join(INDEX_FILE_NAME);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let index: Self = serde_yaml::from_str(&crate::util::read_file_to_string(&path)?)?;
+        if index.version > cur_version() {
+            bail!("Unsupported version of {}", path.display());
+        }
+        Ok(Some(index))
+    }
+
+    pub fn store(&self, proofs_dir: &Path) -> Result<()> {
+        crate::util::store_str_to_file(
+            &proofs_dir.join(INDEX_FILE_NAME),
+            &serde_yaml::to_string(self)?,
+        )?;
+        Ok(())
+    }
+
+    /// Files with proofs about `crates`, and the general ones
+    ///
+    /// Paths leading outside of the proof directory are left out.
+    pub fn files_for(&self, crates: &HashSet<String>) -> BTreeSet<PathBuf> {
+        self.crates
+            .iter()
+            .filter(|(name, _)| crates.contains(*name))
+            .flat_map(|(_, files)| files)
+            .chain(&self.general)
+            .filter_map(|path| parse_rel_path(path))
+            .collect()
+    }
+}
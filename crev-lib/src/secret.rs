@@ -0,0 +1,53 @@
+//! Sources of passphrases protecting CrevIDs
+use crate::Result;
+use crev_common;
+use std::path::Path;
+
+/// Supplies passphrases when a library flow needs them
+///
+/// Lets frontends without a terminal (GUIs, tests) provide them
+/// programmatically.
+pub trait SecretProvider {
+    /// Passphrase to unlock an existing CrevID
+    fn read_passphrase(&self) -> Result<String>;
+
+    /// Passphrase to protect a new CrevID with
+    fn read_new_passphrase(&self) -> Result<String>;
+
+    /// Passphrase of an SSH key file to derive a CrevID from
+    fn read_ssh_key_passphrase(&self, path: &Path) -> Result<String>;
+}
+
+/// Asks on the terminal, unless `CREV_PASSPHRASE` is set
+pub struct TerminalSecretProvider;
+
+impl SecretProvider for TerminalSecretProvider {
+    fn read_passphrase(&self) -> Result<String> {
+        Ok(crev_common::read_passphrase()?)
+    }
+
+    fn read_new_passphrase(&self) -> Result<String> {
+        Ok(crev_common::read_new_passphrase()?)
+    }
+
+    fn read_ssh_key_passphrase(&self, path: &Path) -> Result<String> {
+        Ok(crev_common::read_ssh_key_passphrase(path)?)
+    }
+}
+
+/// Always supplies the same passphrase
+pub struct FixedSecretProvider(pub String);
+
+impl SecretProvider for FixedSecretProvider {
+    fn read_passphrase(&self) -> Result<String> {
+        Ok(self.0.clone())
+    }
+
+    fn read_new_passphrase(&self) -> Result<String> {
+        Ok(self.0.clone())
+    }
+
+    fn read_ssh_key_passphrase(&self, _path: &Path) -> Result<String> {
+        Ok(self.0.clone())
+    }
+}
@@ -0,0 +1,50 @@
+//! Signing with SSH keys held by `ssh-agent`
+use crate::Result;
+use crev_data::ssh;
+use std::env;
+
+#[cfg(unix)]
+fn request(msg: &[u8]) -> Result<Vec<u8>> {
+    use std::io::{Read, Write};
+
+    let path = env::var_os("SSH_AUTH_SOCK")
+        .ok_or_else(|| format_err!("SSH_AUTH_SOCK is not set; is ssh-agent running?"))?;
+    let mut stream = std::os::unix::net::UnixStream::connect(&path)?;
+    stream.write_all(&(msg.len() as u32).to_be_bytes())?;
+    stream.write_all(msg)?;
+    let mut len = [0; 4];
+    stream.read_exact(&mut len)?;
+    let mut reply = vec![0; u32::from_be_bytes(len) as usize];
+    stream.read_exact(&mut reply)?;
+    Ok(reply)
+}
+
+#[cfg(not(unix))]
+fn request(_msg: &[u8]) -> Result<Vec<u8>> {
+    bail!("ssh-agent is supported only on Unix")
+}
+
+/// Ed25519 signature of `data` by a key held by the agent
+///
+/// `public_key` (in the `.pub` file format) picks the key, if the agent
+/// holds more than one ed25519 key.
+pub fn sign_ed25519(public_key: Option<&str>, data: &[u8]) -> Result<Vec<u8>> {
+    let wanted = public_key.map(ssh::read_public_key_blob).transpose()?;
+    let mut keys: Vec<_> =
+        ssh::read_agent_ed25519_identities(&request(&ssh::agent_identities_request())?)?
+            .into_iter()
+            .filter(|(blob, _comment)| wanted.as_ref().is_none_or(|wanted| wanted == blob))
+            .collect();
+    let blob = match keys.len() {
+        0 => bail!("ssh-agent holds no such ed25519 key"),
+        1 => keys.remove(0).0,
+        _ => bail!(
+            "ssh-agent holds several ed25519 keys ({}); pick one by its public key",
+            keys.iter()
+                .map(|(_blob, comment)| comment.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+    ssh::read_agent_ed25519_signature(&request(&ssh::agent_sign_request(&blob, data))?)
+}
@@ -1,9 +1,34 @@
 use super::*;
 
 use crate::trustdb::{self, TrustDB};
-use crev_data::proof::trust::TrustLevel;
+use crev_data::proof::{membership::MembershipStatus, trust::TrustLevel};
 use crev_data::OwnId;
 
+/// Review of a crates.io package by `id`, with a digest of zeros, to be completed
+fn package_review(
+    id: &OwnId,
+    name: &str,
+    version: &str,
+) -> crev_data::proof::review::PackageBuilder {
+    let mut builder = crev_data::proof::review::PackageBuilder::default();
+    builder
+        .from(id.id.to_owned())
+        .package(crev_data::proof::PackageInfo {
+            id: None,
+            source: "https://crates.io".into(),
+            name: name.into(),
+            version: version.into(),
+            digest: vec![0; 32],
+            digest_type: crev_data::proof::default_digest_type(),
+            revision: "".into(),
+            revision_type: crev_data::proof::default_revision_type(),
+            digest_ignore: vec![],
+            checksum: None,
+            repository: None,
+        });
+    builder
+}
+
 #[test]
 fn lock_and_unlock() -> Result<()> {
     let id = OwnId::generate_for_git_url("https://example.com/crev-proofs");
@@ -38,6 +63,7 @@ fn trustdb_distance() -> Result<()> {
         medium_trust_distance: 10,
         low_trust_distance: 100,
         max_distance: 111,
+        ..Default::default()
     };
 
     let a_to_b = a
@@ -79,3 +105,1559 @@ fn trustdb_distance() -> Result<()> {
     assert!(trust_set.contains(e.as_ref()));
     Ok(())
 }
+
+/// Ids `a` to `e`, and a db with trust
+/// `a -(high)-> b -(medium)-> c -(low)-> d -(high)-> e` and `b -(medium)-> d`
+fn trust_graph() -> Result<(Vec<OwnId>, TrustDB)> {
+    let ids: Vec<_> = ["a", "b", "c", "d", "e"]
+        .iter()
+        .map(|name| OwnId::generate_for_git_url(&format!("https://{}", name)))
+        .collect();
+    let mut proofs = vec![];
+    for &(from, to, level) in &[
+        (0, 1, TrustLevel::High),
+        (1, 2, TrustLevel::Medium),
+        (2, 3, TrustLevel::Low),
+        (3, 4, TrustLevel::High),
+        (1, 3, TrustLevel::Medium),
+    ] {
+        proofs.push(
+            ids[from]
+                .create_trust_proof(vec![ids[to].as_pubid().to_owned()], level)?
+                .sign_by(&ids[from])?,
+        );
+    }
+    let mut trustdb = TrustDB::new();
+    trustdb.import_from_iter(proofs.into_iter());
+    Ok((ids, trustdb))
+}
+
+fn graph_distance_params() -> trustdb::TrustDistanceParams {
+    trustdb::TrustDistanceParams {
+        high_trust_distance: 1,
+        medium_trust_distance: 10,
+        low_trust_distance: 100,
+        max_distance: 111,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn trustdb_trust_info() -> Result<()> {
+    let (ids, trustdb) = trust_graph()?;
+    let trust_set = trustdb.calculate_trust_set(ids[0].as_ref(), &graph_distance_params());
+
+    assert_eq!(
+        trust_set.get(ids[3].as_ref()).map(|info| info.distance),
+        Some(11)
+    );
+    assert_eq!(
+        trust_set
+            .get(ids[4].as_ref())
+            .map(|info| info.effective_trust),
+        Some(TrustLevel::High)
+    );
+    assert_eq!(
+        trust_set.get_path(ids[4].as_ref()),
+        Some(vec![
+            ids[0].id.id.clone(),
+            ids[1].id.id.clone(),
+            ids[3].id.id.clone(),
+            ids[4].id.id.clone()
+        ])
+    );
+    Ok(())
+}
+
+#[test]
+fn trustdb_weakest_link_and_min_trust() -> Result<()> {
+    let (ids, trustdb) = trust_graph()?;
+
+    let capped_params = trustdb::TrustDistanceParams {
+        cap_by_weakest_link: true,
+        ..graph_distance_params()
+    };
+    let trust_set = trustdb.calculate_trust_set(ids[0].as_ref(), &capped_params);
+    assert_eq!(
+        trust_set
+            .get(ids[4].as_ref())
+            .map(|info| info.effective_trust),
+        Some(TrustLevel::Medium)
+    );
+
+    let min_trust_params = trustdb::TrustDistanceParams {
+        min_trust: TrustLevel::High,
+        ..capped_params
+    };
+    let trust_set = trustdb.calculate_trust_set(ids[0].as_ref(), &min_trust_params);
+    assert!(trust_set.contains(ids[1].as_ref()));
+    assert!(!trust_set.contains(ids[3].as_ref()));
+    assert!(!trust_set.contains(ids[4].as_ref()));
+    Ok(())
+}
+
+#[test]
+fn trustdb_ignored_ids() -> Result<()> {
+    let (ids, trustdb) = trust_graph()?;
+
+    let ignoring_params = trustdb::TrustDistanceParams {
+        ignored_ids: vec![ids[1].id.id.clone()].into_iter().collect(),
+        ..Default::default()
+    };
+    let trust_set = trustdb.calculate_trust_set(ids[0].as_ref(), &ignoring_params);
+    assert_eq!(trust_set.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn trustdb_ids_adjacent_to_trust_set() -> Result<()> {
+    let (ids, trustdb) = trust_graph()?;
+
+    let params = trustdb::TrustDistanceParams {
+        max_distance: 11,
+        ..graph_distance_params()
+    };
+    let trust_set = trustdb.calculate_trust_set(ids[0].as_ref(), &params);
+    assert!(trust_set.contains(ids[3].as_ref()));
+    assert!(!trust_set.contains(ids[4].as_ref()));
+    // trusted by `d`, left out only because of the distance
+    assert_eq!(
+        trustdb
+            .get_ids_adjacent_to(&trust_set)
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec![ids[4].id.id.clone()]
+    );
+
+    // ignored Ids are not worth a look
+    let trust_set = trustdb.calculate_trust_set(
+        ids[0].as_ref(),
+        &trustdb::TrustDistanceParams {
+            ignored_ids: vec![ids[4].id.id.clone()].into_iter().collect(),
+            ..params
+        },
+    );
+    assert!(trustdb.get_ids_adjacent_to(&trust_set).is_empty());
+    Ok(())
+}
+
+#[test]
+fn trustdb_org_members() -> Result<()> {
+    let a = OwnId::generate_for_git_url("https://a");
+    let org = OwnId::generate_for_git_url("https://org");
+    let m = OwnId::generate_for_git_url("https://m");
+    let n = OwnId::generate_for_git_url("https://n");
+
+    let distance_params = trustdb::TrustDistanceParams {
+        high_trust_distance: 1,
+        medium_trust_distance: 10,
+        low_trust_distance: 100,
+        max_distance: 2,
+        ..Default::default()
+    };
+
+    let a_to_org = a
+        .create_trust_proof(vec![org.as_pubid().to_owned()], TrustLevel::High)?
+        .sign_by(&a)?;
+    let org_to_m = org
+        .create_membership_proof(vec![m.as_pubid().to_owned()], MembershipStatus::Member)?
+        .sign_by(&org)?;
+    // each membership step costs as much as a trust step
+    let m_to_n = m
+        .create_membership_proof(vec![n.as_pubid().to_owned()], MembershipStatus::Member)?
+        .sign_by(&m)?;
+
+    let mut trustdb = TrustDB::new();
+    trustdb.import_from_iter(vec![a_to_org, org_to_m, m_to_n].into_iter());
+
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &distance_params);
+    assert!(trust_set.contains(org.as_ref()));
+    assert!(trust_set.contains(m.as_ref()));
+    assert!(!trust_set.contains(n.as_ref()));
+
+    let mut revoke =
+        org.create_membership_proof(vec![m.as_pubid().to_owned()], MembershipStatus::Revoked)?;
+    revoke.date = revoke.date + chrono::Duration::seconds(1);
+    trustdb.import_from_iter(vec![revoke.sign_by(&org)?].into_iter());
+
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &distance_params);
+    assert!(trust_set.contains(org.as_ref()));
+    assert!(!trust_set.contains(m.as_ref()));
+    Ok(())
+}
+
+#[test]
+fn trustdb_incremental() -> Result<()> {
+    let a = OwnId::generate_for_git_url("https://a");
+    let b = OwnId::generate_for_git_url("https://b");
+    let c = OwnId::generate_for_git_url("https://c");
+    let distance_params = trustdb::TrustDistanceParams::default();
+
+    let a_to_b = a
+        .create_trust_proof(vec![b.as_pubid().to_owned()], TrustLevel::High)?
+        .sign_by(&a)?;
+    let a_to_c = a
+        .create_trust_proof(vec![c.as_pubid().to_owned()], TrustLevel::High)?
+        .sign_by(&a)?;
+
+    let changes = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let mut trustdb = TrustDB::new();
+    {
+        let changes = changes.clone();
+        trustdb.add_listener(move |_| {
+            changes.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        });
+    }
+
+    trustdb.add_proof(&a_to_b)?;
+    trustdb.add_proof(&a_to_b)?;
+    assert!(trustdb
+        .calculate_trust_set(a.as_ref(), &distance_params)
+        .contains(b.as_ref()));
+    assert_eq!(changes.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    let removed = trustdb.replace_proof(&a_to_b.signature, &a_to_c)?;
+    assert_eq!(removed.map(|proof| proof.signature), Some(a_to_b.signature));
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &distance_params);
+    assert!(!trust_set.contains(b.as_ref()));
+    assert!(trust_set.contains(c.as_ref()));
+    assert_eq!(changes.load(std::sync::atomic::Ordering::SeqCst), 3);
+
+    assert!(trustdb.remove_proof(&a_to_c.signature).is_some());
+    assert!(trustdb.proofs().is_empty());
+    Ok(())
+}
+
+#[test]
+fn trustdb_stats() -> Result<()> {
+    let a = OwnId::generate_for_git_url("https://a");
+    let b = OwnId::generate_for_git_url("https://b");
+    let c = OwnId::generate_for_git_url("https://c");
+
+    let a_to_bc = a
+        .create_trust_proof(
+            vec![b.as_pubid().to_owned(), c.as_pubid().to_owned()],
+            TrustLevel::High,
+        )?
+        .sign_by(&a)?;
+
+    let mut trustdb = TrustDB::new();
+    trustdb.import_from_iter(vec![a_to_bc].into_iter());
+
+    let stats = trustdb.get_stats();
+    assert_eq!(stats.id_count, 3);
+    assert_eq!(stats.trust_edge_count, 2);
+    assert!(stats.package_reviews_by_source.is_empty());
+    assert!(stats.oldest_proof_date.is_some());
+    assert_eq!(stats.oldest_proof_date, stats.newest_proof_date);
+
+    let edges = trustdb.get_trust_edges();
+    assert_eq!(edges.len(), 2);
+    assert!(edges.iter().all(|edge| edge.from == *a.as_ref()));
+
+    let trusters = trustdb.get_trusters_of(b.as_ref());
+    assert_eq!(trusters.len(), 1);
+    assert_eq!(trusters[0].from, *a.as_ref());
+    assert_eq!(trusters[0].level, TrustLevel::High);
+    assert!(trustdb.get_trusters_of(a.as_ref()).is_empty());
+    Ok(())
+}
+
+#[test]
+fn compact_removes_superseded_package_reviews() -> Result<()> {
+    let a = OwnId::generate_for_git_url("https://a");
+    let b = OwnId::generate_for_git_url("https://b");
+
+    let package_review = |date: &str| -> Result<crev_data::proof::Proof> {
+        crev_data::proof::review::PackageBuilder::default()
+            .from(a.id.to_owned())
+            .date(chrono::DateTime::parse_from_rfc3339(date)?)
+            .package(crev_data::proof::PackageInfo {
+                id: None,
+                source: "https://crates.io".into(),
+                name: "foo".into(),
+                version: "1.0.0".into(),
+                digest: vec![0; 32],
+                digest_type: crev_data::proof::default_digest_type(),
+                revision: "".into(),
+                revision_type: crev_data::proof::default_revision_type(),
+                digest_ignore: vec![],
+                checksum: None,
+                repository: None,
+            })
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .sign_by(&a)
+    };
+
+    let older = package_review("2018-12-01T00:00:00+00:00")?;
+    let newer = package_review("2018-12-02T00:00:00+00:00")?;
+    let trust = a
+        .create_trust_proof(vec![b.as_pubid().to_owned()], TrustLevel::High)?
+        .sign_by(&a)?;
+
+    let compacted = proof::compact(vec![vec![older, trust.clone()], vec![newer.clone(), trust]]);
+
+    assert_eq!(compacted[0].len(), 1);
+    assert_eq!(compacted[1].len(), 1);
+    assert_eq!(compacted[1][0].signature, newer.signature);
+    Ok(())
+}
+
+#[test]
+fn trustdb_advisories() -> Result<()> {
+    use crev_data::proof::review::{Advisory, AdvisoryRange};
+
+    let a = OwnId::generate_for_git_url("https://a");
+    let b = OwnId::generate_for_git_url("https://b");
+
+    let fix_review = |id: &OwnId, version: &str, range| -> Result<crev_data::proof::Proof> {
+        package_review(id, "foo", version)
+            .advisory(Some(Advisory {
+                ids: vec!["RUSTSEC-2019-0001".into()],
+                severity: crev_data::Level::High,
+                range,
+            }))
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .sign_by(id)
+    };
+
+    let mut trustdb = TrustDB::new();
+    trustdb.import_from_iter(
+        vec![
+            fix_review(&a, "1.2.3", AdvisoryRange::Minor)?,
+            fix_review(&b, "2.0.0", AdvisoryRange::All)?,
+        ]
+        .into_iter(),
+    );
+
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &Default::default());
+    let count = |version| {
+        trustdb
+            .get_advisories_for_version("https://crates.io", "foo", version, &trust_set)
+            .len()
+    };
+    assert_eq!(count("1.2.3"), 0);
+    assert_eq!(count("1.2.1"), 1);
+    assert_eq!(count("1.1.9"), 0);
+    // `b` is not trusted
+    assert_eq!(count("1.3.0"), 0);
+    Ok(())
+}
+
+#[test]
+fn trustdb_issues() -> Result<()> {
+    let a = OwnId::generate_for_git_url("https://a");
+    let b = OwnId::generate_for_git_url("https://b");
+
+    let issue = |id: &OwnId, versions: &str| -> Result<crev_data::proof::Proof> {
+        crev_data::proof::IssueBuilder::default()
+            .from(id.id.to_owned())
+            .source("https://crates.io".into())
+            .name("foo".into())
+            .versions(versions.into())
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .sign_by(id)
+    };
+
+    let proofs = [issue(&a, ">=1.0.0, <1.2.3")?, issue(&b, "*")?];
+    let serialized: String = proofs.iter().map(|proof| proof.to_string()).collect();
+    let parsed = crev_data::proof::Proof::parse_strict(serialized.as_bytes())?;
+    assert_eq!(parsed.len(), 2);
+
+    let mut trustdb = TrustDB::new();
+    trustdb.import_from_iter(parsed.into_iter());
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &Default::default());
+
+    let verify = |version| {
+        trustdb.verify_package_version(&[], "https://crates.io", "foo", version, &trust_set)
+    };
+    assert_eq!(verify("1.1.0"), VerificationStatus::Flagged);
+    assert_eq!(verify("1.2.3"), VerificationStatus::Unknown);
+    // `b` is not trusted
+    assert_eq!(verify("0.9.0"), VerificationStatus::Unknown);
+    Ok(())
+}
+
+#[test]
+fn trustdb_alternatives() -> Result<()> {
+    let a = OwnId::generate_for_git_url("https://a");
+    let b = OwnId::generate_for_git_url("https://b");
+
+    let alternative = |id: &OwnId, alternative: &str| -> Result<crev_data::proof::Proof> {
+        crev_data::proof::AlternativeBuilder::default()
+            .from(id.id.to_owned())
+            .source("https://crates.io".into())
+            .name("foo".into())
+            .alternative(alternative.into())
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .sign_by(id)
+    };
+
+    let proofs = [alternative(&a, "bar")?, alternative(&b, "baz")?];
+    let serialized: String = proofs.iter().map(|proof| proof.to_string()).collect();
+    let parsed = crev_data::proof::Proof::parse_strict(serialized.as_bytes())?;
+
+    let mut trustdb = TrustDB::new();
+    trustdb.import_from_iter(parsed.into_iter());
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &Default::default());
+
+    // `b` is not trusted
+    let alternatives = trustdb.get_alternatives("https://crates.io", "foo", &trust_set);
+    assert_eq!(alternatives.len(), 1);
+    assert_eq!(alternatives[0].alternative, "bar");
+    assert!(trustdb
+        .get_alternatives("https://crates.io", "bar", &trust_set)
+        .is_empty());
+    Ok(())
+}
+
+#[test]
+fn trustdb_usage_count() -> Result<()> {
+    let a = OwnId::generate_for_git_url("https://a");
+    let b = OwnId::generate_for_git_url("https://b");
+
+    let usage = |id: &OwnId, version: &str| -> Result<crev_data::proof::Proof> {
+        crev_data::proof::UsageBuilder::default()
+            .from(id.id.to_owned())
+            .source("https://crates.io".into())
+            .name("foo".into())
+            .package_version(version.into())
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .sign_by(id)
+    };
+
+    let proofs = [
+        usage(&a, "1.0.0")?,
+        usage(&a, "1.0.0")?,
+        usage(&b, "1.0.0")?,
+    ];
+    let serialized: String = proofs.iter().map(|proof| proof.to_string()).collect();
+    let parsed = crev_data::proof::Proof::parse_strict(serialized.as_bytes())?;
+
+    let mut trustdb = TrustDB::new();
+    trustdb.import_from_iter(parsed.into_iter());
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &Default::default());
+
+    // `a` counts once, and `b` is not trusted
+    assert_eq!(
+        trustdb.get_usage_count("https://crates.io", "foo", "1.0.0", &trust_set),
+        1
+    );
+    assert_eq!(
+        trustdb.get_usage_count("https://crates.io", "foo", "1.0.1", &trust_set),
+        0
+    );
+    Ok(())
+}
+
+#[test]
+fn trustdb_reviews_applying_to_versions() -> Result<()> {
+    let a = OwnId::generate_for_git_url("https://a");
+
+    let review = crev_data::proof::review::PackageBuilder::default()
+        .from(a.id.to_owned())
+        .package(crev_data::proof::PackageInfo {
+            id: None,
+            source: "https://crates.io".into(),
+            name: "foo".into(),
+            version: "1.2.0".into(),
+            digest: vec![0; 32],
+            digest_type: crev_data::proof::default_digest_type(),
+            revision: "".into(),
+            revision_type: crev_data::proof::default_revision_type(),
+            digest_ignore: vec![],
+            checksum: None,
+            repository: None,
+        })
+        .applies_to(Some(">=1.2.0, <1.3.0".into()))
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .sign_by(&a)?;
+
+    let mut trustdb = TrustDB::new();
+    trustdb.import_from_iter(vec![review].into_iter());
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &Default::default());
+
+    let count = |version| {
+        trustdb
+            .get_package_reviews_for_package("https://crates.io", Some("foo"), Some(version))
+            .count()
+    };
+    assert_eq!(count("1.2.0"), 1);
+    assert_eq!(count("1.2.5"), 1);
+    assert_eq!(count("1.3.0"), 0);
+
+    let verify = |version| {
+        trustdb.verify_package_version(&[], "https://crates.io", "foo", version, &trust_set)
+    };
+    assert_eq!(verify("1.2.5"), VerificationStatus::Verified);
+    assert_eq!(verify("1.3.0"), VerificationStatus::Unknown);
+    assert_eq!(
+        trustdb.get_verifying_reviewers(&[], "https://crates.io", "foo", "1.2.5", &trust_set),
+        vec![a.id.id.clone()]
+    );
+    Ok(())
+}
+
+#[test]
+fn trustdb_search_review_comments() -> Result<()> {
+    let a = OwnId::generate_for_git_url("https://a");
+
+    let review = |name: &str, comment: &str| -> Result<crev_data::proof::Proof> {
+        crev_data::proof::review::PackageBuilder::default()
+            .from(a.id.to_owned())
+            .package(crev_data::proof::PackageInfo {
+                id: None,
+                source: "https://crates.io".into(),
+                name: name.into(),
+                version: "1.0.0".into(),
+                digest: vec![0; 32],
+                digest_type: crev_data::proof::default_digest_type(),
+                revision: "".into(),
+                revision_type: crev_data::proof::default_revision_type(),
+                digest_ignore: vec![],
+                checksum: None,
+                repository: None,
+            })
+            .comment(comment.into())
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .sign_by(&a)
+    };
+
+    let mut trustdb = TrustDB::new();
+    trustdb.import_from_iter(
+        vec![
+            review("foo", "Uses an unsafe transmute, but it's sound")?,
+            review("bar", "Unsound: the transmute is not checked")?,
+        ]
+        .into_iter(),
+    );
+
+    let search = |query| -> Vec<String> {
+        trustdb
+            .search_package_reviews(query)
+            .map(|review| review.package.name)
+            .collect()
+    };
+    assert_eq!(search("transmute").len(), 2);
+    assert_eq!(search("UNSAFE transm"), vec!["foo"]);
+    assert_eq!(search("unsound"), vec!["bar"]);
+    assert!(search("unsafe unsound").is_empty());
+    Ok(())
+}
+
+#[test]
+fn trustdb_verify_across_digest_types() -> Result<()> {
+    let a = OwnId::generate_for_git_url("https://a");
+    let b = OwnId::generate_for_git_url("https://b");
+
+    let dir = tempdir::TempDir::new("crev-digest-test")?;
+    std::fs::write(dir.path().join("lib.rs"), "fn main() {}")?;
+    let ignore_list: HashSet<PathBuf> = HashSet::new();
+
+    let review = |id: &OwnId, digest_type: DigestType, review| -> Result<crev_data::proof::Proof> {
+        crev_data::proof::review::PackageBuilder::default()
+            .from(id.id.to_owned())
+            .package(crev_data::proof::PackageInfo {
+                id: None,
+                source: "https://crates.io".into(),
+                name: "foo".into(),
+                version: "1.0.0".into(),
+                digest: get_dir_digest_of_type(
+                    dir.path(),
+                    &ignore_list,
+                    digest_type,
+                    &DigestOptions::default(),
+                )?
+                .into_vec(),
+                digest_type: digest_type.to_string(),
+                revision: "".into(),
+                revision_type: crev_data::proof::default_revision_type(),
+                digest_ignore: vec![],
+                checksum: None,
+                repository: None,
+            })
+            .review(review)
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .sign_by(id)
+    };
+
+    let mut trustdb = TrustDB::new();
+    trustdb.import_from_iter(
+        vec![review(
+            &a,
+            DigestType::Sha256,
+            crev_data::Review::new_positive(),
+        )?]
+        .into_iter(),
+    );
+    trustdb.import_from_iter(
+        vec![a
+            .create_trust_proof(vec![b.as_pubid().to_owned()], TrustLevel::High)?
+            .sign_by(&a)?]
+        .into_iter(),
+    );
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &Default::default());
+    assert_eq!(
+        get_dir_digests(
+            dir.path(),
+            &ignore_list,
+            &DigestOptions::default(),
+            &trustdb
+        )?
+        .len(),
+        2
+    );
+    assert!(
+        match dir_verify(dir.path(), &ignore_list, &trustdb, &trust_set)? {
+            VerificationStatus::Verified => true,
+            _ => false,
+        }
+    );
+    let digests = get_dir_digests(
+        dir.path(),
+        &ignore_list,
+        &DigestOptions::default(),
+        &trustdb,
+    )?;
+    assert_eq!(
+        trustdb.get_verifying_reviewers(&digests, "https://crates.io", "foo", "1.0.0", &trust_set),
+        vec![a.id.id.clone()]
+    );
+
+    trustdb.import_from_iter(
+        vec![review(
+            &b,
+            DigestType::Blake2b,
+            crev_data::Review::new_negative(),
+        )?]
+        .into_iter(),
+    );
+    assert!(
+        match dir_verify(dir.path(), &ignore_list, &trustdb, &trust_set)? {
+            VerificationStatus::Flagged => true,
+            _ => false,
+        }
+    );
+    let digests = get_dir_digests(
+        dir.path(),
+        &ignore_list,
+        &DigestOptions::default(),
+        &trustdb,
+    )?;
+    assert_eq!(
+        trustdb.get_verifying_reviewers(&digests, "https://crates.io", "foo", "1.0.0", &trust_set),
+        vec![b.id.id.clone()]
+    );
+    Ok(())
+}
+
+#[test]
+fn git_commit_signed_by_id() -> Result<()> {
+    use crate::util::git::{self, CommitSignature};
+
+    let id = OwnId::generate_for_git_url("https://a");
+    let dir = tempdir::TempDir::new("crev-git-test")?;
+    let repo = git2::Repository::init(dir.path())?;
+    {
+        let mut config = repo.config()?;
+        config.set_str("user.name", "Test")?;
+        config.set_str("user.email", "test@example.com")?;
+    }
+
+    let first = git::commit_all_signed_by(&repo, &id, "First")?;
+    let second = git::commit_all_signed_by(&repo, &id, "Second")?;
+
+    assert_eq!(repo.head()?.peel_to_commit()?.id(), second);
+    assert_eq!(repo.find_commit(second)?.parent_id(0)?, first);
+    assert_eq!(
+        git::verify_commit_signature(&repo, second)?,
+        CommitSignature::Valid(id.id.id.clone())
+    );
+    Ok(())
+}
+
+#[test]
+fn policy_exceptions() -> Result<()> {
+    let tmp_dir = tempdir::TempDir::new("crev-policy")?;
+    assert!(policy::Policy::load_for_project(tmp_dir.path())?
+        .exceptions
+        .is_empty());
+
+    let path = tmp_dir.path().join(policy::POLICY_FILE_NAME);
+    util::store_str_to_file(
+        &path,
+        "exceptions:
+  - crate: foo
+    version: 1.0.0
+    reason: used only in tests
+    expires: 2019-06-30
+",
+    )?;
+    let policy = policy::Policy::load(&path)?;
+    assert!(policy.find_exception("foo", "1.0.1").is_none());
+    let exception = policy.find_exception("foo", "1.0.0").unwrap();
+    assert!(!exception.is_expired(chrono::NaiveDate::from_ymd(2019, 6, 30)));
+    assert!(exception.is_expired(chrono::NaiveDate::from_ymd(2019, 7, 1)));
+
+    util::store_str_to_file(
+        &path,
+        "exceptions:
+  - crate: foo
+    version: 1.0.0
+    reason: used only in tests
+    expires: next month
+",
+    )?;
+    assert!(policy::Policy::load(&path).is_err());
+    Ok(())
+}
+
+#[test]
+fn local_reviews() -> Result<()> {
+    let tmp_dir = tempdir::TempDir::new("crev-local-reviews")?;
+    let path = tmp_dir.path().join(local_review::LOCAL_REVIEWS_FILE_NAME);
+    let digest = crev_data::Digest::from_vec(crev_common::blake2b256sum(b"foo"));
+    let other = crev_data::Digest::from_vec(crev_common::blake2b256sum(b"bar"));
+
+    let mut reviews = local_review::LocalReviews::load(&path)?;
+    reviews.add(local_review::LocalReview::new(
+        "foo",
+        "1.0.0",
+        DigestType::Blake2b,
+        &other,
+        "",
+    ));
+    // replaces the review of the same version
+    reviews.add(local_review::LocalReview::new(
+        "foo",
+        "1.0.0",
+        DigestType::Blake2b,
+        &digest,
+        "looked fine",
+    ));
+    reviews.store(&path)?;
+
+    let mut reviews = local_review::LocalReviews::load(&path)?;
+    assert_eq!(reviews.reviews.len(), 1);
+    assert!(reviews.find(&[(DigestType::Blake2b, other)]).is_none());
+    assert!(reviews
+        .find(&[(DigestType::Sha256, digest.clone())])
+        .is_none());
+    assert_eq!(
+        reviews
+            .find(&[(DigestType::Blake2b, digest.clone())])
+            .map(|review| review.comment.as_str()),
+        Some("looked fine")
+    );
+
+    assert!(!reviews.remove("foo", "1.0.1"));
+    assert!(reviews.remove("foo", "1.0.0"));
+    assert!(reviews.find(&[(DigestType::Blake2b, digest)]).is_none());
+    Ok(())
+}
+
+#[test]
+fn digest_pins() -> Result<()> {
+    use crate::digest_pin::{DigestPins, PinSettings, DIGEST_PINS_FILE_NAME};
+    let tmp_dir = tempdir::TempDir::new("crev-digest-pins")?;
+    let path = tmp_dir.path().join(DIGEST_PINS_FILE_NAME);
+    let registry = "registry+https://github.com/rust-lang/crates.io-index";
+    let settings = PinSettings::new(&[], &Default::default());
+    let no_tests = PinSettings::new(&["tests".into()], &Default::default());
+
+    let mut pins = DigestPins::load(&path)?;
+    assert!(pins
+        .check(registry, "foo", "1.0.0", "digest", &settings)
+        .is_none());
+    assert!(pins.is_changed());
+    pins.store(&path)?;
+
+    let mut pins = DigestPins::load(&path)?;
+    assert!(pins
+        .check(registry, "foo", "1.0.0", "digest", &settings)
+        .is_none());
+    // pinned separately for other settings, and other sources
+    assert!(pins
+        .check(registry, "foo", "1.0.0", "other", &no_tests)
+        .is_none());
+    assert!(pins
+        .check("git+https://a#1234", "foo", "1.0.0", "other", &settings)
+        .is_none());
+    assert_eq!(
+        pins.check(registry, "foo", "1.0.0", "tampered", &settings)
+            .map(|pin| pin.digest),
+        Some("digest".to_owned())
+    );
+    pins.repin(registry, "foo", "1.0.0", "tampered", &settings);
+    assert!(pins
+        .check(registry, "foo", "1.0.0", "tampered", &settings)
+        .is_none());
+    Ok(())
+}
+
+#[test]
+fn notes() {
+    let mut notes = note::Notes::default();
+    notes.add(note::Note::new("foo", None, "waiting on 2.0"));
+    notes.add(note::Note::new(
+        "foo",
+        Some("1.0.0"),
+        "check the ffi module",
+    ));
+    notes.add(note::Note::new("bar", Some("1.0.0"), "fine"));
+
+    assert_eq!(notes.get("foo", None).len(), 2);
+    assert_eq!(notes.get("foo", Some("1.0.0")).len(), 2);
+    assert_eq!(notes.get("foo", Some("1.1.0")).len(), 1);
+
+    assert_eq!(notes.remove("foo", Some("1.1.0")), 0);
+    assert_eq!(notes.remove("foo", Some("1.0.0")), 1);
+    assert_eq!(notes.get("foo", None)[0].text, "waiting on 2.0");
+}
+
+#[test]
+fn policy_denied_licenses() {
+    let policy = policy::Policy {
+        denied_licenses: vec!["GPL-3.0".into()],
+        ..Default::default()
+    };
+    assert!(policy.is_license_denied("GPL-3.0"));
+    assert!(policy.is_license_denied("MIT AND GPL-3.0"));
+    assert!(!policy.is_license_denied("MIT OR GPL-3.0"));
+    assert!(!policy.is_license_denied("MIT/Apache-2.0"));
+    assert!(!policy::Policy::default().is_license_denied("GPL-3.0"));
+}
+
+#[test]
+fn trust_list_parse() -> Result<()> {
+    let a = OwnId::generate_for_git_url("https://a");
+    let b = OwnId::generate_for_git_url("https://b");
+    let list = format!(
+        "# seed list\n{} https://a\n\n  {}\thttps://b # maintainer of b\n",
+        a.id.id, b.id.id
+    );
+    let entries = trust_list::parse(&list)?;
+    assert_eq!(
+        entries,
+        vec![
+            trust_list::Entry {
+                id: a.id.id.clone(),
+                url: "https://a".into()
+            },
+            trust_list::Entry {
+                id: b.id.id.clone(),
+                url: "https://b".into()
+            },
+        ]
+    );
+
+    assert!(trust_list::parse(&format!("{}\n", a.id.id)).is_err());
+    assert!(trust_list::parse("not-an-id https://a\n").is_err());
+    Ok(())
+}
+
+#[test]
+fn trustdb_reviewer_stats() -> Result<()> {
+    let a = OwnId::generate_for_git_url("https://a");
+    let b = OwnId::generate_for_git_url("https://b");
+
+    let review = |name: &str, version: &str, thoroughness| -> Result<crev_data::proof::Proof> {
+        crev_data::proof::review::PackageBuilder::default()
+            .from(b.id.to_owned())
+            .package(crev_data::proof::PackageInfo {
+                id: None,
+                source: "https://crates.io".into(),
+                name: name.into(),
+                version: version.into(),
+                digest: vec![0; 32],
+                digest_type: crev_data::proof::default_digest_type(),
+                revision: "".into(),
+                revision_type: crev_data::proof::default_revision_type(),
+                digest_ignore: vec![],
+                checksum: None,
+                repository: None,
+            })
+            .review(crev_data::proof::review::Review {
+                thoroughness,
+                ..Default::default()
+            })
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .sign_by(&b)
+    };
+
+    let proofs = vec![
+        a.create_trust_proof(vec![b.as_pubid().to_owned()], TrustLevel::Low)?
+            .sign_by(&a)?,
+        review("foo", "1.0.0", crev_data::Level::High)?,
+        review("foo", "1.1.0", crev_data::Level::Low)?,
+        review("bar", "0.1.0", crev_data::Level::Medium)?,
+    ];
+    let mut trustdb = TrustDB::new();
+    trustdb.import_from_iter(proofs.into_iter());
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &Default::default());
+
+    let stats = trustdb.get_reviewer_stats(&trust_set);
+    assert_eq!(stats[0].id, b.id.id);
+    assert_eq!(stats[0].review_count, 3);
+    assert_eq!(stats[0].package_count, 2);
+    assert_eq!(stats[0].average_thoroughness, Some(2.0));
+    assert_eq!(stats[0].trusted_by, 1);
+    assert!(stats[0].last_activity.is_some());
+
+    let a_stats = stats.iter().find(|s| s.id == a.id.id).expect("a is known");
+    assert_eq!(a_stats.review_count, 0);
+    assert_eq!(a_stats.average_thoroughness, None);
+    assert_eq!(a_stats.trusted_by, 0);
+    Ok(())
+}
+
+#[test]
+fn trustdb_max_review_age() -> Result<()> {
+    let a = OwnId::generate_for_git_url("https://a");
+
+    let review = crev_data::proof::review::PackageBuilder::default()
+        .from(a.id.to_owned())
+        .date(crev_common::now() - chrono::Duration::days(100))
+        .package(crev_data::proof::PackageInfo {
+            id: None,
+            source: "https://crates.io".into(),
+            name: "foo".into(),
+            version: "1.0.0".into(),
+            digest: vec![0; 32],
+            digest_type: crev_data::proof::default_digest_type(),
+            revision: "".into(),
+            revision_type: crev_data::proof::default_revision_type(),
+            digest_ignore: vec![],
+            checksum: None,
+            repository: None,
+        })
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .sign_by(&a)?;
+
+    let mut trustdb = TrustDB::new();
+    trustdb.import_from_iter(vec![review].into_iter());
+    let digests = [(DigestType::Blake2b, Digest::from_vec(vec![0; 32]))];
+    let verify = |max_review_age| {
+        let trust_set = trustdb.calculate_trust_set(
+            a.as_ref(),
+            &trustdb::TrustDistanceParams {
+                max_review_age,
+                ..Default::default()
+            },
+        );
+        (
+            trustdb.verify_package_version(
+                &digests,
+                "https://crates.io",
+                "foo",
+                "1.0.0",
+                &trust_set,
+            ),
+            trustdb
+                .get_verifying_review_date(
+                    &digests,
+                    "https://crates.io",
+                    "foo",
+                    "1.0.0",
+                    &trust_set,
+                )
+                .map(|date| (chrono::Utc::now() - date).num_days()),
+        )
+    };
+    assert_eq!(verify(None), (VerificationStatus::Verified, Some(100)));
+    assert_eq!(
+        verify(Some(chrono::Duration::days(365))),
+        (VerificationStatus::Verified, Some(100))
+    );
+    assert_eq!(
+        verify(Some(chrono::Duration::days(30))),
+        (VerificationStatus::Unknown, None)
+    );
+
+    // the age is counted from `as_of`
+    let trust_set = trustdb.calculate_trust_set(
+        a.as_ref(),
+        &trustdb::TrustDistanceParams {
+            max_review_age: Some(chrono::Duration::days(30)),
+            as_of: Some(chrono::Utc::now() - chrono::Duration::days(90)),
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        trustdb.verify_package_version(&digests, "https://crates.io", "foo", "1.0.0", &trust_set),
+        VerificationStatus::Verified
+    );
+    Ok(())
+}
+
+#[test]
+fn trustdb_as_of() -> Result<()> {
+    let a = OwnId::generate_for_git_url("https://a");
+    let review = |version: &str, age| -> Result<_> {
+        package_review(&a, "foo", version)
+            .date(crev_common::now() - chrono::Duration::days(age))
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .sign_by(&a)
+    };
+    let proofs = vec![review("1.0.0", 100)?, review("2.0.0", 10)?];
+
+    let params = trustdb::TrustDistanceParams {
+        as_of: Some(chrono::Utc::now() - chrono::Duration::days(50)),
+        ..Default::default()
+    };
+    let mut trustdb = TrustDB::new();
+    trustdb.import_from_iter(proofs.into_iter().filter(|proof| params.is_in_time(proof)));
+    let count = |version| {
+        trustdb
+            .get_package_reviews_for_package("https://crates.io", Some("foo"), Some(version))
+            .count()
+    };
+    assert_eq!(count("1.0.0"), 1);
+    assert_eq!(count("2.0.0"), 0);
+    Ok(())
+}
+
+#[test]
+fn trustdb_review_criteria() -> Result<()> {
+    let a = OwnId::generate_for_git_url("https://a");
+    let b = OwnId::generate_for_git_url("https://b");
+
+    let review = |id: &OwnId, name: &str, rating, criteria: &[&str]| {
+        crev_data::proof::review::PackageBuilder::default()
+            .from(id.id.to_owned())
+            .package(crev_data::proof::PackageInfo {
+                id: None,
+                source: "https://crates.io".into(),
+                name: name.into(),
+                version: "1.0.0".into(),
+                digest: crev_common::blake2b256sum(name.as_bytes()),
+                digest_type: crev_data::proof::default_digest_type(),
+                revision: "".into(),
+                revision_type: crev_data::proof::default_revision_type(),
+                digest_ignore: vec![],
+                checksum: None,
+                repository: None,
+            })
+            .review(crev_data::proof::review::Review {
+                rating,
+                criteria: criteria.iter().map(|c| c.to_string()).collect(),
+                ..Default::default()
+            })
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .sign_by(id)
+    };
+    use crev_data::proof::review::Rating;
+    let proofs = vec![
+        a.create_trust_proof(vec![b.as_pubid().to_owned()], TrustLevel::High)?
+            .sign_by(&a)?,
+        review(&a, "foo", Rating::Positive, &["safe-to-run", "no-unsafe"])?,
+        review(&a, "bar", Rating::Positive, &[])?,
+        review(&b, "bar", Rating::Negative, &[])?,
+    ];
+    let mut trustdb = TrustDB::new();
+    trustdb.import_from_iter(proofs.into_iter());
+
+    let verify = |name: &str, criteria: &[&str]| {
+        let trust_set = trustdb.calculate_trust_set(
+            a.as_ref(),
+            &trustdb::TrustDistanceParams {
+                criteria: criteria.iter().map(|c| c.to_string()).collect(),
+                ..Default::default()
+            },
+        );
+        let digests = [(
+            DigestType::Blake2b,
+            Digest::from_vec(crev_common::blake2b256sum(name.as_bytes())),
+        )];
+        trustdb.verify_package_version(&digests, "https://crates.io", name, "1.0.0", &trust_set)
+    };
+    assert_eq!(verify("foo", &[]), VerificationStatus::Verified);
+    assert_eq!(verify("foo", &["no-unsafe"]), VerificationStatus::Verified);
+    assert_eq!(
+        verify("foo", &["no-unsafe", "safe-to-deploy"]),
+        VerificationStatus::Unknown
+    );
+    // negative reviews count regardless of criteria
+    assert_eq!(verify("bar", &["safe-to-run"]), VerificationStatus::Flagged);
+    Ok(())
+}
+
+#[test]
+fn file_lock_is_reentrant() -> Result<()> {
+    let dir = tempdir::TempDir::new("crev")?;
+    let path = dir.path().join("crev.lock");
+    let lock = lock::FileLock::new(path.clone());
+    {
+        let _outer = lock.lock()?;
+        let _inner = lock.lock()?;
+    }
+    // released, so another handle (as in another process) gets it right away
+    let other = lock::FileLock::new(path);
+    let _guard = other.lock()?;
+    Ok(())
+}
+
+#[test]
+fn git_commit_push_pull_without_git_binary() -> Result<()> {
+    use crate::util::git;
+    use std::fs;
+
+    let dir = tempdir::TempDir::new("crev-git-test")?;
+    let origin_path = dir.path().join("origin");
+    git2::Repository::init_bare(&origin_path)?;
+    let origin_url = format!("file://{}", origin_path.display());
+    let clone = |name: &str| -> Result<git2::Repository> {
+        let repo = git2::Repository::clone(&origin_url, dir.path().join(name))?;
+        {
+            let mut config = repo.config()?;
+            config.set_str("user.name", "Test")?;
+            config.set_str("user.email", "test@example.com")?;
+        }
+        Ok(repo)
+    };
+
+    let a = clone("a")?;
+    let b = clone("b")?;
+    let a_dir = a.workdir().expect("not bare").to_owned();
+    fs::write(a_dir.join("proof.crev"), "one\n")?;
+    let mut index = a.index()?;
+    index.add_path(Path::new("proof.crev"))?;
+    index.write()?;
+    git::commit_all(&a, "*", "First")?;
+    git::push(&a)?;
+
+    fs::write(a_dir.join("proof.crev"), "one\ntwo\n")?;
+    assert!(git::diff_head(&a, "*")?.contains("+two\n"));
+    git::commit_all(&a, "*", "Second")?;
+    assert!(git::commit_all(&a, "*", "Nothing").is_err());
+    git::push(&a)?;
+
+    git::pull(&b)?;
+    assert_eq!(
+        fs::read_to_string(b.workdir().expect("not bare").join("proof.crev"))?,
+        "one\ntwo\n"
+    );
+    assert_eq!(b.head()?.peel_to_commit()?.message(), Some("Second"));
+    Ok(())
+}
+
+#[test]
+fn git_commit_and_diff_restricted_to_subdir() -> Result<()> {
+    use crate::util::git;
+    use std::fs;
+
+    let dir = tempdir::TempDir::new("crev-git-test")?;
+    let repo = git2::Repository::init(dir.path())?;
+    {
+        let mut config = repo.config()?;
+        config.set_str("user.name", "Test")?;
+        config.set_str("user.email", "test@example.com")?;
+    }
+    fs::create_dir(dir.path().join("proofs"))?;
+    fs::write(dir.path().join("proofs/proof.crev"), "one\n")?;
+    fs::write(dir.path().join(".bashrc"), "one\n")?;
+    let mut index = repo.index()?;
+    index.add_path(Path::new("proofs/proof.crev"))?;
+    index.add_path(Path::new(".bashrc"))?;
+    index.write()?;
+    git::commit_all(&repo, "*", "First")?;
+
+    fs::write(dir.path().join("proofs/proof.crev"), "one\ntwo\n")?;
+    fs::write(dir.path().join(".bashrc"), "one\nthree\n")?;
+    let diff = git::diff_head(&repo, "proofs")?;
+    assert!(diff.contains("+two\n"));
+    assert!(!diff.contains("+three\n"));
+
+    git::commit_all(&repo, "proofs", "Second")?;
+    assert!(git::commit_all(&repo, "proofs", "Nothing").is_err());
+    assert!(git::diff_head(&repo, "*")?.contains("+three\n"));
+    Ok(())
+}
+
+#[test]
+fn proof_routes() -> Result<()> {
+    let a = OwnId::generate_for_git_url("https://a");
+    let issue = |name: &str| -> Result<crev_data::proof::Content> {
+        Ok(crev_data::proof::IssueBuilder::default()
+            .from(a.id.to_owned())
+            .source("https://crates.io".into())
+            .name(name.into())
+            .versions("*".into())
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .into())
+    };
+    let trust: crev_data::proof::Content = a
+        .create_trust_proof(vec![a.id.to_owned()], TrustLevel::High)?
+        .into();
+
+    let route: local::ProofRoute = serde_yaml::from_str(
+        "url: https://git.acme.com/crev-proofs\npackages: [acme-*, internal]\n",
+    )?;
+    assert!(route.matches(&issue("acme-log")?));
+    assert!(route.matches(&issue("internal")?));
+    assert!(!route.matches(&issue("internal-log")?));
+    assert!(!route.matches(&trust));
+
+    let route: local::ProofRoute =
+        serde_yaml::from_str("url: https://git.acme.com/crev-proofs\nproof-types: [trust]\n")?;
+    assert!(route.matches(&trust));
+    assert!(!route.matches(&issue("acme-log")?));
+    Ok(())
+}
+
+#[test]
+fn transparency_log_anchor() -> Result<()> {
+    use crate::transparency::{self, Anchor};
+    use sha2::{Digest as _, Sha256};
+
+    let a = OwnId::generate_for_git_url("https://a");
+    let review = crev_data::proof::review::PackageBuilder::default()
+        .from(a.id.to_owned())
+        .date(crev_common::now() - chrono::Duration::days(3))
+        .package(crev_data::proof::PackageInfo {
+            id: None,
+            source: "https://crates.io".into(),
+            name: "foo".into(),
+            version: "1.0.0".into(),
+            digest: vec![0; 32],
+            digest_type: crev_data::proof::default_digest_type(),
+            revision: "".into(),
+            revision_type: crev_data::proof::default_revision_type(),
+            digest_ignore: vec![],
+            checksum: None,
+            repository: None,
+        })
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .sign_by(&a)?;
+
+    // a tree of 3 leaves, with the entry of the review last
+    let body = transparency::entry_body(&review)?;
+    let leaf = |data: &[u8]| Sha256::digest(&[&[0u8], data].concat()).to_vec();
+    let node = |l: &[u8], r: &[u8]| Sha256::digest(&[&[1u8], l, r].concat()).to_vec();
+    let left = node(&leaf(b"a"), &leaf(b"b"));
+    let root = node(&left, &leaf(body.as_bytes()));
+    assert!(transparency::verify_inclusion(
+        b"a",
+        0,
+        3,
+        &[leaf(b"b"), leaf(body.as_bytes())],
+        &root
+    ));
+    assert!(!transparency::verify_inclusion(
+        b"b",
+        0,
+        3,
+        &[leaf(b"a"), leaf(body.as_bytes())],
+        &root
+    ));
+
+    // a log, and checkpoints of its tree signed by it, or by someone else
+    let log_key = || -> Result<openssl::pkey::PKey<openssl::pkey::Private>> {
+        let group = openssl::ec::EcGroup::from_curve_name(openssl::nid::Nid::X9_62_PRIME256V1)?;
+        Ok(openssl::pkey::PKey::from_ec_key(
+            openssl::ec::EcKey::generate(&group)?,
+        )?)
+    };
+    let key = log_key()?;
+    let log = transparency::Log::new(
+        "https://log/",
+        std::str::from_utf8(&key.public_key_to_pem()?)?,
+    )?;
+    let checkpoint = |key: &openssl::pkey::PKey<_>, size: u64, root: &[u8]| -> Result<String> {
+        let text = format!("log\n{}\n{}\n", size, openssl::base64::encode_block(root));
+        let mut signer = openssl::sign::Signer::new(openssl::hash::MessageDigest::sha256(), key)?;
+        let signature = [&[0u8; 4][..], &signer.sign_oneshot_to_vec(text.as_bytes())?].concat();
+        Ok(format!(
+            "{}\n\u{2014} log {}\n",
+            text,
+            openssl::base64::encode_block(&signature)
+        ))
+    };
+    assert_eq!(
+        log.verify_checkpoint(&checkpoint(&key, 3, &root)?)?,
+        transparency::TreeHead {
+            size: 3,
+            root_hash: root.clone()
+        }
+    );
+
+    let mut anchor = Anchor {
+        signature: review.signature().to_owned(),
+        log: "https://log".into(),
+        entry_id: "0".into(),
+        log_index: 2,
+        integrated_time: review.content.date().timestamp() + 60,
+        body: body.clone(),
+        checkpoint: checkpoint(&key, 3, &root)?,
+        hashes: vec![hex::encode(&left)],
+    };
+    assert!(anchor.check(&log, &review).is_empty());
+
+    anchor.integrated_time += 3 * 24 * 60 * 60;
+    assert!(anchor.check(&log, &review)[0].message.contains("backdated"));
+
+    // a made-up tree of just the entry, not signed by the log
+    let made_up = Anchor {
+        log_index: 0,
+        checkpoint: checkpoint(&log_key()?, 1, &leaf(body.as_bytes()))?,
+        hashes: vec![],
+        ..anchor.clone()
+    };
+    assert!(made_up.verify(&log, &review).is_err());
+
+    anchor.checkpoint = checkpoint(&key, 4, &root)?;
+    assert!(anchor.verify(&log, &review).is_err());
+
+    let retracted = transparency::check_anchors(
+        &log,
+        vec![(anchor.signature.clone(), anchor)]
+            .into_iter()
+            .collect(),
+        &[],
+    );
+    assert!(retracted[0].message.contains("no longer published"));
+    Ok(())
+}
+
+#[test]
+fn proof_index() -> Result<()> {
+    use crate::proof_index::ProofIndex;
+
+    let a = OwnId::generate_for_git_url("https://a");
+    let b = OwnId::generate_for_git_url("https://b");
+    let proofs = vec![
+        a.create_trust_proof(vec![b.as_pubid().to_owned()], TrustLevel::High)?
+            .sign_by(&a)?,
+        b.create_trust_proof(vec![a.as_pubid().to_owned()], TrustLevel::Low)?
+            .sign_by(&b)?,
+    ];
+
+    let tmp_dir = tempdir::TempDir::new("crev-proof-index")?;
+    let path = tmp_dir.path().join("index.cbor");
+    assert!(ProofIndex::load(&path).is_none());
+    let mut index = ProofIndex::new("1234".into());
+    assert_eq!(index.set_file("a/trust.proof.crev", &proofs[..1]).len(), 1);
+    // only proofs not in the file before are new
+    let new = index.set_file("a/trust.proof.crev", &proofs);
+    assert_eq!(new.len(), 1);
+    assert_eq!(new[0].signature(), proofs[1].signature());
+    index.set_file("b/trust.proof.crev", &proofs[1..]);
+    index.remove_file("b/trust.proof.crev");
+    index.store(&path)?;
+
+    let index = ProofIndex::load(&path).unwrap();
+    assert!(index.get("5678").is_none());
+    let indexed = index.get("1234").unwrap();
+    assert_eq!(
+        indexed.iter().map(|p| p.signature()).collect::<Vec<_>>(),
+        proofs.iter().map(|p| p.signature()).collect::<Vec<_>>()
+    );
+    for proof in &indexed {
+        proof.verify()?;
+    }
+    Ok(())
+}
+
+#[test]
+fn verification_cache() -> Result<()> {
+    use crate::verification_cache::VerificationCache;
+
+    let a = OwnId::generate_for_git_url("https://a");
+    let b = OwnId::generate_for_git_url("https://b");
+    let proof = a
+        .create_trust_proof(vec![b.as_pubid().to_owned()], TrustLevel::High)?
+        .sign_by(&a)?;
+    let mut forged = proof.clone();
+    forged.signature = b
+        .create_trust_proof(vec![a.as_pubid().to_owned()], TrustLevel::High)?
+        .sign_by(&b)?
+        .signature;
+
+    let tmp_dir = tempdir::TempDir::new("crev-verification-cache")?;
+    let path = tmp_dir.path().join("cache.cbor");
+    let mut cache = VerificationCache::new(&path);
+    cache.verify(&proof)?;
+    assert!(cache.verify(&forged).is_err());
+    drop(cache);
+    assert!(path.exists());
+
+    let mut cache = VerificationCache::new(&path);
+    cache.verify(&proof)?;
+    assert!(cache.verify(&forged).is_err());
+    Ok(())
+}
+
+#[test]
+fn repo_index() -> Result<()> {
+    use crate::repo_index::RepoIndex;
+    use std::{collections::HashSet, fs, path::PathBuf};
+
+    let a = OwnId::generate_for_git_url("https://a");
+    let b = OwnId::generate_for_git_url("https://b");
+    let trust = a
+        .create_trust_proof(vec![b.as_pubid().to_owned()], TrustLevel::High)?
+        .sign_by(&a)?;
+    let review = crev_data::proof::review::PackageBuilder::default()
+        .from(a.id.to_owned())
+        .package(crev_data::proof::PackageInfo {
+            id: None,
+            source: "https://crates.io".into(),
+            name: "foo".into(),
+            version: "1.0.0".into(),
+            digest: vec![0; 32],
+            digest_type: crev_data::proof::default_digest_type(),
+            revision: "".into(),
+            revision_type: crev_data::proof::default_revision_type(),
+            digest_ignore: vec![],
+            checksum: None,
+            repository: None,
+        })
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .sign_by(&a)?;
+
+    let tmp_dir = tempdir::TempDir::new("crev-repo-index")?;
+    fs::create_dir_all(tmp_dir.path().join("a"))?;
+    fs::write(tmp_dir.path().join("a/trust.proof.crev"), trust.to_string())?;
+    fs::write(
+        tmp_dir.path().join("a/reviews.proof.crev"),
+        review.to_string(),
+    )?;
+    assert!(RepoIndex::load(tmp_dir.path())?.is_none());
+    RepoIndex::build(tmp_dir.path())?.store(tmp_dir.path())?;
+
+    let mut index = RepoIndex::load(tmp_dir.path())?.unwrap();
+    let trust_file = PathBuf::from("a/trust.proof.crev");
+    let review_file = PathBuf::from("a/reviews.proof.crev");
+    let crates: HashSet<String> = vec!["foo".to_string()].into_iter().collect();
+    assert_eq!(
+        index
+            .files_for(&HashSet::new())
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec![trust_file.clone()]
+    );
+    assert_eq!(index.files_for(&crates).len(), 2);
+    assert!(index.files_for(&crates).contains(&review_file));
+
+    // paths leading out of the proof directory are never used
+    index.general.insert("../outside.proof.crev".into());
+    assert_eq!(index.files_for(&HashSet::new()).len(), 1);
+
+    // proofs added without updating the index make it outdated
+    assert!(index.is_up_to_date(tmp_dir.path())?);
+    fs::write(
+        tmp_dir.path().join("a/reviews.proof.crev"),
+        format!("{}{}", review, review),
+    )?;
+    assert!(!index.is_up_to_date(tmp_dir.path())?);
+    Ok(())
+}
+
+#[test]
+fn edit_error_annotation() {
+    let text = "comment: foo\n";
+    let once = util::annotate_edit_error(text, "first problem");
+    assert!(once.starts_with("# ERROR: first problem\n"));
+    assert!(once.ends_with(text));
+    // the previous annotation is replaced
+    let twice = util::annotate_edit_error(&once, "second problem");
+    assert!(!twice.contains("first problem"));
+    assert!(twice.starts_with("# ERROR: second problem\n"));
+    assert!(twice.ends_with(text));
+}
+
+#[test]
+fn bundle_roundtrip() -> Result<()> {
+    let a = OwnId::generate_for_git_url("https://a");
+    let b = OwnId::generate_for_git_url("https://b");
+    let c = OwnId::generate_for_git_url("https://c");
+
+    let a_to_b = a
+        .create_trust_proof(vec![b.as_pubid().to_owned()], TrustLevel::High)?
+        .sign_by(&a)?;
+    let b_to_c = b
+        .create_trust_proof(vec![c.as_pubid().to_owned()], TrustLevel::Low)?
+        .sign_by(&b)?;
+
+    let text = bundle::create(&a, &[a_to_b.clone(), b_to_c.clone()])?;
+    let parsed = bundle::Bundle::parse(&text)?;
+    assert_eq!(parsed.header.from.id, a.id.id);
+    assert_eq!(parsed.proofs.len(), 2);
+    assert_eq!(parsed.proofs[1].body, b_to_c.body);
+
+    // dropping a proof on the way breaks the bundle
+    let truncated = text.replace(&b_to_c.to_string(), "");
+    assert!(bundle::Bundle::parse(&truncated).is_err());
+    // so does swapping the proofs under another signed header
+    let other = bundle::create(&c, std::slice::from_ref(&a_to_b))?;
+    let forged = other.replace(&a_to_b.to_string(), &b_to_c.to_string());
+    assert!(bundle::Bundle::parse(&forged).is_err());
+    Ok(())
+}
+
+#[test]
+fn trustdb_simulated_trust() -> Result<()> {
+    let a = OwnId::generate_for_git_url("https://a");
+    let b = OwnId::generate_for_git_url("https://b");
+    let c = OwnId::generate_for_git_url("https://c");
+
+    let b_to_c = b
+        .create_trust_proof(vec![c.as_pubid().to_owned()], TrustLevel::Medium)?
+        .sign_by(&b)?;
+
+    let mut trustdb = TrustDB::new();
+    trustdb.import_from_iter(vec![b_to_c].into_iter());
+    let params = trustdb::TrustDistanceParams::default();
+    assert_eq!(trustdb.calculate_trust_set(a.as_ref(), &params).len(), 1);
+
+    trustdb.add_simulated_trust(a.as_ref(), b.as_ref(), TrustLevel::Medium);
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &params);
+    assert!(trust_set.contains(b.as_ref()));
+    assert!(trust_set.contains(c.as_ref()));
+
+    trustdb.add_simulated_trust(a.as_ref(), b.as_ref(), TrustLevel::Distrust);
+    assert_eq!(trustdb.calculate_trust_set(a.as_ref(), &params).len(), 1);
+    Ok(())
+}
+
+#[test]
+fn trust_profiles() -> Result<()> {
+    let config: local::UserConfig = serde_yaml::from_str(
+        r#"
+version: -1
+current-id: ~
+trust-profiles:
+  strict:
+    trust-depth: 2
+    min-trust: medium
+    criteria: [safe-to-deploy]
+"#,
+    )?;
+    let params = trustdb::TrustDistanceParams::default();
+
+    let strict = config.apply_trust_profile("strict", params.clone())?;
+    assert_eq!(strict.max_distance, 2);
+    assert_eq!(strict.min_trust, TrustLevel::Medium);
+    assert_eq!(strict.criteria, vec!["safe-to-deploy".to_owned()]);
+    assert_eq!(strict.low_trust_distance, params.low_trust_distance);
+
+    let default = config.apply_trust_profile("default", params.clone())?;
+    assert_eq!(default.max_distance, params.max_distance);
+    assert!(config.apply_trust_profile("lenient", params).is_err());
+    Ok(())
+}
@@ -0,0 +1,389 @@
+//! Anchoring proofs in a public transparency log
+//!
+//! Submitting a proof to an append-only log (like sigstore's Rekor) gets
+//! back the time it was logged at and a proof of the entry being included
+//! in the log's Merkle tree (RFC 6962), for a tree head signed by the log.
+//! With these kept next to the proofs, anyone that pins the log's key can
+//! check that a proof existed at that time, so it can't be silently
+//! backdated.
+//!
+//! Anchors of fetched repositories are also remembered outside of them
+//! (see `Local::check_anchors`), so a proof retracted after it was seen,
+//! together with its anchor, is still noticed.
+use crate::Result;
+use chrono::{self, prelude::*};
+use crev_data::proof;
+use hex;
+use openssl::{
+    base64,
+    pkey::{self, PKey, Public},
+    sign::Verifier,
+};
+use serde_yaml;
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, fmt, fs, path::Path};
+
+/// Name of the file keeping anchors, in the root of a proof directory
+pub const ANCHORS_FILE_NAME: &str = "transparency-log.yaml";
+
+/// Kind of the log entries made for proofs
+pub const ENTRY_KIND: &str = "hashedrekord";
+
+/// How much later than its date a proof can be logged without a warning
+pub const MAX_ANCHOR_DELAY_SECS: i64 = 24 * 60 * 60;
+
+/// Tolerated difference between the clocks of proof authors and the log
+const MAX_CLOCK_SKEW_SECS: i64 = 10 * 60;
+
+/// Inclusion of a proof in a transparency log
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Anchor {
+    /// Signature of the anchored proof
+    pub signature: String,
+    /// Base URL of the log
+    pub log: String,
+    /// Id of the entry in the log, to look it up with
+    #[serde(rename = "entry-id")]
+    pub entry_id: String,
+    /// Index of the entry in the log's tree
+    #[serde(rename = "log-index")]
+    pub log_index: u64,
+    /// When the log added the entry (Unix time)
+    #[serde(rename = "integrated-time")]
+    pub integrated_time: i64,
+    /// The logged entry, committing to the digest of the proof
+    pub body: String,
+    /// Tree head signed by the log (a signed note) the inclusion proof is for
+    pub checkpoint: String,
+    /// Hashes of the inclusion proof, from the leaf up (hex)
+    pub hashes: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct EntryBody {
+    kind: String,
+    spec: EntrySpec,
+}
+
+#[derive(Deserialize)]
+struct EntrySpec {
+    data: EntryData,
+    signature: EntrySignature,
+}
+
+#[derive(Deserialize)]
+struct EntryData {
+    hash: EntryHash,
+}
+
+#[derive(Deserialize)]
+struct EntryHash {
+    algorithm: String,
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct EntrySignature {
+    content: String,
+    #[serde(rename = "publicKey")]
+    public_key: EntryPublicKey,
+}
+
+#[derive(Deserialize)]
+struct EntryPublicKey {
+    content: String,
+}
+
+/// Hex SHA-256 of the signed body of a proof
+pub fn proof_digest(proof: &proof::Proof) -> String {
+    hex::encode(Sha256::digest(proof.body.as_bytes()))
+}
+
+/// Public key of the proof's author, PEM encoded
+fn author_key_pem(proof: &proof::Proof) -> Result<Vec<u8>> {
+    let crev_data::Id::Crev { id } = proof.content.author_id();
+    Ok(PKey::public_key_from_raw_bytes(&id, pkey::Id::ED25519)?.public_key_to_pem()?)
+}
+
+/// Log entry to submit for `proof`: a `hashedrekord` of its signed body
+pub fn entry_body(proof: &proof::Proof) -> Result<String> {
+    Ok(format!(
+        r#"{{"apiVersion":"0.0.1","kind":"{}","spec":{{"data":{{"hash":{{"algorithm":"sha256","value":"{}"}}}},"signature":{{"content":"{}","publicKey":{{"content":"{}"}}}}}}}}"#,
+        ENTRY_KIND,
+        proof_digest(proof),
+        base64::encode_block(&crev_common::base64_decode(proof.signature())?),
+        base64::encode_block(&author_key_pem(proof)?)
+    ))
+}
+
+/// Size and root hash of a log's tree, from a tree head the log signed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeHead {
+    pub size: u64,
+    pub root_hash: Vec<u8>,
+}
+
+/// A transparency log pinned in the config, with the key it signs tree
+/// heads with
+pub struct Log {
+    pub url: String,
+    key: PKey<Public>,
+}
+
+impl Log {
+    /// `public_key` is PEM encoded, ECDSA (like Rekor's) or Ed25519
+    pub fn new(url: &str, public_key: &str) -> Result<Self> {
+        Ok(Self {
+            url: url.trim_end_matches('/').to_owned(),
+            key: PKey::public_key_from_pem(public_key.as_bytes())
+                .map_err(|e| format_err!("Invalid transparency log key: {}", e))?,
+        })
+    }
+
+    /// Check a checkpoint (a signed note: origin, tree size and base64 root
+    /// hash lines, a blank line, and signature lines) signed by this log
+    pub fn verify_checkpoint(&self, checkpoint: &str) -> Result<TreeHead> {
+        let split = checkpoint
+            .find("\n\n")
+            .ok_or_else(|| format_err!("Invalid checkpoint"))?;
+        let (text, signatures) = checkpoint.split_at(split + 1);
+        let verified = signatures
+            .lines()
+            .filter_map(|line| line.strip_prefix("\u{2014} "))
+            .filter_map(|line| line.rsplit(' ').next())
+            .filter_map(|signature| base64::decode_block(signature).ok())
+            // the signature is prefixed by a 4 byte hash of the key name
+            .filter(|signature| signature.len() > 4)
+            .any(|signature| self.verify_signature(text.as_bytes(), &signature[4..]));
+        if !verified {
+            bail!("Checkpoint is not signed by {}", self.url);
+        }
+        let mut lines = text.lines().skip(1);
+        let size = lines
+            .next()
+            .and_then(|size| size.parse().ok())
+            .ok_or_else(|| format_err!("Invalid checkpoint tree size"))?;
+        let root_hash = lines
+            .next()
+            .and_then(|root| base64::decode_block(root).ok())
+            .ok_or_else(|| format_err!("Invalid checkpoint root hash"))?;
+        Ok(TreeHead { size, root_hash })
+    }
+
+    fn verify_signature(&self, data: &[u8], signature: &[u8]) -> bool {
+        let verifier = if self.key.id() == pkey::Id::ED25519 {
+            Verifier::new_without_digest(&self.key)
+        } else {
+            Verifier::new(openssl::hash::MessageDigest::sha256(), &self.key)
+        };
+        verifier
+            .and_then(|mut verifier| verifier.verify_oneshot(signature, data))
+            .unwrap_or(false)
+    }
+}
+
+fn leaf_hash(data: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.input([0u8]);
+    hasher.input(data);
+    hasher.result().to_vec()
+}
+
+fn node_hash(left: &[u8], right: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.input([1u8]);
+    hasher.input(left);
+    hasher.input(right);
+    hasher.result().to_vec()
+}
+
+/// Check an RFC 6962 inclusion proof of `leaf` at `index` in a tree of
+/// `tree_size` leaves with `root` hash
+pub fn verify_inclusion(
+    leaf: &[u8],
+    index: u64,
+    tree_size: u64,
+    hashes: &[Vec<u8>],
+    root: &[u8],
+) -> bool {
+    if index >= tree_size {
+        return false;
+    }
+    let mut fn_ = index;
+    let mut sn = tree_size - 1;
+    let mut hash = leaf_hash(leaf);
+    for sibling in hashes {
+        if sn == 0 {
+            return false;
+        }
+        if fn_ & 1 == 1 || fn_ == sn {
+            hash = node_hash(sibling, &hash);
+            while fn_ & 1 == 0 && fn_ != 0 {
+                fn_ >>= 1;
+                sn >>= 1;
+            }
+        } else {
+            hash = node_hash(&hash, sibling);
+        }
+        fn_ >>= 1;
+        sn >>= 1;
+    }
+    sn == 0 && hash.as_slice() == root
+}
+
+/// A problem with the anchor of a proof
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub signature: String,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.signature, self.message)
+    }
+}
+
+impl Anchor {
+    pub fn integrated_time(&self) -> chrono::DateTime<Utc> {
+        Utc.timestamp(self.integrated_time, 0)
+    }
+
+    /// Check that the entry commits to `proof` and is included in the tree
+    /// of `log`
+    pub fn verify(&self, log: &Log, proof: &proof::Proof) -> Result<()> {
+        if self.signature != proof.signature() {
+            bail!("Anchor is for another proof");
+        }
+        let body: EntryBody = serde_yaml::from_str(&self.body)
+            .map_err(|e| format_err!("Invalid log entry: {}", e))?;
+        if body.kind != ENTRY_KIND || body.spec.data.hash.algorithm != "sha256" {
+            bail!("Log entry is not a {} of a proof", ENTRY_KIND);
+        }
+        if body.spec.data.hash.value != proof_digest(proof) {
+            bail!("Log entry is for different proof content");
+        }
+        if base64::decode_block(&body.spec.signature.content)?
+            != crev_common::base64_decode(proof.signature())?
+            || base64::decode_block(&body.spec.signature.public_key.content)?
+                != author_key_pem(proof)?
+        {
+            bail!("Log entry is signed differently than the proof");
+        }
+        self.verify_inclusion(log)
+    }
+
+    /// Check the inclusion proof of the entry, for a tree head signed by `log`
+    pub fn verify_inclusion(&self, log: &Log) -> Result<()> {
+        if self.log.trim_end_matches('/') != log.url {
+            bail!("Logged in {}, not in {}", self.log, log.url);
+        }
+        let tree_head = log.verify_checkpoint(&self.checkpoint)?;
+        let hashes = self
+            .hashes
+            .iter()
+            .map(hex::decode)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        if !verify_inclusion(
+            self.body.as_bytes(),
+            self.log_index,
+            tree_head.size,
+            &hashes,
+            &tree_head.root_hash,
+        ) {
+            bail!("Invalid inclusion proof");
+        }
+        Ok(())
+    }
+
+    /// Check the anchor of `proof` and that the proof wasn't backdated
+    pub fn check(&self, log: &Log, proof: &proof::Proof) -> Vec<Issue> {
+        let issue = |message: String| Issue {
+            signature: proof.signature().to_owned(),
+            message,
+        };
+        if let Err(e) = self.verify(log, proof) {
+            return vec![issue(e.to_string())];
+        }
+        let date = proof.content.date().with_timezone(&Utc);
+        let delay = self.integrated_time() - date;
+        if delay.num_seconds() < -MAX_CLOCK_SKEW_SECS {
+            vec![issue(format!(
+                "dated {}, after it was logged at {}",
+                date,
+                self.integrated_time()
+            ))]
+        } else if delay.num_seconds() > MAX_ANCHOR_DELAY_SECS {
+            vec![issue(format!(
+                "dated {}, but logged only {} days later; it may be backdated",
+                date,
+                delay.num_days()
+            ))]
+        } else {
+            vec![]
+        }
+    }
+}
+
+/// Read anchors from the file at `path`, by proof signature
+pub fn load_anchors_file(path: &Path) -> Result<HashMap<String, Anchor>> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let anchors: Vec<Anchor> = serde_yaml::from_str(&fs::read_to_string(path)?)?;
+    Ok(anchors
+        .into_iter()
+        .map(|anchor| (anchor.signature.clone(), anchor))
+        .collect())
+}
+
+/// Add `anchors` to the ones in the file at `path`
+pub fn store_anchors_file(path: &Path, anchors: Vec<Anchor>) -> Result<()> {
+    let mut all = load_anchors_file(path)?;
+    all.extend(
+        anchors
+            .into_iter()
+            .map(|anchor| (anchor.signature.clone(), anchor)),
+    );
+    let mut all: Vec<_> = all.into_values().collect();
+    all.sort_by_key(|anchor| (anchor.log.clone(), anchor.log_index));
+    crev_common::store_str_to_file(path, &serde_yaml::to_string(&all)?)?;
+    Ok(())
+}
+
+/// Read anchors of proofs in `proofs_dir`, by proof signature
+pub fn load_anchors(proofs_dir: &Path) -> Result<HashMap<String, Anchor>> {
+    load_anchors_file(&proofs_dir.join(ANCHORS_FILE_NAME))
+}
+
+/// Add `anchors` to the ones of proofs in `proofs_dir`
+pub fn store_anchors(proofs_dir: &Path, anchors: Vec<Anchor>) -> Result<()> {
+    store_anchors_file(&proofs_dir.join(ANCHORS_FILE_NAME), anchors)
+}
+
+/// Check `anchors` of `proofs`, against tree heads signed by `log`
+///
+/// Anchors without their proof among `proofs` are reported too, as such
+/// proofs were published once and retracted later.
+pub fn check_anchors(
+    log: &Log,
+    mut anchors: HashMap<String, Anchor>,
+    proofs: &[proof::Proof],
+) -> Vec<Issue> {
+    let mut issues = vec![];
+    for proof in proofs {
+        if let Some(anchor) = anchors.remove(proof.signature()) {
+            issues.extend(anchor.check(log, proof));
+        }
+    }
+    let mut retracted: Vec<_> = anchors.into_values().collect();
+    retracted.sort_by_key(|anchor| anchor.log_index);
+    issues.extend(retracted.into_iter().map(|anchor| Issue {
+        message: format!(
+            "logged at {}, but no longer published",
+            anchor.integrated_time()
+        ),
+        signature: anchor.signature,
+    }));
+    issues
+}
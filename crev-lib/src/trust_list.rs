@@ -0,0 +1,31 @@
+//! Lists of Ids to trust, for bootstrapping the web of trust
+//!
+//! A list has one `<id> <proof-repository-url>` pair per line. Empty lines,
+//! and everything after `#`, are ignored.
+use crate::Result;
+use crev_data::Id;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub id: Id,
+    pub url: String,
+}
+
+pub fn parse(content: &str) -> Result<Vec<Entry>> {
+    let mut entries = vec![];
+    for (i, line) in content.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        match (fields.next(), fields.next(), fields.next()) {
+            (Some(id), Some(url), None) => entries.push(Entry {
+                id: Id::crevid_from_str(id)?,
+                url: url.to_owned(),
+            }),
+            _ => bail!("Line {}: expected `<id> <url>`", i + 1),
+        }
+    }
+    Ok(entries)
+}
@@ -1,13 +1,16 @@
 use crate::VerificationStatus;
 use chrono::{self, offset::Utc, DateTime};
+use common_failures::prelude::*;
 use crev_data::{
     self,
-    proof::review::Rating,
+    proof::membership::MembershipStatus,
+    proof::review::{AdvisoryRange, Common, Rating},
     proof::trust::TrustLevel,
     proof::{self, review, Content, ContentCommon},
-    Digest, Id, Url,
+    Digest, DigestType, Id, Level, Url,
 };
 use default::default;
+use semver;
 use std::collections::BTreeMap;
 use std::collections::{hash_map, BTreeSet, HashMap, HashSet};
 
@@ -38,6 +41,7 @@ impl<T> Timestamped<T> {
 type TimestampedUrl = Timestamped<Url>;
 type TimestampedTrustLevel = Timestamped<TrustLevel>;
 type TimestampedReview = Timestamped<review::Review>;
+type TimestampedMembershipStatus = Timestamped<MembershipStatus>;
 
 impl From<proof::Trust> for TimestampedTrustLevel {
     fn from(trust: proof::Trust) -> Self {
@@ -57,12 +61,89 @@ impl<'a, T: review::Common> From<&'a T> for TimestampedReview {
     }
 }
 
+/// Lowercased words of `text`, as indexed for searching review comments
+fn comment_words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// Verification status given ratings of the latest trusted review by each Id
+fn status_of_ratings<'a>(ratings: impl Iterator<Item = &'a Rating>) -> VerificationStatus {
+    let mut trust_count = 0;
+    let mut distrust_count = 0;
+    for rating in ratings {
+        if Rating::Neutral <= *rating {
+            trust_count += 1;
+        }
+        if *rating < Rating::Neutral {
+            distrust_count += 1;
+        }
+    }
+
+    if distrust_count > 0 {
+        VerificationStatus::Flagged
+    } else if trust_count > 0 {
+        VerificationStatus::Verified
+    } else {
+        VerificationStatus::Unknown
+    }
+}
+
+/// A single trust relationship: `from` trusts `to` at a `level`
+#[derive(Debug, Clone)]
+pub struct TrustEdge {
+    pub from: Id,
+    pub to: Id,
+    pub level: TrustLevel,
+    pub date: chrono::DateTime<Utc>,
+}
+
+/// Summary of the amount of data in a `TrustDB`
+#[derive(Debug, Clone, Default)]
+pub struct TrustDBStats {
+    pub id_count: usize,
+    pub trust_edge_count: usize,
+    pub package_reviews_by_source: BTreeMap<String, usize>,
+    pub package_reviews_by_author: BTreeMap<Id, usize>,
+    pub oldest_proof_date: Option<chrono::DateTime<Utc>>,
+    pub newest_proof_date: Option<chrono::DateTime<Utc>>,
+}
+
+/// Activity of an Id as a reviewer
+#[derive(Debug, Clone)]
+pub struct ReviewerStats {
+    pub id: Id,
+    pub review_count: usize,
+    /// Distinct packages (of any version) reviewed
+    pub package_count: usize,
+    /// Average thoroughness of reviews, from 0 (`none`) to 3 (`high`)
+    pub average_thoroughness: Option<f64>,
+    /// Date of the newest proof by the Id
+    pub last_activity: Option<chrono::DateTime<Utc>>,
+    /// Ids of the trust set trusting the Id (at least at `low` level)
+    pub trusted_by: usize,
+}
+
+/// Change of the content of a `TrustDB`, passed to its listeners
+pub enum TrustDBChange<'a> {
+    Added(&'a proof::Proof),
+    Removed(&'a proof::Proof),
+}
+
+type TrustDBListener = Box<dyn FnMut(&TrustDBChange<'_>) + Send>;
+
 /// In memory database tracking information from proofs
 ///
 /// After population, used for calculating the effcttive trust set, etc.
+///
+/// Can be kept up to date while proofs arrive with `add_proof`,
+/// `remove_proof` and `replace_proof`; see `add_listener`.
 pub struct TrustDB {
     trust_id_to_id: HashMap<Id, HashMap<Id, TimestampedTrustLevel>>, // who -(trusts)-> whom
-    digest_to_reviews: HashMap<Vec<u8>, HashMap<Id, TimestampedReview>>, // what (digest) -(reviewed)-> by whom
+    members_by_org: HashMap<Id, HashMap<Id, TimestampedMembershipStatus>>, // org -(vouches for)-> whom
+    digest_to_reviews: HashMap<(String, Vec<u8>), HashMap<Id, TimestampedReview>>, // what (digest type, digest) -(reviewed)-> by whom
+    digest_types: BTreeSet<String>,
     url_by_id: HashMap<Id, TimestampedUrl>,
     url_by_id_secondary: HashMap<Id, TimestampedUrl>,
 
@@ -70,19 +151,43 @@ pub struct TrustDB {
     package_reviews_by_source: BTreeMap<String, BTreeSet<String>>,
     package_reviews_by_name: BTreeMap<(String, String), BTreeSet<String>>,
     package_reviews_by_version: BTreeMap<(String, String, String), BTreeSet<String>>,
+    package_reviews_by_author: HashMap<Id, BTreeSet<String>>,
+    package_reviews_by_comment_word: BTreeMap<String, BTreeSet<String>>,
+    issues_by_package: HashMap<(String, String), Vec<proof::Issue>>, // (source, name) -> issues
+    alternatives_by_package: HashMap<(String, String), Vec<proof::Alternative>>, // (source, name) -> alternatives
+    identity_claims_by_account: HashMap<(String, String), Vec<proof::IdentityClaim>>, // (service, lowercase name) -> claims
+    users_by_version: HashMap<(String, String, String), HashSet<Id>>, // (source, name, version) -> users
+    package_reviews_applying_to_range: HashMap<(String, String), BTreeSet<String>>, // (source, name) -> signatures
+
+    /// All added proofs, to rebuild the indexes above after removing some
+    proofs: Vec<proof::Proof>,
+    proof_signatures: HashSet<String>,
+    listeners: Vec<TrustDBListener>,
 }
 
 impl Default for TrustDB {
     fn default() -> Self {
         Self {
             trust_id_to_id: Default::default(),
+            members_by_org: Default::default(),
             url_by_id: Default::default(),
             url_by_id_secondary: Default::default(),
             digest_to_reviews: Default::default(),
+            digest_types: default(),
             package_review_by_signature: default(),
             package_reviews_by_source: default(),
             package_reviews_by_name: default(),
             package_reviews_by_version: default(),
+            package_reviews_by_author: default(),
+            package_reviews_by_comment_word: default(),
+            issues_by_package: default(),
+            alternatives_by_package: default(),
+            identity_claims_by_account: default(),
+            users_by_version: default(),
+            package_reviews_applying_to_range: default(),
+            proofs: default(),
+            proof_signatures: default(),
+            listeners: default(),
         }
     }
 }
@@ -96,9 +201,10 @@ impl TrustDB {
         let from = &review.from;
         self.record_url_from_from_field(&review.date_utc(), &from);
         for file in &review.files {
+            self.digest_types.insert(file.digest_type.to_owned());
             TimestampedReview::from(review).insert_into_or_update_to_more_recent(
                 self.digest_to_reviews
-                    .entry(file.digest.to_owned())
+                    .entry((file.digest_type.to_owned(), file.digest.to_owned()))
                     .or_insert_with(HashMap::new)
                     .entry(from.id.clone()),
             )
@@ -109,9 +215,14 @@ impl TrustDB {
         let from = &review.from;
         self.record_url_from_from_field(&review.date_utc(), &from);
 
+        self.digest_types
+            .insert(review.package.digest_type.to_owned());
         TimestampedReview::from(review).insert_into_or_update_to_more_recent(
             self.digest_to_reviews
-                .entry(review.package.digest.to_owned())
+                .entry((
+                    review.package.digest_type.to_owned(),
+                    review.package.digest.to_owned(),
+                ))
                 .or_insert_with(HashMap::new)
                 .entry(from.id.clone()),
         );
@@ -139,6 +250,25 @@ impl TrustDB {
             ))
             .or_default()
             .insert(signature.to_owned());
+        self.package_reviews_by_author
+            .entry(from.id.clone())
+            .or_default()
+            .insert(signature.to_owned());
+        if review.applies_to.is_some() {
+            self.package_reviews_applying_to_range
+                .entry((
+                    review.package.source.to_owned(),
+                    review.package.name.to_owned(),
+                ))
+                .or_default()
+                .insert(signature.to_owned());
+        }
+        for word in comment_words(&review.comment) {
+            self.package_reviews_by_comment_word
+                .entry(word)
+                .or_default()
+                .insert(signature.to_owned());
+        }
     }
 
     pub fn get_package_review_count(
@@ -166,7 +296,39 @@ impl TrustDB {
             (None, Some(_)) => panic!("Wrong usage"),
         }
     }
-    
+
+    /// Reviews of other versions of a package declaring to apply to `version` too
+    fn get_package_reviews_applying_to(
+        &self,
+        source: &str,
+        name: &str,
+        version: &str,
+    ) -> Vec<&proof::review::Package> {
+        let parsed_version = match semver::Version::parse(version) {
+            Ok(version) => version,
+            Err(_) => return vec![],
+        };
+        self.package_reviews_applying_to_range
+            .get(&(source.to_owned(), name.to_owned()))
+            .into_iter()
+            .flatten()
+            .map(|signature| &self.package_review_by_signature[signature])
+            .filter(|review| {
+                review.package.version != version
+                    && review
+                        .applies_to
+                        .as_ref()
+                        .and_then(|range| semver::VersionReq::parse(range).ok())
+                        .map(|range| range.matches(&parsed_version))
+                        .unwrap_or(false)
+            })
+            .collect()
+    }
+
+    /// Package reviews of a given package or its version
+    ///
+    /// With a `version`, reviews of other versions that declare
+    /// to apply to it (`applies-to`) are included.
     pub fn get_package_reviews_for_package(
         &self,
         source: &str,
@@ -177,12 +339,12 @@ impl TrustDB {
             (Some(name), Some(version)) => self
                 .package_reviews_by_version
                 .get(&(source.to_owned(), name.to_owned(), version.to_owned()))
-                .map(|set| {
-                    set.iter()
-                        .map(|signature| self.package_review_by_signature[signature].clone())
-                        .collect()
-                })
-                .unwrap_or_else(|| vec![]),
+                .into_iter()
+                .flatten()
+                .map(|signature| &self.package_review_by_signature[signature])
+                .chain(self.get_package_reviews_applying_to(source, name, version))
+                .cloned()
+                .collect(),
 
             (Some(name), None) => self
                 .package_reviews_by_name
@@ -210,6 +372,99 @@ impl TrustDB {
         proofs.into_iter()
     }
 
+    /// Package reviews of trusted Ids that carry advisories
+    pub fn get_advisories(
+        &self,
+        source: &str,
+        name: Option<&str>,
+        trust_set: &TrustSet,
+    ) -> Vec<proof::review::Package> {
+        self.get_package_reviews_for_package(source, name, None)
+            .filter(|review| review.advisory.is_some() && trust_set.contains(&review.from.id))
+            .collect()
+    }
+
+    /// Package reviews of trusted Ids with advisories affecting `version`
+    pub fn get_advisories_for_version(
+        &self,
+        source: &str,
+        name: &str,
+        version: &str,
+        trust_set: &TrustSet,
+    ) -> Vec<proof::review::Package> {
+        let version = match semver::Version::parse(version) {
+            Ok(version) => version,
+            Err(_) => return vec![],
+        };
+        self.get_advisories(source, Some(name), trust_set)
+            .into_iter()
+            .filter(|review| {
+                match (
+                    &review.advisory,
+                    semver::Version::parse(&review.package.version),
+                ) {
+                    (Some(advisory), Ok(fixed)) => {
+                        is_affected_by_advisory(&version, &fixed, advisory.range)
+                    }
+                    _ => false,
+                }
+            })
+            .collect()
+    }
+
+    /// All package reviews published by `id`, oldest first
+    pub fn get_package_reviews_by_author(
+        &self,
+        id: &Id,
+    ) -> impl Iterator<Item = proof::review::Package> {
+        let mut proofs: Vec<_> = self
+            .package_reviews_by_author
+            .get(id)
+            .map(|set| {
+                set.iter()
+                    .map(|signature| self.package_review_by_signature[signature].clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        proofs.sort_by(|a, b| a.date().cmp(b.date()));
+
+        proofs.into_iter()
+    }
+
+    /// Package reviews with comments matching `query`
+    ///
+    /// Every word of `query` has to start a word in the comment, ignoring
+    /// case, eg. `unsafe transm` matches "Uses unsafe transmute".
+    pub fn search_package_reviews(
+        &self,
+        query: &str,
+    ) -> impl Iterator<Item = proof::review::Package> {
+        let mut matching: Option<BTreeSet<&String>> = None;
+        for query_word in comment_words(query) {
+            let with_word: BTreeSet<&String> = self
+                .package_reviews_by_comment_word
+                .range(query_word.clone()..)
+                .take_while(|(word, _)| word.starts_with(&query_word))
+                .flat_map(|(_, signatures)| signatures)
+                .collect();
+            matching = Some(match matching {
+                Some(matching) => matching.intersection(&with_word).cloned().collect(),
+                None => with_word,
+            });
+        }
+
+        let mut proofs: Vec<_> = matching
+            .unwrap_or_default()
+            .into_iter()
+            .map(|signature| self.package_review_by_signature[signature].clone())
+            .collect();
+
+        proofs.sort_by(|a, b| a.date().cmp(b.date()));
+
+        proofs.into_iter()
+    }
+
     fn add_trust_raw(&mut self, from: &Id, to: &Id, date: DateTime<Utc>, trust: TrustLevel) {
         TimestampedTrustLevel { value: trust, date }.insert_into_or_update_to_more_recent(
             self.trust_id_to_id
@@ -219,6 +474,69 @@ impl TrustDB {
         );
     }
 
+    /// Record trust of `from` in `to` as if it was just published, eg. to
+    /// see what trusting someone would change before doing it
+    pub fn add_simulated_trust(&mut self, from: &Id, to: &Id, trust: TrustLevel) {
+        self.add_trust_raw(from, to, Utc::now(), trust);
+    }
+
+    /// All trust relationships, sorted by truster and trustee
+    pub fn get_trust_edges(&self) -> Vec<TrustEdge> {
+        let mut edges: Vec<_> = self
+            .trust_id_to_id
+            .iter()
+            .flat_map(|(from, map)| {
+                map.iter().map(move |(to, trust)| TrustEdge {
+                    from: from.clone(),
+                    to: to.clone(),
+                    level: trust.value,
+                    date: trust.date,
+                })
+            })
+            .collect();
+
+        edges.sort_by(|a, b| (&a.from, &a.to).cmp(&(&b.from, &b.to)));
+        edges
+    }
+
+    /// Ids outside of `trust_set` that Ids in it published trust in
+    ///
+    /// They didn't make it into the trust set only because of its limits
+    /// (eg. distance), so their proofs are likely worth a look. Ignored Ids
+    /// are not.
+    pub fn get_ids_adjacent_to(&self, trust_set: &TrustSet) -> BTreeSet<Id> {
+        trust_set
+            .ids()
+            .flat_map(|id| self.get_ids_trusted_by(id))
+            .filter(|(level, id)| {
+                !matches!(level, TrustLevel::None | TrustLevel::Distrust)
+                    && !trust_set.contains(id)
+                    && !trust_set.ignored.contains(id)
+            })
+            .map(|(_, id)| id.clone())
+            .collect()
+    }
+
+    /// Latest trust of every Id that published trust (or distrust) in `id`,
+    /// the most recent first
+    pub fn get_trusters_of(&self, id: &Id) -> Vec<TrustEdge> {
+        let mut edges: Vec<_> = self
+            .trust_id_to_id
+            .iter()
+            .filter_map(|(from, map)| {
+                map.get(id).map(|trust| TrustEdge {
+                    from: from.clone(),
+                    to: id.clone(),
+                    level: trust.value,
+                    date: trust.date,
+                })
+            })
+            .collect();
+
+        edges.sort_by(|a, b| (b.date, &a.from).cmp(&(a.date, &b.from)));
+        edges
+    }
+
     fn add_trust(&mut self, trust: &proof::Trust) {
         let from = &trust.from;
         self.record_url_from_from_field(&trust.date_utc(), &from);
@@ -230,6 +548,287 @@ impl TrustDB {
         }
     }
 
+    pub fn get_stats(&self) -> TrustDBStats {
+        let trust_dates = self
+            .trust_id_to_id
+            .values()
+            .flat_map(|map| map.values().map(|trust| trust.date));
+        let review_dates = self
+            .digest_to_reviews
+            .values()
+            .flat_map(|map| map.values().map(|review| review.date));
+        let package_review_dates = self
+            .package_review_by_signature
+            .values()
+            .map(|review| review.date_utc());
+        let dates: Vec<_> = trust_dates
+            .chain(review_dates)
+            .chain(package_review_dates)
+            .collect();
+
+        TrustDBStats {
+            id_count: self.all_known_ids().len(),
+            trust_edge_count: self.trust_id_to_id.values().map(|map| map.len()).sum(),
+            package_reviews_by_source: self
+                .package_reviews_by_source
+                .iter()
+                .map(|(source, set)| (source.clone(), set.len()))
+                .collect(),
+            package_reviews_by_author: self
+                .package_reviews_by_author
+                .iter()
+                .map(|(id, set)| (id.clone(), set.len()))
+                .collect(),
+            oldest_proof_date: dates.iter().min().cloned(),
+            newest_proof_date: dates.iter().max().cloned(),
+        }
+    }
+
+    /// Activity of all known Ids, most active reviewers first
+    pub fn get_reviewer_stats(&self, trust_set: &TrustSet) -> Vec<ReviewerStats> {
+        let mut last_activity: HashMap<Id, chrono::DateTime<Utc>> = HashMap::new();
+        for proof in &self.proofs {
+            let date = proof.content.date().with_timezone(&Utc);
+            let entry = last_activity
+                .entry(proof.content.author_id())
+                .or_insert(date);
+            if *entry < date {
+                *entry = date;
+            }
+        }
+
+        let mut trusted_by: HashMap<&Id, usize> = HashMap::new();
+        for (from, map) in &self.trust_id_to_id {
+            if !trust_set.contains(from) {
+                continue;
+            }
+            for (to, trust) in map {
+                match trust.value {
+                    TrustLevel::Low | TrustLevel::Medium | TrustLevel::High => {
+                        *trusted_by.entry(to).or_default() += 1
+                    }
+                    TrustLevel::None | TrustLevel::Distrust => {}
+                }
+            }
+        }
+
+        let mut ids = self.all_known_ids();
+        ids.extend(self.package_reviews_by_author.keys().cloned());
+        let mut stats: Vec<_> = ids
+            .into_iter()
+            .map(|id| {
+                let reviews: Vec<_> = self
+                    .package_reviews_by_author
+                    .get(&id)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|signature| self.package_review_by_signature.get(signature))
+                    .collect();
+                let packages: HashSet<_> = reviews
+                    .iter()
+                    .map(|review| (&review.package.source, &review.package.name))
+                    .collect();
+                let thoroughness: Vec<f64> = reviews
+                    .iter()
+                    .map(|review| match review.review().thoroughness {
+                        Level::None => 0.0,
+                        Level::Low => 1.0,
+                        Level::Medium => 2.0,
+                        Level::High => 3.0,
+                    })
+                    .collect();
+                ReviewerStats {
+                    review_count: reviews.len(),
+                    package_count: packages.len(),
+                    average_thoroughness: if thoroughness.is_empty() {
+                        None
+                    } else {
+                        Some(thoroughness.iter().sum::<f64>() / thoroughness.len() as f64)
+                    },
+                    last_activity: last_activity.get(&id).cloned(),
+                    trusted_by: trusted_by.get(&id).cloned().unwrap_or(0),
+                    id,
+                }
+            })
+            .collect();
+        stats.sort_by(|a, b| {
+            b.review_count
+                .cmp(&a.review_count)
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        stats
+    }
+
+    fn add_membership(&mut self, membership: &proof::Membership) {
+        let from = &membership.from;
+        self.record_url_from_from_field(&membership.date_utc(), from);
+        for member in &membership.members {
+            TimestampedMembershipStatus {
+                value: membership.status,
+                date: membership.date_utc(),
+            }
+            .insert_into_or_update_to_more_recent(
+                self.members_by_org
+                    .entry(from.id.clone())
+                    .or_default()
+                    .entry(member.id.clone()),
+            );
+        }
+        for member in &membership.members {
+            self.record_url_from_to_field(&membership.date_utc(), member)
+        }
+    }
+
+    fn add_issue(&mut self, issue: &proof::Issue) {
+        self.record_url_from_from_field(&issue.date_utc(), &issue.from);
+        self.issues_by_package
+            .entry((issue.source.clone(), issue.name.clone()))
+            .or_default()
+            .push(issue.clone());
+    }
+
+    /// Issues reported by trusted Ids affecting `version` of a package
+    ///
+    /// Issues with `versions` that are not a valid semver requirement are ignored.
+    pub fn get_issues_for_version(
+        &self,
+        source: &str,
+        name: &str,
+        version: &str,
+        trust_set: &TrustSet,
+    ) -> Vec<proof::Issue> {
+        let version = match semver::Version::parse(version) {
+            Ok(version) => version,
+            Err(_) => return vec![],
+        };
+        let mut issues: Vec<_> = self
+            .issues_by_package
+            .get(&(source.to_owned(), name.to_owned()))
+            .into_iter()
+            .flatten()
+            .filter(|issue| {
+                trust_set.contains(&issue.from.id)
+                    && semver::VersionReq::parse(&issue.versions)
+                        .map(|req| req.matches(&version))
+                        .unwrap_or(false)
+            })
+            .cloned()
+            .collect();
+        issues.sort_by(|a, b| a.date().cmp(b.date()));
+        issues
+    }
+
+    fn add_identity_claim(&mut self, claim: &proof::IdentityClaim) {
+        self.record_url_from_from_field(&claim.date_utc(), &claim.from);
+        for account in &claim.accounts {
+            self.identity_claims_by_account
+                .entry((account.service.clone(), account.name.to_lowercase()))
+                .or_default()
+                .push(claim.clone());
+        }
+    }
+
+    /// Latest claims of every Id to own an account on an external `service`
+    ///
+    /// Claims are not verified here: the account needs to have published the Id.
+    pub fn get_identity_claims(&self, service: &str, name: &str) -> Vec<proof::IdentityClaim> {
+        let mut latest: HashMap<&Id, &proof::IdentityClaim> = HashMap::new();
+        for claim in self
+            .identity_claims_by_account
+            .get(&(service.to_owned(), name.to_lowercase()))
+            .into_iter()
+            .flatten()
+        {
+            let entry = latest.entry(&claim.from.id).or_insert(claim);
+            if entry.date() < claim.date() {
+                *entry = claim;
+            }
+        }
+        let mut claims: Vec<_> = latest.values().map(|&claim| claim.clone()).collect();
+        claims.sort_by(|a, b| b.date().cmp(a.date()));
+        claims
+    }
+
+    fn add_alternative(&mut self, alternative: &proof::Alternative) {
+        self.record_url_from_from_field(&alternative.date_utc(), &alternative.from);
+        self.alternatives_by_package
+            .entry((alternative.source.clone(), alternative.name.clone()))
+            .or_default()
+            .push(alternative.clone());
+    }
+
+    /// Alternatives to a package recommended by trusted Ids, the latest first
+    ///
+    /// Only the most recent recommendation of every Id counts.
+    pub fn get_alternatives(
+        &self,
+        source: &str,
+        name: &str,
+        trust_set: &TrustSet,
+    ) -> Vec<proof::Alternative> {
+        let mut latest: HashMap<&Id, &proof::Alternative> = HashMap::new();
+        for alternative in self
+            .alternatives_by_package
+            .get(&(source.to_owned(), name.to_owned()))
+            .into_iter()
+            .flatten()
+            .filter(|alternative| trust_set.contains(&alternative.from.id))
+        {
+            let entry = latest.entry(&alternative.from.id).or_insert(alternative);
+            if entry.date() < alternative.date() {
+                *entry = alternative;
+            }
+        }
+        let mut alternatives: Vec<_> = latest.values().map(|&a| a.clone()).collect();
+        alternatives.sort_by(|a, b| b.date().cmp(a.date()));
+        alternatives
+    }
+
+    fn add_usage(&mut self, usage: &proof::Usage) {
+        self.record_url_from_from_field(&usage.date_utc(), &usage.from);
+        self.users_by_version
+            .entry((
+                usage.source.clone(),
+                usage.name.clone(),
+                usage.package_version.clone(),
+            ))
+            .or_default()
+            .insert(usage.from.id.clone());
+    }
+
+    /// Number of trusted Ids using a package version in production
+    pub fn get_usage_count(
+        &self,
+        source: &str,
+        name: &str,
+        version: &str,
+        trust_set: &TrustSet,
+    ) -> usize {
+        self.users_by_version
+            .get(&(source.to_owned(), name.to_owned(), version.to_owned()))
+            .into_iter()
+            .flatten()
+            .filter(|id| trust_set.contains(id))
+            .count()
+    }
+
+    /// Did `id` state to use a package version in production
+    pub fn is_used_by(&self, source: &str, name: &str, version: &str, id: &Id) -> bool {
+        self.users_by_version
+            .get(&(source.to_owned(), name.to_owned(), version.to_owned()))
+            .is_some_and(|users| users.contains(id))
+    }
+
+    /// Current (not revoked) members of an organization Id
+    pub fn get_members_of(&self, org: &Id) -> impl Iterator<Item = &Id> {
+        self.members_by_org
+            .get(org)
+            .into_iter()
+            .flatten()
+            .filter(|(_, status)| status.value == MembershipStatus::Member)
+            .map(|(id, _)| id)
+    }
+
     pub fn all_known_ids(&self) -> BTreeSet<Id> {
         self.url_by_id
             .keys()
@@ -238,43 +837,195 @@ impl TrustDB {
             .collect()
     }
 
-    fn get_reviews_of(&self, digest: &Digest) -> Option<&HashMap<Id, TimestampedReview>> {
-        self.digest_to_reviews.get(digest.as_slice())
+    fn get_reviews_of(
+        &self,
+        digest_type: DigestType,
+        digest: &Digest,
+    ) -> Option<&HashMap<Id, TimestampedReview>> {
+        self.digest_to_reviews
+            .get(&(digest_type.to_string(), digest.as_slice().to_owned()))
+    }
+
+    /// Known digest types used by any of the reviews
+    pub fn get_digest_types(&self) -> BTreeSet<DigestType> {
+        self.digest_types
+            .iter()
+            .filter_map(|digest_type| digest_type.parse().ok())
+            .collect()
+    }
+
+    pub fn verify_digest(&self, digest: &Digest, trust_set: &TrustSet) -> VerificationStatus {
+        self.verify_digests(&[(DigestType::Blake2b, digest.clone())], trust_set)
     }
 
-    pub fn verify_digest<H>(
+    /// Most recent reviews of any of the `digests` by each Id in the `trust_set`
+    fn get_latest_trusted_reviews_of(
         &self,
-        digest: &Digest,
-        trust_set: &HashSet<Id, H>,
-    ) -> VerificationStatus
-    where
-        H: std::hash::BuildHasher + std::default::Default,
-    {
-        if let Some(reviews) = self.get_reviews_of(digest) {
-            // Faster somehow maybe?
-            let reviews_by: HashSet<Id, H> = reviews.keys().map(|s| s.to_owned()).collect();
-            let matching_reviewers = trust_set.intersection(&reviews_by);
-            let mut trust_count = 0;
-            let mut distrust_count = 0;
-            for matching_reviewer in matching_reviewers {
-                if Rating::Neutral <= reviews[matching_reviewer].value.rating {
-                    trust_count += 1;
+        digests: &[(DigestType, Digest)],
+        trust_set: &TrustSet,
+    ) -> HashMap<&Id, &TimestampedReview> {
+        let mut reviews: HashMap<&Id, &TimestampedReview> = HashMap::new();
+        for (digest_type, digest) in digests {
+            for (id, review) in self
+                .get_reviews_of(*digest_type, digest)
+                .into_iter()
+                .flatten()
+            {
+                if !trust_set.uses_review(id, &review.date) {
+                    continue;
                 }
-                if reviews[matching_reviewer].value.rating < Rating::Neutral {
-                    distrust_count += 1;
+                let entry = reviews.entry(id).or_insert(review);
+                if entry.date < review.date {
+                    *entry = review;
                 }
             }
+        }
+        reviews
+    }
+
+    /// Verify content using its digests of different types
+    ///
+    /// Reviews of any of the digests count; if an Id reviewed more than one,
+    /// only the most recent review is used.
+    pub fn verify_digests(
+        &self,
+        digests: &[(DigestType, Digest)],
+        trust_set: &TrustSet,
+    ) -> VerificationStatus {
+        let reviews = self.get_latest_trusted_reviews_of(digests, trust_set);
+        status_of_ratings(
+            reviews
+                .values()
+                .filter(|review| trust_set.counts_review(&review.value))
+                .map(|review| &review.value.rating),
+        )
+    }
 
-            if distrust_count > 0 {
-                VerificationStatus::Flagged
-            } else if trust_count > 0 {
-                VerificationStatus::Verified
-            } else {
-                VerificationStatus::Unknown
+    /// Like `verify_package_reviews`, but `Flagged` if trusted Ids reported
+    /// issues affecting the `version`
+    pub fn verify_package_version(
+        &self,
+        digests: &[(DigestType, Digest)],
+        source: &str,
+        name: &str,
+        version: &str,
+        trust_set: &TrustSet,
+    ) -> VerificationStatus {
+        if !self
+            .get_issues_for_version(source, name, version, trust_set)
+            .is_empty()
+        {
+            return VerificationStatus::Flagged;
+        }
+
+        self.verify_package_reviews(digests, source, name, version, trust_set)
+    }
+
+    /// Like `verify_digests`, but also using reviews of other versions
+    /// that apply to the `version` of the package (`applies-to`)
+    pub fn verify_package_reviews(
+        &self,
+        digests: &[(DigestType, Digest)],
+        source: &str,
+        name: &str,
+        version: &str,
+        trust_set: &TrustSet,
+    ) -> VerificationStatus {
+        let reviews =
+            self.get_latest_trusted_package_reviews(digests, source, name, version, trust_set);
+        status_of_ratings(reviews.values().map(|(_, review)| &review.rating))
+    }
+
+    /// Date of the newest review backing the `Verified` status of a package
+    /// version (see `verify_package_version`)
+    ///
+    /// `None` if the version is not verified.
+    pub fn get_verifying_review_date(
+        &self,
+        digests: &[(DigestType, Digest)],
+        source: &str,
+        name: &str,
+        version: &str,
+        trust_set: &TrustSet,
+    ) -> Option<DateTime<Utc>> {
+        if self.verify_package_version(digests, source, name, version, trust_set)
+            != VerificationStatus::Verified
+        {
+            return None;
+        }
+        self.get_latest_trusted_package_reviews(digests, source, name, version, trust_set)
+            .values()
+            .filter(|(_, review)| Rating::Neutral <= review.rating)
+            .map(|(date, _)| *date)
+            .max()
+    }
+
+    /// Most recent reviews of a package version by each Id in the `trust_set`,
+    /// either of its `digests` or applying to the `version`
+    fn get_latest_trusted_package_reviews(
+        &self,
+        digests: &[(DigestType, Digest)],
+        source: &str,
+        name: &str,
+        version: &str,
+        trust_set: &TrustSet,
+    ) -> HashMap<&Id, (DateTime<Utc>, &review::Review)> {
+        let mut reviews: HashMap<&Id, (DateTime<Utc>, &review::Review)> = self
+            .get_latest_trusted_reviews_of(digests, trust_set)
+            .into_iter()
+            .map(|(id, review)| (id, (review.date, &review.value)))
+            .collect();
+        for review in self.get_package_reviews_applying_to(source, name, version) {
+            let date = review.date_utc();
+            if !trust_set.uses_review(&review.from.id, &date) {
+                continue;
             }
-        } else {
-            VerificationStatus::Unknown
+            let entry = reviews
+                .entry(&review.from.id)
+                .or_insert((date, review.review()));
+            if entry.0 < date {
+                *entry = (date, review.review());
+            }
+        }
+        reviews.retain(|_, (_, review)| trust_set.counts_review(review));
+        reviews
+    }
+
+    /// Ids that determined the result of `verify_package_version`
+    ///
+    /// The ones that reported issues affecting the version, or had negative
+    /// reviews of it, if it's flagged; otherwise the ones with positive
+    /// reviews. Reviews count the same way as when verifying: of the
+    /// `digests`, or of other versions applying to this one, by Ids from
+    /// the `trust_set` only.
+    pub fn get_verifying_reviewers(
+        &self,
+        digests: &[(DigestType, Digest)],
+        source: &str,
+        name: &str,
+        version: &str,
+        trust_set: &TrustSet,
+    ) -> Vec<Id> {
+        let mut ids: Vec<Id> = self
+            .get_issues_for_version(source, name, version, trust_set)
+            .into_iter()
+            .map(|issue| issue.from.id)
+            .collect();
+        if ids.is_empty() {
+            let reviews =
+                self.get_latest_trusted_package_reviews(digests, source, name, version, trust_set);
+            let flagged = reviews
+                .values()
+                .any(|(_, review)| review.rating < Rating::Neutral);
+            ids = reviews
+                .into_iter()
+                .filter(|(_, (_, review))| (review.rating < Rating::Neutral) == flagged)
+                .map(|(id, _)| id.clone())
+                .collect();
         }
+        ids.sort();
+        ids.dedup();
+        ids
     }
 
     fn record_url_from_to_field(&mut self, date: &DateTime<Utc>, to: &crev_data::PubId) {
@@ -293,23 +1044,114 @@ impl TrustDB {
         }
         .insert_into_or_update_to_more_recent(self.url_by_id.entry(from.id.clone()));
     }
-    fn add_proof(&mut self, proof: &proof::Proof) {
-        proof
-            .verify()
-            .expect("All proofs were supposed to be valid here");
+    fn index_proof(&mut self, proof: &proof::Proof) {
         match proof.content {
             Content::Code(ref review) => self.add_code_review(&review),
             Content::Package(ref review) => self.add_package_review(&review, &proof.signature),
             Content::Trust(ref trust) => self.add_trust(&trust),
+            Content::Membership(ref membership) => self.add_membership(&membership),
+            Content::Issue(ref issue) => self.add_issue(issue),
+            Content::Alternative(ref alternative) => self.add_alternative(alternative),
+            Content::IdentityClaim(ref claim) => self.add_identity_claim(claim),
+            Content::Usage(ref usage) => self.add_usage(usage),
+            // readable only once decrypted, see `Local::decrypt_proofs`
+            Content::Encrypted(_) => {}
         }
     }
 
+    fn notify(&mut self, change: &TrustDBChange<'_>) {
+        for listener in &mut self.listeners {
+            listener(change);
+        }
+    }
+
+    /// Store and index a verified `proof`, unless it was already added
+    fn insert_proof(&mut self, proof: proof::Proof) {
+        if !self.proof_signatures.insert(proof.signature.clone()) {
+            return;
+        }
+        self.index_proof(&proof);
+        self.notify(&TrustDBChange::Added(&proof));
+        self.proofs.push(proof);
+    }
+
     pub fn import_from_iter(&mut self, i: impl Iterator<Item = proof::Proof>) {
         for proof in i {
-            self.add_proof(&proof);
+            proof
+                .verify()
+                .expect("All proofs were supposed to be valid here");
+            self.insert_proof(proof);
         }
     }
 
+    /// Like `import_from_iter`, for proofs with signatures verified already
+    /// (with a `VerificationCache`, or when indexed in a `ProofIndex`)
+    pub(crate) fn import_verified_from_iter(&mut self, i: impl Iterator<Item = proof::Proof>) {
+        for proof in i {
+            debug_assert!(
+                proof.verify().is_ok(),
+                "All proofs were supposed to be verified here"
+            );
+            self.insert_proof(proof);
+        }
+    }
+
+    /// Add a single proof, updating the database in place
+    ///
+    /// Adding a proof that is already in the database does nothing.
+    pub fn add_proof(&mut self, proof: &proof::Proof) -> Result<()> {
+        proof.verify()?;
+        self.insert_proof(proof.clone());
+        Ok(())
+    }
+
+    /// Remove a proof with a given `signature`, returning it
+    ///
+    /// Indexes only keep the most recent information, so they are rebuilt
+    /// from the remaining proofs.
+    pub fn remove_proof(&mut self, signature: &str) -> Option<proof::Proof> {
+        let index = self
+            .proofs
+            .iter()
+            .position(|proof| proof.signature == signature)?;
+        let removed = self.proofs.remove(index);
+        self.rebuild();
+        self.notify(&TrustDBChange::Removed(&removed));
+        Some(removed)
+    }
+
+    /// Replace a proof with a given `signature` by a `new` one (eg. its edited version)
+    pub fn replace_proof(
+        &mut self,
+        signature: &str,
+        new: &proof::Proof,
+    ) -> Result<Option<proof::Proof>> {
+        new.verify()?;
+        let removed = self.remove_proof(signature);
+        self.insert_proof(new.clone());
+        Ok(removed)
+    }
+
+    fn rebuild(&mut self) {
+        let proofs = std::mem::take(&mut self.proofs);
+        let listeners = std::mem::take(&mut self.listeners);
+        *self = Self::new();
+        for proof in proofs {
+            self.insert_proof(proof);
+        }
+        self.listeners = listeners;
+    }
+
+    /// Call `listener` on every change of the database
+    pub fn add_listener(&mut self, listener: impl FnMut(&TrustDBChange<'_>) + Send + 'static) {
+        self.listeners.push(Box::new(listener));
+    }
+
+    /// All proofs in the database, in the order they were added
+    pub fn proofs(&self) -> &[proof::Proof] {
+        &self.proofs
+    }
+
     fn get_ids_trusted_by(&self, id: &Id) -> impl Iterator<Item = (TrustLevel, &Id)> {
         if let Some(map) = self.trust_id_to_id.get(id) {
             Some(map.iter().map(|(id, trust)| (trust.value, id)))
@@ -321,7 +1163,7 @@ impl TrustDB {
     }
 
     // Oh god, please someone verify this :D
-    pub fn calculate_trust_set(&self, for_id: &Id, params: &TrustDistanceParams) -> HashSet<Id> {
+    pub fn calculate_trust_set(&self, for_id: &Id, params: &TrustDistanceParams) -> TrustSet {
         #[derive(PartialOrd, Ord, Eq, PartialEq, Clone, Debug)]
         struct Visit {
             distance: u64,
@@ -333,39 +1175,80 @@ impl TrustDB {
             id: for_id.clone(),
         });
 
-        let mut visited = HashMap::<&Id, _>::new();
-        visited.insert(&for_id, 0);
+        let mut visited = HashMap::<Id, TrustInfo>::new();
+        visited.insert(
+            for_id.clone(),
+            TrustInfo {
+                distance: 0,
+                effective_trust: TrustLevel::High,
+                through: None,
+            },
+        );
         while let Some(current) = pending.iter().next().cloned() {
             pending.remove(&current);
 
-            if let Some(visited_distance) = visited.get(&current.id) {
-                if *visited_distance < current.distance {
-                    continue;
-                }
-            }
+            let current_trust = match visited.get(&current.id) {
+                Some(info) if info.distance < current.distance => continue,
+                Some(info) => info.effective_trust,
+                None => continue,
+            };
 
-            for (level, candidate_id) in self.get_ids_trusted_by(&&current.id) {
-                let candidate_distance_from_current =
-                    if let Some(v) = params.distance_by_level(level) {
-                        v
+            let trusted = self
+                .get_ids_trusted_by(&current.id)
+                .filter_map(|(level, id)| params.distance_by_level(level).map(|d| (d, level, id)))
+                .map(|(distance, level, id)| {
+                    let level = if params.cap_by_weakest_link {
+                        weaker_trust(current_trust, level)
                     } else {
-                        continue;
+                        level
                     };
+                    (distance, level, id)
+                })
+                .filter(|(_, level, _)| weaker_trust(*level, params.min_trust) == params.min_trust);
+            // members of an organization are trusted as much as the organization
+            // itself, one trust step further, so that members adding members
+            // can't get around `max_distance`
+            let member_distance = params.distance_by_level(current_trust);
+            let members = self
+                .get_members_of(&current.id)
+                .filter_map(|id| member_distance.map(|distance| (distance, current_trust, id)));
+
+            for (candidate_distance_from_current, candidate_trust, candidate_id) in
+                trusted.chain(members)
+            {
                 let candidate_total_distance = current.distance + candidate_distance_from_current;
                 if candidate_total_distance > params.max_distance {
+                    trace!(
+                        "Not trusting {} through {}: distance {} is over {}",
+                        candidate_id,
+                        current.id,
+                        candidate_total_distance,
+                        params.max_distance
+                    );
+                    continue;
+                }
+                if params.ignored_ids.contains(candidate_id) {
+                    debug!("Not trusting {}: ignored", candidate_id);
                     continue;
                 }
 
-                if let Some(prev_candidate_distance) = visited.get(candidate_id).cloned() {
-                    if prev_candidate_distance > candidate_total_distance {
-                        visited.insert(candidate_id, candidate_total_distance);
-                        pending.insert(Visit {
+                let is_shorter = visited
+                    .get(candidate_id)
+                    .map(|prev| prev.distance > candidate_total_distance)
+                    .unwrap_or(true);
+                if is_shorter {
+                    debug!(
+                        "Trusting {} ({}) at distance {}, through {}",
+                        candidate_id, candidate_trust, candidate_total_distance, current.id
+                    );
+                    visited.insert(
+                        candidate_id.clone(),
+                        TrustInfo {
                             distance: candidate_total_distance,
-                            id: candidate_id.to_owned(),
-                        });
-                    }
-                } else {
-                    visited.insert(candidate_id, candidate_total_distance);
+                            effective_trust: candidate_trust,
+                            through: Some(current.id.clone()),
+                        },
+                    );
                     pending.insert(Visit {
                         distance: candidate_total_distance,
                         id: candidate_id.to_owned(),
@@ -374,7 +1257,35 @@ impl TrustDB {
             }
         }
 
-        visited.keys().map(|id| (*id).clone()).collect()
+        TrustSet {
+            trusted: visited,
+            oldest_review_date: params
+                .max_review_age
+                .map(|age| params.as_of.unwrap_or_else(Utc::now) - age),
+            criteria: params.criteria.clone(),
+            ignored: params.ignored_ids.clone(),
+        }
+    }
+
+    /// Trust level `from` has in `to`, if `from` issued any trust proof for it
+    pub fn get_trust_level(&self, from: &Id, to: &Id) -> Option<TrustLevel> {
+        self.trust_id_to_id
+            .get(from)
+            .and_then(|map| map.get(to))
+            .map(|trust| trust.value)
+    }
+
+    /// All known Ids using a given proof repository `url`
+    pub fn lookup_ids_by_url(&self, url: &str) -> Vec<Id> {
+        let url = url.trim_end_matches('/');
+        self.all_known_ids()
+            .into_iter()
+            .filter(|id| {
+                self.lookup_url(id)
+                    .map(|id_url| id_url.url.trim_end_matches('/') == url)
+                    .unwrap_or(false)
+            })
+            .collect()
     }
 
     pub fn lookup_url(&self, id: &Id) -> Option<&Url> {
@@ -385,14 +1296,143 @@ impl TrustDB {
     }
 }
 
+/// How an Id in a `TrustSet` is trusted
+#[derive(Debug, Clone)]
+pub struct TrustInfo {
+    /// Sum of the distances of trust links leading to the Id
+    pub distance: u64,
+    /// Trust level of the last link leading to the Id, or of the weakest
+    /// one (see `TrustDistanceParams::cap_by_weakest_link`)
+    ///
+    /// Members of an organization get the one of the organization;
+    /// the Id the set was calculated for is trusted `High`.
+    pub effective_trust: TrustLevel,
+    /// Id this one is trusted through; `None` for the Id the set was calculated for
+    pub through: Option<Id>,
+}
+
+/// Ids trusted by a given Id, along with how they are trusted
+///
+/// Calculated with `TrustDB::calculate_trust_set`.
+#[derive(Debug, Clone, Default)]
+pub struct TrustSet {
+    trusted: HashMap<Id, TrustInfo>,
+    /// Reviews older than this are not used (see `TrustDistanceParams::max_review_age`)
+    oldest_review_date: Option<DateTime<Utc>>,
+    /// Criteria positive reviews have to assert (see `TrustDistanceParams::criteria`)
+    criteria: Vec<String>,
+    /// Ids left out on purpose (see `TrustDistanceParams::ignored_ids`)
+    ignored: HashSet<Id>,
+}
+
+impl TrustSet {
+    pub fn contains(&self, id: &Id) -> bool {
+        self.trusted.contains_key(id)
+    }
+
+    /// Is a review by `id` from `date` used for verification?
+    pub fn uses_review(&self, id: &Id, date: &DateTime<Utc>) -> bool {
+        self.contains(id)
+            && self
+                .oldest_review_date
+                .map(|oldest| oldest <= *date)
+                .unwrap_or(true)
+    }
+
+    /// Does `review` count for verification, given the required criteria?
+    ///
+    /// Negative reviews always do.
+    pub fn counts_review(&self, review: &review::Review) -> bool {
+        review.rating < Rating::Neutral
+            || self
+                .criteria
+                .iter()
+                .all(|criteria| review.criteria.contains(criteria))
+    }
+
+    pub fn get(&self, id: &Id) -> Option<&TrustInfo> {
+        self.trusted.get(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Id, &TrustInfo)> {
+        self.trusted.iter()
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &Id> {
+        self.trusted.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.trusted.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trusted.is_empty()
+    }
+
+    /// The shortest chain of trust leading to `id`
+    ///
+    /// Starts with the Id the set was calculated for, and ends with `id`;
+    /// `None` if `id` is not in the set.
+    pub fn get_path(&self, id: &Id) -> Option<Vec<Id>> {
+        let mut path = vec![id.clone()];
+        let mut info = self.trusted.get(id)?;
+        while let Some(through) = &info.through {
+            path.push(through.clone());
+            info = &self.trusted[through];
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// The weaker of two trust levels
+fn weaker_trust(a: TrustLevel, b: TrustLevel) -> TrustLevel {
+    // `TrustLevel` is ordered from the strongest
+    std::cmp::max(a, b)
+}
+
+#[derive(Clone)]
 pub struct TrustDistanceParams {
     pub max_distance: u64,
     pub high_trust_distance: u64,
     pub medium_trust_distance: u64,
     pub low_trust_distance: u64,
+    /// Limit the effective trust of an Id to the weakest link of its trust path
+    ///
+    /// Otherwise it's the trust level of the last link.
+    pub cap_by_weakest_link: bool,
+    /// Leave out Ids with lower effective trust
+    pub min_trust: TrustLevel,
+    /// Leave out these Ids, along with anyone trusted only through them
+    pub ignored_ids: HashSet<Id>,
+    /// Don't use reviews older than this for verification, even if the
+    /// reviewed content didn't change since
+    pub max_review_age: Option<chrono::Duration>,
+    /// Only use proofs created before this time (eg. to check what was
+    /// verified when a release was shipped); `max_review_age` is counted
+    /// from it too. Applied when loading proofs (see `Local::load_db`
+    /// and `is_in_time`).
+    pub as_of: Option<DateTime<Utc>>,
+    /// Only count positive reviews asserting all of these criteria
+    /// (eg. `safe-to-deploy`)
+    pub criteria: Vec<String>,
 }
 
 impl TrustDistanceParams {
+    /// Was `proof` created before `as_of` (always, without `as_of`)
+    ///
+    /// Goes by the date the proof declares for itself: nothing stops its
+    /// author from signing a proof with any date, so a backdated proof
+    /// passes the filter. Without a transparency log anchoring proofs in
+    /// time, `as_of` only answers what proofs *claim* to have existed then.
+    pub fn is_in_time(&self, proof: &proof::Proof) -> bool {
+        match self.as_of {
+            Some(as_of) => proof.content.date().with_timezone(&Utc) < as_of,
+            None => true,
+        }
+    }
+
     fn distance_by_level(&self, level: TrustLevel) -> Option<u64> {
         use crev_data::proof::trust::TrustLevel::*;
         Some(match level {
@@ -412,6 +1452,26 @@ impl Default for TrustDistanceParams {
             high_trust_distance: 0,
             medium_trust_distance: 1,
             low_trust_distance: 5,
+            cap_by_weakest_link: false,
+            min_trust: TrustLevel::Low,
+            ignored_ids: HashSet::new(),
+            max_review_age: None,
+            as_of: None,
+            criteria: vec![],
         }
     }
 }
+
+/// Is `version` affected by an advisory attached to a review of `fixed` version
+fn is_affected_by_advisory(
+    version: &semver::Version,
+    fixed: &semver::Version,
+    range: AdvisoryRange,
+) -> bool {
+    version < fixed
+        && match range {
+            AdvisoryRange::All => true,
+            AdvisoryRange::Major => version.major == fixed.major,
+            AdvisoryRange::Minor => version.major == fixed.major && version.minor == fixed.minor,
+        }
+}
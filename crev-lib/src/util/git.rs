@@ -0,0 +1,344 @@
+use crate::Result;
+use crev_data::{id::OwnId, Id};
+use git2;
+use std::{
+    env,
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+/// Commit header field holding the CrevID signature of a commit
+///
+/// The value is `<id> <signature>`, where `signature` is over
+/// the whole commit object without the header itself.
+pub const COMMIT_SIGNATURE_FIELD: &str = "crevsig";
+
+/// Outcome of checking a commit for a CrevID signature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommitSignature {
+    /// Signed with a valid signature of the given Id
+    Valid(Id),
+    /// Carries a signature that doesn't match the commit
+    Invalid,
+    /// Not signed with a CrevID
+    Missing,
+}
+
+fn format_git_signature(sig: &git2::Signature<'_>) -> Result<String> {
+    let when = sig.when();
+    let offset = when.offset_minutes();
+    Ok(format!(
+        "{} <{}> {} {}{:02}{:02}",
+        sig.name()
+            .ok_or_else(|| format_err!("Git signature name is not utf8"))?,
+        sig.email()
+            .ok_or_else(|| format_err!("Git signature email is not utf8"))?,
+        when.seconds(),
+        if offset < 0 { '-' } else { '+' },
+        offset.abs() / 60,
+        offset.abs() % 60
+    ))
+}
+
+/// Add all changes to the tracked files matching `pathspec` to the index,
+/// returning its tree
+fn update_index(repo: &git2::Repository, pathspec: &str) -> Result<git2::Oid> {
+    let mut index = repo.index()?;
+    index.update_all(vec![pathspec], None)?;
+    index.write()?;
+    Ok(index.write_tree()?)
+}
+
+/// Commit `HEAD` points to; `None` in a repository without commits
+fn head_commit(repo: &git2::Repository) -> Result<Option<git2::Commit<'_>>> {
+    match repo.head() {
+        Ok(head) => Ok(Some(head.peel_to_commit()?)),
+        Err(ref e) if e.code() == git2::ErrorCode::UnbornBranch => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Commit all changes to the tracked files matching `pathspec` (eg. `*`),
+/// like `git commit -a`
+///
+/// Fails if there's nothing to commit.
+pub fn commit_all(repo: &git2::Repository, pathspec: &str, message: &str) -> Result<git2::Oid> {
+    let tree_id = update_index(repo, pathspec)?;
+    let parent = head_commit(repo)?;
+    if parent.as_ref().map(|parent| parent.tree_id()) == Some(tree_id) {
+        bail!("Nothing to commit");
+    }
+    let tree = repo.find_tree(tree_id)?;
+    let sig = repo.signature()?;
+    let parents: Vec<&git2::Commit<'_>> = parent.iter().collect();
+    Ok(repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)?)
+}
+
+/// Commit the current index and sign the commit with `id`
+///
+/// Works like `git commit -a`: all changes to the tracked files are
+/// included. `HEAD` (or the branch it points to) is updated to the new commit.
+pub fn commit_all_signed_by(
+    repo: &git2::Repository,
+    id: &OwnId,
+    message: &str,
+) -> Result<git2::Oid> {
+    commit_signed_by(repo, id, "*", message)
+}
+
+/// Like `commit_all_signed_by`, but only with changes to the files matching `pathspec`
+pub fn commit_signed_by(
+    repo: &git2::Repository,
+    id: &OwnId,
+    pathspec: &str,
+    message: &str,
+) -> Result<git2::Oid> {
+    let tree_id = update_index(repo, pathspec)?;
+    let parent = head_commit(repo)?;
+
+    let git_sig = repo.signature()?;
+    let mut content = String::new();
+    writeln!(content, "tree {}", tree_id)?;
+    if let Some(ref parent) = parent {
+        writeln!(content, "parent {}", parent.id())?;
+    }
+    writeln!(content, "author {}", format_git_signature(&git_sig)?)?;
+    writeln!(content, "committer {}", format_git_signature(&git_sig)?)?;
+    writeln!(content)?;
+    content.write_str(message.trim_end())?;
+    content.write_str("\n")?;
+
+    let signature = crev_common::base64_encode(&id.sign(content.as_bytes()));
+    let commit_id = repo.commit_signed(
+        &content,
+        &format!("{} {}", id.id.id, signature),
+        Some(COMMIT_SIGNATURE_FIELD),
+    )?;
+
+    let head = repo.find_reference("HEAD")?;
+    match head.symbolic_target() {
+        Some(branch) => {
+            repo.reference(branch, commit_id, true, message)?;
+        }
+        None => repo.set_head_detached(commit_id)?,
+    }
+
+    Ok(commit_id)
+}
+
+/// Check the CrevID signature of a given commit
+pub fn verify_commit_signature(
+    repo: &git2::Repository,
+    commit_id: git2::Oid,
+) -> Result<CommitSignature> {
+    let (signature, content) = match repo
+        .extract_signature(&commit_id, Some(COMMIT_SIGNATURE_FIELD))
+    {
+        Ok(res) => res,
+        Err(ref e) if e.code() == git2::ErrorCode::NotFound => return Ok(CommitSignature::Missing),
+        Err(e) => return Err(e.into()),
+    };
+
+    let signature = signature
+        .as_str()
+        .ok_or_else(|| format_err!("Commit signature is not utf8"))?;
+    let mut parts = signature.trim().splitn(2, ' ');
+    let (id, sig) = match (parts.next(), parts.next()) {
+        (Some(id), Some(sig)) => (id, sig),
+        _ => return Ok(CommitSignature::Invalid),
+    };
+    let id = match Id::crevid_from_str(id) {
+        Ok(id) => id,
+        Err(_) => return Ok(CommitSignature::Invalid),
+    };
+
+    Ok(match id.verify_signature(&content, sig) {
+        Ok(()) => CommitSignature::Valid(id),
+        Err(_) => CommitSignature::Invalid,
+    })
+}
+
+/// Private keys in `~/.ssh` that `ssh` would try by default
+fn default_ssh_keys() -> Vec<PathBuf> {
+    let home = match env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")) {
+        Some(home) => PathBuf::from(home),
+        None => return vec![],
+    };
+    ["id_ed25519", "id_ecdsa", "id_rsa"]
+        .iter()
+        .map(|name| home.join(".ssh").join(name))
+        .filter(|path| path.exists())
+        .collect()
+}
+
+/// Callbacks authenticating like `git` does by default
+///
+/// SSH remotes are tried with the keys of the ssh agent, and then with
+/// keys without passphrase from `~/.ssh`. HTTPS remotes use git credential
+/// helpers (`credential.helper`), eg. to authenticate with a token.
+pub fn remote_callbacks(config: &git2::Config) -> git2::RemoteCallbacks<'_> {
+    // libgit2 keeps asking as long as credentials are rejected,
+    // so every way is tried once
+    let mut tried_agent = false;
+    let mut ssh_keys = default_ssh_keys().into_iter();
+    let mut tried_helper = false;
+    let mut callbacks = git2::RemoteCallbacks::new();
+    callbacks.credentials(move |url, username, allowed| {
+        if allowed.contains(git2::CredentialType::USERNAME) {
+            return git2::Cred::username(username.unwrap_or("git"));
+        }
+        if allowed.contains(git2::CredentialType::SSH_KEY) {
+            let username = username.unwrap_or("git");
+            if !tried_agent {
+                tried_agent = true;
+                return git2::Cred::ssh_key_from_agent(username);
+            }
+            if let Some(key) = ssh_keys.next() {
+                return git2::Cred::ssh_key(username, None, &key, None);
+            }
+        }
+        if allowed.contains(git2::CredentialType::USER_PASS_PLAINTEXT) && !tried_helper {
+            tried_helper = true;
+            return git2::Cred::credential_helper(config, url, username);
+        }
+        Err(git2::Error::from_str(&format!(
+            "no (more) credentials to authenticate to {}",
+            url
+        )))
+    });
+    callbacks
+}
+
+/// Clone `url` to `dir`, authenticating with `remote_callbacks`
+///
+/// `on_progress` gets the number of received and of all objects.
+pub fn clone(
+    url: &str,
+    dir: &Path,
+    on_progress: &mut dyn FnMut(usize, usize),
+) -> Result<git2::Repository> {
+    let config = git2::Config::open_default()?;
+    let mut callbacks = remote_callbacks(&config);
+    callbacks.transfer_progress(|progress| {
+        on_progress(progress.received_objects(), progress.total_objects());
+        true
+    });
+    let mut opts = git2::FetchOptions::new();
+    opts.remote_callbacks(callbacks);
+    let repo = git2::build::RepoBuilder::new()
+        .fetch_options(opts)
+        .clone(url, dir)?;
+    Ok(repo)
+}
+
+/// Fetch `branch` of `origin` into `FETCH_HEAD`
+///
+/// `on_progress` gets the number of received and of all objects.
+pub fn fetch_origin(
+    repo: &git2::Repository,
+    branch: &str,
+    on_progress: &mut dyn FnMut(usize, usize),
+) -> Result<()> {
+    let config = repo.config()?;
+    let mut callbacks = remote_callbacks(&config);
+    callbacks.transfer_progress(|progress| {
+        on_progress(progress.received_objects(), progress.total_objects());
+        true
+    });
+    let mut opts = git2::FetchOptions::new();
+    opts.remote_callbacks(callbacks);
+    repo.find_remote("origin")?
+        .fetch(&[branch], Some(&mut opts), None)?;
+    Ok(())
+}
+
+/// Name of the branch `HEAD` points to, even if it has no commits yet
+fn current_branch(repo: &git2::Repository) -> Result<String> {
+    let head = repo.find_reference("HEAD")?;
+    match head.symbolic_target() {
+        Some(target) if target.starts_with("refs/heads/") => {
+            Ok(target["refs/heads/".len()..].to_owned())
+        }
+        _ => bail!("Not on any branch"),
+    }
+}
+
+/// Push the current branch to the same branch of `origin`, like `git push`
+pub fn push(repo: &git2::Repository) -> Result<()> {
+    let branch = current_branch(repo)?;
+    let refspec = format!("refs/heads/{}", branch);
+    let config = repo.config()?;
+    let mut rejection = None;
+    {
+        let mut callbacks = remote_callbacks(&config);
+        callbacks.push_update_reference(|_refname, status| {
+            rejection = status.map(ToOwned::to_owned);
+            Ok(())
+        });
+        let mut opts = git2::PushOptions::new();
+        opts.remote_callbacks(callbacks);
+        repo.find_remote("origin")?.push(
+            &[format!("{}:{}", refspec, refspec).as_str()],
+            Some(&mut opts),
+        )?;
+    }
+    if let Some(rejection) = rejection {
+        bail!("Push of {} was rejected: {}", branch, rejection);
+    }
+    Ok(())
+}
+
+/// Fetch the current branch from `origin` and fast-forward to it, like `git pull --ff-only`
+pub fn pull(repo: &git2::Repository) -> Result<()> {
+    let branch = current_branch(repo)?;
+    fetch_origin(repo, &branch, &mut |_, _| {})?;
+    let fetched = repo.reference_to_annotated_commit(&repo.find_reference("FETCH_HEAD")?)?;
+    let (analysis, _) = repo.merge_analysis(&[&fetched])?;
+    if analysis.is_up_to_date() {
+        return Ok(());
+    }
+    if !analysis.is_fast_forward() && !analysis.is_unborn() {
+        bail!(
+            "{} has diverged from origin; merge it with `cargo crev git pull`",
+            branch
+        );
+    }
+    let target = repo.find_object(fetched.id(), None)?;
+    repo.checkout_tree(&target, Some(git2::build::CheckoutBuilder::new().safe()))?;
+    repo.reference(
+        &format!("refs/heads/{}", branch),
+        fetched.id(),
+        true,
+        "pull: fast-forward",
+    )?;
+    Ok(())
+}
+
+/// Changes of files matching `pathspec` since `HEAD`, staged or not,
+/// as a patch, like `git diff HEAD`
+pub fn diff_head(repo: &git2::Repository, pathspec: &str) -> Result<String> {
+    let tree = match head_commit(repo)? {
+        Some(commit) => Some(commit.tree()?),
+        None => None,
+    };
+    let pathspec = git2::Pathspec::new(vec![pathspec])?;
+    let diff = repo.diff_tree_to_workdir_with_index(tree.as_ref(), None)?;
+    let mut patch = String::new();
+    diff.print(git2::DiffFormat::Patch, |delta, _hunk, line| {
+        let in_pathspec = delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path())
+            .map(|path| pathspec.matches_path(path, git2::PathspecFlags::DEFAULT))
+            .unwrap_or(false);
+        if !in_pathspec {
+            return true;
+        }
+        if let '+' | '-' | ' ' = line.origin() {
+            patch.push(line.origin());
+        }
+        patch.push_str(&String::from_utf8_lossy(line.content()));
+        true
+    })?;
+    Ok(patch)
+}
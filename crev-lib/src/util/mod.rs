@@ -6,13 +6,15 @@ use std::fmt::Write as FmtWrite;
 use std::{self, env, ffi, fs, io::Write, path::Path, process};
 use tempdir;
 
+pub mod git;
+
 pub use crev_common::{read_file_to_string, store_str_to_file, store_to_file_with};
 pub const APP_INFO: app_dirs::AppInfo = app_dirs::AppInfo {
     name: "crev",
     author: "Dawid Ciężarkiewicz",
 };
 
-fn get_editor_to_use() -> ffi::OsString {
+pub fn get_editor_to_use() -> ffi::OsString {
     if let Some(v) = env::var_os("VISUAL") {
         return v;
     } else if let Some(v) = env::var_os("EDITOR") {
@@ -54,11 +56,67 @@ pub fn get_documentation_for(content: &proof::Content) -> &'static str {
     use crev_data::proof::Content;
     match content {
         Content::Trust(_) => include_str!("../../rc/doc/editing-trust.md"),
+        Content::Membership(_) => include_str!("../../rc/doc/editing-membership.md"),
+        Content::Issue(_) => include_str!("../../rc/doc/editing-issue.md"),
+        Content::Alternative(_) => include_str!("../../rc/doc/editing-alternative.md"),
+        Content::IdentityClaim(_) => include_str!("../../rc/doc/editing-identity-claim.md"),
+        // never edited
+        Content::Encrypted(_) => "",
+        Content::Usage(_) => include_str!("../../rc/doc/editing-usage.md"),
         Content::Code(_) => include_str!("../../rc/doc/editing-code-review.md"),
         Content::Package(_) => include_str!("../../rc/doc/editing-package-review.md"),
     }
 }
 
+/// Check what parsing a draft doesn't: fields that must be semver requirements
+fn validate_draft(content: &proof::Content) -> Result<()> {
+    use crev_data::proof::Content;
+    match content {
+        Content::Issue(issue) => {
+            // would never match any version otherwise
+            semver::VersionReq::parse(&issue.versions)
+                .map_err(|e| format_err!("`versions`: {}", e))?;
+        }
+        Content::Package(review) => {
+            if let Some(applies_to) = &review.applies_to {
+                semver::VersionReq::parse(applies_to)
+                    .map_err(|e| format_err!("`applies-to`: {}", e))?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Marks lines describing a problem with the edited text
+const EDIT_ERROR_PREFIX: &str = "# ERROR: ";
+
+/// `text` with the `error` annotation at the top, replacing the previous one
+pub(crate) fn annotate_edit_error(text: &str, error: &str) -> String {
+    let mut annotated = String::new();
+    for line in error.lines() {
+        annotated.push_str(EDIT_ERROR_PREFIX);
+        annotated.push_str(line);
+        annotated.push('\n');
+    }
+    annotated.push_str(EDIT_ERROR_PREFIX);
+    annotated.push_str("fix the problem, or remove everything to cancel\n");
+    for line in text
+        .lines()
+        .skip_while(|line| line.starts_with(EDIT_ERROR_PREFIX))
+    {
+        annotated.push_str(line);
+        annotated.push('\n');
+    }
+    annotated
+}
+
+/// Let the user edit the draft of `content`
+///
+/// Drafts that don't parse or validate are opened again, annotated with
+/// the problem, until they do, or the user removes everything. If an
+/// annotated draft comes back unchanged (eg. the editor returned right
+/// away), editing is given up on instead.
 pub fn edit_proof_content_iteractively(content: &proof::Content) -> Result<proof::Content> {
     let mut text = String::new();
 
@@ -68,14 +126,26 @@ pub fn edit_proof_content_iteractively(content: &proof::Content) -> Result<proof
     for line in get_documentation_for(content).lines() {
         text.write_fmt(format_args!("# {}\n", line))?;
     }
+    let mut has_error = false;
     loop {
-        text = edit_text_iteractively(&text)?;
-        match proof::Content::parse_draft(content, &text) {
+        let edited = edit_text_iteractively(&text)?;
+        if has_error && edited == text {
+            bail!("The draft with errors was not changed");
+        }
+        text = edited;
+        let is_empty = text
+            .lines()
+            .all(|line| line.trim().is_empty() || line.trim_start().starts_with('#'));
+        if is_empty {
+            bail!("User canceled");
+        }
+        match proof::Content::parse_draft(content, &text)
+            .and_then(|content| validate_draft(&content).map(|()| content))
+        {
             Err(e) => {
-                eprintln!("There was an error parsing content: {}", e);
-                if !crev_common::yes_or_no_was_y("Try again (y/n) ")? {
-                    bail!("User canceled");
-                }
+                eprintln!("There was an error in the edited content: {}", e);
+                text = annotate_edit_error(&text, &e.to_string());
+                has_error = true;
             }
             Ok(content) => return Ok(content),
         }
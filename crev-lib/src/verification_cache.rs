@@ -0,0 +1,82 @@
+//! Cache of successful proof signature verifications
+//!
+//! Checking signatures is the most expensive part of loading proofs.
+//! A proof whose signature was verified once, with exactly the same body,
+//! doesn't need to be verified again, so `(body digest, signature)` pairs
+//! of verified proofs are remembered in the local cache.
+use crate::Result;
+use crev_data::proof;
+use serde_cbor;
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Name of the cache file, in the cache directory
+pub const VERIFICATION_CACHE_FILE_NAME: &str = "verified-signatures.cbor";
+
+/// Key of a verified proof: a digest of its body digest and signature
+fn key(proof: &proof::Proof) -> Vec<u8> {
+    crev_common::blake2b256sum(&[proof.digest.as_slice(), proof.signature().as_bytes()].concat())
+}
+
+/// Verified proofs, loaded on first use and stored when dropped
+pub struct VerificationCache {
+    path: PathBuf,
+    verified: Option<HashSet<Vec<u8>>>,
+    changed: bool,
+}
+
+impl VerificationCache {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_owned(),
+            verified: None,
+            changed: false,
+        }
+    }
+
+    fn verified(&mut self) -> &mut HashSet<Vec<u8>> {
+        let path = &self.path;
+        self.verified.get_or_insert_with(|| {
+            // an unreadable cache is just rebuilt
+            fs::File::open(path)
+                .ok()
+                .and_then(|file| serde_cbor::from_reader(file).ok())
+                .unwrap_or_default()
+        })
+    }
+
+    /// Verify the signature of `proof`, unless it was verified before
+    pub fn verify(&mut self, proof: &proof::Proof) -> Result<()> {
+        let key = key(proof);
+        if self.verified().contains(&key) {
+            return Ok(());
+        }
+        proof.verify()?;
+        self.verified().insert(key);
+        self.changed = true;
+        Ok(())
+    }
+
+    pub fn store(&mut self) -> Result<()> {
+        if let Some(verified) = &self.verified {
+            if self.changed {
+                crev_common::store_to_file_with(&self.path, |mut file| {
+                    serde_cbor::to_writer(&mut file, verified)
+                })??;
+                self.changed = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for VerificationCache {
+    fn drop(&mut self) {
+        if let Err(e) = self.store() {
+            warn!("Couldn't store {}: {}", self.path.display(), e);
+        }
+    }
+}
@@ -28,6 +28,61 @@ fn read_file_to_digest_input(path: &Path, input: &mut impl digest::Digest) -> st
     Ok(())
 }
 
+/// How symbolic links are digested
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Digest the path the link points to (not the content there)
+    ///
+    /// On Windows `\\` separators are digested as `/`, to match other systems.
+    Target,
+    /// Digest the content the link points to, as if it was a regular file or directory
+    Follow,
+    /// Leave links out, as if they didn't exist
+    Skip,
+    /// Fail with `DigestError::SymlinkNotAllowed`
+    Reject,
+}
+
+/// Options affecting how a directory tree is digested
+///
+/// The defaults match the original digest format, so that existing digests
+/// stay valid. Changing any of them changes digests of affected content.
+#[derive(Clone, Debug)]
+pub struct DigestOptions {
+    pub symlinks: SymlinkPolicy,
+    /// Include directories that don't contain any files
+    ///
+    /// Some tools (eg. git) can't represent them, so copies of the same
+    /// content might or might not have them.
+    pub empty_dirs: bool,
+    /// Include the executable bit of files
+    ///
+    /// There is no such bit on Windows, so there all files are considered
+    /// not executable.
+    pub executable_bit: bool,
+}
+
+impl Default for DigestOptions {
+    fn default() -> Self {
+        DigestOptions {
+            symlinks: SymlinkPolicy::Target,
+            empty_dirs: true,
+            executable_bit: false,
+        }
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
 /// Sorted list of all descendants of a directory
 type Descendants = BTreeMap<OsString, Entry>;
 
@@ -37,6 +92,7 @@ struct Entry(Descendants);
 struct RecursiveDigest<Digest = blake2::Blake2b> {
     root_path: PathBuf,
     root: Entry,
+    options: DigestOptions,
     digest: std::marker::PhantomData<Digest>,
 }
 
@@ -51,7 +107,9 @@ pub enum DigestError {
     #[fail(display = "an entry that was supposed to be a file, contains sub-entries")]
     FileWithSubentriesError,
     #[fail(display = "file not supported: {}", _0)]
-    FileNotSupported(String)
+    FileNotSupported(String),
+    #[fail(display = "symlinks are not allowed: {}", _0)]
+    SymlinkNotAllowed(String),
 }
 
 impl From<std::io::Error> for DigestError {
@@ -71,13 +129,14 @@ where
     Digest: digest::Digest<OutputSize = OutputSize> + digest::FixedOutput,
     OutputSize: generic_array::ArrayLength<u8>,
 {
-    fn new<I>(root_path: PathBuf, rel_paths: I) -> Self
+    fn new<I>(root_path: PathBuf, rel_paths: I, options: DigestOptions) -> Self
     where
         I: IntoIterator<Item = PathBuf>,
     {
         let mut s = Self {
             root_path,
             root: Entry(Default::default()),
+            options,
             digest: std::marker::PhantomData,
         };
 
@@ -120,13 +179,24 @@ where
         entry: &Entry,
         hasher: &mut Digest,
     ) -> Result<(), DigestError> {
-        let attr = fs::symlink_metadata(full_path)?;
+        let mut attr = fs::symlink_metadata(full_path)?;
+        if attr.file_type().is_symlink() {
+            match self.options.symlinks {
+                SymlinkPolicy::Target => {
+                    return self.read_content_of_symlink(full_path, entry, hasher)
+                }
+                SymlinkPolicy::Follow => attr = fs::metadata(full_path)?,
+                SymlinkPolicy::Skip | SymlinkPolicy::Reject => {
+                    return Err(DigestError::SymlinkNotAllowed(
+                        full_path.to_string_lossy().to_string(),
+                    ))
+                }
+            }
+        }
         if attr.is_file() {
-            self.read_content_of_file(full_path, entry, hasher)
+            self.read_content_of_file(full_path, &attr, entry, hasher)
         } else if attr.is_dir() {
             self.read_content_of_dir(full_path, entry, hasher)
-        } else if attr.file_type().is_symlink() {
-            self.read_content_of_symlink(full_path, entry, hasher)
         } else {
             Err(DigestError::FileNotSupported(full_path.to_string_lossy().to_string()))
         }
@@ -140,6 +210,11 @@ where
     ) -> Result<(), DigestError> {
         parent_hasher.input(b"D");
         for (k, v) in &entry.0 {
+            let full_path = full_path.join(k);
+            if self.is_left_out(&full_path, v)? {
+                continue;
+            }
+
             let mut hasher = Digest::new();
             hasher.input(
                 k.to_str()
@@ -149,7 +224,6 @@ where
             parent_hasher.input(hasher.fixed_result().as_slice());
 
             let mut hasher = Digest::new();
-            let full_path = full_path.join(k);
             self.read_content_of(&full_path, &v, &mut hasher)?;
             parent_hasher.input(hasher.fixed_result().as_slice());
         }
@@ -157,9 +231,32 @@ where
         Ok(())
     }
 
+    /// Should a directory entry be left out, according to `options`
+    fn is_left_out(&self, full_path: &Path, entry: &Entry) -> Result<bool, DigestError> {
+        let mut attr = fs::symlink_metadata(full_path)?;
+        if attr.file_type().is_symlink() {
+            match self.options.symlinks {
+                SymlinkPolicy::Skip => return Ok(true),
+                SymlinkPolicy::Follow => attr = fs::metadata(full_path)?,
+                SymlinkPolicy::Target | SymlinkPolicy::Reject => return Ok(false),
+            }
+        }
+        if attr.is_dir() && !self.options.empty_dirs {
+            // a directory is empty if all its entries are left out
+            for (k, v) in &entry.0 {
+                if !self.is_left_out(&full_path.join(k), v)? {
+                    return Ok(false);
+                }
+            }
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
     fn read_content_of_file(
         &self,
         full_path: &Path,
+        attr: &fs::Metadata,
         entry: &Entry,
         parent_hasher: &mut Digest,
     ) -> Result<(), DigestError> {
@@ -167,7 +264,11 @@ where
             return Err(DigestError::FileWithSubentriesError);
         }
 
-        parent_hasher.input(b"F");
+        if self.options.executable_bit && is_executable(attr) {
+            parent_hasher.input(b"X");
+        } else {
+            parent_hasher.input(b"F");
+        }
         read_file_to_digest_input(full_path, parent_hasher)?;
         Ok(())
     }
@@ -179,14 +280,14 @@ where
         parent_hasher: &mut Digest,
     ) -> Result<(), DigestError> {
         assert!(entry.0.is_empty());
+        let target = full_path.read_link()?;
+        let target = target.to_str().ok_or(DigestError::OsStrConversionError)?;
         parent_hasher.input(b"L");
-        parent_hasher.input(
-            full_path
-                .read_link()?
-                .to_str()
-                .ok_or(DigestError::OsStrConversionError)?
-                .as_bytes(),
-        );
+        if cfg!(windows) {
+            parent_hasher.input(target.replace('\\', "/").as_bytes());
+        } else {
+            parent_hasher.input(target.as_bytes());
+        }
         Ok(())
     }
 }
@@ -198,7 +299,25 @@ pub fn get_recursive_digest_for_paths<Digest: digest::Digest + digest::FixedOutp
 where
     H: std::hash::BuildHasher,
 {
-    RecursiveDigest::<Digest>::new(root_path.into(), paths).get_digest()
+    get_recursive_digest_for_paths_with_options::<Digest, H>(
+        root_path,
+        paths,
+        &DigestOptions::default(),
+    )
+}
+
+pub fn get_recursive_digest_for_paths_with_options<
+    Digest: digest::Digest + digest::FixedOutput,
+    H,
+>(
+    root_path: &Path,
+    paths: HashSet<PathBuf, H>,
+    options: &DigestOptions,
+) -> Result<Vec<u8>, DigestError>
+where
+    H: std::hash::BuildHasher,
+{
+    RecursiveDigest::<Digest>::new(root_path.into(), paths, options.clone()).get_digest()
 }
 
 /// A helper function that strips a root folder from a path. If the root folder
@@ -233,14 +352,34 @@ pub fn get_recursive_digest_for_dir<
     root_path: &Path,
     rel_path_ignore_list: &HashSet<PathBuf, H>,
 ) -> Result<Vec<u8>, DigestError> {
-    let mut hasher = RecursiveDigest::<Digest>::new(root_path.into(), None);
+    get_recursive_digest_for_dir_with_options::<Digest, H>(
+        root_path,
+        rel_path_ignore_list,
+        &DigestOptions::default(),
+    )
+}
 
-    for entry in walkdir::WalkDir::new(root_path).into_iter() {
+pub fn get_recursive_digest_for_dir_with_options<
+    Digest: digest::Digest + digest::FixedOutput,
+    H: std::hash::BuildHasher,
+>(
+    root_path: &Path,
+    rel_path_ignore_list: &HashSet<PathBuf, H>,
+    options: &DigestOptions,
+) -> Result<Vec<u8>, DigestError> {
+    let mut hasher = RecursiveDigest::<Digest>::new(root_path.into(), None, options.clone());
+
+    // ignored directories are skipped along with everything inside them
+    for entry in walkdir::WalkDir::new(root_path)
+        .follow_links(options.symlinks == SymlinkPolicy::Follow)
+        .into_iter()
+        .filter_entry(|entry| {
+            !rel_path_ignore_list.contains(strip_root_path_if_included(root_path, entry.path()))
+        })
+    {
         let entry = entry?;
         let path = strip_root_path_if_included(&root_path, entry.path());
-        if !rel_path_ignore_list.contains(path) {
-            hasher.insert_path(path);
-        }
+        hasher.insert_path(path);
     }
 
     hasher.get_digest()
@@ -190,3 +190,105 @@ fn test_exclude_include_path() -> Result<(), DigestError> {
 
     Ok(())
 }
+
+#[test]
+fn test_exclude_dir() -> Result<(), DigestError> {
+    let tmp_dir = TempDir::new("recursive-digest-test4")?;
+    fs::write(tmp_dir.path().join("bar"), b"bar_content")?;
+
+    let mut excluded = HashSet::new();
+    excluded.insert(Path::new("vendor").to_path_buf());
+    let before = crev_recursive_digest::get_recursive_digest_for_dir::<blake2::Blake2b, _>(
+        tmp_dir.path(),
+        &excluded,
+    )?;
+
+    fs::create_dir_all(tmp_dir.path().join("vendor").join("big"))?;
+    fs::write(tmp_dir.path().join("vendor").join("big").join("foo"), b"foo")?;
+    let after = crev_recursive_digest::get_recursive_digest_for_dir::<blake2::Blake2b, _>(
+        tmp_dir.path(),
+        &excluded,
+    )?;
+
+    assert_eq!(before, after);
+    Ok(())
+}
+
+#[test]
+fn test_options() -> Result<(), DigestError> {
+    use crev_recursive_digest::{DigestOptions, SymlinkPolicy};
+
+    let tmp_dir = TempDir::new("recursive-digest-test5")?;
+    fs::write(tmp_dir.path().join("bar"), b"bar_content")?;
+    let empty = HashSet::new();
+    let digest = |options: &DigestOptions| {
+        crev_recursive_digest::get_recursive_digest_for_dir_with_options::<blake2::Blake2b, _>(
+            tmp_dir.path(),
+            &empty,
+            options,
+        )
+    };
+
+    let default = DigestOptions::default();
+    let no_empty_dirs = DigestOptions {
+        empty_dirs: false,
+        ..DigestOptions::default()
+    };
+    let skip_symlinks = DigestOptions {
+        symlinks: SymlinkPolicy::Skip,
+        ..DigestOptions::default()
+    };
+    let reject_symlinks = DigestOptions {
+        symlinks: SymlinkPolicy::Reject,
+        ..DigestOptions::default()
+    };
+    let before = digest(&default)?;
+
+    fs::create_dir_all(tmp_dir.path().join("empty").join("nested"))?;
+    let with_empty_dir = digest(&default)?;
+    assert_ne!(with_empty_dir, before);
+    assert_eq!(digest(&no_empty_dirs)?, before);
+
+    symlink_file("bar", tmp_dir.path().join("link"))?;
+    assert_ne!(digest(&default)?, with_empty_dir);
+    assert_eq!(digest(&skip_symlinks)?, with_empty_dir);
+    assert!(digest(&reject_symlinks).is_err());
+
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn test_executable_bit() -> Result<(), DigestError> {
+    use crev_recursive_digest::DigestOptions;
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp_dir = TempDir::new("recursive-digest-test6")?;
+    let path = tmp_dir.path().join("bar");
+    fs::write(&path, b"bar_content")?;
+    let empty = HashSet::new();
+    let with_bit = DigestOptions {
+        executable_bit: true,
+        ..DigestOptions::default()
+    };
+    let digest = |options: &DigestOptions| {
+        crev_recursive_digest::get_recursive_digest_for_dir_with_options::<blake2::Blake2b, _>(
+            tmp_dir.path(),
+            &empty,
+            options,
+        )
+    };
+
+    let before = digest(&with_bit)?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    assert_ne!(digest(&with_bit)?, before);
+    assert_eq!(
+        digest(&DigestOptions::default())?,
+        crev_recursive_digest::get_recursive_digest_for_dir::<blake2::Blake2b, _>(
+            tmp_dir.path(),
+            &empty,
+        )?
+    );
+
+    Ok(())
+}